@@ -1,7 +1,10 @@
 use crate::oids;
 use oid::ObjectIdentifier;
 use picky_asn1::tag::{Tag, TagPeeker};
-use picky_asn1::wrapper::{IntegerAsn1, ObjectIdentifierAsn1, OctetStringAsn1};
+use picky_asn1::wrapper::{
+    ExplicitContextTag0, ExplicitContextTag1, ExplicitContextTag2, ExplicitContextTag3, IntegerAsn1,
+    ObjectIdentifierAsn1, OctetStringAsn1,
+};
 use serde::{de, ser, Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
@@ -127,6 +130,13 @@ impl AlgorithmIdentifier {
         })
     }
 
+    pub fn new_rsassa_pss(parameters: RsassaPssParameters) -> Self {
+        Self {
+            algorithm: oids::rsassa_pss().into(),
+            parameters: AlgorithmIdentifierParameters::RsassaPss(Box::new(parameters)),
+        }
+    }
+
     pub fn new_dsa_with_sha1() -> Self {
         Self {
             algorithm: oids::dsa_with_sha1().into(),
@@ -137,7 +147,7 @@ impl AlgorithmIdentifier {
     pub fn new_ecdsa_with_sha512() -> Self {
         Self {
             algorithm: oids::ecdsa_with_sha512().into(),
-            parameters: AlgorithmIdentifierParameters::Null,
+            parameters: AlgorithmIdentifierParameters::None,
         }
     }
 
@@ -217,6 +227,9 @@ impl ser::Serialize for AlgorithmIdentifier {
             AlgorithmIdentifierParameters::Aes(aes_params) => {
                 seq.serialize_element(aes_params)?;
             }
+            AlgorithmIdentifierParameters::RsassaPss(pss_params) => {
+                seq.serialize_element(pss_params)?;
+            }
         }
         seq.end()
     }
@@ -267,6 +280,14 @@ impl<'de> de::Deserialize<'de> for AlgorithmIdentifier {
                         // SEQUENCE of one component: the OBJECT IDENTIFIER id-dsa-with-sha1.
                         AlgorithmIdentifierParameters::None
                     }
+                    oids::RSASSA_PSS => {
+                        // Parameters are absent when the algorithm identifies a RSASSA-PSS public key
+                        // without restrictions (RFC 4055 section 3.1).
+                        match seq.next_element()? {
+                            Some(pss_params) => AlgorithmIdentifierParameters::RsassaPss(Box::new(pss_params)),
+                            None => AlgorithmIdentifierParameters::None,
+                        }
+                    }
                     oids::EC_PUBLIC_KEY => AlgorithmIdentifierParameters::Ec(seq_next_element!(
                         seq,
                         AlgorithmIdentifier,
@@ -278,8 +299,12 @@ impl<'de> de::Deserialize<'de> for AlgorithmIdentifier {
                     ),
                     // SHA
                     x if x.starts_with("2.16.840.1.101.3.4.2.") || x == oids::SHA1 => {
-                        seq_next_element!(seq, AlgorithmIdentifier, "sha algorithm identifier");
-                        AlgorithmIdentifierParameters::Null
+                        // RFC 4055 section 2.1: implementations must accept hash algorithm
+                        // identifiers with absent parameters as well as with NULL parameters.
+                        match seq.next_element::<()>()? {
+                            Some(()) => AlgorithmIdentifierParameters::Null,
+                            None => AlgorithmIdentifierParameters::None,
+                        }
                     }
                     _ => {
                         return Err(serde_invalid_value!(
@@ -307,6 +332,7 @@ pub enum AlgorithmIdentifierParameters {
     Null,
     Aes(AesParameters),
     Ec(EcParameters),
+    RsassaPss(Box<RsassaPssParameters>),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -378,6 +404,167 @@ impl<'de> de::Deserialize<'de> for EcParameters {
     }
 }
 
+/// [Additional Algorithms and Identifiers for RSA Cryptography](https://tools.ietf.org/html/rfc4055#section-3.1)
+///
+/// # Section 3.1
+///
+/// ```not_rust
+/// RSASSA-PSS-params  ::=  SEQUENCE  {
+///     hashAlgorithm      [0] HashAlgorithm DEFAULT sha1Identifier,
+///     maskGenAlgorithm   [1] MaskGenAlgorithm DEFAULT mgf1SHA1Identifier,
+///     saltLength         [2] INTEGER DEFAULT 20,
+///     trailerField       [3] INTEGER DEFAULT 1  }
+///
+/// HashAlgorithm  ::=  AlgorithmIdentifier
+///
+/// MaskGenAlgorithm  ::=  AlgorithmIdentifier
+/// ```
+///
+/// Fields equal to their default value are omitted when serializing.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RsassaPssParameters {
+    pub hash_algorithm: AlgorithmIdentifier,
+    pub mask_gen_algorithm: MaskGenAlgorithm,
+    pub salt_length: u32,
+    pub trailer_field: u8,
+}
+
+impl RsassaPssParameters {
+    pub const DEFAULT_SALT_LENGTH: u32 = 20;
+    pub const TRAILER_FIELD_BC: u8 = 1;
+
+    /// Parameters using the given hash algorithm for both the message digest and MGF1.
+    pub fn new(hash: ShaVariant, salt_length: u32) -> Self {
+        Self {
+            hash_algorithm: AlgorithmIdentifier::new_sha(hash),
+            mask_gen_algorithm: MaskGenAlgorithm::new_mgf1(hash),
+            salt_length,
+            trailer_field: Self::TRAILER_FIELD_BC,
+        }
+    }
+}
+
+impl Default for RsassaPssParameters {
+    fn default() -> Self {
+        Self::new(ShaVariant::SHA1, Self::DEFAULT_SALT_LENGTH)
+    }
+}
+
+impl ser::Serialize for RsassaPssParameters {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeSeq;
+
+        let default = Self::default();
+
+        let mut seq = serializer.serialize_seq(Some(4))?;
+        if self.hash_algorithm != default.hash_algorithm {
+            seq.serialize_element(&ExplicitContextTag0(&self.hash_algorithm))?;
+        }
+        if self.mask_gen_algorithm != default.mask_gen_algorithm {
+            seq.serialize_element(&ExplicitContextTag1(&self.mask_gen_algorithm))?;
+        }
+        if self.salt_length != default.salt_length {
+            seq.serialize_element(&ExplicitContextTag2(self.salt_length))?;
+        }
+        if self.trailer_field != default.trailer_field {
+            seq.serialize_element(&ExplicitContextTag3(self.trailer_field))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> de::Deserialize<'de> for RsassaPssParameters {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = RsassaPssParameters;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded RSASSA-PSS-params")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut params = RsassaPssParameters::default();
+
+                // Fields are ordered and all of them are optional
+                let mut next_tag = 0;
+                while let Some(tag_peeker) = seq.next_element::<TagPeeker>()? {
+                    let tag = tag_peeker.next_tag;
+                    if tag == Tag::context_specific_constructed(0) && next_tag <= 0 {
+                        let hash_algorithm: ExplicitContextTag0<AlgorithmIdentifier> =
+                            seq_next_element!(seq, RsassaPssParameters, "hash algorithm");
+                        params.hash_algorithm = hash_algorithm.0;
+                        next_tag = 1;
+                    } else if tag == Tag::context_specific_constructed(1) && next_tag <= 1 {
+                        let mask_gen_algorithm: ExplicitContextTag1<MaskGenAlgorithm> =
+                            seq_next_element!(seq, RsassaPssParameters, "mask generation algorithm");
+                        params.mask_gen_algorithm = mask_gen_algorithm.0;
+                        next_tag = 2;
+                    } else if tag == Tag::context_specific_constructed(2) && next_tag <= 2 {
+                        let salt_length: ExplicitContextTag2<u32> =
+                            seq_next_element!(seq, RsassaPssParameters, "salt length");
+                        params.salt_length = salt_length.0;
+                        next_tag = 3;
+                    } else if tag == Tag::context_specific_constructed(3) && next_tag <= 3 {
+                        let trailer_field: ExplicitContextTag3<u8> =
+                            seq_next_element!(seq, RsassaPssParameters, "trailer field");
+                        params.trailer_field = trailer_field.0;
+                        next_tag = 4;
+                    } else {
+                        return Err(serde_invalid_value!(
+                            RsassaPssParameters,
+                            "unexpected tag",
+                            "hash algorithm [0], mask generation algorithm [1], salt length [2] or trailer field [3]"
+                        ));
+                    }
+                }
+
+                Ok(params)
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// Mask generation function used by RSASSA-PSS.
+///
+/// Only MGF1 is defined by [RFC 8017](https://tools.ietf.org/html/rfc8017#appendix-B.2.1),
+/// its parameter is the hash algorithm identifier.
+///
+/// ```not_rust
+/// MaskGenAlgorithm ::= AlgorithmIdentifier { {PKCS1MGFAlgorithms} }
+///
+/// PKCS1MGFAlgorithms    ALGORITHM-IDENTIFIER ::= {
+///     { OID id-mgf1 PARAMETERS HashAlgorithm },
+///     ...  -- Allows for future expansion --
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct MaskGenAlgorithm {
+    pub algorithm: ObjectIdentifierAsn1,
+    pub hash_algorithm: AlgorithmIdentifier,
+}
+
+impl MaskGenAlgorithm {
+    pub fn new_mgf1(hash: ShaVariant) -> Self {
+        Self {
+            algorithm: oids::id_mgf1().into(),
+            hash_algorithm: AlgorithmIdentifier::new_sha(hash),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum AesMode {
     Ecb,
@@ -638,6 +825,61 @@ mod tests {
         check_serde!(sha: AlgorithmIdentifier in expected);
     }
 
+    #[test]
+    fn sha256_absent_params() {
+        let expected = [48, 11, 6, 9, 96, 134, 72, 1, 101, 3, 4, 2, 1];
+        let sha = AlgorithmIdentifier {
+            algorithm: oids::sha256().into(),
+            parameters: AlgorithmIdentifierParameters::None,
+        };
+        check_serde!(sha: AlgorithmIdentifier in expected);
+    }
+
+    #[test]
+    fn rsassa_pss_params() {
+        let expected = [
+            0x30, 0x41, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0A, 0x30, 0x34, 0xA0, 0x0F, 0x30,
+            0x0D, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00, 0xA1, 0x1C, 0x30, 0x1A,
+            0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x08, 0x30, 0x0D, 0x06, 0x09, 0x60, 0x86, 0x48,
+            0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00, 0xA2, 0x03, 0x02, 0x01, 0x20,
+        ];
+        let rsassa_pss = AlgorithmIdentifier::new_rsassa_pss(RsassaPssParameters::new(ShaVariant::SHA2_256, 32));
+        check_serde!(rsassa_pss: AlgorithmIdentifier in expected);
+    }
+
+    #[test]
+    fn rsassa_pss_params_with_defaults() {
+        // SHA-512 with MGF1 SHA-256, default salt length and trailer field
+        let expected = [
+            0x30, 0x3C, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0A, 0x30, 0x2F, 0xA0, 0x0F, 0x30,
+            0x0D, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03, 0x05, 0x00, 0xA1, 0x1C, 0x30, 0x1A,
+            0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x08, 0x30, 0x0D, 0x06, 0x09, 0x60, 0x86, 0x48,
+            0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00,
+        ];
+        let rsassa_pss = AlgorithmIdentifier::new_rsassa_pss(RsassaPssParameters {
+            hash_algorithm: AlgorithmIdentifier::new_sha(ShaVariant::SHA2_512),
+            mask_gen_algorithm: MaskGenAlgorithm::new_mgf1(ShaVariant::SHA2_256),
+            ..RsassaPssParameters::default()
+        });
+        check_serde!(rsassa_pss: AlgorithmIdentifier in expected);
+
+        let all_defaults = [
+            0x30, 0x0D, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0A, 0x30, 0x00,
+        ];
+        let rsassa_pss = AlgorithmIdentifier::new_rsassa_pss(RsassaPssParameters::default());
+        check_serde!(rsassa_pss: AlgorithmIdentifier in all_defaults);
+    }
+
+    #[test]
+    fn rsassa_pss_params_out_of_order_err() {
+        // salt length [2] before hash algorithm [0]
+        let encoded = [
+            0x30, 0x21, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x0A, 0x30, 0x14, 0xA2, 0x03, 0x02,
+            0x01, 0x20, 0xA0, 0x0D, 0x30, 0x0B, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01,
+        ];
+        picky_asn1_der::from_bytes::<AlgorithmIdentifier>(&encoded).expect_err("parameters are out of order");
+    }
+
     #[test]
     fn ec_params() {
         let expected = [
@@ -648,6 +890,13 @@ mod tests {
         check_serde!(ec_params: AlgorithmIdentifier in expected);
     }
 
+    #[test]
+    fn ecdsa_with_sha512_absent_params() {
+        let expected = [48, 10, 6, 8, 42, 134, 72, 206, 61, 4, 3, 4];
+        let ecdsa_with_sha512 = AlgorithmIdentifier::new_ecdsa_with_sha512();
+        check_serde!(ecdsa_with_sha512: AlgorithmIdentifier in expected);
+    }
+
     #[test]
     fn digest_info() {
         let digest = picky_asn1_der::to_vec(&DigestInfo {
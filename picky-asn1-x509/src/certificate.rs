@@ -58,6 +58,14 @@ impl Certificate {
     }
 }
 
+/// [RFC 5280 #4.1](https://tools.ietf.org/html/rfc5280#section-4.1)
+///
+/// ```not_rust
+/// CertificateSerialNumber  ::=  INTEGER
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CertificateSerialNumber(pub IntegerAsn1);
+
 /// [RFC 5280 #4.1](https://tools.ietf.org/html/rfc5280#section-4.1)
 ///
 /// ```not_rust
@@ -0,0 +1,266 @@
+use crate::{AlgorithmIdentifier, CertificateSerialNumber, Extensions, Name, Time, Version};
+use picky_asn1::tag::{Tag, TagPeeker};
+use picky_asn1::wrapper::{Asn1SequenceOf, BitStringAsn1, ExplicitContextTag0};
+use serde::{de, Deserialize, Serialize};
+
+/// CRLs are specified in X.509
+/// ``` not_rust
+/// [RFC 5280 #5.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.1)
+/// CertificateList  ::=  SEQUENCE  {
+///         tbsCertList          TBSCertList,
+///         signatureAlgorithm   AlgorithmIdentifier,
+///         signatureValue       BIT STRING  }
+///
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CertificateList {
+    pub tbs_cert_list: TbsCertList,
+    pub signature_algorithm: AlgorithmIdentifier,
+    pub signature_value: BitStringAsn1,
+}
+
+/// ``` not_rust
+/// [RFC 5280 #5.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.1)
+///  TBSCertList  ::=  SEQUENCE  {
+///         version                 Version OPTIONAL,
+///                                      -- if present, MUST be v2
+///         signature               AlgorithmIdentifier,
+///         issuer                  Name,
+///         thisUpdate              Time,
+///         nextUpdate              Time OPTIONAL,
+///         revokedCertificates     SEQUENCE OF SEQUENCE  {
+///              userCertificate         CertificateSerialNumber,
+///              revocationDate          Time,
+///              crlEntryExtensions      Extensions OPTIONAL
+///                                       -- if present, version MUST be v2
+///                                   }  OPTIONAL,
+///         crlExtensions           [0]  EXPLICIT Extensions OPTIONAL
+///                                       -- if present, version MUST be v2
+///                                 }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct TbsCertList {
+    pub version: Option<Version>,
+    pub signature: AlgorithmIdentifier,
+    pub issuer: Name,
+    pub this_update: Time,
+    pub next_update: Option<Time>,
+    pub revoked_certificates: Option<RevokedCertificates>,
+    pub crl_extension: ExplicitContextTag0<Option<Extensions>>,
+}
+
+impl<'de> de::Deserialize<'de> for TbsCertList {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        use std::fmt;
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = TbsCertList;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded TbsCertList")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let version = seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
+                if version.is_some() && !version.eq(&Some(Version::V2)) {
+                    return Err(serde_invalid_value!(
+                        TbsCertList,
+                        "Version of TbsCertList doesn't equal to v2",
+                        "Version of TbsCertList equals to v2"
+                    ));
+                }
+
+                let signature = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let issuer = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let this_update = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let next_update = seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
+
+                // Both revokedCertificates and crlExtensions may be absent at the end of the sequence
+                let revoked_certificates = match seq.next_element::<TagPeeker>()? {
+                    Some(tag_peeker) if tag_peeker.next_tag == Tag::SEQUENCE => {
+                        seq.next_element()?.ok_or_else(|| de::Error::invalid_length(5, &self))?
+                    }
+                    _ => None,
+                };
+
+                let crl_extension = seq
+                    .next_element()
+                    .unwrap_or(Some(ExplicitContextTag0(None)))
+                    .unwrap_or(ExplicitContextTag0(None));
+
+                Ok(TbsCertList {
+                    version,
+                    signature,
+                    issuer,
+                    this_update,
+                    next_update,
+                    revoked_certificates,
+                    crl_extension,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
+pub struct RevokedCertificates(pub Asn1SequenceOf<RevokedCertificate>);
+
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct RevokedCertificate {
+    pub user_certificate: CertificateSerialNumber,
+    pub revocation_data: Time,
+    pub crl_entry_extensions: Option<Extensions>,
+}
+
+impl<'de> de::Deserialize<'de> for RevokedCertificate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        use std::fmt;
+
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = RevokedCertificate;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-decoded TbsCertList")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(RevokedCertificate {
+                    user_certificate: seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?,
+                    revocation_data: seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?,
+                    crl_entry_extensions: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #5.3.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.3.1)
+///
+/// ``` not_rust
+/// CRLReason ::= ENUMERATED {
+///         unspecified             (0),
+///         keyCompromise           (1),
+///         cACompromise            (2),
+///         affiliationChanged      (3),
+///         superseded              (4),
+///         cessationOfOperation    (5),
+///         certificateHold         (6),
+///              -- value 7 is not used
+///         removeFromCRL           (8),
+///         privilegeWithdrawn      (9),
+///         aACompromise           (10) }
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CrlReason {
+    Unspecified = 0,
+    KeyCompromise = 1,
+    CaCompromise = 2,
+    AffiliationChanged = 3,
+    Superseded = 4,
+    CessationOfOperation = 5,
+    CertificateHold = 6,
+    RemoveFromCrl = 8,
+    PrivilegeWithdrawn = 9,
+    AaCompromise = 10,
+}
+
+asn1_enumerated!(CrlReason {
+    Unspecified = 0,
+    KeyCompromise = 1,
+    CaCompromise = 2,
+    AffiliationChanged = 3,
+    Superseded = 4,
+    CessationOfOperation = 5,
+    CertificateHold = 6,
+    RemoveFromCrl = 8,
+    PrivilegeWithdrawn = 9,
+    AaCompromise = 10,
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use picky_asn1::wrapper::IntegerAsn1;
+
+    #[test]
+    fn decode_v1_crl_without_entries() {
+        let encoded = base64::decode(
+            "MIIBWzBFMA0GCSqGSIb3DQEBCwUAMBYxFDASBgNVBAMMC1Rlc3QgQ1JMIENBFw0y\
+             NjEwMTcwMjI0MjZaFw0yNjExMTYwMjI0MjZaMA0GCSqGSIb3DQEBCwUAA4IBAQCA\
+             sTjoOHhznqlo+clVle0sFD3eZnb+qgQ8xy6a8WkvUH3AJ0BPAMapKS4QvHjF9RMs\
+             6/AHYi6/DQ4yLk/cmUcHRys23Pb5B+ppFapOAljCR7GvQF8LBrXAyUgX5qXPWWzG\
+             RxJNzFrsi/kIqOeo61v/hpSsz3i64p4s5icingdnsh2uIE/+qlDKTkqi44+SxISV\
+             LC9nbsQtGnu06DTLzVn0jXdpR8BOnRqEdoFQFJXitY51oGsDL9pZRfle4H6nx9rT\
+             cA/xzfPb7b9MBvKQG2MeVaJVKrcQ8JdQb/uzurAet2bs0qtyv643SLOo+CFaE58B\
+             gkX/ilEGRDA/X126PuaP",
+        )
+        .unwrap();
+
+        let crl: CertificateList = picky_asn1_der::from_bytes(&encoded).unwrap();
+        let tbs_cert_list = &crl.tbs_cert_list;
+        assert_eq!(tbs_cert_list.version, None);
+        assert!(tbs_cert_list.next_update.is_some());
+        assert_eq!(tbs_cert_list.revoked_certificates, None);
+        assert_eq!(tbs_cert_list.crl_extension.0, None);
+
+        check_serde!(crl: CertificateList in encoded);
+    }
+
+    #[test]
+    fn decode_v2_crl_with_entries_and_extensions() {
+        let encoded = base64::decode(
+            "MIIBtTCBngIBATANBgkqhkiG9w0BAQsFADAWMRQwEgYDVQQDDAtUZXN0IENSTCBD\
+             QRcNMjYxMDE3MDIyNDM3WhcNMjYxMTE2MDIyNDM3WjAiMCACAQEXDTI2MTAxNzAy\
+             MjQyNlowDDAKBgNVHRUEAwoBAaAwMC4wHwYDVR0jBBgwFoAUKpLNWAumqxYV/MKK\
+             T/9ZBh8RyzgwCwYDVR0UBAQCAhAAMA0GCSqGSIb3DQEBCwUAA4IBAQBJyuVPDL+V\
+             4inZ3Jlp0hAFZ+LNXDa7RUw+D8JPwENsCIm5E2SVrKqqQV5pfFRbkRUmKwLUx5wf\
+             40sLWgbLylEuMfGeZQEKY5alKCVr1FsVddgrSeoGLgmH+Bg4RFQ3/csHOVkWzsQb\
+             iZ1mOySg2O7YcENI2JzOYYU+ItgoC0osXEXFOyiC9l99M/H/dwpovkfERcpljWAd\
+             fZgrSHP5vIjDBNz0r4sdjz9rOnbC6D4xGxcu/yw+NdHBYMUJBnZcRxeUvIRw9tYr\
+             B/oakekqwH3qH3V7ZF/R/fEykEXxt5N1oUp72AmWUZtbImyi+qBG0qPF8V+jjaxM\
+             gQvS5Lo/hAlz",
+        )
+        .unwrap();
+
+        let crl: CertificateList = picky_asn1_der::from_bytes(&encoded).unwrap();
+        let tbs_cert_list = &crl.tbs_cert_list;
+        assert_eq!(tbs_cert_list.version, Some(Version::V2));
+
+        let revoked_certificates = &tbs_cert_list.revoked_certificates.as_ref().unwrap().0 .0;
+        assert_eq!(revoked_certificates.len(), 1);
+        assert_eq!(
+            revoked_certificates[0].user_certificate,
+            CertificateSerialNumber(IntegerAsn1::from(vec![0x01]))
+        );
+        assert_eq!(
+            revoked_certificates[0].crl_entry_extensions.as_ref().unwrap().0.len(),
+            1
+        );
+
+        let crl_extensions = tbs_cert_list.crl_extension.0.as_ref().unwrap();
+        assert_eq!(crl_extensions.0.len(), 2);
+        assert_eq!(crl_extensions.0[1].extn_id(), &crate::oids::crl_number());
+
+        check_serde!(crl: CertificateList in encoded);
+    }
+}
@@ -1,9 +1,12 @@
-use crate::{oids, GeneralName, GeneralNames};
+use crate::{oids, CrlReason, GeneralName, GeneralNames, RelativeDistinguishedName};
 use core::slice::{Iter, IterMut};
 use picky_asn1::bit_string::BitString;
+use picky_asn1::restricted_string::IA5String;
+use picky_asn1::tag::{Tag, TagPeeker};
 use picky_asn1::wrapper::{
-    Asn1SequenceOf, BitStringAsn1, ExplicitContextTag1, ImplicitContextTag0, ImplicitContextTag2, IntegerAsn1,
-    ObjectIdentifierAsn1, OctetStringAsn1, OctetStringAsn1Container, Optional,
+    Asn1SequenceOf, BMPStringAsn1, BitStringAsn1, ExplicitContextTag0, ExplicitContextTag1, IA5StringAsn1,
+    ImplicitContextTag0, ImplicitContextTag1, ImplicitContextTag2, IntegerAsn1, ObjectIdentifierAsn1, OctetStringAsn1,
+    OctetStringAsn1Container, Optional,
 };
 use serde::{de, ser, Deserialize, Serialize};
 use std::fmt;
@@ -135,6 +138,82 @@ impl Extension {
         }
     }
 
+    /// This extension MAY, at the option of the certificate issuer, be either critical or non-critical.
+    ///
+    /// Default is non-critical.
+    pub fn new_certificate_policies<P: Into<CertificatePolicies>>(policies: P) -> Self {
+        Self {
+            extn_id: oids::certificate_policies().into(),
+            critical: false.into(),
+            extn_value: ExtensionValue::CertificatePolicies(policies.into().into()),
+        }
+    }
+
+    /// Conforming CAs SHOULD mark this extension as critical
+    ///
+    /// Default is critical.
+    pub fn new_policy_mappings<M: Into<PolicyMappings>>(mappings: M) -> Self {
+        Self {
+            extn_id: oids::policy_mappings().into(),
+            critical: true.into(),
+            extn_value: ExtensionValue::PolicyMappings(mappings.into().into()),
+        }
+    }
+
+    /// Conforming CAs MUST mark this extension as critical
+    ///
+    /// Default is critical.
+    pub fn new_policy_constraints<REP, IPM>(require_explicit_policy: REP, inhibit_policy_mapping: IPM) -> Self
+    where
+        REP: Into<Option<u8>>,
+        IPM: Into<Option<u8>>,
+    {
+        Self {
+            extn_id: oids::policy_constraints().into(),
+            critical: true.into(),
+            extn_value: ExtensionValue::PolicyConstraints(
+                PolicyConstraints {
+                    require_explicit_policy: require_explicit_policy.into().map(ImplicitContextTag0),
+                    inhibit_policy_mapping: inhibit_policy_mapping.into().map(ImplicitContextTag1),
+                }
+                .into(),
+            ),
+        }
+    }
+
+    /// Conforming CAs MUST mark this extension as critical
+    ///
+    /// Default is critical.
+    pub fn new_inhibit_any_policy(skip_certs: u8) -> Self {
+        Self {
+            extn_id: oids::inhibit_any_policy().into(),
+            critical: true.into(),
+            extn_value: ExtensionValue::InhibitAnyPolicy(skip_certs.into()),
+        }
+    }
+
+    /// The extension SHOULD be non-critical.
+    ///
+    /// Default is non-critical.
+    pub fn new_crl_distribution_points<P: Into<CrlDistributionPoints>>(distribution_points: P) -> Self {
+        Self {
+            extn_id: oids::crl_distribution_points().into(),
+            critical: false.into(),
+            extn_value: ExtensionValue::CrlDistributionPoints(distribution_points.into().into()),
+        }
+    }
+
+    /// Conforming CAs MUST mark this extension as non-critical.
+    ///
+    /// Default is non-critical.
+    pub fn new_authority_info_access<A: Into<AuthorityInfoAccess>>(access_descriptions: A) -> Self {
+        Self {
+            extn_id: oids::authority_info_access().into(),
+            critical: false.into(),
+            extn_value: ExtensionValue::AuthorityInfoAccess(access_descriptions.into().into()),
+        }
+    }
+
     /// If the subject field contains an empty sequence, then the issuing CA MUST include a
     /// subjectAltName extension that is marked as critical. When including
     /// the subjectAltName extension in a certificate that has a non-empty
@@ -163,6 +242,17 @@ impl Extension {
         }
     }
 
+    /// Conforming CAs MUST mark this extension as critical
+    ///
+    /// Default is critical.
+    pub fn new_name_constraints<N: Into<NameConstraints>>(name_constraints: N) -> Self {
+        Self {
+            extn_id: oids::name_constraints().into(),
+            critical: true.into(),
+            extn_value: ExtensionValue::NameConstraints(name_constraints.into().into()),
+        }
+    }
+
     pub fn new_crl_number<N: Into<OctetStringAsn1Container<IntegerAsn1>>>(number: N) -> Self {
         Self {
             extn_id: oids::crl_number().into(),
@@ -170,6 +260,30 @@ impl Extension {
             extn_value: ExtensionValue::CrlNumber(number.into()),
         }
     }
+
+    /// CRL entry extension identifying the reason for the certificate revocation.
+    ///
+    /// Default is non-critical.
+    pub fn new_crl_reason(reason: CrlReason) -> Self {
+        let reason_der = picky_asn1_der::to_vec(&reason).unwrap();
+        Self {
+            extn_id: oids::crl_reason_code().into(),
+            critical: false.into(),
+            extn_value: ExtensionValue::Generic(reason_der.into()),
+        }
+    }
+
+    /// OCSP nonce extension ([RFC 8954](https://datatracker.ietf.org/doc/html/rfc8954)).
+    ///
+    /// The nonce is wrapped into an OCTET STRING as done by most responders.
+    pub fn new_ocsp_nonce<V: Into<Vec<u8>>>(nonce: V) -> Self {
+        let nonce_der = picky_asn1_der::to_vec(&OctetStringAsn1(nonce.into())).unwrap();
+        Self {
+            extn_id: oids::id_pkix_ocsp_nonce().into(),
+            critical: false.into(),
+            extn_value: ExtensionValue::Generic(nonce_der.into()),
+        }
+    }
 }
 
 impl ser::Serialize for Extension {
@@ -221,6 +335,12 @@ impl<'de> de::Deserialize<'de> for Extension {
                         ExtensionValue::SubjectKeyIdentifier(seq_next_element!(seq, Extension, "SubjectKeyIdentifier"))
                     }
                     oids::KEY_USAGE => ExtensionValue::KeyUsage(seq_next_element!(seq, Extension, "KeyUsage")),
+                    oids::CERTIFICATE_POLICIES => {
+                        ExtensionValue::CertificatePolicies(seq_next_element!(seq, Extension, "CertificatePolicies"))
+                    }
+                    oids::POLICY_MAPPINGS => {
+                        ExtensionValue::PolicyMappings(seq_next_element!(seq, Extension, "PolicyMappings"))
+                    }
                     oids::SUBJECT_ALTERNATIVE_NAME => {
                         ExtensionValue::SubjectAltName(seq_next_element!(seq, Extension, "SubjectAltName"))
                     }
@@ -230,9 +350,26 @@ impl<'de> de::Deserialize<'de> for Extension {
                     oids::BASIC_CONSTRAINTS => {
                         ExtensionValue::BasicConstraints(seq_next_element!(seq, Extension, "BasicConstraints"))
                     }
+                    oids::NAME_CONSTRAINTS => {
+                        ExtensionValue::NameConstraints(seq_next_element!(seq, Extension, "NameConstraints"))
+                    }
+                    oids::POLICY_CONSTRAINTS => {
+                        ExtensionValue::PolicyConstraints(seq_next_element!(seq, Extension, "PolicyConstraints"))
+                    }
                     oids::EXTENDED_KEY_USAGE => {
                         ExtensionValue::ExtendedKeyUsage(seq_next_element!(seq, Extension, "ExtendedKeyUsage"))
                     }
+                    oids::CRL_DISTRIBUTION_POINTS => ExtensionValue::CrlDistributionPoints(seq_next_element!(
+                        seq,
+                        Extension,
+                        "CrlDistributionPoints"
+                    )),
+                    oids::INHIBIT_ANY_POLICY => {
+                        ExtensionValue::InhibitAnyPolicy(seq_next_element!(seq, Extension, "InhibitAnyPolicy"))
+                    }
+                    oids::AUTHORITY_INFO_ACCESS => {
+                        ExtensionValue::AuthorityInfoAccess(seq_next_element!(seq, Extension, "AuthorityInfoAccess"))
+                    }
                     oids::CRL_NUMBER => ExtensionValue::CrlNumber(seq_next_element!(seq, Extension, "CrlNumber")),
                     _ => ExtensionValue::Generic(seq_next_element!(seq, Extension, "Generic")),
                 };
@@ -255,10 +392,17 @@ pub enum ExtensionView<'a> {
     AuthorityKeyIdentifier(&'a AuthorityKeyIdentifier),
     SubjectKeyIdentifier(&'a SubjectKeyIdentifier),
     KeyUsage(&'a KeyUsage),
+    CertificatePolicies(&'a CertificatePolicies),
+    PolicyMappings(&'a PolicyMappings),
     SubjectAltName(super::name::GeneralNames),
     IssuerAltName(super::name::GeneralNames),
     BasicConstraints(&'a BasicConstraints),
+    NameConstraints(&'a NameConstraints),
+    PolicyConstraints(&'a PolicyConstraints),
     ExtendedKeyUsage(&'a ExtendedKeyUsage),
+    CrlDistributionPoints(&'a CrlDistributionPoints),
+    InhibitAnyPolicy(u8),
+    AuthorityInfoAccess(&'a AuthorityInfoAccess),
     Generic(&'a OctetStringAsn1),
     CrlNumber(&'a OctetStringAsn1Container<IntegerAsn1>),
 }
@@ -269,10 +413,17 @@ impl<'a> From<&'a ExtensionValue> for ExtensionView<'a> {
             ExtensionValue::AuthorityKeyIdentifier(OctetStringAsn1Container(val)) => Self::AuthorityKeyIdentifier(val),
             ExtensionValue::SubjectKeyIdentifier(OctetStringAsn1Container(val)) => Self::SubjectKeyIdentifier(val),
             ExtensionValue::KeyUsage(OctetStringAsn1Container(val)) => Self::KeyUsage(val),
+            ExtensionValue::CertificatePolicies(OctetStringAsn1Container(val)) => Self::CertificatePolicies(val),
+            ExtensionValue::PolicyMappings(OctetStringAsn1Container(val)) => Self::PolicyMappings(val),
             ExtensionValue::SubjectAltName(OctetStringAsn1Container(val)) => Self::SubjectAltName(val.clone()),
             ExtensionValue::IssuerAltName(OctetStringAsn1Container(val)) => Self::IssuerAltName(val.clone()),
             ExtensionValue::BasicConstraints(OctetStringAsn1Container(val)) => Self::BasicConstraints(val),
+            ExtensionValue::NameConstraints(OctetStringAsn1Container(val)) => Self::NameConstraints(val),
+            ExtensionValue::PolicyConstraints(OctetStringAsn1Container(val)) => Self::PolicyConstraints(val),
             ExtensionValue::ExtendedKeyUsage(OctetStringAsn1Container(val)) => Self::ExtendedKeyUsage(val),
+            ExtensionValue::CrlDistributionPoints(OctetStringAsn1Container(val)) => Self::CrlDistributionPoints(val),
+            ExtensionValue::InhibitAnyPolicy(OctetStringAsn1Container(val)) => Self::InhibitAnyPolicy(*val),
+            ExtensionValue::AuthorityInfoAccess(OctetStringAsn1Container(val)) => Self::AuthorityInfoAccess(val),
             ExtensionValue::Generic(val) => Self::Generic(val),
             ExtensionValue::CrlNumber(val) => Self::CrlNumber(val),
         }
@@ -284,18 +435,19 @@ enum ExtensionValue {
     AuthorityKeyIdentifier(OctetStringAsn1Container<AuthorityKeyIdentifier>),
     SubjectKeyIdentifier(OctetStringAsn1Container<SubjectKeyIdentifier>),
     KeyUsage(OctetStringAsn1Container<KeyUsage>),
-    //CertificatePolicies(OctetStringAsn1Container<Asn1SequenceOf<PolicyInformation>>),
-    //PolicyMappings(OctetStringAsn1Container<Asn1SequenceOfPolicyMapping>>),
+    CertificatePolicies(OctetStringAsn1Container<CertificatePolicies>),
+    PolicyMappings(OctetStringAsn1Container<PolicyMappings>),
     SubjectAltName(OctetStringAsn1Container<SubjectAltName>),
     IssuerAltName(OctetStringAsn1Container<IssuerAltName>),
     //SubjectDirectoryAttributes(OctetStringAsn1Container<Asn1SequenceOf<Attribute>>),
     BasicConstraints(OctetStringAsn1Container<BasicConstraints>),
-    //NameConstraints(…),
-    //PolicyConstraints(…),
+    NameConstraints(OctetStringAsn1Container<NameConstraints>),
+    PolicyConstraints(OctetStringAsn1Container<PolicyConstraints>),
     ExtendedKeyUsage(OctetStringAsn1Container<ExtendedKeyUsage>),
-    //CRLDistributionPoints(…),
-    //InhibitAnyPolicy(…),
+    CrlDistributionPoints(OctetStringAsn1Container<CrlDistributionPoints>),
+    InhibitAnyPolicy(OctetStringAsn1Container<u8>),
     //FreshestCRL(…),
+    AuthorityInfoAccess(OctetStringAsn1Container<AuthorityInfoAccess>),
     Generic(OctetStringAsn1),
     CrlNumber(OctetStringAsn1Container<IntegerAsn1>),
 }
@@ -309,10 +461,17 @@ impl ser::Serialize for ExtensionValue {
             ExtensionValue::AuthorityKeyIdentifier(aki) => aki.serialize(serializer),
             ExtensionValue::SubjectKeyIdentifier(ski) => ski.serialize(serializer),
             ExtensionValue::KeyUsage(key_usage) => key_usage.serialize(serializer),
+            ExtensionValue::CertificatePolicies(policies) => policies.serialize(serializer),
+            ExtensionValue::PolicyMappings(mappings) => mappings.serialize(serializer),
             ExtensionValue::SubjectAltName(san) => san.serialize(serializer),
             ExtensionValue::IssuerAltName(ian) => ian.serialize(serializer),
             ExtensionValue::BasicConstraints(basic_constraints) => basic_constraints.serialize(serializer),
+            ExtensionValue::NameConstraints(name_constraints) => name_constraints.serialize(serializer),
+            ExtensionValue::PolicyConstraints(policy_constraints) => policy_constraints.serialize(serializer),
             ExtensionValue::ExtendedKeyUsage(eku) => eku.serialize(serializer),
+            ExtensionValue::CrlDistributionPoints(distribution_points) => distribution_points.serialize(serializer),
+            ExtensionValue::InhibitAnyPolicy(skip_certs) => skip_certs.serialize(serializer),
+            ExtensionValue::AuthorityInfoAccess(aia) => aia.serialize(serializer),
             ExtensionValue::Generic(octet_string) => octet_string.serialize(serializer),
             ExtensionValue::CrlNumber(integer) => integer.serialize(serializer),
         }
@@ -433,36 +592,75 @@ impl KeyUsage {
     }
 }
 
-/// [RFC 5280 #4.2.1.6](https://tools.ietf.org/html/rfc5280#section-4.2.1.6)
-type SubjectAltName = GeneralNames;
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// ```not_rust
+/// certificatePolicies ::= SEQUENCE SIZE (1..MAX) OF PolicyInformation
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CertificatePolicies(Asn1SequenceOf<PolicyInformation>);
 
-/// [RFC 5280 #4.2.1.7](https://tools.ietf.org/html/rfc5280#section-4.2.1.7)
-type IssuerAltName = GeneralNames;
+impl From<Vec<PolicyInformation>> for CertificatePolicies {
+    fn from(policies: Vec<PolicyInformation>) -> Self {
+        Self::new(policies)
+    }
+}
 
-/// [RFC 5280 #4.2.1.9](https://tools.ietf.org/html/rfc5280#section-4.2.1.9)
+impl CertificatePolicies {
+    pub fn new(policies: Vec<PolicyInformation>) -> Self {
+        Self(policies.into())
+    }
+
+    pub fn iter(&self) -> Iter<PolicyInformation> {
+        (self.0).0.iter()
+    }
+
+    pub fn contains<C: PartialEq<oid::ObjectIdentifier>>(&self, policy_identifier: C) -> bool {
+        (self.0)
+            .0
+            .iter()
+            .any(|policy| policy_identifier.eq(&policy.policy_identifier.0))
+    }
+}
+
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
 ///
 /// ```not_rust
-/// BasicConstraints ::= SEQUENCE {
-///      cA                      BOOLEAN DEFAULT FALSE,
-///      pathLenConstraint       INTEGER (0..MAX) OPTIONAL }
+/// PolicyInformation ::= SEQUENCE {
+///      policyIdentifier   CertPolicyId,
+///      policyQualifiers   SEQUENCE SIZE (1..MAX) OF
+///                              PolicyQualifierInfo OPTIONAL }
+///
+/// CertPolicyId ::= OBJECT IDENTIFIER
 /// ```
 #[derive(Serialize, Debug, PartialEq, Clone)]
-pub struct BasicConstraints {
-    ca: Optional<Option<bool>>, // default is false
-    path_len_constraint: Optional<Option<u8>>,
+pub struct PolicyInformation {
+    pub policy_identifier: ObjectIdentifierAsn1,
+    pub policy_qualifiers: Option<Asn1SequenceOf<PolicyQualifierInfo>>,
 }
 
-impl BasicConstraints {
-    pub fn ca(&self) -> Option<bool> {
-        self.ca.0
+impl PolicyInformation {
+    /// Empty qualifiers list is omitted from the encoded policy.
+    pub fn new<OID: Into<ObjectIdentifierAsn1>>(policy_identifier: OID, qualifiers: Vec<PolicyQualifierInfo>) -> Self {
+        Self {
+            policy_identifier: policy_identifier.into(),
+            policy_qualifiers: if qualifiers.is_empty() {
+                None
+            } else {
+                Some(qualifiers.into())
+            },
+        }
     }
 
-    pub fn pathlen(&self) -> Option<u8> {
-        self.path_len_constraint.0
+    pub fn policy_qualifiers(&self) -> &[PolicyQualifierInfo] {
+        self.policy_qualifiers
+            .as_ref()
+            .map(|qualifiers| qualifiers.0.as_slice())
+            .unwrap_or(&[])
     }
 }
 
-impl<'de> de::Deserialize<'de> for BasicConstraints {
+impl<'de> de::Deserialize<'de> for PolicyInformation {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
     where
         D: de::Deserializer<'de>,
@@ -470,19 +668,19 @@ impl<'de> de::Deserialize<'de> for BasicConstraints {
         struct Visitor;
 
         impl<'de> de::Visitor<'de> for Visitor {
-            type Value = BasicConstraints;
+            type Value = PolicyInformation;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a valid DER-encoded basic constraints extension")
+                formatter.write_str("a valid DER-encoded policy information")
             }
 
             fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
             where
                 A: de::SeqAccess<'de>,
             {
-                Ok(BasicConstraints {
-                    ca: Optional(seq.next_element().unwrap_or(Some(None)).unwrap_or(None)),
-                    path_len_constraint: Optional(seq.next_element().unwrap_or(Some(None)).unwrap_or(None)),
+                Ok(PolicyInformation {
+                    policy_identifier: seq_next_element!(seq, PolicyInformation, "policy identifier"),
+                    policy_qualifiers: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
                 })
             }
         }
@@ -491,105 +689,1219 @@ impl<'de> de::Deserialize<'de> for BasicConstraints {
     }
 }
 
-/// [RFC 5280 #4.2.1.12](https://tools.ietf.org/html/rfc5280#section-4.2.1.12)
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct ExtendedKeyUsage(Asn1SequenceOf<ObjectIdentifierAsn1>);
-
-impl<OID: Into<ObjectIdentifierAsn1>> From<Vec<OID>> for ExtendedKeyUsage {
-    fn from(purpose_oids: Vec<OID>) -> Self {
-        ExtendedKeyUsage::new(purpose_oids)
-    }
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// ```not_rust
+/// PolicyQualifierInfo ::= SEQUENCE {
+///      policyQualifierId  PolicyQualifierId,
+///      qualifier          ANY DEFINED BY policyQualifierId }
+///
+/// PolicyQualifierId ::= OBJECT IDENTIFIER ( id-qt-cps | id-qt-unotice )
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct PolicyQualifierInfo {
+    pub policy_qualifier_id: ObjectIdentifierAsn1,
+    pub qualifier: Qualifier,
 }
 
-impl ExtendedKeyUsage {
-    pub fn new<OID: Into<ObjectIdentifierAsn1>>(purpose_oids: Vec<OID>) -> Self {
-        Self(
-            purpose_oids
-                .into_iter()
-                .map(|oid| oid.into())
-                .collect::<Vec<_>>()
-                .into(),
-        )
+impl PolicyQualifierInfo {
+    pub fn new_cps_uri(cps_uri: IA5String) -> Self {
+        Self {
+            policy_qualifier_id: oids::id_qt_cps().into(),
+            qualifier: Qualifier::CpsUri(cps_uri.into()),
+        }
     }
 
-    pub fn iter(&self) -> Iter<ObjectIdentifierAsn1> {
-        (self.0).0.iter()
+    pub fn new_user_notice(user_notice: UserNotice) -> Self {
+        Self {
+            policy_qualifier_id: oids::id_qt_unotice().into(),
+            qualifier: Qualifier::UserNotice(user_notice),
+        }
     }
+}
 
-    pub fn iter_mut(&mut self) -> IterMut<ObjectIdentifierAsn1> {
-        (self.0).0.iter_mut()
-    }
+impl<'de> de::Deserialize<'de> for PolicyQualifierInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
 
-    pub fn contains<C: PartialEq<oid::ObjectIdentifier>>(&self, item: C) -> bool {
-        (self.0).0.iter().any(|id| item.eq(&id.0))
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = PolicyQualifierInfo;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded policy qualifier info")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let policy_qualifier_id: ObjectIdentifierAsn1 =
+                    seq_next_element!(seq, PolicyQualifierInfo, "policy qualifier id");
+                let qualifier = match Into::<String>::into(&policy_qualifier_id.0).as_str() {
+                    oids::ID_QT_CPS => Qualifier::CpsUri(seq_next_element!(seq, PolicyQualifierInfo, "cps uri")),
+                    oids::ID_QT_UNOTICE => {
+                        Qualifier::UserNotice(seq_next_element!(seq, PolicyQualifierInfo, "user notice"))
+                    }
+                    _ => Qualifier::Other(seq_next_element!(seq, PolicyQualifierInfo, "qualifier")),
+                };
+
+                Ok(PolicyQualifierInfo {
+                    policy_qualifier_id,
+                    qualifier,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::GeneralName;
-    use picky_asn1::restricted_string::IA5String;
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// ```not_rust
+/// Qualifier ::= CHOICE {
+///      cPSuri           CPSuri,
+///      userNotice       UserNotice }
+///
+/// CPSuri ::= IA5String
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum Qualifier {
+    CpsUri(IA5StringAsn1),
+    UserNotice(UserNotice),
+    /// Qualifier of an unknown type kept as raw DER
+    Other(picky_asn1_der::Asn1RawDer),
+}
 
-    #[test]
-    fn key_usage() {
-        let encoded: [u8; 4] = [0x03, 0x02, 0x01, 0xA0];
-        let mut key_usage = KeyUsage::new(7);
-        key_usage.set_digital_signature(true);
-        key_usage.set_key_encipherment(true);
-        assert_eq!(key_usage.as_bytes(), &[0xA0]);
-        check_serde!(key_usage: KeyUsage in encoded);
+impl ser::Serialize for Qualifier {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Qualifier::CpsUri(cps_uri) => cps_uri.serialize(serializer),
+            Qualifier::UserNotice(user_notice) => user_notice.serialize(serializer),
+            Qualifier::Other(raw_der) => raw_der.serialize(serializer),
+        }
     }
+}
 
-    #[test]
-    fn eku_ku_bc_san_extensions() {
-        let cert_der = base64::decode(
-            "MIIDIjCCAgoCAQAwIDELMAkGA1UEBhMCRlIxETAPBgNVBAMMCERyYXBlYXUhMIIB\
-                           IjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA5GqDEM7AfctJizsFEqtAvXd5\
-                           Fl1GtyXDAnx68MUTuSL22t8aBZoCCi3/9AlS75uUqKggHnRuY2MRYPQaUzpE1F1a\
-                           aZJNr6tXQy39FtdXrDq2zfwZdDmLW6sPmhvJrBO4yWjuG3wh1paPHy+rBHOjYt+9\
-                           Pbl/FmDDjIzF8B2LZDuLdnS94Fs/JhogJL/XF4b6RLW60gEnYFjL+ebYdV/f3JYi\
-                           ccQxY4imvbB2URlIO3t+aG9WMmhHZbbOi/HBdFG1fB7Hsa9Ek2FXshULzEDCJcMz\
-                           n8HD96XbVBmlaz9nYIcZ83eCOhra67FfFy4pIE1M9saxYJg/OJrMHG12r89yUQID\
-                           AQABoIG8MIG5BgkqhkiG9w0BCQ4xgaswgagwCQYDVR0TBAIwADALBgNVHQ8EBAMC\
-                           BeAwJwYDVR0lBCAwHgYIKwYBBQUHAwIGCCsGAQUFBwMBBggrBgEFBQcDAzBlBgNV\
-                           HREEXjBcghFkZXZlbC5leGFtcGxlLmNvbYIQaXB2Ni5leGFtcGxlLmNvbYIQaXB2\
-                           NC5leGFtcGxlLmNvbYIQdGVzdC5leGFtcGxlLmNvbYIRcGFydHkuZXhhbXBsZS5j\
-                           b20wDQYJKoZIhvcNAQELBQADggEBANaSDnpQUGcGypAaafJKAGME2Od8F4pvKjKF\
-                           lREoWC7JFGIGE/pUrnvrE7qIFmCM3mnFWXEHvResFsdPmEWar+1jMdFinxBg0+J+\
-                           Op0fxOwfHpxs++8hPsQgnDdL9pIjYFwmIAm64jnyq6wsYIl5CpkvBjGVRVddXkTb\
-                           VDWhWaGncSdDur6++dp2OAGYTAv4XIHc0nhtcBoxeL4VhjcuksOdGg3JF02gW6Rc\
-                           B1gipqD0jun8kPgWcQY22zhmP2HuPp0y58t9cu9FsnUcAFa//5pQA1LuaSFp65D4\
-                           92uaByS3lH18xzrkygzn1BeHRpo0fk4I9Rk8uy2QygCk43Pv6SU=",
-        )
-        .expect("cert der");
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// ```not_rust
+/// UserNotice ::= SEQUENCE {
+///      noticeRef        NoticeReference OPTIONAL,
+///      explicitText     DisplayText OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct UserNotice {
+    pub notice_ref: Option<NoticeReference>,
+    pub explicit_text: Option<DisplayText>,
+}
 
-        let encoded = &cert_der[359..359 + 3 + 168];
+impl<'de> de::Deserialize<'de> for UserNotice {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
 
-        let mut key_usage = KeyUsage::new(3);
-        key_usage.set_digital_signature(true);
-        key_usage.set_content_commitment(true);
-        key_usage.set_key_encipherment(true);
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = UserNotice;
 
-        let extensions = Extensions(vec![
-            Extension::new_basic_constraints(None, None).into_non_critical(),
-            Extension::new_key_usage(key_usage).into_non_critical(),
-            Extension::new_extended_key_usage(vec![
-                oids::kp_client_auth(),
-                oids::kp_server_auth(),
-                oids::kp_code_signing(),
-            ])
-            .into_non_critical(),
-            Extension::new_subject_alt_name(vec![
-                GeneralName::DnsName(IA5String::from_string("devel.example.com".into()).unwrap().into()),
-                GeneralName::DnsName(IA5String::from_string("ipv6.example.com".into()).unwrap().into()),
-                GeneralName::DnsName(IA5String::from_string("ipv4.example.com".into()).unwrap().into()),
-                GeneralName::DnsName(IA5String::from_string("test.example.com".into()).unwrap().into()),
-                GeneralName::DnsName(IA5String::from_string("party.example.com".into()).unwrap().into()),
-            ])
-            .into_non_critical(),
-        ]);
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded user notice")
+            }
 
-        check_serde!(extensions: Extensions in encoded);
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let next_tag = seq
+                    .next_element::<TagPeeker>()
+                    .unwrap_or(None)
+                    .map(|peeker| peeker.next_tag);
+                let notice_ref = if next_tag == Some(Tag::SEQUENCE) {
+                    Some(seq_next_element!(seq, UserNotice, "notice reference"))
+                } else {
+                    None
+                };
+
+                let next_tag = seq
+                    .next_element::<TagPeeker>()
+                    .unwrap_or(None)
+                    .map(|peeker| peeker.next_tag);
+                let explicit_text = if next_tag.is_some() {
+                    Some(seq_next_element!(seq, UserNotice, "explicit text"))
+                } else {
+                    None
+                };
+
+                Ok(UserNotice {
+                    notice_ref,
+                    explicit_text,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// ```not_rust
+/// NoticeReference ::= SEQUENCE {
+///      organization     DisplayText,
+///      noticeNumbers    SEQUENCE OF INTEGER }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct NoticeReference {
+    pub organization: DisplayText,
+    pub notice_numbers: Asn1SequenceOf<IntegerAsn1>,
+}
+
+/// [RFC 5280 #4.2.1.4](https://tools.ietf.org/html/rfc5280#section-4.2.1.4)
+///
+/// VisibleString characters are a subset of IA5String ones and are therefore held as such.
+///
+/// ```not_rust
+/// DisplayText ::= CHOICE {
+///      ia5String        IA5String      (SIZE (1..200)),
+///      visibleString    VisibleString  (SIZE (1..200)),
+///      bmpString        BMPString      (SIZE (1..200)),
+///      utf8String       UTF8String     (SIZE (1..200)) }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum DisplayText {
+    Ia5String(IA5StringAsn1),
+    VisibleString(IA5StringAsn1),
+    BmpString(BMPStringAsn1),
+    Utf8String(String),
+}
+
+impl fmt::Display for DisplayText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayText::Ia5String(string) | DisplayText::VisibleString(string) => string.0.fmt(f),
+            DisplayText::BmpString(string) => {
+                let utf16 = string
+                    .as_bytes()
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]));
+                core::char::decode_utf16(utf16)
+                    .map(|c| c.unwrap_or(core::char::REPLACEMENT_CHARACTER))
+                    .try_for_each(|c| write!(f, "{}", c))
+            }
+            DisplayText::Utf8String(string) => string.fmt(f),
+        }
+    }
+}
+
+impl From<&str> for DisplayText {
+    fn from(string: &str) -> Self {
+        Self::Utf8String(string.to_owned())
+    }
+}
+
+impl From<String> for DisplayText {
+    fn from(string: String) -> Self {
+        Self::Utf8String(string)
+    }
+}
+
+// VisibleString is not supported by picky-asn1: its encoding is retagged from / to IA5String
+
+impl ser::Serialize for DisplayText {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            DisplayText::Ia5String(string) => string.serialize(serializer),
+            DisplayText::VisibleString(string) => {
+                let mut raw_der = picky_asn1_der::to_vec(string).map_err(ser::Error::custom)?;
+                raw_der[0] = Tag::VISIBLE_STRING.inner();
+                picky_asn1_der::Asn1RawDer(raw_der).serialize(serializer)
+            }
+            DisplayText::BmpString(string) => string.serialize(serializer),
+            DisplayText::Utf8String(string) => string.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for DisplayText {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut raw_der = picky_asn1_der::Asn1RawDer::deserialize(deserializer)?.0;
+        match Tag::from(raw_der[0]) {
+            Tag::IA5_STRING => Ok(DisplayText::Ia5String(
+                picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?,
+            )),
+            Tag::VISIBLE_STRING => {
+                raw_der[0] = Tag::IA5_STRING.inner();
+                Ok(DisplayText::VisibleString(
+                    picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?,
+                ))
+            }
+            Tag::BMP_STRING => Ok(DisplayText::BmpString(
+                picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?,
+            )),
+            Tag::UTF8_STRING => Ok(DisplayText::Utf8String(
+                picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?,
+            )),
+            _ => Err(serde_invalid_value!(
+                DisplayText,
+                "unknown string type",
+                "a supported DisplayText choice"
+            )),
+        }
+    }
+}
+
+/// [RFC 5280 #4.2.1.5](https://tools.ietf.org/html/rfc5280#section-4.2.1.5)
+///
+/// ```not_rust
+/// PolicyMappings ::= SEQUENCE SIZE (1..MAX) OF SEQUENCE {
+///      issuerDomainPolicy      CertPolicyId,
+///      subjectDomainPolicy     CertPolicyId }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PolicyMappings(Asn1SequenceOf<PolicyMapping>);
+
+impl From<Vec<PolicyMapping>> for PolicyMappings {
+    fn from(mappings: Vec<PolicyMapping>) -> Self {
+        Self::new(mappings)
+    }
+}
+
+impl PolicyMappings {
+    pub fn new(mappings: Vec<PolicyMapping>) -> Self {
+        Self(mappings.into())
+    }
+
+    pub fn iter(&self) -> Iter<PolicyMapping> {
+        (self.0).0.iter()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PolicyMapping {
+    pub issuer_domain_policy: ObjectIdentifierAsn1,
+    pub subject_domain_policy: ObjectIdentifierAsn1,
+}
+
+impl PolicyMapping {
+    pub fn new<I, S>(issuer_domain_policy: I, subject_domain_policy: S) -> Self
+    where
+        I: Into<ObjectIdentifierAsn1>,
+        S: Into<ObjectIdentifierAsn1>,
+    {
+        Self {
+            issuer_domain_policy: issuer_domain_policy.into(),
+            subject_domain_policy: subject_domain_policy.into(),
+        }
+    }
+}
+
+/// [RFC 5280 #4.2.1.6](https://tools.ietf.org/html/rfc5280#section-4.2.1.6)
+type SubjectAltName = GeneralNames;
+
+/// [RFC 5280 #4.2.1.7](https://tools.ietf.org/html/rfc5280#section-4.2.1.7)
+type IssuerAltName = GeneralNames;
+
+/// [RFC 5280 #4.2.1.9](https://tools.ietf.org/html/rfc5280#section-4.2.1.9)
+///
+/// ```not_rust
+/// BasicConstraints ::= SEQUENCE {
+///      cA                      BOOLEAN DEFAULT FALSE,
+///      pathLenConstraint       INTEGER (0..MAX) OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct BasicConstraints {
+    ca: Optional<Option<bool>>, // default is false
+    path_len_constraint: Optional<Option<u8>>,
+}
+
+impl BasicConstraints {
+    pub fn ca(&self) -> Option<bool> {
+        self.ca.0
+    }
+
+    pub fn pathlen(&self) -> Option<u8> {
+        self.path_len_constraint.0
+    }
+}
+
+impl<'de> de::Deserialize<'de> for BasicConstraints {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = BasicConstraints;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded basic constraints extension")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(BasicConstraints {
+                    ca: Optional(seq.next_element().unwrap_or(Some(None)).unwrap_or(None)),
+                    path_len_constraint: Optional(seq.next_element().unwrap_or(Some(None)).unwrap_or(None)),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.10](https://tools.ietf.org/html/rfc5280#section-4.2.1.10)
+///
+/// ```not_rust
+/// NameConstraints ::= SEQUENCE {
+///      permittedSubtrees       [0]     GeneralSubtrees OPTIONAL,
+///      excludedSubtrees        [1]     GeneralSubtrees OPTIONAL }
+///
+/// GeneralSubtrees ::= SEQUENCE SIZE (1..MAX) OF GeneralSubtree
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct NameConstraints {
+    permitted_subtrees: Option<Asn1SequenceOf<GeneralSubtree>>,
+    excluded_subtrees: Option<Asn1SequenceOf<GeneralSubtree>>,
+}
+
+impl NameConstraints {
+    /// Empty lists are omitted from the encoded extension.
+    pub fn new(permitted_subtrees: Vec<GeneralSubtree>, excluded_subtrees: Vec<GeneralSubtree>) -> Self {
+        let to_subtrees = |subtrees: Vec<GeneralSubtree>| {
+            if subtrees.is_empty() {
+                None
+            } else {
+                Some(Asn1SequenceOf(subtrees))
+            }
+        };
+
+        Self {
+            permitted_subtrees: to_subtrees(permitted_subtrees),
+            excluded_subtrees: to_subtrees(excluded_subtrees),
+        }
+    }
+
+    pub fn permitted_subtrees(&self) -> &[GeneralSubtree] {
+        self.permitted_subtrees
+            .as_ref()
+            .map(|subtrees| subtrees.0.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn excluded_subtrees(&self) -> &[GeneralSubtree] {
+        self.excluded_subtrees
+            .as_ref()
+            .map(|subtrees| subtrees.0.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+// This is a workaround for constructed encoding as implicit
+
+fn h_serialize_general_subtrees<S: ser::Serializer>(
+    subtrees: &Asn1SequenceOf<GeneralSubtree>,
+    tag: Tag,
+) -> Result<picky_asn1_der::Asn1RawDer, S::Error> {
+    let mut raw_der = picky_asn1_der::to_vec(subtrees).map_err(ser::Error::custom)?;
+    raw_der[0] = tag.inner();
+    Ok(picky_asn1_der::Asn1RawDer(raw_der))
+}
+
+fn h_deserialize_general_subtrees<'de, A: de::SeqAccess<'de>>(
+    seq: &mut A,
+    tag: Tag,
+) -> Result<Option<Asn1SequenceOf<GeneralSubtree>>, A::Error> {
+    let next_tag = seq
+        .next_element::<TagPeeker>()
+        .unwrap_or(None)
+        .map(|peeker| peeker.next_tag);
+    if next_tag != Some(tag) {
+        return Ok(None);
+    }
+
+    let mut raw_der = seq_next_element!(seq, picky_asn1_der::Asn1RawDer, NameConstraints, "GeneralSubtrees").0;
+    raw_der[0] = Tag::SEQUENCE.inner();
+    let subtrees = picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?;
+    Ok(Some(subtrees))
+}
+
+impl ser::Serialize for NameConstraints {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(None)?;
+
+        if let Some(permitted_subtrees) = &self.permitted_subtrees {
+            seq.serialize_element(&h_serialize_general_subtrees::<S>(
+                permitted_subtrees,
+                Tag::context_specific_constructed(0),
+            )?)?;
+        }
+
+        if let Some(excluded_subtrees) = &self.excluded_subtrees {
+            seq.serialize_element(&h_serialize_general_subtrees::<S>(
+                excluded_subtrees,
+                Tag::context_specific_constructed(1),
+            )?)?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de> de::Deserialize<'de> for NameConstraints {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = NameConstraints;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded name constraints extension")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(NameConstraints {
+                    permitted_subtrees: h_deserialize_general_subtrees(&mut seq, Tag::context_specific_constructed(0))?,
+                    excluded_subtrees: h_deserialize_general_subtrees(&mut seq, Tag::context_specific_constructed(1))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.10](https://tools.ietf.org/html/rfc5280#section-4.2.1.10)
+///
+/// ```not_rust
+/// GeneralSubtree ::= SEQUENCE {
+///      base                    GeneralName,
+///      minimum         [0]     BaseDistance DEFAULT 0,
+///      maximum         [1]     BaseDistance OPTIONAL }
+///
+/// BaseDistance ::= INTEGER (0..MAX)
+/// ```
+///
+/// Within the PKIX profile, `minimum` and `maximum` are never used.
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct GeneralSubtree {
+    base: GeneralName,
+    minimum: Option<ImplicitContextTag0<IntegerAsn1>>, // default is 0
+    maximum: Option<ImplicitContextTag1<IntegerAsn1>>,
+}
+
+impl GeneralSubtree {
+    pub fn new(base: GeneralName) -> Self {
+        Self {
+            base,
+            minimum: None,
+            maximum: None,
+        }
+    }
+
+    pub fn base(&self) -> &GeneralName {
+        &self.base
+    }
+}
+
+impl From<GeneralName> for GeneralSubtree {
+    fn from(base: GeneralName) -> Self {
+        Self::new(base)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for GeneralSubtree {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = GeneralSubtree;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded general subtree")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(GeneralSubtree {
+                    base: seq_next_element!(seq, GeneralSubtree, "base"),
+                    minimum: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
+                    maximum: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.11](https://tools.ietf.org/html/rfc5280#section-4.2.1.11)
+///
+/// ```not_rust
+/// PolicyConstraints ::= SEQUENCE {
+///      requireExplicitPolicy           [0] SkipCerts OPTIONAL,
+///      inhibitPolicyMapping            [1] SkipCerts OPTIONAL }
+///
+/// SkipCerts ::= INTEGER (0..MAX)
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct PolicyConstraints {
+    require_explicit_policy: Option<ImplicitContextTag0<u8>>,
+    inhibit_policy_mapping: Option<ImplicitContextTag1<u8>>,
+}
+
+impl PolicyConstraints {
+    pub fn require_explicit_policy(&self) -> Option<u8> {
+        self.require_explicit_policy.as_ref().map(|skip_certs| skip_certs.0)
+    }
+
+    pub fn inhibit_policy_mapping(&self) -> Option<u8> {
+        self.inhibit_policy_mapping.as_ref().map(|skip_certs| skip_certs.0)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for PolicyConstraints {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = PolicyConstraints;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded policy constraints extension")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(PolicyConstraints {
+                    require_explicit_policy: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
+                    inhibit_policy_mapping: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.12](https://tools.ietf.org/html/rfc5280#section-4.2.1.12)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ExtendedKeyUsage(Asn1SequenceOf<ObjectIdentifierAsn1>);
+
+impl<OID: Into<ObjectIdentifierAsn1>> From<Vec<OID>> for ExtendedKeyUsage {
+    fn from(purpose_oids: Vec<OID>) -> Self {
+        ExtendedKeyUsage::new(purpose_oids)
+    }
+}
+
+impl ExtendedKeyUsage {
+    pub fn new<OID: Into<ObjectIdentifierAsn1>>(purpose_oids: Vec<OID>) -> Self {
+        Self(
+            purpose_oids
+                .into_iter()
+                .map(|oid| oid.into())
+                .collect::<Vec<_>>()
+                .into(),
+        )
+    }
+
+    pub fn iter(&self) -> Iter<ObjectIdentifierAsn1> {
+        (self.0).0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<ObjectIdentifierAsn1> {
+        (self.0).0.iter_mut()
+    }
+
+    pub fn contains<C: PartialEq<oid::ObjectIdentifier>>(&self, item: C) -> bool {
+        (self.0).0.iter().any(|id| item.eq(&id.0))
+    }
+}
+
+/// [RFC 5280 #4.2.1.13](https://tools.ietf.org/html/rfc5280#section-4.2.1.13)
+///
+/// ```not_rust
+/// CRLDistributionPoints ::= SEQUENCE SIZE (1..MAX) OF DistributionPoint
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CrlDistributionPoints(Asn1SequenceOf<DistributionPoint>);
+
+impl From<Vec<DistributionPoint>> for CrlDistributionPoints {
+    fn from(distribution_points: Vec<DistributionPoint>) -> Self {
+        Self::new(distribution_points)
+    }
+}
+
+impl CrlDistributionPoints {
+    pub fn new(distribution_points: Vec<DistributionPoint>) -> Self {
+        Self(distribution_points.into())
+    }
+
+    pub fn iter(&self) -> Iter<DistributionPoint> {
+        (self.0).0.iter()
+    }
+}
+
+/// [RFC 5280 #4.2.1.13](https://tools.ietf.org/html/rfc5280#section-4.2.1.13)
+///
+/// ```not_rust
+/// DistributionPoint ::= SEQUENCE {
+///      distributionPoint       [0]     DistributionPointName OPTIONAL,
+///      reasons                 [1]     ReasonFlags OPTIONAL,
+///      cRLIssuer               [2]     GeneralNames OPTIONAL }
+///
+/// ReasonFlags ::= BIT STRING
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct DistributionPoint {
+    pub distribution_point: Option<DistributionPointName>,
+    pub reasons: Option<BitStringAsn1>,
+    pub crl_issuer: Option<GeneralNames>,
+}
+
+impl DistributionPoint {
+    /// Distribution point for all revocation reasons, identified by its full name (usually an URI).
+    pub fn new_full_name<N: Into<GeneralNames>>(full_name: N) -> Self {
+        Self {
+            distribution_point: Some(DistributionPointName::FullName(full_name.into())),
+            reasons: None,
+            crl_issuer: None,
+        }
+    }
+}
+
+impl ser::Serialize for DistributionPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        use ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(None)?;
+
+        if let Some(distribution_point) = &self.distribution_point {
+            seq.serialize_element(&ExplicitContextTag0(distribution_point))?;
+        }
+
+        if let Some(reasons) = &self.reasons {
+            seq.serialize_element(&ImplicitContextTag1(reasons))?;
+        }
+
+        if let Some(crl_issuer) = &self.crl_issuer {
+            let mut raw_der = picky_asn1_der::to_vec(crl_issuer).map_err(ser::Error::custom)?;
+            raw_der[0] = Tag::context_specific_constructed(2).inner();
+            seq.serialize_element(&picky_asn1_der::Asn1RawDer(raw_der))?;
+        }
+
+        seq.end()
+    }
+}
+
+impl<'de> de::Deserialize<'de> for DistributionPoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = DistributionPoint;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded distribution point")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let distribution_point: Option<ExplicitContextTag0<DistributionPointName>> =
+                    seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
+                let reasons: Option<ImplicitContextTag1<BitStringAsn1>> =
+                    seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
+
+                let next_tag = seq
+                    .next_element::<TagPeeker>()
+                    .unwrap_or(None)
+                    .map(|peeker| peeker.next_tag);
+                let crl_issuer = if next_tag == Some(Tag::context_specific_constructed(2)) {
+                    let mut raw_der =
+                        seq_next_element!(seq, picky_asn1_der::Asn1RawDer, DistributionPoint, "cRLIssuer").0;
+                    raw_der[0] = Tag::SEQUENCE.inner();
+                    Some(picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?)
+                } else {
+                    None
+                };
+
+                Ok(DistributionPoint {
+                    distribution_point: distribution_point.map(|dp| dp.0),
+                    reasons: reasons.map(|reasons| reasons.0),
+                    crl_issuer,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 5280 #4.2.1.13](https://tools.ietf.org/html/rfc5280#section-4.2.1.13)
+///
+/// ```not_rust
+/// DistributionPointName ::= CHOICE {
+///      fullName                [0]     GeneralNames,
+///      nameRelativeToCRLIssuer [1]     RelativeDistinguishedName }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum DistributionPointName {
+    FullName(GeneralNames),
+    NameRelativeToCrlIssuer(RelativeDistinguishedName),
+}
+
+impl ser::Serialize for DistributionPointName {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        let (mut raw_der, tag) = match self {
+            DistributionPointName::FullName(full_name) => (
+                picky_asn1_der::to_vec(full_name).map_err(ser::Error::custom)?,
+                Tag::context_specific_constructed(0),
+            ),
+            DistributionPointName::NameRelativeToCrlIssuer(name) => (
+                picky_asn1_der::to_vec(name).map_err(ser::Error::custom)?,
+                Tag::context_specific_constructed(1),
+            ),
+        };
+        raw_der[0] = tag.inner();
+        picky_asn1_der::Asn1RawDer(raw_der).serialize(serializer)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for DistributionPointName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut raw_der = picky_asn1_der::Asn1RawDer::deserialize(deserializer)?.0;
+        match Tag::from(raw_der[0]) {
+            tag if tag == Tag::context_specific_constructed(0) => {
+                raw_der[0] = Tag::SEQUENCE.inner();
+                let full_name = picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?;
+                Ok(DistributionPointName::FullName(full_name))
+            }
+            tag if tag == Tag::context_specific_constructed(1) => {
+                raw_der[0] = Tag::SET.inner();
+                let name = picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?;
+                Ok(DistributionPointName::NameRelativeToCrlIssuer(name))
+            }
+            _ => Err(serde_invalid_value!(
+                DistributionPointName,
+                "unknown choice value",
+                "a supported DistributionPointName choice"
+            )),
+        }
+    }
+}
+
+/// [RFC 5280 #4.2.2.1](https://tools.ietf.org/html/rfc5280#section-4.2.2.1)
+///
+/// ```not_rust
+/// AuthorityInfoAccessSyntax  ::=
+///         SEQUENCE SIZE (1..MAX) OF AccessDescription
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AuthorityInfoAccess(Asn1SequenceOf<AccessDescription>);
+
+impl From<Vec<AccessDescription>> for AuthorityInfoAccess {
+    fn from(access_descriptions: Vec<AccessDescription>) -> Self {
+        Self::new(access_descriptions)
+    }
+}
+
+impl AuthorityInfoAccess {
+    pub fn new(access_descriptions: Vec<AccessDescription>) -> Self {
+        Self(access_descriptions.into())
+    }
+
+    pub fn iter(&self) -> Iter<AccessDescription> {
+        (self.0).0.iter()
+    }
+}
+
+/// [RFC 5280 #4.2.2.1](https://tools.ietf.org/html/rfc5280#section-4.2.2.1)
+///
+/// ```not_rust
+/// AccessDescription  ::=  SEQUENCE {
+///         accessMethod          OBJECT IDENTIFIER,
+///         accessLocation        GeneralName  }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AccessDescription {
+    pub access_method: ObjectIdentifierAsn1,
+    pub access_location: GeneralName,
+}
+
+impl AccessDescription {
+    pub fn new<M, L>(access_method: M, access_location: L) -> Self
+    where
+        M: Into<ObjectIdentifierAsn1>,
+        L: Into<GeneralName>,
+    {
+        Self {
+            access_method: access_method.into(),
+            access_location: access_location.into(),
+        }
+    }
+
+    /// Location of the OCSP responder of the issuer.
+    pub fn new_ocsp<L: Into<GeneralName>>(access_location: L) -> Self {
+        Self::new(oids::id_ad_ocsp(), access_location)
+    }
+
+    /// Location of certificates issued to the issuer (usually an URI).
+    pub fn new_ca_issuers<L: Into<GeneralName>>(access_location: L) -> Self {
+        Self::new(oids::id_ad_ca_issuers(), access_location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeneralName, Name};
+    use picky_asn1::restricted_string::IA5String;
+
+    #[test]
+    fn key_usage() {
+        let encoded: [u8; 4] = [0x03, 0x02, 0x01, 0xA0];
+        let mut key_usage = KeyUsage::new(7);
+        key_usage.set_digital_signature(true);
+        key_usage.set_key_encipherment(true);
+        assert_eq!(key_usage.as_bytes(), &[0xA0]);
+        check_serde!(key_usage: KeyUsage in encoded);
+    }
+
+    #[test]
+    fn eku_ku_bc_san_extensions() {
+        let cert_der = base64::decode(
+            "MIIDIjCCAgoCAQAwIDELMAkGA1UEBhMCRlIxETAPBgNVBAMMCERyYXBlYXUhMIIB\
+                           IjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEA5GqDEM7AfctJizsFEqtAvXd5\
+                           Fl1GtyXDAnx68MUTuSL22t8aBZoCCi3/9AlS75uUqKggHnRuY2MRYPQaUzpE1F1a\
+                           aZJNr6tXQy39FtdXrDq2zfwZdDmLW6sPmhvJrBO4yWjuG3wh1paPHy+rBHOjYt+9\
+                           Pbl/FmDDjIzF8B2LZDuLdnS94Fs/JhogJL/XF4b6RLW60gEnYFjL+ebYdV/f3JYi\
+                           ccQxY4imvbB2URlIO3t+aG9WMmhHZbbOi/HBdFG1fB7Hsa9Ek2FXshULzEDCJcMz\
+                           n8HD96XbVBmlaz9nYIcZ83eCOhra67FfFy4pIE1M9saxYJg/OJrMHG12r89yUQID\
+                           AQABoIG8MIG5BgkqhkiG9w0BCQ4xgaswgagwCQYDVR0TBAIwADALBgNVHQ8EBAMC\
+                           BeAwJwYDVR0lBCAwHgYIKwYBBQUHAwIGCCsGAQUFBwMBBggrBgEFBQcDAzBlBgNV\
+                           HREEXjBcghFkZXZlbC5leGFtcGxlLmNvbYIQaXB2Ni5leGFtcGxlLmNvbYIQaXB2\
+                           NC5leGFtcGxlLmNvbYIQdGVzdC5leGFtcGxlLmNvbYIRcGFydHkuZXhhbXBsZS5j\
+                           b20wDQYJKoZIhvcNAQELBQADggEBANaSDnpQUGcGypAaafJKAGME2Od8F4pvKjKF\
+                           lREoWC7JFGIGE/pUrnvrE7qIFmCM3mnFWXEHvResFsdPmEWar+1jMdFinxBg0+J+\
+                           Op0fxOwfHpxs++8hPsQgnDdL9pIjYFwmIAm64jnyq6wsYIl5CpkvBjGVRVddXkTb\
+                           VDWhWaGncSdDur6++dp2OAGYTAv4XIHc0nhtcBoxeL4VhjcuksOdGg3JF02gW6Rc\
+                           B1gipqD0jun8kPgWcQY22zhmP2HuPp0y58t9cu9FsnUcAFa//5pQA1LuaSFp65D4\
+                           92uaByS3lH18xzrkygzn1BeHRpo0fk4I9Rk8uy2QygCk43Pv6SU=",
+        )
+        .expect("cert der");
+
+        let encoded = &cert_der[359..359 + 3 + 168];
+
+        let mut key_usage = KeyUsage::new(3);
+        key_usage.set_digital_signature(true);
+        key_usage.set_content_commitment(true);
+        key_usage.set_key_encipherment(true);
+
+        let extensions = Extensions(vec![
+            Extension::new_basic_constraints(None, None).into_non_critical(),
+            Extension::new_key_usage(key_usage).into_non_critical(),
+            Extension::new_extended_key_usage(vec![
+                oids::kp_client_auth(),
+                oids::kp_server_auth(),
+                oids::kp_code_signing(),
+            ])
+            .into_non_critical(),
+            Extension::new_subject_alt_name(vec![
+                GeneralName::DnsName(IA5String::from_string("devel.example.com".into()).unwrap().into()),
+                GeneralName::DnsName(IA5String::from_string("ipv6.example.com".into()).unwrap().into()),
+                GeneralName::DnsName(IA5String::from_string("ipv4.example.com".into()).unwrap().into()),
+                GeneralName::DnsName(IA5String::from_string("test.example.com".into()).unwrap().into()),
+                GeneralName::DnsName(IA5String::from_string("party.example.com".into()).unwrap().into()),
+            ])
+            .into_non_critical(),
+        ]);
+
+        check_serde!(extensions: Extensions in encoded);
+    }
+
+    #[test]
+    fn name_constraints() {
+        let encoded = [
+            0x30, 0x3E, 0x06, 0x03, 0x55, 0x1D, 0x1E, 0x01, 0x01, 0xFF, 0x04, 0x34, 0x30, 0x32, 0xA0, 0x1B, 0x30, 0x0D,
+            0x82, 0x0B, 0x65, 0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63, 0x6F, 0x6D, 0x30, 0x0A, 0x87, 0x08, 0xC0,
+            0xA8, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0xA1, 0x13, 0x30, 0x11, 0x82, 0x0F, 0x62, 0x61, 0x64, 0x2E, 0x65,
+            0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63, 0x6F, 0x6D,
+        ];
+
+        let name_constraints = Extension::new_name_constraints(NameConstraints::new(
+            vec![
+                GeneralName::DnsName(IA5String::from_string("example.com".into()).unwrap().into()).into(),
+                GeneralName::IpAddress(vec![192, 168, 0, 0, 255, 255, 0, 0].into()).into(),
+            ],
+            vec![GeneralName::DnsName(IA5String::from_string("bad.example.com".into()).unwrap().into()).into()],
+        ));
+
+        check_serde!(name_constraints: Extension in encoded);
+
+        match name_constraints.extn_value() {
+            ExtensionView::NameConstraints(nc) => {
+                assert_eq!(nc.permitted_subtrees().len(), 2);
+                assert_eq!(nc.excluded_subtrees().len(), 1);
+            }
+            _ => panic!("unexpected extension view"),
+        }
+    }
+
+    #[test]
+    fn name_constraints_excluded_only() {
+        let name_constraints = NameConstraints::new(
+            Vec::new(),
+            vec![GeneralName::DnsName(IA5String::from_string("bad.example.com".into()).unwrap().into()).into()],
+        );
+        let encoded = picky_asn1_der::to_vec(&name_constraints).unwrap();
+        assert_eq!(&encoded[..4], &[0x30, 0x15, 0xA1, 0x13]);
+
+        let decoded: NameConstraints = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, name_constraints);
+        assert!(decoded.permitted_subtrees().is_empty());
+    }
+
+    #[test]
+    fn policy_extensions() {
+        let encoded = base64::decode(
+            "MIGJBgNVHSABAf8EfzB9MAUGAyoDBDB0BgkrBgEEAYaNHwEwZzAjBggrBgEFBQcC\
+             ARYXaHR0cHM6Ly9leGFtcGxlLmNvbS9jcHMwQAYIKwYBBQUHAgIwNDAeGhFPcmdh\
+             bmlzYXRpb24gTmFtZTAJAgEBAgECAgEDGhJFeHBsaWNpdCBUZXh0IEhlcmUwFQYD\
+             VR0hBA4wDDAKBgMqAwQGAyoDBTAPBgNVHSQECDAGgAEAgQEBMAoGA1UdNgQDAgEC",
+        )
+        .expect("extensions der");
+
+        let ia5 = |s: &str| IA5String::from_string(s.into()).unwrap();
+        let visible = |s: &str| DisplayText::VisibleString(ia5(s).into());
+
+        let certificate_policies = Extension::new_certificate_policies(vec![
+            PolicyInformation::new(oid::ObjectIdentifier::try_from("1.2.3.4").unwrap(), Vec::new()),
+            PolicyInformation::new(
+                oid::ObjectIdentifier::try_from("1.3.6.1.4.1.99999.1").unwrap(),
+                vec![
+                    PolicyQualifierInfo::new_cps_uri(ia5("https://example.com/cps")),
+                    PolicyQualifierInfo::new_user_notice(UserNotice {
+                        notice_ref: Some(NoticeReference {
+                            organization: visible("Organisation Name"),
+                            notice_numbers: vec![
+                                IntegerAsn1::from(vec![1]),
+                                IntegerAsn1::from(vec![2]),
+                                IntegerAsn1::from(vec![3]),
+                            ]
+                            .into(),
+                        }),
+                        explicit_text: Some(visible("Explicit Text Here")),
+                    }),
+                ],
+            ),
+        ])
+        .into_critical();
+        check_serde!(certificate_policies: Extension in encoded[0..140]);
+
+        let policy_mappings = Extension::new_policy_mappings(vec![PolicyMapping::new(
+            oid::ObjectIdentifier::try_from("1.2.3.4").unwrap(),
+            oid::ObjectIdentifier::try_from("1.2.3.5").unwrap(),
+        )])
+        .into_non_critical();
+        check_serde!(policy_mappings: Extension in encoded[140..163]);
+
+        let policy_constraints = Extension::new_policy_constraints(0, 1).into_non_critical();
+        check_serde!(policy_constraints: Extension in encoded[163..180]);
+
+        let inhibit_any_policy = Extension::new_inhibit_any_policy(2).into_non_critical();
+        check_serde!(inhibit_any_policy: Extension in encoded[180..192]);
+
+        match certificate_policies.extn_value() {
+            ExtensionView::CertificatePolicies(policies) => {
+                assert!(policies.contains(oid::ObjectIdentifier::try_from("1.2.3.4").unwrap()));
+                let qualifiers = policies.iter().nth(1).unwrap().policy_qualifiers();
+                match &qualifiers[1].qualifier {
+                    Qualifier::UserNotice(notice) => {
+                        assert_eq!(notice.explicit_text.as_ref().unwrap().to_string(), "Explicit Text Here")
+                    }
+                    _ => panic!("unexpected qualifier"),
+                }
+            }
+            _ => panic!("unexpected extension view"),
+        }
+
+        match policy_constraints.extn_value() {
+            ExtensionView::PolicyConstraints(constraints) => {
+                assert_eq!(constraints.require_explicit_policy(), Some(0));
+                assert_eq!(constraints.inhibit_policy_mapping(), Some(1));
+            }
+            _ => panic!("unexpected extension view"),
+        }
+
+        assert_eq!(inhibit_any_policy.extn_value(), ExtensionView::InhibitAnyPolicy(2));
+    }
+
+    #[test]
+    fn display_text() {
+        let encoded = [0x1E, 0x04, 0x00, 0x68, 0x00, 0xE9];
+        let bmp_string: DisplayText = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(bmp_string.to_string(), "hé");
+        assert_eq!(picky_asn1_der::to_vec(&bmp_string).unwrap(), encoded);
+
+        let utf8_string = DisplayText::from("notice");
+        let encoded = picky_asn1_der::to_vec(&utf8_string).unwrap();
+        assert_eq!(encoded[0], Tag::UTF8_STRING.inner());
+        assert_eq!(
+            picky_asn1_der::from_bytes::<DisplayText>(&encoded).unwrap(),
+            utf8_string
+        );
+    }
+
+    #[test]
+    fn unknown_policy_qualifier() {
+        // qualifier with unknown id 1.2.3 and a NULL value
+        let encoded = [0x30, 0x06, 0x06, 0x02, 0x2A, 0x03, 0x05, 0x00];
+        let qualifier_info: PolicyQualifierInfo = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(
+            qualifier_info.qualifier,
+            Qualifier::Other(picky_asn1_der::Asn1RawDer(vec![0x05, 0x00]))
+        );
+        assert_eq!(picky_asn1_der::to_vec(&qualifier_info).unwrap(), encoded);
+    }
+
+    #[test]
+    fn authority_info_access() {
+        let encoded = [
+            0x30, 0x5A, 0x06, 0x08, 0x2B, 0x06, 0x01, 0x05, 0x05, 0x07, 0x01, 0x01, 0x04, 0x4E, 0x30, 0x4C, 0x30, 0x23,
+            0x06, 0x08, 0x2B, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x01, 0x86, 0x17, 0x68, 0x74, 0x74, 0x70, 0x3A, 0x2F,
+            0x2F, 0x6F, 0x63, 0x73, 0x70, 0x2E, 0x65, 0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63, 0x6F, 0x6D, 0x30,
+            0x25, 0x06, 0x08, 0x2B, 0x06, 0x01, 0x05, 0x05, 0x07, 0x30, 0x02, 0x86, 0x19, 0x68, 0x74, 0x74, 0x70, 0x3A,
+            0x2F, 0x2F, 0x65, 0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63, 0x6F, 0x6D, 0x2F, 0x63, 0x61, 0x2E, 0x63,
+            0x72, 0x74,
+        ];
+
+        let aia = Extension::new_authority_info_access(vec![
+            AccessDescription::new_ocsp(GeneralName::Uri(
+                IA5String::from_string("http://ocsp.example.com".into()).unwrap().into(),
+            )),
+            AccessDescription::new_ca_issuers(GeneralName::Uri(
+                IA5String::from_string("http://example.com/ca.crt".into())
+                    .unwrap()
+                    .into(),
+            )),
+        ]);
+
+        check_serde!(aia: Extension in encoded);
+
+        match aia.extn_value() {
+            ExtensionView::AuthorityInfoAccess(aia) => {
+                let methods: Vec<String> = aia.iter().map(|desc| (&desc.access_method.0).into()).collect();
+                assert_eq!(methods, [oids::ID_AD_OCSP, oids::ID_AD_CA_ISSUERS]);
+            }
+            _ => panic!("unexpected extension view"),
+        }
+    }
+
+    #[test]
+    fn crl_distribution_points() {
+        let encoded = [
+            0x30, 0x48, 0x06, 0x03, 0x55, 0x1D, 0x1F, 0x04, 0x41, 0x30, 0x3F, 0x30, 0x3D, 0xA0, 0x1D, 0xA0, 0x1B, 0x86,
+            0x19, 0x68, 0x74, 0x74, 0x70, 0x3A, 0x2F, 0x2F, 0x65, 0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63, 0x6F,
+            0x6D, 0x2F, 0x63, 0x61, 0x2E, 0x63, 0x72, 0x6C, 0x81, 0x02, 0x05, 0x60, 0xA2, 0x18, 0x86, 0x16, 0x68, 0x74,
+            0x74, 0x70, 0x3A, 0x2F, 0x2F, 0x63, 0x72, 0x6C, 0x2E, 0x65, 0x78, 0x61, 0x6D, 0x70, 0x6C, 0x65, 0x2E, 0x63,
+            0x6F, 0x6D,
+        ];
+
+        let mut reasons = BitString::with_len(3);
+        reasons.set(1, true);
+        reasons.set(2, true);
+
+        let distribution_point = DistributionPoint {
+            reasons: Some(reasons.into()),
+            crl_issuer: Some(
+                vec![GeneralName::Uri(
+                    IA5String::from_string("http://crl.example.com".into()).unwrap().into(),
+                )]
+                .into(),
+            ),
+            ..DistributionPoint::new_full_name(vec![GeneralName::Uri(
+                IA5String::from_string("http://example.com/ca.crl".into())
+                    .unwrap()
+                    .into(),
+            )])
+        };
+        let cdp = Extension::new_crl_distribution_points(vec![distribution_point]);
+
+        check_serde!(cdp: Extension in encoded);
+
+        match cdp.extn_value() {
+            ExtensionView::CrlDistributionPoints(cdp) => {
+                let distribution_point = cdp.iter().next().unwrap();
+                assert!(matches!(
+                    distribution_point.distribution_point,
+                    Some(DistributionPointName::FullName(_))
+                ));
+                assert_eq!(distribution_point.crl_issuer.as_ref().unwrap().0.len(), 1);
+            }
+            _ => panic!("unexpected extension view"),
+        }
+
+        let relative_name = DistributionPoint {
+            distribution_point: Some(DistributionPointName::NameRelativeToCrlIssuer(
+                Name::new_common_name("CRL1").0 .0[0].clone(),
+            )),
+            reasons: None,
+            crl_issuer: None,
+        };
+        let encoded = picky_asn1_der::to_vec(&relative_name).unwrap();
+        assert_eq!(encoded[2], 0xA0);
+        assert_eq!(encoded[4], 0xA1);
+        let decoded: DistributionPoint = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, relative_name);
     }
 }
@@ -6,9 +6,11 @@ pub mod attribute;
 pub mod attribute_type_and_value;
 pub mod certificate;
 pub mod certification_request;
+pub mod crl;
 pub mod directory_string;
 pub mod extension;
 pub mod name;
+pub mod ocsp;
 pub mod oids;
 #[cfg(feature = "pkcs7")]
 pub mod pkcs7;
@@ -22,9 +24,11 @@ pub use attribute::*;
 pub use attribute_type_and_value::*;
 pub use certificate::*;
 pub use certification_request::*;
+pub use crl::*;
 pub use directory_string::*;
 pub use extension::*;
 pub use name::*;
+pub use ocsp::*;
 #[cfg(feature = "pkcs7")]
 pub use pkcs7::*;
 pub use private_key_info::*;
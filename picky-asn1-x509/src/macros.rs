@@ -29,6 +29,43 @@ macro_rules! seq_next_element {
     }};
 }
 
+/// Implements DER serialization and deserialization of a fieldless enum as an ASN.1 ENUMERATED value.
+///
+/// ENUMERATED isn't supported by picky-asn1-der, so the value is handled as raw DER (single byte contents).
+macro_rules! asn1_enumerated {
+    ($typ:ident { $( $variant:ident = $value:literal ),+ $(,)? }) => {
+        impl serde::Serialize for $typ {
+            fn serialize<S>(&self, serializer: S) -> Result<<S as serde::Serializer>::Ok, <S as serde::Serializer>::Error>
+            where
+                S: serde::Serializer,
+            {
+                let value: u8 = match self {
+                    $( $typ::$variant => $value, )+
+                };
+                picky_asn1_der::Asn1RawDer(vec![picky_asn1::tag::Tag::ENUMERATED.inner(), 0x01, value]).serialize(serializer)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $typ {
+            fn deserialize<D>(deserializer: D) -> Result<Self, <D as serde::Deserializer<'de>>::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                use serde::de;
+
+                let raw_der = picky_asn1_der::Asn1RawDer::deserialize(deserializer)?.0;
+                match raw_der.as_slice() {
+                    [tag, 0x01, value] if *tag == picky_asn1::tag::Tag::ENUMERATED.inner() => match value {
+                        $( $value => Ok($typ::$variant), )+
+                        _ => Err(serde_invalid_value!($typ, "unknown enumerated value", "a known enumerated value")),
+                    },
+                    _ => Err(serde_invalid_value!($typ, "not an enumerated value", "a DER-encoded ENUMERATED")),
+                }
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 #[macro_use]
 mod tests {
@@ -0,0 +1,788 @@
+use crate::{
+    AlgorithmIdentifier, Certificate, CertificateSerialNumber, CrlReason, Extensions, GeneralName, Name, Version,
+};
+use picky_asn1::tag::{Tag, TagPeeker};
+use picky_asn1::wrapper::{
+    Asn1SequenceOf, BitStringAsn1, ExplicitContextTag0, ExplicitContextTag1, ExplicitContextTag2, GeneralizedTimeAsn1,
+    ObjectIdentifierAsn1, OctetStringAsn1,
+};
+use serde::{de, ser, Deserialize, Serialize};
+use std::fmt;
+
+fn version_is_default(version: &Version) -> bool {
+    version == &Version::default()
+}
+
+fn h_peek_tag<'de, A: de::SeqAccess<'de>>(seq: &mut A) -> Result<Option<Tag>, A::Error> {
+    Ok(seq.next_element::<TagPeeker>()?.map(|tag_peeker| tag_peeker.next_tag))
+}
+
+/// [RFC 6960 #4.1.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1.1)
+///
+/// ```not_rust
+/// OCSPRequest ::= SEQUENCE {
+///     tbsRequest                  TBSRequest,
+///     optionalSignature   [0]     EXPLICIT Signature OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct OcspRequest {
+    pub tbs_request: TbsRequest,
+    pub optional_signature: Option<ExplicitContextTag0<OcspSignature>>,
+}
+
+impl<'de> de::Deserialize<'de> for OcspRequest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = OcspRequest;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded OCSPRequest")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(OcspRequest {
+                    tbs_request: seq_next_element!(seq, OcspRequest, "tbsRequest"),
+                    optional_signature: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.1.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1.1)
+///
+/// ```not_rust
+/// TBSRequest ::= SEQUENCE {
+///     version             [0]     EXPLICIT Version DEFAULT v1,
+///     requestorName       [1]     EXPLICIT GeneralName OPTIONAL,
+///     requestList                 SEQUENCE OF Request,
+///     requestExtensions   [2]     EXPLICIT Extensions OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct TbsRequest {
+    #[serde(skip_serializing_if = "version_is_default")]
+    pub version: ExplicitContextTag0<Version>,
+    pub requestor_name: Option<ExplicitContextTag1<GeneralName>>,
+    pub request_list: Asn1SequenceOf<Request>,
+    pub request_extensions: Option<ExplicitContextTag2<Extensions>>,
+}
+
+impl<'de> de::Deserialize<'de> for TbsRequest {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = TbsRequest;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded TBSRequest")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let version = if h_peek_tag(&mut seq)? == Some(Tag::context_specific_constructed(0)) {
+                    seq_next_element!(seq, TbsRequest, "version")
+                } else {
+                    ExplicitContextTag0(Version::V1)
+                };
+
+                let requestor_name = if h_peek_tag(&mut seq)? == Some(Tag::context_specific_constructed(1)) {
+                    Some(seq_next_element!(seq, TbsRequest, "requestorName"))
+                } else {
+                    None
+                };
+
+                Ok(TbsRequest {
+                    version,
+                    requestor_name,
+                    request_list: seq_next_element!(seq, TbsRequest, "requestList"),
+                    request_extensions: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.1.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1.1)
+///
+/// ```not_rust
+/// Signature ::= SEQUENCE {
+///     signatureAlgorithm      AlgorithmIdentifier,
+///     signature               BIT STRING,
+///     certs               [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct OcspSignature {
+    pub signature_algorithm: AlgorithmIdentifier,
+    pub signature: BitStringAsn1,
+    pub certs: Option<ExplicitContextTag0<Asn1SequenceOf<Certificate>>>,
+}
+
+impl<'de> de::Deserialize<'de> for OcspSignature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = OcspSignature;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded OCSP Signature")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(OcspSignature {
+                    signature_algorithm: seq_next_element!(seq, OcspSignature, "signatureAlgorithm"),
+                    signature: seq_next_element!(seq, OcspSignature, "signature"),
+                    certs: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.1.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1.1)
+///
+/// ```not_rust
+/// Request ::= SEQUENCE {
+///     reqCert                     CertID,
+///     singleRequestExtensions     [0] EXPLICIT Extensions OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct Request {
+    pub req_cert: CertId,
+    pub single_request_extensions: Option<ExplicitContextTag0<Extensions>>,
+}
+
+impl<'de> de::Deserialize<'de> for Request {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = Request;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded OCSP Request")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(Request {
+                    req_cert: seq_next_element!(seq, Request, "reqCert"),
+                    single_request_extensions: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.1.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1.1)
+///
+/// ```not_rust
+/// CertID ::= SEQUENCE {
+///     hashAlgorithm       AlgorithmIdentifier,
+///     issuerNameHash      OCTET STRING, -- Hash of issuer's DN
+///     issuerKeyHash       OCTET STRING, -- Hash of issuer's public key
+///     serialNumber        CertificateSerialNumber }
+/// ```
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CertId {
+    pub hash_algorithm: AlgorithmIdentifier,
+    pub issuer_name_hash: OctetStringAsn1,
+    pub issuer_key_hash: OctetStringAsn1,
+    pub serial_number: CertificateSerialNumber,
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// OCSPResponse ::= SEQUENCE {
+///     responseStatus         OCSPResponseStatus,
+///     responseBytes          [0] EXPLICIT ResponseBytes OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct OcspResponse {
+    pub response_status: OcspResponseStatus,
+    pub response_bytes: Option<ExplicitContextTag0<ResponseBytes>>,
+}
+
+impl<'de> de::Deserialize<'de> for OcspResponse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = OcspResponse;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded OCSPResponse")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(OcspResponse {
+                    response_status: seq_next_element!(seq, OcspResponse, "responseStatus"),
+                    response_bytes: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// OCSPResponseStatus ::= ENUMERATED {
+///     successful            (0),  -- Response has valid confirmations
+///     malformedRequest      (1),  -- Illegal confirmation request
+///     internalError         (2),  -- Internal error in issuer
+///     tryLater              (3),  -- Try again later
+///                                 -- (4) is not used
+///     sigRequired           (5),  -- Must sign the request
+///     unauthorized          (6)   -- Request unauthorized
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum OcspResponseStatus {
+    Successful = 0,
+    MalformedRequest = 1,
+    InternalError = 2,
+    TryLater = 3,
+    SigRequired = 5,
+    Unauthorized = 6,
+}
+
+asn1_enumerated!(OcspResponseStatus {
+    Successful = 0,
+    MalformedRequest = 1,
+    InternalError = 2,
+    TryLater = 3,
+    SigRequired = 5,
+    Unauthorized = 6,
+});
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// ResponseBytes ::= SEQUENCE {
+///     responseType   OBJECT IDENTIFIER,
+///     response       OCTET STRING }
+/// ```
+///
+/// For `id-pkix-ocsp-basic`, `response` is the DER encoding of a `BasicOCSPResponse`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ResponseBytes {
+    pub response_type: ObjectIdentifierAsn1,
+    pub response: OctetStringAsn1,
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// BasicOCSPResponse ::= SEQUENCE {
+///     tbsResponseData      ResponseData,
+///     signatureAlgorithm   AlgorithmIdentifier,
+///     signature            BIT STRING,
+///     certs            [0] EXPLICIT SEQUENCE OF Certificate OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct BasicOcspResponse {
+    pub tbs_response_data: ResponseData,
+    pub signature_algorithm: AlgorithmIdentifier,
+    pub signature: BitStringAsn1,
+    pub certs: Option<ExplicitContextTag0<Asn1SequenceOf<Certificate>>>,
+}
+
+impl<'de> de::Deserialize<'de> for BasicOcspResponse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = BasicOcspResponse;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded BasicOCSPResponse")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(BasicOcspResponse {
+                    tbs_response_data: seq_next_element!(seq, BasicOcspResponse, "tbsResponseData"),
+                    signature_algorithm: seq_next_element!(seq, BasicOcspResponse, "signatureAlgorithm"),
+                    signature: seq_next_element!(seq, BasicOcspResponse, "signature"),
+                    certs: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// ResponseData ::= SEQUENCE {
+///     version              [0] EXPLICIT Version DEFAULT v1,
+///     responderID              ResponderID,
+///     producedAt               GeneralizedTime,
+///     responses                SEQUENCE OF SingleResponse,
+///     responseExtensions   [1] EXPLICIT Extensions OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct ResponseData {
+    #[serde(skip_serializing_if = "version_is_default")]
+    pub version: ExplicitContextTag0<Version>,
+    pub responder_id: ResponderId,
+    pub produced_at: GeneralizedTimeAsn1,
+    pub responses: Asn1SequenceOf<SingleResponse>,
+    pub response_extensions: Option<ExplicitContextTag1<Extensions>>,
+}
+
+impl<'de> de::Deserialize<'de> for ResponseData {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ResponseData;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded ResponseData")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let version = if h_peek_tag(&mut seq)? == Some(Tag::context_specific_constructed(0)) {
+                    seq_next_element!(seq, ResponseData, "version")
+                } else {
+                    ExplicitContextTag0(Version::V1)
+                };
+
+                Ok(ResponseData {
+                    version,
+                    responder_id: seq_next_element!(seq, ResponseData, "responderID"),
+                    produced_at: seq_next_element!(seq, ResponseData, "producedAt"),
+                    responses: seq_next_element!(seq, ResponseData, "responses"),
+                    response_extensions: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// ResponderID ::= CHOICE {
+///     byName   [1] Name,
+///     byKey    [2] KeyHash }
+///
+/// KeyHash ::= OCTET STRING -- SHA-1 hash of responder's public key
+///                          -- (excluding the tag and length fields)
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum ResponderId {
+    ByName(Name),
+    ByKey(OctetStringAsn1),
+}
+
+impl ser::Serialize for ResponderId {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        match &self {
+            ResponderId::ByName(name) => ExplicitContextTag1(name).serialize(serializer),
+            ResponderId::ByKey(key_hash) => ExplicitContextTag2(key_hash).serialize(serializer),
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for ResponderId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ResponderId;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded ResponderID")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let tag_peeker: TagPeeker = seq_next_element!(seq, ResponderId, "choice tag");
+                match tag_peeker.next_tag {
+                    tag if tag == Tag::context_specific_constructed(1) => Ok(ResponderId::ByName(
+                        seq_next_element!(seq, ExplicitContextTag1<Name>, ResponderId, "byName").0,
+                    )),
+                    tag if tag == Tag::context_specific_constructed(2) => Ok(ResponderId::ByKey(
+                        seq_next_element!(seq, ExplicitContextTag2<OctetStringAsn1>, ResponderId, "byKey").0,
+                    )),
+                    _ => Err(serde_invalid_value!(
+                        ResponderId,
+                        "unknown choice value",
+                        "a supported ResponderID choice"
+                    )),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum("ResponderId", &["ByName", "ByKey"], Visitor)
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// SingleResponse ::= SEQUENCE {
+///     certID                       CertID,
+///     certStatus                   CertStatus,
+///     thisUpdate                   GeneralizedTime,
+///     nextUpdate         [0]       EXPLICIT GeneralizedTime OPTIONAL,
+///     singleExtensions   [1]       EXPLICIT Extensions OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct SingleResponse {
+    pub cert_id: CertId,
+    pub cert_status: CertStatus,
+    pub this_update: GeneralizedTimeAsn1,
+    pub next_update: Option<ExplicitContextTag0<GeneralizedTimeAsn1>>,
+    pub single_extensions: Option<ExplicitContextTag1<Extensions>>,
+}
+
+impl<'de> de::Deserialize<'de> for SingleResponse {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = SingleResponse;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded SingleResponse")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let cert_id = seq_next_element!(seq, SingleResponse, "certID");
+                let cert_status = seq_next_element!(seq, SingleResponse, "certStatus");
+                let this_update = seq_next_element!(seq, SingleResponse, "thisUpdate");
+
+                let next_update = if h_peek_tag(&mut seq)? == Some(Tag::context_specific_constructed(0)) {
+                    Some(seq_next_element!(seq, SingleResponse, "nextUpdate"))
+                } else {
+                    None
+                };
+
+                Ok(SingleResponse {
+                    cert_id,
+                    cert_status,
+                    this_update,
+                    next_update,
+                    single_extensions: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// CertStatus ::= CHOICE {
+///     good        [0]     IMPLICIT NULL,
+///     revoked     [1]     IMPLICIT RevokedInfo,
+///     unknown     [2]     IMPLICIT UnknownInfo }
+///
+/// UnknownInfo ::= NULL
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub enum CertStatus {
+    Good,
+    Revoked(RevokedInfo),
+    Unknown,
+}
+
+// This is a workaround for constructed encoding as implicit
+
+impl ser::Serialize for CertStatus {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as ser::Serializer>::Ok, <S as ser::Serializer>::Error>
+    where
+        S: ser::Serializer,
+    {
+        let raw_der = match &self {
+            CertStatus::Good => vec![Tag::context_specific_primitive(0).inner(), 0x00],
+            CertStatus::Revoked(revoked_info) => {
+                let mut raw_der = picky_asn1_der::to_vec(revoked_info).map_err(ser::Error::custom)?;
+                raw_der[0] = Tag::context_specific_constructed(1).inner();
+                raw_der
+            }
+            CertStatus::Unknown => vec![Tag::context_specific_primitive(2).inner(), 0x00],
+        };
+        picky_asn1_der::Asn1RawDer(raw_der).serialize(serializer)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for CertStatus {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let mut raw_der = picky_asn1_der::Asn1RawDer::deserialize(deserializer)?.0;
+        match Tag::from(raw_der[0]) {
+            tag if tag == Tag::context_specific_primitive(0) => Ok(CertStatus::Good),
+            tag if tag == Tag::context_specific_constructed(1) => {
+                raw_der[0] = Tag::SEQUENCE.inner();
+                let revoked_info = picky_asn1_der::from_bytes(&raw_der).map_err(de::Error::custom)?;
+                Ok(CertStatus::Revoked(revoked_info))
+            }
+            tag if tag == Tag::context_specific_primitive(2) => Ok(CertStatus::Unknown),
+            _ => Err(serde_invalid_value!(
+                CertStatus,
+                "unknown choice value",
+                "a supported CertStatus choice"
+            )),
+        }
+    }
+}
+
+/// [RFC 6960 #4.2.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2.1)
+///
+/// ```not_rust
+/// RevokedInfo ::= SEQUENCE {
+///     revocationTime              GeneralizedTime,
+///     revocationReason    [0]     EXPLICIT CRLReason OPTIONAL }
+/// ```
+#[derive(Serialize, Debug, PartialEq, Clone)]
+pub struct RevokedInfo {
+    pub revocation_time: GeneralizedTimeAsn1,
+    pub revocation_reason: Option<ExplicitContextTag0<CrlReason>>,
+}
+
+impl<'de> de::Deserialize<'de> for RevokedInfo {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = RevokedInfo;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded RevokedInfo")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                Ok(RevokedInfo {
+                    revocation_time: seq_next_element!(seq, RevokedInfo, "revocationTime"),
+                    revocation_reason: seq.next_element()?.unwrap_or(None),
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extension, ExtensionView};
+    use picky_asn1::wrapper::IntegerAsn1;
+
+    #[test]
+    fn decode_ocsp_request_with_nonce() {
+        let encoded = base64::decode(
+            "MGgwZjA/MD0wOzAJBgUrDgMCGgUABBSqfpLNB6nFj6UAMw2rfboKrkCDXwQUiRMv\
+             UDeEacwVaCq93LWruI7pvqICAiACoiMwITAfBgkrBgEFBQcwAQIEEgQQ5QgeB5RB\
+             ej+VueuQ6/7amg==",
+        )
+        .unwrap();
+
+        let request: OcspRequest = picky_asn1_der::from_bytes(&encoded).unwrap();
+        let tbs_request = &request.tbs_request;
+        assert_eq!(tbs_request.version.0, Version::V1);
+        assert_eq!(tbs_request.requestor_name, None);
+        assert_eq!(request.optional_signature, None);
+
+        let cert_id = &tbs_request.request_list.0[0].req_cert;
+        assert_eq!(
+            cert_id.hash_algorithm,
+            AlgorithmIdentifier::new_sha(crate::ShaVariant::SHA1)
+        );
+        assert_eq!(
+            cert_id.serial_number,
+            CertificateSerialNumber(IntegerAsn1::from(vec![0x20, 0x02]))
+        );
+
+        let extensions = &tbs_request.request_extensions.as_ref().unwrap().0 .0;
+        assert_eq!(extensions[0].extn_id(), &crate::oids::id_pkix_ocsp_nonce());
+        assert_eq!(
+            extensions[0],
+            Extension::new_ocsp_nonce(base64::decode("5QgeB5RBej+VueuQ6/7amg==").unwrap())
+        );
+
+        check_serde!(request: OcspRequest in encoded);
+    }
+
+    #[test]
+    fn decode_ocsp_response_good_by_key() {
+        let encoded = base64::decode(
+            "MIIB6goBAKCCAeMwggHfBgkrBgEFBQcwAQEEggHQMIIBzDCBtaIWBBSJEy9QN4Rp\
+             zBVoKr3ctau4jum+ohgPMjAyNjEwMTcwMjMzNDZaMGUwYzA7MAkGBSsOAwIaBQAE\
+             FKp+ks0HqcWPpQAzDat9ugquQINfBBSJEy9QN4RpzBVoKr3ctau4jum+ogICIAKA\
+             ABgPMjAyNjEwMTcwMjMzNDZaoBEYDzIxMjYwOTIzMDIzMzQ2WqEjMCEwHwYJKwYB\
+             BQUHMAECBBIEEOUIHgeUQXo/lbnrkOv+2powDQYJKoZIhvcNAQELBQADggEBAG5G\
+             pnsmUZbFXwhPEG0IZ298/hfg3JtMO0Ja1Oj6w/4LhdVyTJLr0YAhDfKXpGqUHSHj\
+             wS9Z4/EkDfftUHdDIywdQBVMMQFyGlLWdL0iFrZf1Ia3hQmdOozSes9djMrDLUfU\
+             UScup+ssIB43wwYeswPr7tSrXYZt0EPNogQgMj3bLs9fH7jezGofEyNntGS32hUx\
+             U1upkeou5ahFGA6zuCXVZrVBlsVJuUN78OrKKcDQZiOayEboS7i40t/Pp20tbDTk\
+             n2y6iyM985AowyE5/jHm/11ruc0Q0StOCdi+ZzICJ7Yy5T3NtgfP/KqVADuRfBHZ\
+             +ez35HeSdE1ZBp4yqmM=",
+        )
+        .unwrap();
+
+        let response: OcspResponse = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(response.response_status, OcspResponseStatus::Successful);
+
+        let response_bytes = &response.response_bytes.as_ref().unwrap().0;
+        assert_eq!(response_bytes.response_type.0, crate::oids::id_pkix_ocsp_basic());
+
+        let basic_response: BasicOcspResponse = picky_asn1_der::from_bytes(&response_bytes.response.0).unwrap();
+        let response_data = &basic_response.tbs_response_data;
+        assert!(matches!(response_data.responder_id, ResponderId::ByKey(_)));
+        assert_eq!(basic_response.certs, None);
+
+        let single_response = &response_data.responses.0[0];
+        assert_eq!(single_response.cert_status, CertStatus::Good);
+        assert!(single_response.next_update.is_some());
+        assert_eq!(single_response.single_extensions, None);
+
+        let response_extensions = &response_data.response_extensions.as_ref().unwrap().0 .0;
+        assert!(matches!(response_extensions[0].extn_value(), ExtensionView::Generic(_)));
+
+        let encoded_basic_response = &response_bytes.response.0;
+        check_serde!(basic_response: BasicOcspResponse in encoded_basic_response);
+        check_serde!(response: OcspResponse in encoded);
+    }
+
+    #[test]
+    fn decode_ocsp_response_revoked_by_name() {
+        let encoded = base64::decode(
+            "MIIB5QoBAKCCAd4wggHaBgkrBgEFBQcwAQEEggHLMIIBxzCBsKEgMB4xHDAaBgNV\
+             BAMME09DU1AgVGVzdCByZXNwb25kZXIYDzIwMjYxMDE3MDIzMzQ5WjB7MHkwOzAJ\
+             BgUrDgMCGgUABBSqfpLNB6nFj6UAMw2rfboKrkCDXwQUiRMvUDeEacwVaCq93LWr\
+             uI7pvqICAhABoRYYDzIwMjYwMTAxMDAwMDAwWqADCgEBGA8yMDI2MTAxNzAyMzM0\
+             OVqgERgPMjEyNjA5MjMwMjMzNDlaMA0GCSqGSIb3DQEBCwUAA4IBAQA+0kSSqg0a\
+             OpihgxMJCMNPF5TGpwGfXDeGEMrmqTONk8C+OnVxd7WF2QgB857FZtQB4BLfrcYV\
+             +OYPCKfznapmnnza8MYdt82fFv8wiOiYm7H+5TTIqe7KvkUjIVHWlQUh8vlu1aiC\
+             R4c458XIBcZkyT/oznxzW0O/l0UVXQmavmcqWm22K49zj7VWcnyLf9ICWPpeMshY\
+             ocp8u6Uogrb8RAAQZ2NRBgHA7z6t7lf1RL1+ULKb+/i+DfZjhVfOmBDqnOmv2eOD\
+             jxCSEtj7IOoBLQrYpotrrV7VRfW0QR0kVXRtzRpeUCuX/dSfEHjaI2sq+4S5PAj1\
+             BoDpap913llM",
+        )
+        .unwrap();
+
+        let response: OcspResponse = picky_asn1_der::from_bytes(&encoded).unwrap();
+        let response_bytes = &response.response_bytes.as_ref().unwrap().0;
+        let basic_response: BasicOcspResponse = picky_asn1_der::from_bytes(&response_bytes.response.0).unwrap();
+        let response_data = &basic_response.tbs_response_data;
+
+        match &response_data.responder_id {
+            ResponderId::ByName(name) => assert_eq!(name.to_string(), "CN=OCSP Test responder"),
+            other => panic!("unexpected responder id: {:?}", other),
+        }
+
+        let single_response = &response_data.responses.0[0];
+        match &single_response.cert_status {
+            CertStatus::Revoked(revoked_info) => {
+                assert_eq!(
+                    revoked_info.revocation_reason.as_ref().map(|reason| reason.0),
+                    Some(CrlReason::KeyCompromise)
+                );
+            }
+            other => panic!("unexpected cert status: {:?}", other),
+        }
+        assert_eq!(response_data.response_extensions, None);
+
+        let encoded_basic_response = &response_bytes.response.0;
+        check_serde!(basic_response: BasicOcspResponse in encoded_basic_response);
+        check_serde!(response: OcspResponse in encoded);
+    }
+
+    #[test]
+    fn unsuccessful_ocsp_response() {
+        let encoded = [0x30, 0x03, 0x0A, 0x01, 0x06];
+
+        let response: OcspResponse = picky_asn1_der::from_bytes(&encoded).unwrap();
+        assert_eq!(response.response_status, OcspResponseStatus::Unauthorized);
+        assert_eq!(response.response_bytes, None);
+
+        check_serde!(response: OcspResponse in encoded);
+    }
+}
@@ -54,6 +54,8 @@ define_oid! {
     RSA_ENCRYPTION => rsa_encryption => "1.2.840.113549.1.1.1",
     MD5_WITH_RSA_ENCRYPTHION => md5_with_rsa_encryption => "1.2.840.113549.1.1.4",
     SHA1_WITH_RSA_ENCRYPTION => sha1_with_rsa_encryption => "1.2.840.113549.1.1.5",
+    ID_MGF1 => id_mgf1 => "1.2.840.113549.1.1.8",
+    RSASSA_PSS => rsassa_pss => "1.2.840.113549.1.1.10",
     SHA256_WITH_RSA_ENCRYPTION => sha256_with_rsa_encryption => "1.2.840.113549.1.1.11",
     SHA384_WITH_RSA_ENCRYPTION => sha384_with_rsa_encryption => "1.2.840.113549.1.1.12",
     SHA512_WITH_RSA_ENCRYPTION => sha512_with_rsa_encryption => "1.2.840.113549.1.1.13",
@@ -130,8 +132,30 @@ define_oid! {
     ISSUER_ALTERNATIVE_NAME => issuer_alternative_name => "2.5.29.18",
     BASIC_CONSTRAINTS => basic_constraints => "2.5.29.19",
     CRL_NUMBER => crl_number => "2.5.29.20",
+    CRL_REASON_CODE => crl_reason_code => "2.5.29.21",
+    NAME_CONSTRAINTS => name_constraints => "2.5.29.30",
+    CRL_DISTRIBUTION_POINTS => crl_distribution_points => "2.5.29.31",
+    CERTIFICATE_POLICIES => certificate_policies => "2.5.29.32",
+    ANY_POLICY => any_policy => "2.5.29.32.0",
+    POLICY_MAPPINGS => policy_mappings => "2.5.29.33",
     AUTHORITY_KEY_IDENTIFIER => authority_key_identifier => "2.5.29.35",
+    POLICY_CONSTRAINTS => policy_constraints => "2.5.29.36",
     EXTENDED_KEY_USAGE => extended_key_usage => "2.5.29.37",
+    INHIBIT_ANY_POLICY => inhibit_any_policy => "2.5.29.54",
+    AUTHORITY_INFO_ACCESS => authority_info_access => "1.3.6.1.5.5.7.1.1",
+
+    // Access descriptors (RFC 5280)
+    ID_AD_OCSP => id_ad_ocsp => "1.3.6.1.5.5.7.48.1",
+    ID_AD_CA_ISSUERS => id_ad_ca_issuers => "1.3.6.1.5.5.7.48.2",
+
+    // Policy qualifiers (RFC 5280)
+    ID_QT_CPS => id_qt_cps => "1.3.6.1.5.5.7.2.1",
+    ID_QT_UNOTICE => id_qt_unotice => "1.3.6.1.5.5.7.2.2",
+
+    // OCSP (RFC 6960)
+    ID_PKIX_OCSP_BASIC => id_pkix_ocsp_basic => "1.3.6.1.5.5.7.48.1.1",
+    ID_PKIX_OCSP_NONCE => id_pkix_ocsp_nonce => "1.3.6.1.5.5.7.48.1.2",
+    ID_PKIX_OCSP_NOCHECK => id_pkix_ocsp_nocheck => "1.3.6.1.5.5.7.48.1.5",
 
     // aes
     // aes-128
@@ -1,11 +1,9 @@
-use super::signer_info::CertificateSerialNumber;
-use crate::{AlgorithmIdentifier, Extensions, Name, Time, Version};
 use picky_asn1::tag::{Tag, TagClass, TagPeeker};
-use picky_asn1::wrapper::{
-    Asn1SequenceOf, BitStringAsn1, ExplicitContextTag0, ImplicitContextTag1, ObjectIdentifierAsn1,
-};
+use picky_asn1::wrapper::{ImplicitContextTag1, ObjectIdentifierAsn1};
 use serde::{de, ser, Deserialize, Serialize};
 
+pub use crate::crl::{CertificateList, RevokedCertificate, RevokedCertificates, TbsCertList};
+
 /// [RFC 5652 #10.2.1](https://datatracker.ietf.org/doc/html/rfc5652#section-10.2.1)
 ///
 /// ```not_rust
@@ -108,153 +106,6 @@ impl<'de> Deserialize<'de> for RevocationInfoChoice {
     }
 }
 
-/// CRLs are specified in X.509
-/// ``` not_rust
-/// [RFC 5280 #5.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.1)
-/// CertificateList  ::=  SEQUENCE  {
-///         tbsCertList          TBSCertList,
-///         signatureAlgorithm   AlgorithmIdentifier,
-///         signatureValue       BIT STRING  }
-///
-/// ```
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct CertificateList {
-    pub tbs_cert_list: TbsCertList,
-    pub signature_algorithm: AlgorithmIdentifier,
-    pub signature_value: BitStringAsn1,
-}
-
-/// ``` not_rust
-/// [RFC 5280 #5.1](https://datatracker.ietf.org/doc/html/rfc5280#section-5.1)
-///  TBSCertList  ::=  SEQUENCE  {
-///         version                 Version OPTIONAL,
-///                                      -- if present, MUST be v2
-///         signature               AlgorithmIdentifier,
-///         issuer                  Name,
-///         thisUpdate              Time,
-///         nextUpdate              Time OPTIONAL,
-///         revokedCertificates     SEQUENCE OF SEQUENCE  {
-///              userCertificate         CertificateSerialNumber,
-///              revocationDate          Time,
-///              crlEntryExtensions      Extensions OPTIONAL
-///                                       -- if present, version MUST be v2
-///                                   }  OPTIONAL,
-///         crlExtensions           [0]  EXPLICIT Extensions OPTIONAL
-///                                       -- if present, version MUST be v2
-///                                 }
-/// ```
-#[derive(Serialize, Debug, PartialEq, Clone)]
-pub struct TbsCertList {
-    pub version: Option<Version>,
-    pub signature: AlgorithmIdentifier,
-    pub issuer: Name,
-    pub this_update: Time,
-    pub next_update: Option<Time>,
-    pub revoked_certificates: Option<RevokedCertificates>,
-    pub crl_extension: ExplicitContextTag0<Option<Extensions>>,
-}
-
-impl<'de> de::Deserialize<'de> for TbsCertList {
-    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
-    where
-        D: de::Deserializer<'de>,
-    {
-        use std::fmt;
-
-        struct Visitor;
-
-        impl<'de> de::Visitor<'de> for Visitor {
-            type Value = TbsCertList;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a valid DER-encoded TbsCertList")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'de>,
-            {
-                let version = seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
-                if version.is_some() && !version.eq(&Some(Version::V2)) {
-                    return Err(serde_invalid_value!(
-                        TbsCertList,
-                        "Version of TbsCertList doesn't equal to v2",
-                        "Version of TbsCertList equals to v2"
-                    ));
-                }
-
-                let signature = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
-                let issuer = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
-                let this_update = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(3, &self))?;
-                let next_update = seq.next_element().unwrap_or(Some(None)).unwrap_or(None);
-
-                let tag_peeker: TagPeeker = seq_next_element!(seq, TbsCertList, "a tag");
-                let revoked_certificates = if tag_peeker.next_tag == Tag::SEQUENCE {
-                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(5, &self))?
-                } else {
-                    None
-                };
-
-                let crl_extension = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(6, &self))?;
-
-                Ok(TbsCertList {
-                    version,
-                    signature,
-                    issuer,
-                    this_update,
-                    next_update,
-                    revoked_certificates,
-                    crl_extension,
-                })
-            }
-        }
-
-        deserializer.deserialize_seq(Visitor)
-    }
-}
-
-#[derive(Deserialize, Serialize, Debug, PartialEq, Clone)]
-pub struct RevokedCertificates(pub Asn1SequenceOf<RevokedCertificate>);
-
-#[derive(Serialize, Debug, PartialEq, Clone)]
-pub struct RevokedCertificate {
-    pub user_certificate: CertificateSerialNumber,
-    pub revocation_data: Time,
-    pub crl_entry_extensions: Option<Extensions>,
-}
-
-impl<'de> de::Deserialize<'de> for RevokedCertificate {
-    fn deserialize<D>(deserializer: D) -> Result<Self, <D as de::Deserializer<'de>>::Error>
-    where
-        D: de::Deserializer<'de>,
-    {
-        use std::fmt;
-
-        struct Visitor;
-
-        impl<'de> de::Visitor<'de> for Visitor {
-            type Value = RevokedCertificate;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("a valid DER-decoded TbsCertList")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: de::SeqAccess<'de>,
-            {
-                Ok(RevokedCertificate {
-                    user_certificate: seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?,
-                    revocation_data: seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?,
-                    crl_entry_extensions: seq.next_element().unwrap_or(Some(None)).unwrap_or(None),
-                })
-            }
-        }
-
-        deserializer.deserialize_seq(Visitor)
-    }
-}
-
 /// [RFC 5652 #10.2.1](https://datatracker.ietf.org/doc/html/rfc5652#section-10.2.1)
 /// ``` not_rust
 /// OtherRevocationInfoFormat ::= SEQUENCE {
@@ -1,9 +1,11 @@
 use crate::cmsversion::CmsVersion;
 use crate::pkcs7::Pkcs7Certificate;
 use crate::{oids, AlgorithmIdentifier, Attribute, Name, SubjectKeyIdentifier};
+
+pub use crate::certificate::CertificateSerialNumber;
 use picky_asn1::tag::{Tag, TagClass, TagPeeker};
 use picky_asn1::wrapper::{
-    Asn1SequenceOf, Asn1SetOf, ImplicitContextTag0, IntegerAsn1, ObjectIdentifierAsn1, OctetStringAsn1, Optional,
+    Asn1SequenceOf, Asn1SetOf, ImplicitContextTag0, ObjectIdentifierAsn1, OctetStringAsn1, Optional,
 };
 use serde::{de, ser, Deserialize, Serialize};
 
@@ -208,13 +210,6 @@ pub struct IssuerAndSerialNumber {
     pub serial_number: CertificateSerialNumber,
 }
 
-/// [RFC 5652 #10.2.4](https://datatracker.ietf.org/doc/html/rfc5652#section-10.2.4)
-/// ``` not_rust
-/// CertificateSerialNumber ::= INTEGER
-/// ```
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
-pub struct CertificateSerialNumber(pub IntegerAsn1);
-
 #[derive(Deserialize, Debug, PartialEq, Clone, Default)]
 pub struct UnsignedAttributes(pub Vec<UnsignedAttribute>);
 
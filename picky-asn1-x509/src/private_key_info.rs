@@ -1,5 +1,10 @@
-use crate::{oids, AlgorithmIdentifier};
-use picky_asn1::wrapper::{IntegerAsn1, OctetStringAsn1Container};
+use crate::{oids, AlgorithmIdentifier, EcParameters};
+use picky_asn1::tag::{Tag, TagPeeker};
+use picky_asn1::wrapper::{
+    BitStringAsn1, ExplicitContextTag0, ExplicitContextTag1, ImplicitContextTag1, IntegerAsn1, OctetStringAsn1,
+    OctetStringAsn1Container,
+};
+use picky_asn1_der::Asn1RawDer;
 #[cfg(not(feature = "legacy"))]
 use serde::Deserialize;
 use serde::{de, ser, Serialize};
@@ -45,12 +50,37 @@ use std::fmt;
 /// `attributes` is a set of attributes.  These are the extended
 /// information that is encrypted along with the private-key
 /// information.
+///
+/// # Asymmetric Key Packages
+///
+/// [RFC 5958](https://tools.ietf.org/html/rfc5958#section-2) later extended this structure into
+/// `OneAsymmetricKey` (also known as PKCS #8 v2), adding an optional public key:
+///
+/// ```not_rust
+/// OneAsymmetricKey ::= SEQUENCE {
+///     version                   Version,
+///     privateKeyAlgorithm       PrivateKeyAlgorithmIdentifier,
+///     privateKey                PrivateKey,
+///     attributes            [0] Attributes OPTIONAL,
+///     ...,
+///     [[2: publicKey        [1] PublicKey OPTIONAL ]],
+///     ...
+/// }
+///
+/// Version ::= INTEGER { v1(0), v2(1) } (v1, ..., v2)
+///
+/// PublicKey ::= BIT STRING
+/// ```
+///
+/// `attributes` are currently ignored when deserializing.
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct PrivateKeyInfo {
     pub version: u8,
     pub private_key_algorithm: AlgorithmIdentifier,
     pub private_key: PrivateKeyValue,
     //pub attributes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<ImplicitContextTag1<BitStringAsn1>>,
 }
 
 impl PrivateKeyInfo {
@@ -81,6 +111,50 @@ impl PrivateKeyInfo {
             version: 0,
             private_key_algorithm: AlgorithmIdentifier::new_rsa_encryption(),
             private_key,
+            public_key: None,
+        }
+    }
+
+    pub fn new_ec_encryption(
+        curve: EcParameters,
+        secret: impl Into<OctetStringAsn1>,
+        public_point: Option<BitStringAsn1>,
+    ) -> Self {
+        // Curve parameters are already provided by the private key algorithm identifier
+        let private_key = PrivateKeyValue::EC(
+            ECPrivateKey {
+                version: vec![1].into(),
+                private_key: secret.into(),
+                parameters: None,
+                public_key: public_point.map(ExplicitContextTag1),
+            }
+            .into(),
+        );
+
+        Self {
+            version: 0,
+            private_key_algorithm: AlgorithmIdentifier::new_elliptic_curve(curve),
+            private_key,
+            public_key: None,
+        }
+    }
+
+    /// Builds an EdDSA private key as defined by [RFC 8410](https://tools.ietf.org/html/rfc8410#section-7).
+    ///
+    /// `algorithm` is `AlgorithmIdentifier::new_ed25519()`.
+    /// Version 2 (`OneAsymmetricKey`) is used when the public key is provided.
+    pub fn new_ed_encryption(
+        algorithm: AlgorithmIdentifier,
+        secret: impl Into<OctetStringAsn1>,
+        public_key: Option<BitStringAsn1>,
+    ) -> Self {
+        let version = if public_key.is_some() { 1 } else { 0 };
+
+        Self {
+            version,
+            private_key_algorithm: algorithm,
+            private_key: PrivateKeyValue::ED(OctetStringAsn1Container(secret.into())),
+            public_key: public_key.map(ImplicitContextTag1),
         }
     }
 }
@@ -104,18 +178,24 @@ impl<'de> de::Deserialize<'de> for PrivateKeyInfo {
                 A: de::SeqAccess<'de>,
             {
                 let version = seq_next_element!(seq, PrivateKeyInfo, "version");
-                if version != 0 {
+                if version != 0 && version != 1 {
                     return Err(serde_invalid_value!(
                         PrivateKeyInfo,
-                        "unsupported version (valid version number: 0)",
+                        "unsupported version (valid version numbers: 0, 1)",
                         "a supported PrivateKeyInfo"
                     ));
                 }
 
                 let private_key_algorithm: AlgorithmIdentifier =
                     seq_next_element!(seq, PrivateKeyInfo, "private key algorithm");
-                let private_key = if private_key_algorithm.is_a(oids::rsa_encryption()) {
+                let private_key = if private_key_algorithm.is_a(oids::rsa_encryption())
+                    || private_key_algorithm.is_a(oids::rsassa_pss())
+                {
                     PrivateKeyValue::RSA(seq_next_element!(seq, PrivateKeyInfo, "rsa oid"))
+                } else if private_key_algorithm.is_a(oids::ec_public_key()) {
+                    PrivateKeyValue::EC(seq_next_element!(seq, PrivateKeyInfo, "ec private key"))
+                } else if private_key_algorithm.is_a(oids::ed25519()) {
+                    PrivateKeyValue::ED(seq_next_element!(seq, PrivateKeyInfo, "curve private key"))
                 } else {
                     return Err(serde_invalid_value!(
                         PrivateKeyInfo,
@@ -124,10 +204,28 @@ impl<'de> de::Deserialize<'de> for PrivateKeyInfo {
                     ));
                 };
 
+                let mut public_key = None;
+
+                while let Some(tag_peeker) = seq.next_element::<TagPeeker>()? {
+                    if tag_peeker.next_tag == Tag::context_specific_constructed(0) {
+                        // attributes are not supported yet
+                        let _: Asn1RawDer = seq_next_element!(seq, PrivateKeyInfo, "attributes");
+                    } else if tag_peeker.next_tag == Tag::context_specific_primitive(1) {
+                        public_key = Some(seq_next_element!(seq, PrivateKeyInfo, "public key"));
+                    } else {
+                        return Err(serde_invalid_value!(
+                            PrivateKeyInfo,
+                            "unexpected tag",
+                            "attributes [0] or public key [1]"
+                        ));
+                    }
+                }
+
                 Ok(PrivateKeyInfo {
                     version,
                     private_key_algorithm,
                     private_key,
+                    public_key,
                 })
             }
         }
@@ -139,6 +237,9 @@ impl<'de> de::Deserialize<'de> for PrivateKeyInfo {
 #[derive(Debug, PartialEq, Clone)]
 pub enum PrivateKeyValue {
     RSA(OctetStringAsn1Container<RsaPrivateKey>),
+    EC(OctetStringAsn1Container<ECPrivateKey>),
+    /// `CurvePrivateKey` from [RFC 8410](https://tools.ietf.org/html/rfc8410#section-7)
+    ED(OctetStringAsn1Container<OctetStringAsn1>),
 }
 
 impl ser::Serialize for PrivateKeyValue {
@@ -148,6 +249,8 @@ impl ser::Serialize for PrivateKeyValue {
     {
         match self {
             PrivateKeyValue::RSA(rsa) => rsa.serialize(serializer),
+            PrivateKeyValue::EC(ec) => ec.serialize(serializer),
+            PrivateKeyValue::ED(ed) => ed.serialize(serializer),
         }
     }
 }
@@ -328,9 +431,95 @@ impl RsaPrivateKey {
     }
 }
 
+/// [Elliptic Curve Private Key Structure](https://datatracker.ietf.org/doc/html/rfc5915#section-3)
+///
+/// # Section 3
+///
+/// ```not_rust
+/// ECPrivateKey ::= SEQUENCE {
+///   version        INTEGER { ecPrivkeyVer1(1) } (ecPrivkeyVer1),
+///   privateKey     OCTET STRING,
+///   parameters [0] ECParameters {{ NamedCurve }} OPTIONAL,
+///   publicKey  [1] BIT STRING OPTIONAL
+/// }
+/// ```
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ECPrivateKey {
+    pub version: IntegerAsn1,
+    pub private_key: OctetStringAsn1,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<ExplicitContextTag0<EcParameters>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<ExplicitContextTag1<BitStringAsn1>>,
+}
+
+impl<'de> de::Deserialize<'de> for ECPrivateKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = ECPrivateKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a valid DER-encoded ECPrivateKey")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let version: IntegerAsn1 = seq_next_element!(seq, ECPrivateKey, "version");
+                if version.as_unsigned_bytes_be() != [1] {
+                    return Err(serde_invalid_value!(
+                        ECPrivateKey,
+                        "unsupported version (valid version number: 1)",
+                        "a supported ECPrivateKey"
+                    ));
+                }
+
+                let private_key = seq_next_element!(seq, ECPrivateKey, "private key");
+
+                let mut parameters = None;
+                let mut public_key = None;
+
+                while let Some(tag_peeker) = seq.next_element::<TagPeeker>()? {
+                    match tag_peeker.next_tag {
+                        tag if tag == Tag::context_specific_constructed(0) && parameters.is_none() => {
+                            parameters = Some(seq_next_element!(seq, ECPrivateKey, "parameters"));
+                        }
+                        tag if tag == Tag::context_specific_constructed(1) && public_key.is_none() => {
+                            public_key = Some(seq_next_element!(seq, ECPrivateKey, "public key"));
+                        }
+                        _ => {
+                            return Err(serde_invalid_value!(
+                                ECPrivateKey,
+                                "unexpected optional field",
+                                "parameters [0] or public key [1]"
+                            ));
+                        }
+                    }
+                }
+
+                Ok(ECPrivateKey {
+                    version,
+                    private_key,
+                    parameters,
+                    public_key,
+                })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use picky_asn1::bit_string::BitString;
 
     #[test]
     fn pkcs_8_private_key() {
@@ -366,6 +555,86 @@ mod tests {
         check_serde!(private_key: PrivateKeyInfo in encoded);
     }
 
+    #[test]
+    fn pkcs_8_ec_private_key() {
+        let encoded = base64::decode(
+            "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgRwj347FDJD3UM6K5\
+             ydrWosOA+tCXAaa+s+/SGnj3uQihRANCAARzzbXulZQJQl7hGDk5oQ8Ngft6bn0t\
+             QSOlKG/Sn2KzYvsh5s33gpU0H6IjLWE/b1mnwyILSrseiTRr5vsxIQB2",
+        )
+        .expect("invalid base64");
+
+        let private_key = PrivateKeyInfo::new_ec_encryption(
+            oids::secp256r1().into(),
+            encoded[36..68].to_vec(),
+            Some(BitString::with_bytes(&encoded[73..138]).into()),
+        );
+        check_serde!(private_key: PrivateKeyInfo in encoded);
+    }
+
+    #[test]
+    fn sec1_ec_private_key() {
+        let encoded = base64::decode(
+            "MHcCAQEEIEcI9+OxQyQ91DOiucna1qLDgPrQlwGmvrPv0hp497kIoAoGCCqGSM49\
+             AwEHoUQDQgAEc8217pWUCUJe4Rg5OaEPDYH7em59LUEjpShv0p9is2L7IebN94KV\
+             NB+iIy1hP29Zp8MiC0q7Hok0a+b7MSEAdg==",
+        )
+        .expect("invalid base64");
+
+        let private_key = ECPrivateKey {
+            version: vec![1].into(),
+            private_key: encoded[7..39].to_vec().into(),
+            parameters: Some(EcParameters::from(oids::secp256r1()).into()),
+            public_key: Some(BitStringAsn1::from(BitString::with_bytes(&encoded[56..121])).into()),
+        };
+        check_serde!(private_key: ECPrivateKey in encoded);
+
+        let without_optional_fields = ECPrivateKey {
+            parameters: None,
+            public_key: None,
+            ..private_key
+        };
+        let mut encoded = encoded[..39].to_vec();
+        encoded[1] = 37;
+        check_serde!(without_optional_fields: ECPrivateKey in encoded);
+    }
+
+    #[test]
+    fn pkcs_8_ed25519_private_key() {
+        let encoded =
+            base64::decode("MC4CAQAwBQYDK2VwBCIEIKUcNQgmecqKgArxO2MBDigXByFcj4iv1Ouk41+im2Be").expect("invalid base64");
+
+        let private_key =
+            PrivateKeyInfo::new_ed_encryption(AlgorithmIdentifier::new_ed25519(), encoded[16..].to_vec(), None);
+        check_serde!(private_key: PrivateKeyInfo in encoded);
+    }
+
+    #[test]
+    fn pkcs_8_v2_ed25519_private_key() {
+        // example from RFC 8410 (with attributes and public key)
+        let encoded = base64::decode(
+            "MHICAQEwBQYDK2VwBCIEINTuctv5E1hK1bbY8fdp+K06/nwoy/HU++CXqI9EdVhC\
+             oB8wHQYKKoZIhvcNAQkJFDEPDA1DdXJkbGUgQ2hhaXJzgSEAGb9ECWmEzf6FQbrB\
+             Z9w7lshQhqowtrbLDFw4rXAxZuE=",
+        )
+        .expect("invalid base64");
+
+        let private_key = PrivateKeyInfo::new_ed_encryption(
+            AlgorithmIdentifier::new_ed25519(),
+            encoded[16..48].to_vec(),
+            Some(BitString::with_bytes(&encoded[84..116]).into()),
+        );
+
+        let decoded: PrivateKeyInfo = picky_asn1_der::from_bytes(&encoded).expect("deserialization");
+        pretty_assertions::assert_eq!(decoded, private_key);
+
+        // attributes are ignored
+        let mut encoded_without_attributes = encoded[..48].to_vec();
+        encoded_without_attributes.extend_from_slice(&encoded[81..]);
+        encoded_without_attributes[1] = 81;
+        check_serde!(private_key: PrivateKeyInfo in encoded_without_attributes);
+    }
+
     #[test]
     #[cfg(feature = "legacy")]
     fn old_broken_key_legacy_support() {
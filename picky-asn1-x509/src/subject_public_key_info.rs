@@ -1,4 +1,4 @@
-use crate::{oids, AlgorithmIdentifier};
+use crate::{oids, AlgorithmIdentifier, EcParameters};
 use picky_asn1::wrapper::{BitStringAsn1, BitStringAsn1Container, IntegerAsn1, OctetStringAsn1};
 use serde::{de, ser, Deserialize, Serialize};
 use std::fmt;
@@ -40,6 +40,21 @@ impl SubjectPublicKeyInfo {
             ),
         }
     }
+
+    pub fn new_ec_key(curve: EcParameters, point: impl Into<BitStringAsn1>) -> Self {
+        Self {
+            algorithm: AlgorithmIdentifier::new_elliptic_curve(curve),
+            subject_public_key: PublicKey::Ec(point.into()),
+        }
+    }
+
+    /// `algorithm` is `AlgorithmIdentifier::new_ed25519()` ([RFC 8410](https://tools.ietf.org/html/rfc8410#section-4))
+    pub fn new_ed_key(algorithm: AlgorithmIdentifier, public_key: impl Into<BitStringAsn1>) -> Self {
+        Self {
+            algorithm,
+            subject_public_key: PublicKey::Ed(public_key.into()),
+        }
+    }
 }
 
 impl ser::Serialize for SubjectPublicKeyInfo {
@@ -80,7 +95,9 @@ impl<'de> de::Deserialize<'de> for SubjectPublicKeyInfo {
                 let algorithm: AlgorithmIdentifier = seq_next_element!(seq, AlgorithmIdentifier, "algorithm oid");
 
                 let subject_public_key = match Into::<String>::into(algorithm.oid()).as_str() {
-                    oids::RSA_ENCRYPTION => PublicKey::Rsa(seq_next_element!(seq, SubjectPublicKeyInfo, "rsa key")),
+                    oids::RSA_ENCRYPTION | oids::RSASSA_PSS => {
+                        PublicKey::Rsa(seq_next_element!(seq, SubjectPublicKeyInfo, "rsa key"))
+                    }
                     oids::EC_PUBLIC_KEY => {
                         PublicKey::Ec(seq_next_element!(seq, SubjectPublicKeyInfo, "elliptic curves key"))
                     }
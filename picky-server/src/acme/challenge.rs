@@ -0,0 +1,507 @@
+use crate::acme::{AcmeError, Identifier};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{lookup_host, TcpStream};
+
+const HTTP_01_DEFAULT_PORT: u16 = 80;
+const HTTP_01_DEFAULT_TIMEOUT_SECS: u64 = 10;
+const HTTP_01_MAX_RESPONSE_SIZE: u64 = 8192;
+
+/// Proves control over an identifier on behalf of an ACME account.
+///
+/// Validators are registered on `AcmeState`, each authorization offers one challenge per validator
+/// supporting its identifier.
+pub trait ChallengeValidator: Send + Sync {
+    /// Challenge type advertised to ACME clients (e.g.: `http-01`)
+    fn challenge_type(&self) -> &'static str;
+
+    fn supports(&self, identifier: &Identifier, wildcard: bool) -> bool;
+
+    /// `key_authorization` is the value the client is expected to provision (RFC 8555 #8.1).
+    fn validate<'a>(
+        &'a self,
+        identifier: &'a Identifier,
+        token: &'a str,
+        key_authorization: &'a str,
+        internal_networks: &'a InternalNetworks,
+    ) -> BoxFuture<'a, Result<(), AcmeError>>;
+}
+
+/// Internal DNS names and addresses ACME clients are allowed to use, none by default.
+///
+/// Special-use and single-label names, as well as non-public addresses, are otherwise rejected.
+#[derive(Debug, Clone, Default)]
+pub struct InternalNetworks {
+    /// Matching the name itself and its subdomains (e.g.: `corp.internal`)
+    pub dns_suffixes: Vec<String>,
+    pub ip_ranges: Vec<IpRange>,
+}
+
+impl InternalNetworks {
+    pub fn allows_dns_name(&self, dns_name: &str) -> bool {
+        let dns_name = dns_name.to_ascii_lowercase();
+        self.dns_suffixes.iter().any(|suffix| {
+            let suffix = suffix.trim_start_matches('.').to_ascii_lowercase();
+            dns_name == suffix || dns_name.ends_with(&format!(".{}", suffix))
+        })
+    }
+
+    pub fn allows_address(&self, ip: IpAddr) -> bool {
+        let ip = unmap_ipv4(ip);
+        self.ip_ranges.iter().any(|range| range.contains(ip))
+    }
+}
+
+/// IP address range in CIDR notation (e.g.: `10.0.0.0/8`), a single address when the prefix length is omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IpRange {
+    address: IpAddr,
+    prefix_len: u8,
+}
+
+impl IpRange {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (network, ip, bits) = match (self.address, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => (u128::from(u32::from(network)), u128::from(u32::from(ip)), 32),
+            (IpAddr::V6(network), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+            _ => return false,
+        };
+        let host_bits = bits - u32::from(self.prefix_len);
+        network.checked_shr(host_bits).unwrap_or(0) == ip.checked_shr(host_bits).unwrap_or(0)
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match range.split_once('/') {
+            Some((address, prefix_len)) => (address, Some(prefix_len)),
+            None => (range, None),
+        };
+
+        let address =
+            IpAddr::from_str(address).map_err(|e| format!("invalid address in IP range '{}': {}", range, e))?;
+        let max_prefix_len = if address.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("invalid prefix length in IP range '{}'", range))?,
+            None => max_prefix_len,
+        };
+
+        Ok(Self { address, prefix_len })
+    }
+}
+
+impl TryFrom<String> for IpRange {
+    type Error = String;
+
+    fn try_from(range: String) -> Result<Self, Self::Error> {
+        range.parse()
+    }
+}
+
+impl From<IpRange> for String {
+    fn from(range: IpRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_len)
+    }
+}
+
+/// HTTP challenge (RFC 8555 #8.3).
+///
+/// Redirections are not followed and identifiers resolving to non-public addresses (loopback, private,
+/// link-local…) are rejected unless allowed by `InternalNetworks`: the server must not be usable to reach
+/// its own network.
+pub struct Http01Challenge {
+    port: u16,
+    timeout: Duration,
+}
+
+impl Default for Http01Challenge {
+    fn default() -> Self {
+        Self {
+            port: HTTP_01_DEFAULT_PORT,
+            timeout: Duration::from_secs(HTTP_01_DEFAULT_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl ChallengeValidator for Http01Challenge {
+    fn challenge_type(&self) -> &'static str {
+        "http-01"
+    }
+
+    fn supports(&self, identifier: &Identifier, wildcard: bool) -> bool {
+        identifier.kind == "dns" && !wildcard
+    }
+
+    fn validate<'a>(
+        &'a self,
+        identifier: &'a Identifier,
+        token: &'a str,
+        key_authorization: &'a str,
+        internal_networks: &'a InternalNetworks,
+    ) -> BoxFuture<'a, Result<(), AcmeError>> {
+        async move {
+            let address = format!("{}:{}", identifier.value, self.port);
+            // HTTP/1.0 ensures the response is not chunked
+            let request = format!(
+                "GET /.well-known/acme-challenge/{} HTTP/1.0\r\nHost: {}\r\nAccept: */*\r\n\r\n",
+                token, identifier.value
+            );
+
+            let exchange = async {
+                // connect to the checked addresses only, a second resolution could return different ones
+                let socket_addrs = resolve_allowed_addresses(&address, internal_networks).await?;
+                http_exchange(&address, &socket_addrs, request.as_bytes()).await
+            };
+
+            let response = tokio::time::timeout(self.timeout, exchange)
+                .await
+                .map_err(|_| AcmeError::Connection(format!("timed out while fetching {}", address)))??;
+
+            let body = parse_http_response(&response)?;
+            if body.trim_end() == key_authorization {
+                Ok(())
+            } else {
+                Err(AcmeError::IncorrectResponse(format!(
+                    "key authorization served by {} doesn't match",
+                    identifier.value
+                )))
+            }
+        }
+        .boxed()
+    }
+}
+
+async fn resolve_allowed_addresses(
+    address: &str,
+    internal_networks: &InternalNetworks,
+) -> Result<Vec<SocketAddr>, AcmeError> {
+    let socket_addrs: Vec<SocketAddr> = lookup_host(address)
+        .await
+        .map_err(|e| AcmeError::Connection(format!("couldn't resolve {}: {}", address, e)))?
+        .collect();
+
+    if socket_addrs.is_empty() {
+        return Err(AcmeError::Connection(format!(
+            "{} doesn't resolve to any address",
+            address
+        )));
+    }
+
+    if let Some(forbidden) = socket_addrs
+        .iter()
+        .find(|socket_addr| !is_public_address(socket_addr.ip()) && !internal_networks.allows_address(socket_addr.ip()))
+    {
+        return Err(AcmeError::RejectedIdentifier(format!(
+            "{} resolves to non-public address {}",
+            address,
+            forbidden.ip()
+        )));
+    }
+
+    Ok(socket_addrs)
+}
+
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => is_public_ipv6(ip),
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0 // "this" network
+        || a >= 240 // reserved
+        || (a == 100 && (64..128).contains(&b)) // shared address space
+        || (a == 198 && (18..20).contains(&b))) // benchmarking
+}
+
+/// IPv4-mapped IPv6 addresses are compared as IPv4 addresses.
+fn unmap_ipv4(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => match ipv6.segments() {
+            [0, 0, 0, 0, 0, 0xffff, high, low] => IpAddr::V4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low))),
+            _ => ip,
+        },
+        IpAddr::V4(_) => ip,
+    }
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    match segments {
+        // IPv4-mapped and IPv4-compatible addresses
+        [0, 0, 0, 0, 0, 0xffff, ..] | [0, 0, 0, 0, 0, 0, ..] if !ip.is_loopback() && !ip.is_unspecified() => {
+            let [_, _, _, _, _, _, high, low] = segments;
+            is_public_ipv4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)))
+        }
+        _ => {
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00 // unique local
+                || (segments[0] & 0xffc0) == 0xfe80 // link-local
+                || (segments[0] & 0xffc0) == 0xfec0 // site-local (deprecated)
+                || (segments[0] == 0x2001 && segments[1] == 0x0db8)) // documentation
+        }
+    }
+}
+
+async fn http_exchange(address: &str, socket_addrs: &[SocketAddr], request: &[u8]) -> Result<Vec<u8>, AcmeError> {
+    let mut last_error = None;
+    let mut stream = None;
+    for socket_addr in socket_addrs {
+        match TcpStream::connect(socket_addr).await {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    let mut stream = stream.ok_or_else(|| {
+        let reason = last_error.map(|e| e.to_string()).unwrap_or_default();
+        AcmeError::Connection(format!("couldn't connect to {}: {}", address, reason))
+    })?;
+
+    stream
+        .write_all(request)
+        .await
+        .map_err(|e| AcmeError::Connection(format!("couldn't send request to {}: {}", address, e)))?;
+
+    let mut response = Vec::new();
+    stream
+        .take(HTTP_01_MAX_RESPONSE_SIZE)
+        .read_to_end(&mut response)
+        .await
+        .map_err(|e| AcmeError::Connection(format!("couldn't read response from {}: {}", address, e)))?;
+
+    Ok(response)
+}
+
+fn parse_http_response(response: &[u8]) -> Result<&str, AcmeError> {
+    let response = std::str::from_utf8(response)
+        .map_err(|_| AcmeError::IncorrectResponse("challenge response is not valid utf-8".to_owned()))?;
+
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| AcmeError::IncorrectResponse("truncated challenge response".to_owned()))?;
+
+    let status = head
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .split(' ')
+        .nth(1)
+        .unwrap_or_default();
+    if status != "200" {
+        return Err(AcmeError::IncorrectResponse(format!(
+            "challenge response has status {}",
+            status
+        )));
+    }
+
+    Ok(body)
+}
+
+/// Accepts every challenge without contacting anything.
+///
+/// To be used by tests only.
+#[cfg(test)]
+pub struct AutoValidChallenge;
+
+#[cfg(test)]
+impl ChallengeValidator for AutoValidChallenge {
+    fn challenge_type(&self) -> &'static str {
+        "auto-valid"
+    }
+
+    fn supports(&self, _: &Identifier, _: bool) -> bool {
+        true
+    }
+
+    fn validate<'a>(
+        &'a self,
+        _: &'a Identifier,
+        _: &'a str,
+        _: &'a str,
+        _: &'a InternalNetworks,
+    ) -> BoxFuture<'a, Result<(), AcmeError>> {
+        async { Ok(()) }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_01_response_parsing() {
+        let response = b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\ntoken.thumbprint\n";
+        assert_eq!(parse_http_response(response).unwrap(), "token.thumbprint\n");
+
+        let response = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(
+            parse_http_response(response).unwrap_err().to_string(),
+            "challenge response has status 404"
+        );
+
+        let response = b"HTTP/1.1 200 OK\r\n";
+        assert_eq!(
+            parse_http_response(response).unwrap_err().to_string(),
+            "truncated challenge response"
+        );
+    }
+
+    #[test]
+    fn public_addresses() {
+        for public in [
+            "93.184.216.34",
+            "2606:2800:220:1:248:1893:25c8:1946",
+            "::ffff:93.184.216.34",
+        ] {
+            assert!(is_public_address(public.parse().unwrap()), "{}", public);
+        }
+
+        for non_public in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "255.255.255.255",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!is_public_address(non_public.parse().unwrap()), "{}", non_public);
+        }
+    }
+
+    #[test]
+    fn loopback_resolution_rejected() {
+        let err =
+            tokio_test::block_on(resolve_allowed_addresses("127.0.0.1:80", &InternalNetworks::default())).unwrap_err();
+        assert_eq!(err.to_string(), "127.0.0.1:80 resolves to non-public address 127.0.0.1");
+    }
+
+    #[test]
+    fn internal_networks() {
+        let internal_networks = InternalNetworks {
+            dns_suffixes: vec!["corp.internal".to_owned(), ".Lan".to_owned()],
+            ip_ranges: vec!["10.0.0.0/8".parse().unwrap(), "fd00::/8".parse().unwrap()],
+        };
+
+        for allowed in ["corp.internal", "printer.corp.internal", "lan", "nas.LAN"] {
+            assert!(internal_networks.allows_dns_name(allowed), "{}", allowed);
+        }
+        for denied in ["internal", "evilcorp.internal", "corp.internal.example.com", "plan"] {
+            assert!(!internal_networks.allows_dns_name(denied), "{}", denied);
+        }
+
+        for allowed in ["10.1.2.3", "::ffff:10.1.2.3", "fd12::1"] {
+            assert!(
+                internal_networks.allows_address(allowed.parse().unwrap()),
+                "{}",
+                allowed
+            );
+        }
+        for denied in ["11.1.2.3", "127.0.0.1", "fe80::1"] {
+            assert!(!internal_networks.allows_address(denied.parse().unwrap()), "{}", denied);
+        }
+
+        let range: IpRange = "192.168.1.42".parse().unwrap();
+        assert_eq!(range.to_string(), "192.168.1.42/32");
+        assert!(range.contains("192.168.1.42".parse().unwrap()));
+        assert!(!range.contains("192.168.1.43".parse().unwrap()));
+        assert!("0.0.0.0/0"
+            .parse::<IpRange>()
+            .unwrap()
+            .contains("8.8.8.8".parse().unwrap()));
+        assert!("::/0"
+            .parse::<IpRange>()
+            .unwrap()
+            .contains("2001:db8::1".parse().unwrap()));
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("10.0.0/8".parse::<IpRange>().is_err());
+    }
+
+    #[tokio::test]
+    async fn http_01_internal_validation() {
+        let mut listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let len = stream.read(&mut request).await.unwrap();
+            assert!(std::str::from_utf8(&request[..len])
+                .unwrap()
+                .starts_with("GET /.well-known/acme-challenge/token HTTP/1.0\r\n"));
+            stream
+                .write_all(b"HTTP/1.0 200 OK\r\n\r\ntoken.thumbprint")
+                .await
+                .unwrap();
+        };
+
+        let challenge = Http01Challenge {
+            port,
+            timeout: Duration::from_secs(5),
+        };
+        let identifier = Identifier {
+            kind: "dns".to_owned(),
+            value: "127.0.0.1".to_owned(),
+        };
+        let internal_networks = InternalNetworks {
+            dns_suffixes: Vec::new(),
+            ip_ranges: vec!["127.0.0.0/8".parse().unwrap()],
+        };
+
+        let err = challenge
+            .validate(&identifier, "token", "token.thumbprint", &InternalNetworks::default())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("127.0.0.1:{} resolves to non-public address 127.0.0.1", port)
+        );
+
+        let (result, ()) = futures::join!(
+            challenge.validate(&identifier, "token", "token.thumbprint", &internal_networks),
+            server
+        );
+        result.expect("allowed internal address");
+    }
+}
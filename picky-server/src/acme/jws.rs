@@ -0,0 +1,98 @@
+use crate::acme::AcmeError;
+use picky::jose::jwk::Jwk;
+use picky::jose::jws::{JwsAlg, JwsJson};
+
+/// Key used to sign an ACME request (RFC 8555 #6.2).
+pub enum RequestKey {
+    /// Only used to create accounts
+    Jwk(Box<Jwk>),
+    /// Account URL
+    Kid(String),
+}
+
+/// ACME request whose signature is not checked yet.
+pub struct SignedRequest {
+    jws: JwsJson,
+    pub nonce: String,
+    pub url: String,
+    pub key: RequestKey,
+}
+
+impl SignedRequest {
+    /// Requests must use the flattened JSON serialization with a single signature
+    /// and integrity protected header parameters only.
+    pub fn parse(body: &[u8]) -> Result<Self, AcmeError> {
+        let body =
+            std::str::from_utf8(body).map_err(|_| AcmeError::Malformed("request body is not utf-8".to_owned()))?;
+        let jws = JwsJson::from_json(body).map_err(|e| AcmeError::Malformed(format!("invalid JWS: {}", e)))?;
+
+        let signature = match jws.signatures.as_slice() {
+            [signature] => signature,
+            _ => return Err(AcmeError::Malformed("JWS must have exactly one signature".to_owned())),
+        };
+
+        if signature.unprotected_header.is_some() {
+            return Err(AcmeError::Malformed(
+                "JWS must not have unprotected header parameters".to_owned(),
+            ));
+        }
+
+        if matches!(signature.header.alg, JwsAlg::HS256 | JwsAlg::HS384 | JwsAlg::HS512) {
+            return Err(AcmeError::BadSignatureAlgorithm(format!(
+                "{:?} is not allowed",
+                signature.header.alg
+            )));
+        }
+
+        let protected_header = signature
+            .protected_header()
+            .map_err(|e| AcmeError::Malformed(format!("invalid protected header: {}", e)))?;
+        let header_string = |name: &str| {
+            protected_header
+                .get(name)
+                .and_then(|value| value.as_str())
+                .map(str::to_owned)
+                .ok_or_else(|| AcmeError::Malformed(format!("missing '{}' protected header parameter", name)))
+        };
+
+        let nonce = header_string("nonce").map_err(|e| AcmeError::BadNonce(e.to_string()))?;
+        let url = header_string("url")?;
+
+        let key = match (&signature.header.jwk, &signature.header.kid) {
+            (Some(jwk), None) => RequestKey::Jwk(Box::new(jwk.clone())),
+            (None, Some(kid)) => RequestKey::Kid(kid.clone()),
+            _ => {
+                return Err(AcmeError::Malformed(
+                    "exactly one of 'jwk' and 'kid' header parameters is expected".to_owned(),
+                ))
+            }
+        };
+
+        Ok(Self { jws, nonce, url, key })
+    }
+
+    pub fn verify(self, jwk: &Jwk) -> Result<Vec<u8>, AcmeError> {
+        let signature = &self.jws.signatures[0];
+
+        if jwk.key.is_private() || jwk.key.is_oct() {
+            return Err(AcmeError::BadPublicKey("a public key is expected".to_owned()));
+        }
+
+        if !jwk.is_suitable_for_jws(signature.header.alg) {
+            return Err(AcmeError::BadSignatureAlgorithm(format!(
+                "{:?} can't be used with this key",
+                signature.header.alg
+            )));
+        }
+
+        let public_key = jwk
+            .to_public_key()
+            .map_err(|e| AcmeError::BadPublicKey(e.to_string()))?;
+
+        signature
+            .check_signature(&self.jws.payload, &public_key)
+            .map_err(|e| AcmeError::Malformed(format!("invalid JWS signature: {}", e)))?;
+
+        Ok(self.jws.payload)
+    }
+}
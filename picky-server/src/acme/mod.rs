@@ -0,0 +1,1597 @@
+//! ACME (RFC 8555) server.
+//!
+//! Accounts, orders, authorizations and challenges are kept in memory: they are short-lived
+//! and issued certificates are saved by the regular storage when `save_certificate` is set.
+//!
+//! This state is not persistent: it is lost when the server restarts and isn't shared between
+//! instances, so ACME clients must be routed to a single instance. Orders (with their authorizations,
+//! challenges and certificate download) are forgotten once expired, idle accounts without orders after
+//! `ACCOUNT_RETENTION_DAYS`, and the number of accounts and orders is capped.
+
+pub mod challenge;
+mod jws;
+
+use crate::acme::challenge::{ChallengeValidator, InternalNetworks};
+use crate::acme::jws::{RequestKey, SignedRequest};
+use picky::hash::HashAlgorithm;
+use picky::jose::jwk::Jwk;
+use picky::x509::Csr;
+use picky_asn1_x509::{ExtensionView, GeneralName};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use thiserror::Error;
+use time::OffsetDateTime;
+
+pub const REPLAY_NONCE_HEADER: &str = "Replay-Nonce";
+
+const MAX_ISSUED_NONCES: usize = 4096;
+const ORDER_VALIDITY_DAYS: i64 = 7;
+const ACCOUNT_RETENTION_DAYS: i64 = 30;
+const MAX_ACCOUNTS: usize = 10_000;
+const MAX_ORDERS: usize = 10_000;
+const MAX_ORDERS_PER_ACCOUNT: usize = 100;
+
+/// Special-use domain names which never resolve to public hosts (RFC 6761, RFC 6762, RFC 8375).
+const RESERVED_DOMAINS: &[&str] = &[
+    "localhost",
+    "local",
+    "internal",
+    "home.arpa",
+    "arpa",
+    "test",
+    "example",
+    "invalid",
+    "onion",
+];
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+const PEM_CHAIN_CONTENT_TYPE: &str = "application/pem-certificate-chain";
+
+/// ACME errors are reported to clients as problem documents (RFC 8555 #6.7).
+#[derive(Debug, Error)]
+pub enum AcmeError {
+    #[error("{0}")]
+    Malformed(String),
+    #[error("{0}")]
+    BadNonce(String),
+    #[error("{0}")]
+    BadSignatureAlgorithm(String),
+    #[error("{0}")]
+    BadPublicKey(String),
+    #[error("{0}")]
+    Unauthorized(String),
+    #[error("no account exists with the provided key")]
+    AccountDoesNotExist,
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    #[error("{0}")]
+    UnsupportedIdentifier(String),
+    #[error("{0}")]
+    RejectedIdentifier(String),
+    #[error("{0}")]
+    OrderNotReady(String),
+    #[error("{0}")]
+    BadCsr(String),
+    #[error("{0}")]
+    IncorrectResponse(String),
+    #[error("{0}")]
+    Connection(String),
+    #[error("{0}")]
+    RateLimited(String),
+    #[error("{0}")]
+    ServerInternal(String),
+}
+
+impl AcmeError {
+    fn problem_type(&self) -> &'static str {
+        match self {
+            AcmeError::Malformed(_) | AcmeError::NotFound(_) => "malformed",
+            AcmeError::BadNonce(_) => "badNonce",
+            AcmeError::BadSignatureAlgorithm(_) => "badSignatureAlgorithm",
+            AcmeError::BadPublicKey(_) => "badPublicKey",
+            AcmeError::Unauthorized(_) => "unauthorized",
+            AcmeError::AccountDoesNotExist => "accountDoesNotExist",
+            AcmeError::UnsupportedIdentifier(_) => "unsupportedIdentifier",
+            AcmeError::RejectedIdentifier(_) => "rejectedIdentifier",
+            AcmeError::OrderNotReady(_) => "orderNotReady",
+            AcmeError::BadCsr(_) => "badCSR",
+            AcmeError::IncorrectResponse(_) => "incorrectResponse",
+            AcmeError::Connection(_) => "connection",
+            AcmeError::RateLimited(_) => "rateLimited",
+            AcmeError::ServerInternal(_) => "serverInternal",
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        match self {
+            AcmeError::Unauthorized(_) | AcmeError::OrderNotReady(_) | AcmeError::IncorrectResponse(_) => 403,
+            AcmeError::NotFound(_) => 404,
+            AcmeError::RateLimited(_) => 429,
+            AcmeError::ServerInternal(_) => 500,
+            _ => 400,
+        }
+    }
+
+    pub fn to_problem(&self) -> Value {
+        json!({
+            "type": format!("urn:ietf:params:acme:error:{}", self.problem_type()),
+            "detail": self.to_string(),
+            "status": self.status(),
+        })
+    }
+}
+
+/// Protocol-level response, the HTTP layer adds a fresh `Replay-Nonce` header.
+#[derive(Debug)]
+pub struct AcmeResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub location: Option<String>,
+    pub link: Option<String>,
+    pub body: Vec<u8>,
+}
+
+impl AcmeResponse {
+    fn json(status: u16, body: &Value) -> Self {
+        Self {
+            status,
+            content_type: JSON_CONTENT_TYPE,
+            location: None,
+            link: None,
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// Responses only conveying a nonce (`newNonce` resource).
+    pub fn no_content(status: u16) -> Self {
+        Self {
+            status,
+            content_type: JSON_CONTENT_TYPE,
+            location: None,
+            link: None,
+            body: Vec::new(),
+        }
+    }
+
+    fn location(mut self, location: String) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    fn link_up(mut self, url: String) -> Self {
+        self.link = Some(format!("<{}>;rel=\"up\"", url));
+        self
+    }
+}
+
+impl From<AcmeError> for AcmeResponse {
+    fn from(e: AcmeError) -> Self {
+        Self {
+            status: e.status(),
+            content_type: PROBLEM_CONTENT_TYPE,
+            location: None,
+            link: None,
+            body: e.to_problem().to_string().into_bytes(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Identifier {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Pending,
+    Ready,
+    Processing,
+    Valid,
+    Invalid,
+    Deactivated,
+    Expired,
+}
+
+struct Account {
+    jwk: Jwk,
+    contact: Vec<String>,
+    status: Status,
+    orders: Vec<String>,
+    last_used: OffsetDateTime,
+}
+
+struct Order {
+    account_id: String,
+    status: Status,
+    expires: OffsetDateTime,
+    identifiers: Vec<Identifier>,
+    authorizations: Vec<String>,
+    certificate: Option<String>,
+    error: Option<Value>,
+}
+
+struct Authorization {
+    account_id: String,
+    identifier: Identifier,
+    wildcard: bool,
+    status: Status,
+    expires: OffsetDateTime,
+    challenges: Vec<String>,
+}
+
+struct Challenge {
+    authorization_id: String,
+    kind: &'static str,
+    token: String,
+    status: Status,
+    validated: Option<OffsetDateTime>,
+    error: Option<Value>,
+}
+
+#[derive(Default)]
+struct Registry {
+    nonces: HashSet<String>,
+    nonces_order: VecDeque<String>,
+    accounts: HashMap<String, Account>,
+    accounts_by_thumbprint: HashMap<String, String>,
+    orders: HashMap<String, Order>,
+    authorizations: HashMap<String, Authorization>,
+    challenges: HashMap<String, Challenge>,
+    certificates: HashMap<String, String>,
+}
+
+/// ACME resources and their URLs relative to the configured base URL.
+pub struct AcmeState {
+    validators: Vec<Box<dyn ChallengeValidator>>,
+    registry: Mutex<Registry>,
+}
+
+impl AcmeState {
+    pub fn new(validators: Vec<Box<dyn ChallengeValidator>>) -> Self {
+        Self {
+            validators,
+            registry: Mutex::new(Registry::default()),
+        }
+    }
+
+    fn registry(&self) -> MutexGuard<'_, Registry> {
+        // registry updates never leave it inconsistent, a panic elsewhere mustn't take the ACME service down
+        self.registry.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn directory(&self, base_url: &str) -> AcmeResponse {
+        let urls = Urls(base_url);
+        AcmeResponse::json(
+            200,
+            &json!({
+                "newNonce": urls.resource("new-nonce"),
+                "newAccount": urls.resource("new-account"),
+                "newOrder": urls.resource("new-order"),
+            }),
+        )
+    }
+
+    pub fn new_nonce(&self) -> String {
+        let nonce = random_id(16);
+
+        let mut registry = self.registry();
+        if registry.nonces_order.len() >= MAX_ISSUED_NONCES {
+            if let Some(oldest) = registry.nonces_order.pop_front() {
+                registry.nonces.remove(&oldest);
+            }
+        }
+        registry.nonces.insert(nonce.clone());
+        registry.nonces_order.push_back(nonce.clone());
+
+        nonce
+    }
+
+    pub fn new_account(&self, base_url: &str, body: &[u8]) -> Result<AcmeResponse, AcmeError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct NewAccountPayload {
+            #[serde(default)]
+            contact: Vec<String>,
+            #[serde(default)]
+            only_return_existing: bool,
+        }
+
+        let urls = Urls(base_url);
+        let request = SignedRequest::parse(body)?;
+        let jwk = match &request.key {
+            RequestKey::Jwk(jwk) => jwk.as_ref().clone(),
+            RequestKey::Kid(_) => {
+                return Err(AcmeError::Malformed(
+                    "new accounts must be requested using a 'jwk' header parameter".to_owned(),
+                ))
+            }
+        };
+        let payload = self.verify_request(&urls.resource("new-account"), request, &jwk)?;
+        let payload: NewAccountPayload = parse_payload(&payload)?;
+
+        let thumbprint = jwk.thumbprint_base64url(HashAlgorithm::SHA2_256);
+
+        let now = OffsetDateTime::now_utc();
+        let mut registry = self.registry();
+        registry.purge_expired(now);
+
+        if let Some(account_id) = registry.accounts_by_thumbprint.get(&thumbprint).cloned() {
+            let account = registry.accounts.get_mut(&account_id).expect("account");
+            account.last_used = now;
+            return Ok(AcmeResponse::json(200, &account_json(&urls, &account_id, account))
+                .location(urls.resource_id("account", &account_id)));
+        }
+
+        if payload.only_return_existing {
+            return Err(AcmeError::AccountDoesNotExist);
+        }
+
+        if registry.accounts.len() >= MAX_ACCOUNTS {
+            return Err(AcmeError::RateLimited("too many ACME accounts".to_owned()));
+        }
+
+        let account_id = random_id(12);
+        let account = Account {
+            jwk,
+            contact: payload.contact,
+            status: Status::Valid,
+            orders: Vec::new(),
+            last_used: now,
+        };
+        let response = AcmeResponse::json(201, &account_json(&urls, &account_id, &account))
+            .location(urls.resource_id("account", &account_id));
+
+        registry.accounts_by_thumbprint.insert(thumbprint, account_id.clone());
+        registry.accounts.insert(account_id, account);
+
+        Ok(response)
+    }
+
+    pub fn account(&self, base_url: &str, account_id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeError> {
+        #[derive(Deserialize)]
+        struct AccountUpdatePayload {
+            contact: Option<Vec<String>>,
+            status: Option<Status>,
+        }
+
+        let urls = Urls(base_url);
+        let (requester_id, payload) = self.authenticate(&urls, &urls.resource_id("account", account_id), body)?;
+        if requester_id != account_id {
+            return Err(AcmeError::Unauthorized(
+                "account doesn't belong to requester".to_owned(),
+            ));
+        }
+
+        let mut registry = self.registry();
+        let account = registry
+            .accounts
+            .get_mut(account_id)
+            .ok_or(AcmeError::NotFound("account"))?;
+
+        if !payload.is_empty() {
+            let update: AccountUpdatePayload = parse_payload(&payload)?;
+
+            if let Some(contact) = update.contact {
+                account.contact = contact;
+            }
+
+            match update.status {
+                Some(Status::Deactivated) => account.status = Status::Deactivated,
+                Some(status) => {
+                    return Err(AcmeError::Malformed(format!(
+                        "account status can't be changed to {:?}",
+                        status
+                    )))
+                }
+                None => {}
+            }
+        }
+
+        Ok(AcmeResponse::json(200, &account_json(&urls, account_id, account)))
+    }
+
+    pub fn account_orders(&self, base_url: &str, account_id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeError> {
+        let urls = Urls(base_url);
+        let url = format!("{}/orders", urls.resource_id("account", account_id));
+        let (requester_id, _) = self.authenticate(&urls, &url, body)?;
+        if requester_id != account_id {
+            return Err(AcmeError::Unauthorized(
+                "account doesn't belong to requester".to_owned(),
+            ));
+        }
+
+        let registry = self.registry();
+        let orders = registry
+            .accounts
+            .get(account_id)
+            .ok_or(AcmeError::NotFound("account"))?
+            .orders
+            .iter()
+            .map(|order_id| urls.resource_id("order", order_id))
+            .collect::<Vec<String>>();
+
+        Ok(AcmeResponse::json(200, &json!({ "orders": orders })))
+    }
+
+    pub fn new_order(
+        &self,
+        base_url: &str,
+        body: &[u8],
+        internal_networks: &InternalNetworks,
+    ) -> Result<AcmeResponse, AcmeError> {
+        #[derive(Deserialize)]
+        struct NewOrderPayload {
+            identifiers: Vec<Identifier>,
+        }
+
+        let urls = Urls(base_url);
+        let (account_id, payload) = self.authenticate(&urls, &urls.resource("new-order"), body)?;
+        let payload: NewOrderPayload = parse_payload(&payload)?;
+
+        if payload.identifiers.is_empty() {
+            return Err(AcmeError::Malformed("at least one identifier is expected".to_owned()));
+        }
+
+        let expires = OffsetDateTime::now_utc() + time::Duration::days(ORDER_VALIDITY_DAYS);
+
+        let mut identifiers = Vec::new();
+        let mut authorizations = Vec::new();
+        let mut challenges = Vec::new();
+        for identifier in payload.identifiers {
+            let (identifier, wildcard) = normalize_identifier(identifier, internal_networks)?;
+            if identifiers.contains(&identifier) {
+                continue;
+            }
+
+            let authorization_id = random_id(12);
+            let authorization_identifier = Identifier {
+                kind: identifier.kind.clone(),
+                value: identifier.value.trim_start_matches("*.").to_owned(),
+            };
+
+            let mut challenge_ids = Vec::new();
+            for validator in self
+                .validators
+                .iter()
+                .filter(|validator| validator.supports(&authorization_identifier, wildcard))
+            {
+                let challenge_id = random_id(12);
+                challenges.push((
+                    challenge_id.clone(),
+                    Challenge {
+                        authorization_id: authorization_id.clone(),
+                        kind: validator.challenge_type(),
+                        token: random_id(32),
+                        status: Status::Pending,
+                        validated: None,
+                        error: None,
+                    },
+                ));
+                challenge_ids.push(challenge_id);
+            }
+
+            if challenge_ids.is_empty() {
+                return Err(AcmeError::RejectedIdentifier(format!(
+                    "no challenge available for {}",
+                    identifier.value
+                )));
+            }
+
+            authorizations.push((
+                authorization_id,
+                Authorization {
+                    account_id: account_id.clone(),
+                    identifier: authorization_identifier,
+                    wildcard,
+                    status: Status::Pending,
+                    expires,
+                    challenges: challenge_ids,
+                },
+            ));
+            identifiers.push(identifier);
+        }
+
+        let order_id = random_id(12);
+        let order = Order {
+            account_id: account_id.clone(),
+            status: Status::Pending,
+            expires,
+            identifiers,
+            authorizations: authorizations.iter().map(|(id, _)| id.clone()).collect(),
+            certificate: None,
+            error: None,
+        };
+        let response = AcmeResponse::json(201, &order_json(&urls, &order_id, &order))
+            .location(urls.resource_id("order", &order_id));
+
+        let mut registry = self.registry();
+        registry.purge_expired(OffsetDateTime::now_utc());
+
+        let account_orders = registry.accounts.get(&account_id).map(|account| account.orders.len());
+        if registry.orders.len() >= MAX_ORDERS || account_orders.unwrap_or_default() >= MAX_ORDERS_PER_ACCOUNT {
+            return Err(AcmeError::RateLimited("too many pending ACME orders".to_owned()));
+        }
+
+        registry.challenges.extend(challenges);
+        registry.authorizations.extend(authorizations);
+        registry.orders.insert(order_id.clone(), order);
+        if let Some(account) = registry.accounts.get_mut(&account_id) {
+            account.orders.push(order_id);
+        }
+
+        Ok(response)
+    }
+
+    pub fn order(&self, base_url: &str, order_id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeError> {
+        let urls = Urls(base_url);
+        let (account_id, _) = self.authenticate(&urls, &urls.resource_id("order", order_id), body)?;
+
+        let mut registry = self.registry();
+        let order = registry.owned_order(order_id, &account_id)?;
+
+        Ok(AcmeResponse::json(200, &order_json(&urls, order_id, order)))
+    }
+
+    pub fn authorization(
+        &self,
+        base_url: &str,
+        authorization_id: &str,
+        body: &[u8],
+    ) -> Result<AcmeResponse, AcmeError> {
+        let urls = Urls(base_url);
+        let url = urls.resource_id("authz", authorization_id);
+        let (account_id, _) = self.authenticate(&urls, &url, body)?;
+
+        let mut registry = self.registry();
+        registry.owned_authorization(authorization_id, &account_id)?;
+
+        Ok(AcmeResponse::json(
+            200,
+            &authorization_json(&urls, &registry, authorization_id),
+        ))
+    }
+
+    /// Any payload other than POST-as-GET (empty payload) requests the challenge validation.
+    pub async fn challenge(
+        &self,
+        base_url: &str,
+        challenge_id: &str,
+        body: &[u8],
+        internal_networks: &InternalNetworks,
+    ) -> Result<AcmeResponse, AcmeError> {
+        let urls = Urls(base_url);
+        let url = urls.resource_id("chall", challenge_id);
+        let (account_id, payload) = self.authenticate(&urls, &url, body)?;
+
+        let validation = {
+            let mut registry = self.registry();
+            let challenge = registry
+                .challenges
+                .get(challenge_id)
+                .ok_or(AcmeError::NotFound("challenge"))?;
+            let authorization_id = challenge.authorization_id.clone();
+            let authorization = registry.owned_authorization(&authorization_id, &account_id)?;
+            let identifier = authorization.identifier.clone();
+            let authorization_status = authorization.status;
+
+            let challenge = registry
+                .challenges
+                .get_mut(challenge_id)
+                .ok_or(AcmeError::NotFound("challenge"))?;
+            if !payload.is_empty() && challenge.status == Status::Pending && authorization_status == Status::Pending {
+                challenge.status = Status::Processing;
+                let token = challenge.token.clone();
+                let kind = challenge.kind;
+
+                let thumbprint = registry
+                    .accounts
+                    .get(&account_id)
+                    .ok_or(AcmeError::NotFound("account"))?
+                    .jwk
+                    .thumbprint_base64url(HashAlgorithm::SHA2_256);
+                Some((identifier, token, kind, thumbprint))
+            } else {
+                None
+            }
+        };
+
+        if let Some((identifier, token, kind, thumbprint)) = validation {
+            let validator = self
+                .validators
+                .iter()
+                .find(|validator| validator.challenge_type() == kind)
+                .ok_or_else(|| AcmeError::ServerInternal(format!("no validator for {} challenges", kind)))?;
+
+            let key_authorization = format!("{}.{}", token, thumbprint);
+            let result = validator
+                .validate(&identifier, &token, &key_authorization, internal_networks)
+                .await;
+
+            // the order may have expired and been purged during the validation
+            let mut registry = self.registry();
+            let challenge = registry
+                .challenges
+                .get_mut(challenge_id)
+                .ok_or(AcmeError::NotFound("challenge"))?;
+            let status = match result {
+                Ok(()) => {
+                    challenge.validated = Some(OffsetDateTime::now_utc());
+                    Status::Valid
+                }
+                Err(e) => {
+                    log::warn!("{} challenge failed for {}: {}", kind, identifier.value, e);
+                    challenge.error = Some(e.to_problem());
+                    Status::Invalid
+                }
+            };
+            challenge.status = status;
+            let authorization_id = challenge.authorization_id.clone();
+            if let Some(authorization) = registry.authorizations.get_mut(&authorization_id) {
+                authorization.status = status;
+            }
+        }
+
+        let registry = self.registry();
+        let challenge = registry
+            .challenges
+            .get(challenge_id)
+            .ok_or(AcmeError::NotFound("challenge"))?;
+        Ok(AcmeResponse::json(200, &challenge_json(&urls, challenge_id, challenge))
+            .link_up(urls.resource_id("authz", &challenge.authorization_id)))
+    }
+
+    /// `issue` signs the CSR for the given DNS names and returns the PEM certificate chain.
+    pub async fn finalize<F, Fut>(
+        &self,
+        base_url: &str,
+        order_id: &str,
+        body: &[u8],
+        issue: F,
+    ) -> Result<AcmeResponse, AcmeError>
+    where
+        F: FnOnce(Csr, Vec<String>) -> Fut,
+        Fut: Future<Output = Result<String, String>>,
+    {
+        #[derive(Deserialize)]
+        struct FinalizePayload {
+            csr: String,
+        }
+
+        let urls = Urls(base_url);
+        let url = format!("{}/finalize", urls.resource_id("order", order_id));
+        let (account_id, payload) = self.authenticate(&urls, &url, body)?;
+        let payload: FinalizePayload = parse_payload(&payload)?;
+
+        let csr = base64::decode_config(&payload.csr, base64::URL_SAFE_NO_PAD)
+            .map_err(|e| AcmeError::BadCsr(format!("invalid base64url encoding: {}", e)))
+            .and_then(|der| Csr::from_der(&der).map_err(|e| AcmeError::BadCsr(e.to_string())))?;
+        csr.verify().map_err(|e| AcmeError::BadCsr(e.to_string()))?;
+        let csr_names = csr_dns_names(&csr)?;
+
+        {
+            let mut registry = self.registry();
+            let order = registry.owned_order(order_id, &account_id)?;
+            if order.status != Status::Ready {
+                return Err(AcmeError::OrderNotReady(
+                    format!("order is {:?}", order.status).to_lowercase(),
+                ));
+            }
+
+            let mut order_names = order
+                .identifiers
+                .iter()
+                .map(|identifier| identifier.value.clone())
+                .collect::<Vec<String>>();
+            order_names.sort();
+            if order_names != csr_names {
+                return Err(AcmeError::BadCsr(format!(
+                    "CSR names ({}) don't match the order identifiers ({})",
+                    csr_names.join(", "),
+                    order_names.join(", ")
+                )));
+            }
+
+            registry.orders.get_mut(order_id).expect("order").status = Status::Processing;
+        }
+
+        let result = issue(csr, csr_names).await;
+
+        let mut registry = self.registry();
+        match result {
+            Ok(chain) => {
+                let certificate_id = random_id(12);
+                registry.certificates.insert(certificate_id.clone(), chain);
+                let order = registry.orders.get_mut(order_id).expect("order");
+                order.certificate = Some(certificate_id);
+                order.status = Status::Valid;
+            }
+            Err(e) => {
+                log::error!("couldn't issue ACME certificate: {}", e);
+                let order = registry.orders.get_mut(order_id).expect("order");
+                order.error = Some(AcmeError::ServerInternal("couldn't issue certificate".to_owned()).to_problem());
+                order.status = Status::Invalid;
+            }
+        }
+
+        let order = &registry.orders[order_id];
+        Ok(AcmeResponse::json(200, &order_json(&urls, order_id, order)).location(urls.resource_id("order", order_id)))
+    }
+
+    pub fn certificate(&self, base_url: &str, certificate_id: &str, body: &[u8]) -> Result<AcmeResponse, AcmeError> {
+        let urls = Urls(base_url);
+        let (account_id, _) = self.authenticate(&urls, &urls.resource_id("cert", certificate_id), body)?;
+
+        let registry = self.registry();
+        let is_owner = registry
+            .orders
+            .values()
+            .any(|order| order.account_id == account_id && order.certificate.as_deref() == Some(certificate_id));
+        let chain = registry
+            .certificates
+            .get(certificate_id)
+            .filter(|_| is_owner)
+            .ok_or(AcmeError::NotFound("certificate"))?;
+
+        Ok(AcmeResponse {
+            status: 200,
+            content_type: PEM_CHAIN_CONTENT_TYPE,
+            location: None,
+            link: None,
+            body: chain.clone().into_bytes(),
+        })
+    }
+
+    /// Authenticates a request signed by an existing account, returns the account ID and the payload.
+    fn authenticate(&self, urls: &Urls<'_>, url: &str, body: &[u8]) -> Result<(String, Vec<u8>), AcmeError> {
+        let request = SignedRequest::parse(body)?;
+
+        let account_id = match &request.key {
+            RequestKey::Kid(kid) => kid
+                .strip_prefix(&format!("{}/", urls.resource("account")))
+                .ok_or(AcmeError::AccountDoesNotExist)?
+                .to_owned(),
+            RequestKey::Jwk(_) => {
+                return Err(AcmeError::Malformed(
+                    "requests must be signed using a 'kid' header parameter".to_owned(),
+                ))
+            }
+        };
+
+        let jwk = {
+            let mut registry = self.registry();
+            let account = registry
+                .accounts
+                .get_mut(&account_id)
+                .ok_or(AcmeError::AccountDoesNotExist)?;
+            if account.status != Status::Valid {
+                return Err(AcmeError::Unauthorized("account is deactivated".to_owned()));
+            }
+            account.last_used = OffsetDateTime::now_utc();
+            account.jwk.clone()
+        };
+
+        let payload = self.verify_request(url, request, &jwk)?;
+
+        Ok((account_id, payload))
+    }
+
+    fn verify_request(&self, url: &str, request: SignedRequest, jwk: &Jwk) -> Result<Vec<u8>, AcmeError> {
+        if request.url != url {
+            return Err(AcmeError::Unauthorized(format!(
+                "JWS 'url' header parameter doesn't match the request URL ({})",
+                url
+            )));
+        }
+
+        let nonce = request.nonce.clone();
+        let payload = request.verify(jwk)?;
+
+        // nonces are consumed once the signature is known to be valid
+        let mut registry = self.registry();
+        if !registry.nonces.remove(&nonce) {
+            return Err(AcmeError::BadNonce("unknown or already used nonce".to_owned()));
+        }
+        registry.nonces_order.retain(|issued| *issued != nonce);
+
+        Ok(payload)
+    }
+}
+
+impl Registry {
+    /// Forgets expired orders and idle accounts without any order.
+    ///
+    /// Orders being finalized are kept until the certificate is issued.
+    fn purge_expired(&mut self, now: OffsetDateTime) {
+        let expired_orders = self
+            .orders
+            .iter()
+            .filter(|(_, order)| order.expires < now && order.status != Status::Processing)
+            .map(|(order_id, _)| order_id.clone())
+            .collect::<Vec<String>>();
+
+        for order_id in expired_orders {
+            let order = self.orders.remove(&order_id).expect("order");
+
+            for authorization_id in &order.authorizations {
+                if let Some(authorization) = self.authorizations.remove(authorization_id) {
+                    for challenge_id in &authorization.challenges {
+                        self.challenges.remove(challenge_id);
+                    }
+                }
+            }
+
+            if let Some(certificate_id) = &order.certificate {
+                self.certificates.remove(certificate_id);
+            }
+
+            if let Some(account) = self.accounts.get_mut(&order.account_id) {
+                account.orders.retain(|id| *id != order_id);
+            }
+        }
+
+        let account_expiration = now - time::Duration::days(ACCOUNT_RETENTION_DAYS);
+        let accounts = &mut self.accounts;
+        accounts.retain(|_, account| !account.orders.is_empty() || account.last_used >= account_expiration);
+        self.accounts_by_thumbprint
+            .retain(|_, account_id| accounts.contains_key(account_id));
+    }
+
+    fn owned_order(&mut self, order_id: &str, account_id: &str) -> Result<&Order, AcmeError> {
+        let order = self.orders.get(order_id).filter(|order| order.account_id == account_id);
+        let (status, authorizations) = match order {
+            Some(order) => (order.status, order.authorizations.clone()),
+            None => return Err(AcmeError::NotFound("order")),
+        };
+
+        let now = OffsetDateTime::now_utc();
+        for authorization_id in &authorizations {
+            if let Some(authorization) = self.authorizations.get_mut(authorization_id) {
+                if authorization.status == Status::Pending && authorization.expires < now {
+                    authorization.status = Status::Expired;
+                }
+            }
+        }
+
+        if status == Status::Pending {
+            let statuses = authorizations
+                .iter()
+                .filter_map(|authorization_id| self.authorizations.get(authorization_id))
+                .map(|authorization| authorization.status)
+                .collect::<Vec<Status>>();
+
+            let order = self.orders.get_mut(order_id).expect("order");
+            if statuses.iter().all(|status| *status == Status::Valid) {
+                order.status = Status::Ready;
+            } else if statuses
+                .iter()
+                .any(|status| matches!(status, Status::Invalid | Status::Expired | Status::Deactivated))
+                || order.expires < now
+            {
+                order.status = Status::Invalid;
+            }
+        }
+
+        Ok(&self.orders[order_id])
+    }
+
+    fn owned_authorization(&mut self, authorization_id: &str, account_id: &str) -> Result<&Authorization, AcmeError> {
+        let authorization = self
+            .authorizations
+            .get_mut(authorization_id)
+            .filter(|authorization| authorization.account_id == account_id)
+            .ok_or(AcmeError::NotFound("authorization"))?;
+
+        if authorization.status == Status::Pending && authorization.expires < OffsetDateTime::now_utc() {
+            authorization.status = Status::Expired;
+        }
+
+        Ok(authorization)
+    }
+}
+
+struct Urls<'a>(&'a str);
+
+impl Urls<'_> {
+    fn resource(&self, name: &str) -> String {
+        format!("{}/acme/{}", self.0.trim_end_matches('/'), name)
+    }
+
+    fn resource_id(&self, kind: &str, id: &str) -> String {
+        format!("{}/acme/{}/{}", self.0.trim_end_matches('/'), kind, id)
+    }
+}
+
+fn account_json(urls: &Urls<'_>, account_id: &str, account: &Account) -> Value {
+    json!({
+        "status": account.status,
+        "contact": account.contact,
+        "orders": format!("{}/orders", urls.resource_id("account", account_id)),
+    })
+}
+
+fn order_json(urls: &Urls<'_>, order_id: &str, order: &Order) -> Value {
+    let mut json = json!({
+        "status": order.status,
+        "expires": rfc3339(order.expires),
+        "identifiers": order.identifiers,
+        "authorizations": order
+            .authorizations
+            .iter()
+            .map(|authorization_id| urls.resource_id("authz", authorization_id))
+            .collect::<Vec<String>>(),
+        "finalize": format!("{}/finalize", urls.resource_id("order", order_id)),
+    });
+
+    if let Some(certificate_id) = &order.certificate {
+        json["certificate"] = Value::String(urls.resource_id("cert", certificate_id));
+    }
+
+    if let Some(error) = &order.error {
+        json["error"] = error.clone();
+    }
+
+    json
+}
+
+fn authorization_json(urls: &Urls<'_>, registry: &Registry, authorization_id: &str) -> Value {
+    let authorization = &registry.authorizations[authorization_id];
+
+    let mut json = json!({
+        "status": authorization.status,
+        "expires": rfc3339(authorization.expires),
+        "identifier": authorization.identifier,
+        "challenges": authorization
+            .challenges
+            .iter()
+            .filter_map(|challenge_id| {
+                registry
+                    .challenges
+                    .get(challenge_id)
+                    .map(|challenge| challenge_json(urls, challenge_id, challenge))
+            })
+            .collect::<Vec<Value>>(),
+    });
+
+    if authorization.wildcard {
+        json["wildcard"] = Value::Bool(true);
+    }
+
+    json
+}
+
+fn challenge_json(urls: &Urls<'_>, challenge_id: &str, challenge: &Challenge) -> Value {
+    let mut json = json!({
+        "type": challenge.kind,
+        "url": urls.resource_id("chall", challenge_id),
+        "status": challenge.status,
+        "token": challenge.token,
+    });
+
+    if let Some(validated) = challenge.validated {
+        json["validated"] = Value::String(rfc3339(validated));
+    }
+
+    if let Some(error) = &challenge.error {
+        json["error"] = error.clone();
+    }
+
+    json
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(payload: &[u8]) -> Result<T, AcmeError> {
+    serde_json::from_slice(payload).map_err(|e| AcmeError::Malformed(format!("invalid payload: {}", e)))
+}
+
+/// Only DNS identifiers are supported. Returns the normalized identifier and whether it is a wildcard.
+fn normalize_identifier(
+    identifier: Identifier,
+    internal_networks: &InternalNetworks,
+) -> Result<(Identifier, bool), AcmeError> {
+    if identifier.kind != "dns" {
+        return Err(AcmeError::UnsupportedIdentifier(format!(
+            "unsupported identifier type: {}",
+            identifier.kind
+        )));
+    }
+
+    let value = identifier.value.to_ascii_lowercase();
+    let (domain, wildcard) = match value.strip_prefix("*.") {
+        Some(domain) => (domain, true),
+        None => (value.as_str(), false),
+    };
+
+    let is_valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.bytes().all(|byte| byte.is_ascii_alphanumeric() || byte == b'-')
+    };
+
+    if domain.len() > 253 || !domain.split('.').all(is_valid_label) {
+        return Err(AcmeError::RejectedIdentifier(format!(
+            "invalid DNS name: {}",
+            identifier.value
+        )));
+    }
+
+    // IP literals, single-label names and special-use domains (RFC 6761) can't be publicly validated,
+    // the latter two are accepted for configured internal domains
+    let tld = domain.rsplit('.').next().unwrap_or_default();
+    if domain.parse::<IpAddr>().is_ok() || tld.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(AcmeError::RejectedIdentifier(format!(
+            "IP address can't be used as DNS name: {}",
+            identifier.value
+        )));
+    }
+
+    let is_internal = internal_networks.allows_dns_name(domain);
+
+    if !is_internal && !domain.contains('.') {
+        return Err(AcmeError::RejectedIdentifier(format!(
+            "DNS name must be fully qualified: {}",
+            identifier.value
+        )));
+    }
+
+    let is_reserved = RESERVED_DOMAINS
+        .iter()
+        .any(|reserved| domain == *reserved || domain.ends_with(&format!(".{}", reserved)));
+    if !is_internal && is_reserved {
+        return Err(AcmeError::RejectedIdentifier(format!(
+            "reserved DNS name: {}",
+            identifier.value
+        )));
+    }
+
+    Ok((
+        Identifier {
+            kind: identifier.kind,
+            value,
+        },
+        wildcard,
+    ))
+}
+
+/// Subject common name and DNS subject alternative names, sorted and deduplicated.
+fn csr_dns_names(csr: &Csr) -> Result<Vec<String>, AcmeError> {
+    let mut names = Vec::new();
+
+    if let Some(common_name) = csr.subject_name().find_common_name() {
+        names.push(common_name.to_string().to_ascii_lowercase());
+    }
+
+    for extension in csr.requested_extensions() {
+        if let ExtensionView::SubjectAltName(subject_alt_name) = extension.extn_value() {
+            for name in subject_alt_name.0 {
+                match name {
+                    GeneralName::DnsName(dns_name) => names.push(dns_name.to_string().to_ascii_lowercase()),
+                    _ => return Err(AcmeError::BadCsr("only DNS names are supported".to_owned())),
+                }
+            }
+        }
+    }
+
+    names.sort();
+    names.dedup();
+
+    if names.is_empty() {
+        return Err(AcmeError::BadCsr("CSR doesn't contain any name".to_owned()));
+    }
+
+    Ok(names)
+}
+
+fn random_id(len: usize) -> String {
+    let mut bytes = vec![0; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn rfc3339(date: OffsetDateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        date.year(),
+        u8::from(date.month()),
+        date.day(),
+        date.hour(),
+        date.minute(),
+        date.second()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::acme::challenge::AutoValidChallenge;
+    use futures::channel::oneshot;
+    use futures::future::BoxFuture;
+    use futures::FutureExt;
+    use picky::key::PrivateKey;
+    use picky::signature::SignatureAlgorithm;
+    use picky::x509::csr::Attribute;
+    use picky::x509::name::{DirectoryName, GeneralName, GeneralNames};
+    use picky::x509::Extension;
+    use tokio_test::block_on;
+
+    const BASE_URL: &str = "https://picky.example.com";
+
+    struct Client {
+        key: PrivateKey,
+        account_url: Option<String>,
+    }
+
+    impl Client {
+        fn new(key_pem: &str) -> Self {
+            Self {
+                key: PrivateKey::from_pem_str(key_pem).unwrap(),
+                account_url: None,
+            }
+        }
+
+        fn sign(&self, nonce: &str, url: &str, payload: &str) -> Vec<u8> {
+            let mut protected = json!({ "alg": "RS256", "nonce": nonce, "url": url });
+            match &self.account_url {
+                Some(account_url) => protected["kid"] = Value::String(account_url.clone()),
+                None => {
+                    let jwk = Jwk::from_public_key(&self.key.to_public_key()).unwrap();
+                    protected["jwk"] = serde_json::to_value(&jwk).unwrap();
+                }
+            }
+
+            let protected = base64::encode_config(protected.to_string(), base64::URL_SAFE_NO_PAD);
+            let payload = base64::encode_config(payload, base64::URL_SAFE_NO_PAD);
+            let signature = SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256)
+                .sign(format!("{}.{}", protected, payload).as_bytes(), &self.key)
+                .unwrap();
+
+            json!({
+                "protected": protected,
+                "payload": payload,
+                "signature": base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+            })
+            .to_string()
+            .into_bytes()
+        }
+
+        fn post(&self, state: &AcmeState, url: &str, payload: &str) -> Vec<u8> {
+            self.sign(&state.new_nonce(), url, payload)
+        }
+    }
+
+    fn body(response: &AcmeResponse) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    fn id(url: &str) -> &str {
+        url.rsplit('/').next().unwrap()
+    }
+
+    fn generate_csr(common_name: &str, dns_names: &[&str]) -> String {
+        let key = PrivateKey::from_pem_str(crate::test_files::RSA_2048_PK_3).unwrap();
+        let san = dns_names.iter().fold(
+            GeneralNames::new(GeneralName::new_dns_name(dns_names[0]).unwrap()),
+            |san, name| san.with_name(GeneralName::new_dns_name(*name).unwrap()),
+        );
+        let csr = Csr::generate_with_attributes(
+            DirectoryName::new_common_name(common_name),
+            &key,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+            vec![Attribute::new_extension_request(vec![Extension::new_subject_alt_name(
+                san,
+            )])],
+        )
+        .unwrap();
+        base64::encode_config(csr.to_der().unwrap(), base64::URL_SAFE_NO_PAD)
+    }
+
+    #[test]
+    fn account_order_and_finalize() {
+        let state = AcmeState::new(vec![Box::new(AutoValidChallenge)]);
+        let mut client = Client::new(crate::test_files::RSA_2048_PK_1);
+
+        let directory = body(&state.directory(BASE_URL));
+        let new_account_url = directory["newAccount"].as_str().unwrap();
+        let new_order_url = directory["newOrder"].as_str().unwrap();
+
+        let payload = r#"{"contact":["mailto:admin@example.com"],"termsOfServiceAgreed":true}"#;
+        let response = state
+            .new_account(BASE_URL, &client.post(&state, new_account_url, payload))
+            .unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(body(&response)["status"], "valid");
+        let account_url = response.location.unwrap();
+
+        // the same key always maps to the same account
+        let response = state
+            .new_account(BASE_URL, &client.post(&state, new_account_url, "{}"))
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.location.as_ref(), Some(&account_url));
+
+        client.account_url = Some(account_url);
+
+        let payload =
+            r#"{"identifiers":[{"type":"dns","value":"example.com"},{"type":"dns","value":"WWW.example.com"}]}"#;
+        let response = state
+            .new_order(
+                BASE_URL,
+                &client.post(&state, new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap();
+        assert_eq!(response.status, 201);
+        let order_url = response.location.clone().unwrap();
+        let order = body(&response);
+        assert_eq!(order["status"], "pending");
+        assert_eq!(order["identifiers"][1]["value"], "www.example.com");
+        let finalize_url = order["finalize"].as_str().unwrap();
+
+        let csr = generate_csr("example.com", &["www.example.com"]);
+        let payload = format!(r#"{{"csr":"{}"}}"#, csr);
+        let err = block_on(state.finalize(
+            BASE_URL,
+            id(&order_url),
+            &client.post(&state, finalize_url, &payload),
+            |_, _| async { Ok(String::new()) },
+        ))
+        .unwrap_err();
+        assert_eq!(err.to_problem()["type"], "urn:ietf:params:acme:error:orderNotReady");
+
+        for authorization_url in order["authorizations"].as_array().unwrap() {
+            let authorization_url = authorization_url.as_str().unwrap();
+            let response = state
+                .authorization(
+                    BASE_URL,
+                    id(authorization_url),
+                    &client.post(&state, authorization_url, ""),
+                )
+                .unwrap();
+            let authorization = body(&response);
+            assert_eq!(authorization["status"], "pending");
+
+            let challenge = &authorization["challenges"][0];
+            assert_eq!(challenge["type"], "auto-valid");
+            let challenge_url = challenge["url"].as_str().unwrap();
+            let response = block_on(state.challenge(
+                BASE_URL,
+                id(challenge_url),
+                &client.post(&state, challenge_url, "{}"),
+                &InternalNetworks::default(),
+            ))
+            .unwrap();
+            assert_eq!(body(&response)["status"], "valid");
+            assert_eq!(response.link, Some(format!("<{}>;rel=\"up\"", authorization_url)));
+        }
+
+        let response = state
+            .order(BASE_URL, id(&order_url), &client.post(&state, &order_url, ""))
+            .unwrap();
+        assert_eq!(body(&response)["status"], "ready");
+
+        let mismatching_csr = generate_csr("example.com", &["mail.example.com"]);
+        let payload = format!(r#"{{"csr":"{}"}}"#, mismatching_csr);
+        let err = block_on(state.finalize(
+            BASE_URL,
+            id(&order_url),
+            &client.post(&state, finalize_url, &payload),
+            |_, _| async { Ok(String::new()) },
+        ))
+        .unwrap_err();
+        assert_eq!(err.to_problem()["type"], "urn:ietf:params:acme:error:badCSR");
+
+        let payload = format!(r#"{{"csr":"{}"}}"#, csr);
+        let response = block_on(state.finalize(
+            BASE_URL,
+            id(&order_url),
+            &client.post(&state, finalize_url, &payload),
+            |_, dns_names| async move {
+                assert_eq!(dns_names, ["example.com", "www.example.com"]);
+                Ok("-----BEGIN CERTIFICATE-----".to_owned())
+            },
+        ))
+        .unwrap();
+        let order = body(&response);
+        assert_eq!(order["status"], "valid");
+
+        let certificate_url = order["certificate"].as_str().unwrap();
+        let response = state
+            .certificate(BASE_URL, id(certificate_url), &client.post(&state, certificate_url, ""))
+            .unwrap();
+        assert_eq!(response.content_type, "application/pem-certificate-chain");
+        assert_eq!(response.body, b"-----BEGIN CERTIFICATE-----");
+    }
+
+    #[test]
+    fn request_authentication() {
+        let state = AcmeState::new(vec![Box::new(AutoValidChallenge)]);
+        let new_account_url = format!("{}/acme/new-account", BASE_URL);
+        let new_order_url = format!("{}/acme/new-order", BASE_URL);
+
+        let mut alice = Client::new(crate::test_files::RSA_2048_PK_1);
+        let request = alice.post(&state, &new_account_url, "{}");
+        alice.account_url = state.new_account(BASE_URL, &request).unwrap().location;
+
+        // nonces can't be replayed
+        let err = state.new_account(BASE_URL, &request).unwrap_err();
+        assert_eq!(err.to_problem()["type"], "urn:ietf:params:acme:error:badNonce");
+
+        let payload = r#"{"identifiers":[{"type":"dns","value":"example.com"}]}"#;
+        let err = state
+            .new_order(
+                BASE_URL,
+                &alice.sign("unknown", &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err.to_problem()["type"], "urn:ietf:params:acme:error:badNonce");
+
+        // the signed URL must be the requested one
+        let err = state
+            .new_order(
+                BASE_URL,
+                &alice.post(&state, &new_account_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap_err();
+        assert_eq!(err.status(), 403);
+
+        let response = state
+            .new_order(
+                BASE_URL,
+                &alice.post(&state, &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap();
+        let order_url = response.location.unwrap();
+
+        let mut bob = Client::new(crate::test_files::RSA_2048_PK_2);
+        let err = state
+            .new_account(
+                BASE_URL,
+                &bob.post(&state, &new_account_url, r#"{"onlyReturnExisting":true}"#),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_problem()["type"],
+            "urn:ietf:params:acme:error:accountDoesNotExist"
+        );
+
+        // unknown accounts can't sign requests
+        bob.account_url = alice.account_url.clone();
+        let err = state
+            .order(BASE_URL, id(&order_url), &bob.post(&state, &order_url, ""))
+            .unwrap_err();
+        assert_eq!(err.to_problem()["type"], "urn:ietf:params:acme:error:malformed");
+
+        // orders are only visible to their account
+        bob.account_url = None;
+        bob.account_url = state
+            .new_account(BASE_URL, &bob.post(&state, &new_account_url, "{}"))
+            .unwrap()
+            .location;
+        let err = state
+            .order(BASE_URL, id(&order_url), &bob.post(&state, &order_url, ""))
+            .unwrap_err();
+        assert_eq!(err.status(), 404);
+
+        let payload = r#"{"identifiers":[{"type":"ip","value":"10.0.0.1"}]}"#;
+        let err = state
+            .new_order(
+                BASE_URL,
+                &bob.post(&state, &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_problem()["type"],
+            "urn:ietf:params:acme:error:unsupportedIdentifier"
+        );
+    }
+
+    #[test]
+    fn expired_entries_purge() {
+        let state = AcmeState::new(vec![Box::new(AutoValidChallenge)]);
+        let new_account_url = format!("{}/acme/new-account", BASE_URL);
+        let new_order_url = format!("{}/acme/new-order", BASE_URL);
+
+        let mut client = Client::new(crate::test_files::RSA_2048_PK_1);
+        client.account_url = state
+            .new_account(BASE_URL, &client.post(&state, &new_account_url, "{}"))
+            .unwrap()
+            .location;
+        let account_id = id(client.account_url.as_ref().unwrap()).to_owned();
+
+        let payload = r#"{"identifiers":[{"type":"dns","value":"example.com"}]}"#;
+        let response = state
+            .new_order(
+                BASE_URL,
+                &client.post(&state, &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap();
+        let order_url = response.location.unwrap();
+        let order_id = id(&order_url).to_owned();
+
+        let now = OffsetDateTime::now_utc();
+
+        // nothing expired yet
+        state.registry().purge_expired(now);
+        assert_eq!(state.registry().orders.len(), 1);
+        assert_eq!(state.registry().authorizations.len(), 1);
+        assert_eq!(state.registry().challenges.len(), 1);
+
+        // expired orders are forgotten with their authorizations and challenges
+        state
+            .registry()
+            .purge_expired(now + time::Duration::days(ORDER_VALIDITY_DAYS + 1));
+        {
+            let registry = state.registry();
+            assert!(registry.orders.is_empty());
+            assert!(registry.authorizations.is_empty());
+            assert!(registry.challenges.is_empty());
+            assert!(registry.accounts[&account_id].orders.is_empty());
+        }
+        let err = state
+            .order(BASE_URL, &order_id, &client.post(&state, &order_url, ""))
+            .unwrap_err();
+        assert_eq!(err.status(), 404);
+
+        // then idle accounts
+        state
+            .registry()
+            .purge_expired(now + time::Duration::days(ACCOUNT_RETENTION_DAYS + 1));
+        assert!(state.registry().accounts.is_empty());
+        assert!(state.registry().accounts_by_thumbprint.is_empty());
+        let err = state
+            .new_order(
+                BASE_URL,
+                &client.post(&state, &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_problem()["type"],
+            "urn:ietf:params:acme:error:accountDoesNotExist"
+        );
+    }
+
+    /// Validation completes once released by the test.
+    struct ReleasedChallenge(Mutex<Option<oneshot::Receiver<()>>>);
+
+    impl ChallengeValidator for ReleasedChallenge {
+        fn challenge_type(&self) -> &'static str {
+            "released"
+        }
+
+        fn supports(&self, _: &Identifier, _: bool) -> bool {
+            true
+        }
+
+        fn validate<'a>(
+            &'a self,
+            _: &'a Identifier,
+            _: &'a str,
+            _: &'a str,
+            _: &'a InternalNetworks,
+        ) -> BoxFuture<'a, Result<(), AcmeError>> {
+            let release = self.0.lock().unwrap().take().expect("validated once");
+            async move {
+                release.await.expect("release");
+                Ok(())
+            }
+            .boxed()
+        }
+    }
+
+    #[test]
+    fn order_purged_during_validation() {
+        let (release, released) = oneshot::channel();
+        let state = AcmeState::new(vec![Box::new(ReleasedChallenge(Mutex::new(Some(released))))]);
+        let new_account_url = format!("{}/acme/new-account", BASE_URL);
+        let new_order_url = format!("{}/acme/new-order", BASE_URL);
+
+        let mut client = Client::new(crate::test_files::RSA_2048_PK_1);
+        client.account_url = state
+            .new_account(BASE_URL, &client.post(&state, &new_account_url, "{}"))
+            .unwrap()
+            .location;
+
+        let payload = r#"{"identifiers":[{"type":"dns","value":"example.com"}]}"#;
+        let response = state
+            .new_order(
+                BASE_URL,
+                &client.post(&state, &new_order_url, payload),
+                &InternalNetworks::default(),
+            )
+            .unwrap();
+        let authorization_url = body(&response)["authorizations"][0].as_str().unwrap().to_owned();
+        let response = state
+            .authorization(
+                BASE_URL,
+                id(&authorization_url),
+                &client.post(&state, &authorization_url, ""),
+            )
+            .unwrap();
+        let challenge_url = body(&response)["challenges"][0]["url"].as_str().unwrap().to_owned();
+
+        let request = client.post(&state, &challenge_url, "{}");
+        let internal_networks = InternalNetworks::default();
+        let validation = state.challenge(BASE_URL, id(&challenge_url), &request, &internal_networks);
+        let purge = async {
+            state
+                .registry()
+                .purge_expired(OffsetDateTime::now_utc() + time::Duration::days(ORDER_VALIDITY_DAYS + 1));
+            release.send(()).unwrap();
+        };
+        let (result, ()) = block_on(futures::future::join(validation, purge));
+        assert_eq!(result.unwrap_err().status(), 404);
+
+        // a panic while holding the registry lock doesn't make it unusable
+        let poisoned = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _registry = state.registry();
+            panic!("poison the registry lock");
+        }));
+        assert!(poisoned.is_err());
+        assert!(!state.new_nonce().is_empty());
+    }
+
+    #[test]
+    fn identifier_normalization() {
+        let dns = |value: &str| Identifier {
+            kind: "dns".to_owned(),
+            value: value.to_owned(),
+        };
+
+        let (identifier, wildcard) = normalize_identifier(dns("*.Example.COM"), &InternalNetworks::default()).unwrap();
+        assert_eq!(identifier.value, "*.example.com");
+        assert!(wildcard);
+
+        for (value, expected_err) in [
+            ("exa_mple.com", "invalid DNS name: exa_mple.com"),
+            ("127.0.0.1", "IP address can't be used as DNS name: 127.0.0.1"),
+            (
+                "169.254.169.254",
+                "IP address can't be used as DNS name: 169.254.169.254",
+            ),
+            ("intranet", "DNS name must be fully qualified: intranet"),
+            ("localhost", "DNS name must be fully qualified: localhost"),
+            ("api.localhost", "reserved DNS name: api.localhost"),
+            ("printer.local", "reserved DNS name: printer.local"),
+            ("1.0.0.127.in-addr.arpa", "reserved DNS name: 1.0.0.127.in-addr.arpa"),
+        ] {
+            let err = normalize_identifier(dns(value), &InternalNetworks::default()).unwrap_err();
+            assert_eq!(err.to_string(), expected_err);
+            assert_eq!(
+                err.to_problem()["type"],
+                "urn:ietf:params:acme:error:rejectedIdentifier"
+            );
+        }
+
+        // configured internal domains are accepted
+        let internal_networks = InternalNetworks {
+            dns_suffixes: vec!["corp.internal".to_owned(), "intranet".to_owned()],
+            ip_ranges: Vec::new(),
+        };
+        for value in ["printer.corp.internal", "*.corp.internal", "intranet"] {
+            let (identifier, _) = normalize_identifier(dns(value), &internal_networks).unwrap();
+            assert_eq!(identifier.value, value);
+        }
+        for value in ["printer.local", "other.internal", "10.1.2.3"] {
+            assert!(
+                normalize_identifier(dns(value), &internal_networks).is_err(),
+                "{}",
+                value
+            );
+        }
+    }
+}
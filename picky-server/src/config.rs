@@ -1,7 +1,9 @@
+use crate::acme::challenge::{InternalNetworks, IpRange};
 use crate::utils::PathOr;
 use clap::App;
 use log::LevelFilter;
 use picky::hash::HashAlgorithm;
+use picky::jose::jwk::JwkSet;
 use picky::key::{PrivateKey, PublicKey};
 use picky::pem::Pem;
 use picky::signature::SignatureAlgorithm;
@@ -18,6 +20,8 @@ const PICKY_BACKEND_ENV: &str = "PICKY_BACKEND";
 const PICKY_FILE_BACKEND_PATH_ENV: &str = "PICKY_FILE_BACKEND_PATH";
 const PICKY_DATABASE_URL_ENV: &str = "PICKY_DATABASE_URL";
 const PICKY_DATABASE_NAME_ENV: &str = "PICKY_DATABASE_NAME";
+const PICKY_SQL_DATABASE_URL_ENV: &str = "PICKY_SQL_DATABASE_URL";
+const PICKY_SQL_MAX_CONNECTIONS_ENV: &str = "PICKY_SQL_MAX_CONNECTIONS";
 
 const PICKY_ROOT_CERT_ENV: &str = "PICKY_ROOT_CERT";
 const PICKY_ROOT_CERT_PATH_ENV: &str = "PICKY_ROOT_CERT_PATH";
@@ -29,9 +33,24 @@ const PICKY_INTERMEDIATE_CERT_PATH_ENV: &str = "PICKY_INTERMEDIATE_CERT_PATH";
 const PICKY_INTERMEDIATE_KEY_ENV: &str = "PICKY_INTERMEDIATE_KEY";
 const PICKY_INTERMEDIATE_KEY_PATH_ENV: &str = "PICKY_INTERMEDIATE_KEY_PATH";
 
+const PICKY_OCSP_RESPONDER_CERT_ENV: &str = "PICKY_OCSP_RESPONDER_CERT";
+const PICKY_OCSP_RESPONDER_CERT_PATH_ENV: &str = "PICKY_OCSP_RESPONDER_CERT_PATH";
+const PICKY_OCSP_RESPONDER_KEY_ENV: &str = "PICKY_OCSP_RESPONDER_KEY";
+const PICKY_OCSP_RESPONDER_KEY_PATH_ENV: &str = "PICKY_OCSP_RESPONDER_KEY_PATH";
+
 const PICKY_PROVISIONER_PUBLIC_KEY_ENV: &str = "PICKY_PROVISIONER_PUBLIC_KEY";
 const PICKY_PROVISIONER_PUBLIC_KEY_PATH_ENV: &str = "PICKY_PROVISIONER_PUBLIC_KEY_PATH";
 
+const PICKY_ADMIN_PUBLIC_KEY_ENV: &str = "PICKY_ADMIN_PUBLIC_KEY";
+const PICKY_ADMIN_PUBLIC_KEY_PATH_ENV: &str = "PICKY_ADMIN_PUBLIC_KEY_PATH";
+
+const PICKY_ACME_BASE_URL_ENV: &str = "PICKY_ACME_BASE_URL";
+
+const PICKY_EST_USERNAME_ENV: &str = "PICKY_EST_USERNAME";
+const PICKY_EST_PASSWORD_ENV: &str = "PICKY_EST_PASSWORD";
+const PICKY_EST_CLIENT_CERT_HEADER_ENV: &str = "PICKY_EST_CLIENT_CERT_HEADER";
+const PICKY_EST_PROXY_SECRET_ENV: &str = "PICKY_EST_PROXY_SECRET";
+
 fn default_picky_realm() -> String {
     String::from("Picky")
 }
@@ -44,6 +63,14 @@ fn default_database_name() -> String {
     String::from("picky")
 }
 
+fn default_sql_database_url() -> String {
+    String::from("sqlite:picky.db?mode=rwc")
+}
+
+const fn default_sql_max_connections() -> u32 {
+    5
+}
+
 fn default_file_backend_path() -> PathBuf {
     Path::new("database/").to_owned()
 }
@@ -60,6 +87,14 @@ const fn default_signing_algorithm() -> SignatureAlgorithm {
     SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256)
 }
 
+const fn default_acme_x509_duration_secs() -> u64 {
+    7_776_000 // 90 days
+}
+
+const fn default_est_x509_duration_secs() -> u64 {
+    31_536_000 // 365 days
+}
+
 fn parse_level_filter(s: &str) -> LevelFilter {
     match s.to_lowercase().as_str() {
         "error" => LevelFilter::Error,
@@ -77,6 +112,7 @@ pub enum BackendType {
     MongoDb,
     Memory,
     File,
+    Sql,
 }
 
 impl Default for BackendType {
@@ -91,6 +127,7 @@ impl From<&str> for BackendType {
             "mongodb" => Self::MongoDb,
             "memory" => Self::Memory,
             "file" => Self::File,
+            "sql" => Self::Sql,
             _ => Self::default(),
         }
     }
@@ -102,6 +139,132 @@ pub struct CertKeyPair {
     pub key: PathOr<PrivateKey>,
 }
 
+/// ACME endpoints are only available when this section is provided.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AcmeConfig {
+    /// Public URL of this server, used to build ACME resource URLs
+    pub base_url: String,
+    #[serde(default = "default_acme_x509_duration_secs")]
+    pub x509_duration_secs: u64,
+    /// Named provisioner whose profile applies to issued certificates (default leaf profile otherwise)
+    #[serde(default)]
+    pub provisioner: Option<String>,
+    /// Internal domains (e.g.: `corp.internal`) accepted as identifiers along with their subdomains.
+    ///
+    /// Special-use (`.internal`, `.local`, `.home.arpa`…) and single-label names are rejected otherwise.
+    #[serde(default)]
+    pub allowed_dns_suffixes: Vec<String>,
+    /// Non-public address ranges (e.g.: `10.0.0.0/8`) challenge validation is allowed to connect to
+    #[serde(default)]
+    pub allowed_cidrs: Vec<IpRange>,
+}
+
+impl AcmeConfig {
+    pub fn internal_networks(&self) -> InternalNetworks {
+        InternalNetworks {
+            dns_suffixes: self.allowed_dns_suffixes.clone(),
+            ip_ranges: self.allowed_cidrs.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EstUser {
+    pub username: String,
+    pub password: String,
+}
+
+/// EST endpoints are only available when this section is provided.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EstConfig {
+    /// Accepted HTTP basic authentication credentials, `provisioner` must be set along with users
+    #[serde(default)]
+    pub users: Vec<EstUser>,
+    /// Header holding the URL-encoded PEM client certificate forwarded by the TLS terminating proxy.
+    ///
+    /// This header is only trusted on requests also carrying `proxy_secret` in the `Picky-Proxy-Secret` header.
+    /// The proxy must set both headers, overwriting any value sent by the client, and picky must only be
+    /// reachable through this proxy.
+    #[serde(default)]
+    pub client_cert_header: Option<String>,
+    /// Shared secret proving that a request was forwarded by the TLS terminating proxy
+    #[serde(default)]
+    pub proxy_secret: Option<String>,
+    #[serde(default = "default_est_x509_duration_secs")]
+    pub x509_duration_secs: u64,
+    /// Named provisioner whose profile applies to issued certificates (default leaf profile otherwise)
+    #[serde(default)]
+    pub provisioner: Option<String>,
+}
+
+impl Default for EstConfig {
+    fn default() -> Self {
+        Self {
+            users: Vec::new(),
+            client_cert_header: None,
+            proxy_secret: None,
+            x509_duration_secs: default_est_x509_duration_secs(),
+            provisioner: None,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    Rsa,
+    Ec,
+    /// Edwards curves (Ed25519)
+    Ed,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyUsageFlag {
+    DigitalSignature,
+    ContentCommitment,
+    KeyEncipherment,
+    DataEncipherment,
+    KeyAgreement,
+}
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtendedKeyUsagePurpose {
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OcspSigning,
+}
+
+/// Certificate profile of a named provisioner.
+///
+/// Empty restriction lists allow anything, missing templates use the defaults of leaf certificates.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProvisionerConfig {
+    /// Matched against the `kid` header parameter, then against the `iss` claim of tokens
+    pub name: String,
+    #[serde(default)]
+    pub public_key: Option<PathOr<PublicKey>>,
+    /// Keys are selected using the `kid` header parameter of tokens
+    #[serde(default)]
+    pub jwks: Option<PathOr<JwkSet>>,
+    /// DNS name patterns for subject common name and subject alternative names (e.g.: `*.example.com`)
+    #[serde(default)]
+    pub san_patterns: Vec<String>,
+    /// Longer durations requested by tokens are capped
+    #[serde(default)]
+    pub max_x509_duration_secs: Option<u64>,
+    #[serde(default)]
+    pub key_usage: Option<Vec<KeyUsageFlag>>,
+    #[serde(default)]
+    pub extended_key_usage: Option<Vec<ExtendedKeyUsagePurpose>>,
+    #[serde(default)]
+    pub key_types: Vec<KeyType>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Config {
     #[serde(default = "default_picky_realm")]
@@ -121,13 +284,33 @@ pub struct Config {
     pub database_url: String,
     #[serde(default = "default_database_name")]
     pub database_name: String,
+    /// `sqlite:` or `postgres:` URL of the SQL backend
+    #[serde(default = "default_sql_database_url")]
+    pub sql_database_url: String,
+    #[serde(default = "default_sql_max_connections")]
+    pub sql_max_connections: u32,
 
     #[serde(default)]
     pub root: Option<CertKeyPair>,
     #[serde(default)]
     pub intermediate: Option<CertKeyPair>,
     #[serde(default)]
+    pub ocsp_responder: Option<CertKeyPair>,
+    #[serde(default)]
     pub provisioner_public_key: Option<PathOr<PublicKey>>,
+    #[serde(default)]
+    pub provisioners: Vec<ProvisionerConfig>,
+    /// When named provisioners are configured, tokens matching none of them are rejected unless this is set:
+    /// they are then validated using `provisioner_public_key` and no profile applies
+    #[serde(default)]
+    pub provisioner_public_key_fallback: bool,
+    /// Validates tokens of administration endpoints (e.g.: revocation), provisioner tokens are not accepted there
+    #[serde(default)]
+    pub admin_public_key: Option<PathOr<PublicKey>>,
+    #[serde(default)]
+    pub acme: Option<AcmeConfig>,
+    #[serde(default)]
+    pub est: Option<EstConfig>,
 }
 
 impl Default for Config {
@@ -141,9 +324,17 @@ impl Default for Config {
             file_backend_path: default_file_backend_path(),
             database_url: default_database_url(),
             database_name: default_database_name(),
+            sql_database_url: default_sql_database_url(),
+            sql_max_connections: default_sql_max_connections(),
             root: None,
             intermediate: None,
+            ocsp_responder: None,
             provisioner_public_key: None,
+            provisioners: Vec::new(),
+            provisioner_public_key_fallback: false,
+            admin_public_key: None,
+            acme: None,
+            est: None,
         }
     }
 }
@@ -159,13 +350,99 @@ impl Config {
         config.inject_env();
         config.inject_cli();
 
+        // logs aren't initialized yet: configuration is needed to set them up
+        if let Err(e) = config.load_provisioners() {
+            eprintln!("invalid provisioners configuration: {}", e);
+            std::process::exit(1);
+        }
+
         config
     }
 
     pub fn init_yaml() -> Result<Self, String> {
         let yaml_conf =
             std::fs::read_to_string(YAML_CONF_PATH).map_err(|e| format!("couldn't read yaml config: {}", e))?;
-        serde_yaml::from_str(&yaml_conf).map_err(|e| format!("invalid yaml conf: {}", e))
+        let mut config: Self = serde_yaml::from_str(&yaml_conf).map_err(|e| format!("invalid yaml conf: {}", e))?;
+        config.load_provisioners()?;
+        Ok(config)
+    }
+
+    /// Returns the named provisioner, `None` when no name is given.
+    pub fn find_provisioner(&self, name: Option<&str>) -> Result<Option<&ProvisionerConfig>, String> {
+        match name {
+            Some(name) => self
+                .provisioners
+                .iter()
+                .find(|provisioner| provisioner.name == name)
+                .map(Some)
+                .ok_or_else(|| format!("unknown provisioner: {}", name)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads provisioner keys once instead of on every request and checks provisioner references.
+    fn load_provisioners(&mut self) -> Result<(), String> {
+        for provisioner in &mut self.provisioners {
+            if let Some(PathOr::Path(path)) = &provisioner.public_key {
+                let pem_str = std::fs::read_to_string(path).map_err(|e| {
+                    format!(
+                        "couldn't read public key of provisioner {} at {}: {}",
+                        provisioner.name,
+                        path.display(),
+                        e
+                    )
+                })?;
+                let pem = pem_str.parse::<Pem>().map_err(|e| {
+                    format!(
+                        "couldn't parse public key pem of provisioner {} at {}: {}",
+                        provisioner.name,
+                        path.display(),
+                        e
+                    )
+                })?;
+                let public_key = PublicKey::from_pem(&pem).map_err(|e| {
+                    format!(
+                        "couldn't parse public key of provisioner {} at {}: {}",
+                        provisioner.name,
+                        path.display(),
+                        e
+                    )
+                })?;
+                provisioner.public_key = Some(PathOr::Some(public_key));
+            }
+
+            if let Some(PathOr::Path(path)) = &provisioner.jwks {
+                let json = std::fs::read_to_string(path).map_err(|e| {
+                    format!(
+                        "couldn't read JWKS of provisioner {} at {}: {}",
+                        provisioner.name,
+                        path.display(),
+                        e
+                    )
+                })?;
+                let jwk_set = JwkSet::from_json(&json).map_err(|e| {
+                    format!(
+                        "couldn't parse JWKS of provisioner {} at {}: {}",
+                        provisioner.name,
+                        path.display(),
+                        e
+                    )
+                })?;
+                provisioner.jwks = Some(PathOr::Some(jwk_set));
+            }
+        }
+
+        self.find_provisioner(self.acme.as_ref().and_then(|acme| acme.provisioner.as_deref()))?;
+        self.find_provisioner(self.est.as_ref().and_then(|est| est.provisioner.as_deref()))?;
+
+        // basic authentication users could otherwise enroll any name
+        if let Some(est) = &self.est {
+            if !est.users.is_empty() && est.provisioner.is_none() {
+                return Err("EST users require an EST provisioner restricting enrolled names".to_owned());
+            }
+        }
+
+        Ok(())
     }
 
     fn inject_cli(&mut self) {
@@ -234,6 +511,14 @@ impl Config {
             self.database_name = val;
         }
 
+        if let Ok(val) = env::var(PICKY_SQL_DATABASE_URL_ENV) {
+            self.sql_database_url = val;
+        }
+
+        if let Ok(val) = env::var(PICKY_SQL_MAX_CONNECTIONS_ENV) {
+            self.sql_max_connections = val.parse::<u32>().expect("sql max connections env variable");
+        }
+
         if !inject_cert_key_pair(&mut self.root, PICKY_ROOT_CERT_ENV, PICKY_ROOT_KEY_ENV) {
             inject_cert_key_pair_path(&mut self.root, PICKY_ROOT_CERT_PATH_ENV, PICKY_ROOT_KEY_PATH_ENV);
         }
@@ -250,6 +535,18 @@ impl Config {
             );
         }
 
+        if !inject_cert_key_pair(
+            &mut self.ocsp_responder,
+            PICKY_OCSP_RESPONDER_CERT_ENV,
+            PICKY_OCSP_RESPONDER_KEY_ENV,
+        ) {
+            inject_cert_key_pair_path(
+                &mut self.ocsp_responder,
+                PICKY_OCSP_RESPONDER_CERT_PATH_ENV,
+                PICKY_OCSP_RESPONDER_KEY_PATH_ENV,
+            );
+        }
+
         if let Ok(pem_str) = env::var(PICKY_PROVISIONER_PUBLIC_KEY_ENV) {
             let pem = pem_str
                 .parse::<Pem>()
@@ -259,6 +556,44 @@ impl Config {
         } else if let Ok(val) = env::var(PICKY_PROVISIONER_PUBLIC_KEY_PATH_ENV) {
             self.provisioner_public_key = Some(PathOr::Path(val.into()));
         }
+
+        if let Ok(pem_str) = env::var(PICKY_ADMIN_PUBLIC_KEY_ENV) {
+            let pem = pem_str.parse::<Pem>().expect("couldn't parse admin public key pem");
+            let public_key = PublicKey::from_pem(&pem).expect("couldn't parse admin public key");
+            self.admin_public_key = Some(PathOr::Some(public_key));
+        } else if let Ok(val) = env::var(PICKY_ADMIN_PUBLIC_KEY_PATH_ENV) {
+            self.admin_public_key = Some(PathOr::Path(val.into()));
+        }
+
+        if let Ok(base_url) = env::var(PICKY_ACME_BASE_URL_ENV) {
+            match &mut self.acme {
+                Some(acme) => acme.base_url = base_url,
+                None => {
+                    self.acme = Some(AcmeConfig {
+                        base_url,
+                        x509_duration_secs: default_acme_x509_duration_secs(),
+                        provisioner: None,
+                        allowed_dns_suffixes: Vec::new(),
+                        allowed_cidrs: Vec::new(),
+                    })
+                }
+            }
+        }
+
+        if let (Ok(username), Ok(password)) = (env::var(PICKY_EST_USERNAME_ENV), env::var(PICKY_EST_PASSWORD_ENV)) {
+            self.est
+                .get_or_insert_with(EstConfig::default)
+                .users
+                .push(EstUser { username, password });
+        }
+
+        if let Ok(header) = env::var(PICKY_EST_CLIENT_CERT_HEADER_ENV) {
+            self.est.get_or_insert_with(EstConfig::default).client_cert_header = Some(header);
+        }
+
+        if let Ok(secret) = env::var(PICKY_EST_PROXY_SECRET_ENV) {
+            self.est.get_or_insert_with(EstConfig::default).proxy_secret = Some(secret);
+        }
     }
 }
 
@@ -1,7 +1,10 @@
 use crate::addressing::{encode_to_alternative_addresses, encode_to_canonical_address};
 use crate::config::Config;
 use crate::db::config::DatabaseConfig;
-use crate::db::{CertificateEntry, PickyStorage, StorageError, SCHEMA_LAST_VERSION};
+use crate::db::{
+    CertificateEntry, CertificatePage, CertificateQuery, PickyStorage, RevocationEntry, StorageError,
+    SCHEMA_LAST_VERSION,
+};
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use std::fs::File;
@@ -93,10 +96,14 @@ const REPO_CERTIFICATE: &str = "certificate_store/";
 const REPO_KEY: &str = "key_store/";
 const REPO_CERT_NAME: &str = "name_store/";
 const REPO_KEY_IDENTIFIER: &str = "key_identifier_store/";
+const REPO_SERIAL_NUMBER: &str = "serial_number_store/";
 const REPO_HASH_LOOKUP_TABLE: &str = "hash_lookup_store/";
 const REPO_AUTHENTICODE_TIMESTAMP: &str = "timestamp_counter_store/";
+const REPO_REVOCATION: &str = "revocation_store/";
+const REPO_CRL_NUMBER: &str = "crl_number_store/";
 const TXT_EXT: &str = ".txt";
 const DER_EXT: &str = ".der";
+const JSON_EXT: &str = ".json";
 
 const CONFIG_FILE_NAME: &str = "config.json";
 
@@ -105,8 +112,11 @@ pub struct FileStorage {
     cert: FileRepo<Vec<u8>>,
     keys: FileRepo<Vec<u8>>,
     key_identifiers: FileRepo<String>,
+    serial_numbers: FileRepo<String>,
     hash_lookup: FileRepo<String>,
     issued_timestamps_counter: FileRepo<[u8; 4]>,
+    revocations: FileRepo<String>,
+    crl_number: FileRepo<[u8; 8]>,
 }
 
 impl FileStorage {
@@ -141,13 +151,34 @@ impl FileStorage {
             keys: FileRepo::new(&config.file_backend_path, REPO_KEY).expect("couldn't initialize keys repo"),
             key_identifiers: FileRepo::new(&config.file_backend_path, REPO_KEY_IDENTIFIER)
                 .expect("couldn't initialize key identifiers repo"),
+            serial_numbers: FileRepo::new(&config.file_backend_path, REPO_SERIAL_NUMBER)
+                .expect("couldn't initialize serial numbers repo"),
             hash_lookup: FileRepo::new(&config.file_backend_path, REPO_HASH_LOOKUP_TABLE)
                 .expect("couldn't initialize hash lookup table repo"),
             issued_timestamps_counter: FileRepo::new(&config.file_backend_path, REPO_AUTHENTICODE_TIMESTAMP)
                 .expect("couldn't initialize authenticode timestamp counter repo"),
+            revocations: FileRepo::new(&config.file_backend_path, REPO_REVOCATION)
+                .expect("couldn't initialize revocations repo"),
+            crl_number: FileRepo::new(&config.file_backend_path, REPO_CRL_NUMBER)
+                .expect("couldn't initialize crl number repo"),
         }
     }
 
+    async fn h_read_revocation(&self, file_name: &str) -> Result<RevocationEntry, FileStorageError> {
+        let file_path = self.revocations.folder_path.join(file_name);
+        let json = tokio::fs::read_to_string(&file_path)
+            .await
+            .map_err(|e| format!("error reading file '{}': {}", file_path.to_string_lossy(), e))?;
+        let entry = serde_json::from_str(&json).map_err(|e| {
+            format!(
+                "couldn't decode revocation entry '{}': {}",
+                file_path.to_string_lossy(),
+                e
+            )
+        })?;
+        Ok(entry)
+    }
+
     async fn h_get<'a>(
         &'a self,
         hash: &'a str,
@@ -193,6 +224,7 @@ impl PickyStorage for FileStorage {
         let name = entry.name;
         let cert = entry.cert;
         let key_identifier = entry.key_identifier;
+        let serial_number = entry.serial_number;
         let key = entry.key;
 
         async move {
@@ -212,6 +244,9 @@ impl PickyStorage for FileStorage {
             self.key_identifiers
                 .insert(&format!("{}{}", key_identifier, TXT_EXT), &addressing_hash)
                 .await?;
+            self.serial_numbers
+                .insert(&format!("{}{}", serial_number, TXT_EXT), &addressing_hash)
+                .await?;
 
             for alternative_address in alternative_addresses.into_iter() {
                 self.hash_lookup
@@ -338,4 +373,108 @@ impl PickyStorage for FileStorage {
         }
         .boxed()
     }
+
+    fn get_addressing_hash_by_serial_number(&self, serial_number: &str) -> BoxFuture<'_, Result<String, StorageError>> {
+        let serial_number = format!("{}{}", serial_number, TXT_EXT);
+        async move {
+            let file = self
+                .serial_numbers
+                .get_collection()
+                .await?
+                .into_iter()
+                .find(|filename| filename.eq(&serial_number))
+                .ok_or_else(|| FileStorageError::Other {
+                    description: format!("'{}' not found", serial_number),
+                })?;
+            let file_path = self.serial_numbers.folder_path.join(file);
+            Ok(tokio::fs::read_to_string(&file_path)
+                .await
+                .map_err(|e| FileStorageError::Other {
+                    description: format!("error reading file '{}': {}", file_path.to_string_lossy(), e),
+                })?)
+        }
+        .boxed()
+    }
+
+    fn revoke(&self, entry: RevocationEntry) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            let json = serde_json::to_string(&entry).map_err(|e| FileStorageError::Other {
+                description: format!("couldn't encode revocation entry: {}", e),
+            })?;
+            self.revocations
+                .insert(&format!("{}{}", entry.serial_number, JSON_EXT), &json)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_revocation_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RevocationEntry>, StorageError>> {
+        let file_name = format!("{}{}", serial_number, JSON_EXT);
+        async move {
+            if self.revocations.get_collection().await?.contains(&file_name) {
+                Ok(Some(self.h_read_revocation(&file_name).await?))
+            } else {
+                Ok(None)
+            }
+        }
+        .boxed()
+    }
+
+    fn get_revocations(&self) -> BoxFuture<'_, Result<Vec<RevocationEntry>, StorageError>> {
+        async move {
+            let mut revocations = Vec::new();
+            for file_name in self.revocations.get_collection().await? {
+                revocations.push(self.h_read_revocation(&file_name).await?);
+            }
+            Ok(revocations)
+        }
+        .boxed()
+    }
+
+    fn find_certificates<'a>(
+        &'a self,
+        query: &'a CertificateQuery,
+    ) -> BoxFuture<'a, Result<CertificatePage, StorageError>> {
+        async move {
+            let mut certificates = Vec::new();
+            for file_name in self.cert.get_collection().await? {
+                let hash = match file_name.strip_suffix(DER_EXT) {
+                    Some(hash) => hash.to_owned(),
+                    None => continue,
+                };
+                let file_path = self.cert.folder_path.join(&file_name);
+                let der = tokio::fs::read(&file_path).await.map_err(|e| FileStorageError::Other {
+                    description: format!("error reading file '{}': {}", file_path.to_string_lossy(), e),
+                })?;
+                certificates.push((hash, der));
+            }
+            Ok(query.apply(certificates))
+        }
+        .boxed()
+    }
+
+    fn increase_crl_number(&self) -> BoxFuture<'_, Result<u64, StorageError>> {
+        let name = format!("{}{}", "crl_number_store", TXT_EXT);
+
+        async move {
+            let mut content = [0; 8];
+            if let Err(err) = self.crl_number.get(&name, &mut content).await {
+                if let FileStorageError::Io(io_error) = &err {
+                    if io_error.kind() != ErrorKind::NotFound {
+                        return Err(err.into());
+                    }
+                }
+            }
+
+            let counter = u64::from_le_bytes(content) + 1;
+            self.crl_number.insert(&name, &counter.to_le_bytes()).await?;
+
+            Ok(counter)
+        }
+        .boxed()
+    }
 }
@@ -1,5 +1,5 @@
 use crate::addressing::{encode_to_alternative_addresses, encode_to_canonical_address};
-use crate::db::{CertificateEntry, PickyStorage, StorageError};
+use crate::db::{CertificateEntry, CertificatePage, CertificateQuery, PickyStorage, RevocationEntry, StorageError};
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use std::collections::HashMap;
@@ -19,11 +19,19 @@ impl From<String> for MemoryStorageError {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct MemoryRepository<T> {
     repo: RwLock<HashMap<String, T>>,
 }
 
+impl<T> Default for MemoryRepository<T> {
+    fn default() -> Self {
+        Self {
+            repo: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
 impl<'a, T> MemoryRepository<T>
 where
     T: Eq + Clone + Hash,
@@ -51,8 +59,11 @@ pub struct MemoryStorage {
     cert: MemoryRepository<Vec<u8>>,
     keys: MemoryRepository<Vec<u8>>,
     key_identifiers: MemoryRepository<String>,
+    serial_numbers: MemoryRepository<String>,
     hash_lookup: MemoryRepository<String>,
     issued_timestamps_counter: MemoryRepository<u32>,
+    revocations: MemoryRepository<RevocationEntry>,
+    crl_number: MemoryRepository<u64>,
 }
 
 impl MemoryStorage {
@@ -70,6 +81,7 @@ impl PickyStorage for MemoryStorage {
         let name = entry.name;
         let cert = entry.cert;
         let key_identifier = entry.key_identifier;
+        let serial_number = entry.serial_number;
         let key = entry.key;
 
         async move {
@@ -83,6 +95,7 @@ impl PickyStorage for MemoryStorage {
             self.name.insert(name, addressing_hash.clone());
             self.cert.insert(addressing_hash.clone(), cert);
             self.key_identifiers.insert(key_identifier, addressing_hash.clone());
+            self.serial_numbers.insert(serial_number, addressing_hash.clone());
 
             for alternative_address in alternative_addresses.into_iter() {
                 self.hash_lookup.insert(alternative_address, addressing_hash.clone());
@@ -185,4 +198,72 @@ impl PickyStorage for MemoryStorage {
         }
         .boxed()
     }
+
+    fn get_addressing_hash_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            Ok(self
+                .serial_numbers
+                .get_collection()
+                .get(serial_number)
+                .cloned()
+                .ok_or_else(|| MemoryStorageError::Other {
+                    description: "hash not found".to_owned(),
+                })?)
+        }
+        .boxed()
+    }
+
+    fn revoke(&self, entry: RevocationEntry) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            self.revocations.insert(entry.serial_number.clone(), entry);
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_revocation_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RevocationEntry>, StorageError>> {
+        async move { Ok(self.revocations.get_collection().get(serial_number).cloned()) }.boxed()
+    }
+
+    fn get_revocations(&self) -> BoxFuture<'_, Result<Vec<RevocationEntry>, StorageError>> {
+        async move { Ok(self.revocations.get_collection().values().cloned().collect()) }.boxed()
+    }
+
+    fn find_certificates<'a>(
+        &'a self,
+        query: &'a CertificateQuery,
+    ) -> BoxFuture<'a, Result<CertificatePage, StorageError>> {
+        async move {
+            let certificates = self
+                .cert
+                .get_collection()
+                .iter()
+                .map(|(hash, der)| (hash.clone(), der.clone()))
+                .collect::<Vec<_>>();
+            Ok(query.apply(certificates))
+        }
+        .boxed()
+    }
+
+    fn increase_crl_number(&self) -> BoxFuture<'_, Result<u64, StorageError>> {
+        async move {
+            let mut coll = self
+                .crl_number
+                .repo
+                .write()
+                .expect("couldn't get write lock on repo (poisoned)");
+            let counter = coll.entry("crl_number".to_string()).or_insert(0);
+
+            *counter += 1;
+
+            Ok(*counter)
+        }
+        .boxed()
+    }
 }
@@ -2,14 +2,20 @@ mod config;
 mod file;
 mod memory;
 mod mongodb;
+mod query;
+mod sql;
 
 use crate::config::{BackendType, Config};
 use crate::db::file::{FileStorage, FileStorageError};
 use crate::db::memory::{MemoryStorage, MemoryStorageError};
 use crate::db::mongodb::{MongoStorage, MongoStorageError};
+use crate::db::sql::{SqlStorage, SqlStorageError};
 use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub use query::{CertificatePage, CertificateQuery, CertificateSummary, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+
 pub const SCHEMA_LAST_VERSION: u8 = 1;
 
 #[derive(Debug, Error)]
@@ -22,6 +28,9 @@ pub enum StorageError {
 
     #[error("memory storage error: {}", source)]
     Memory { source: MemoryStorageError },
+
+    #[error("sql storage error: {}", source)]
+    Sql { source: SqlStorageError },
 }
 
 impl From<MongoStorageError> for StorageError {
@@ -42,6 +51,12 @@ impl From<MemoryStorageError> for StorageError {
     }
 }
 
+impl From<SqlStorageError> for StorageError {
+    fn from(source: SqlStorageError) -> Self {
+        Self::Sql { source }
+    }
+}
+
 pub type BoxedPickyStorage = Box<dyn PickyStorage>;
 
 pub async fn get_storage(config: &Config) -> BoxedPickyStorage {
@@ -53,6 +68,7 @@ pub async fn get_storage(config: &Config) -> BoxedPickyStorage {
         }
         BackendType::Memory => Box::new(MemoryStorage::new()),
         BackendType::File => Box::new(FileStorage::new(config)),
+        BackendType::Sql => Box::new(SqlStorage::new(config).await),
     }
 }
 
@@ -61,9 +77,19 @@ pub struct CertificateEntry {
     pub name: String,
     pub cert: Vec<u8>,
     pub key_identifier: String,
+    pub serial_number: String,
     pub key: Option<Vec<u8>>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RevocationEntry {
+    pub serial_number: String,
+    /// Unix timestamp
+    pub revocation_date: i64,
+    /// CRLReason code as defined by RFC 5280
+    pub reason: u8,
+}
+
 pub trait PickyStorage: Send + Sync {
     fn health(&self) -> BoxFuture<'_, Result<(), StorageError>>;
     fn store(&self, entry: CertificateEntry) -> BoxFuture<'_, Result<(), StorageError>>;
@@ -76,4 +102,102 @@ pub trait PickyStorage: Send + Sync {
         key_identifier: &'a str,
     ) -> BoxFuture<'a, Result<String, StorageError>>;
     fn lookup_addressing_hash<'a>(&'a self, lookup_key: &'a str) -> BoxFuture<'a, Result<String, StorageError>>;
+    fn get_addressing_hash_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<String, StorageError>>;
+    fn revoke(&self, entry: RevocationEntry) -> BoxFuture<'_, Result<(), StorageError>>;
+    fn get_revocation_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RevocationEntry>, StorageError>>;
+    fn get_revocations(&self) -> BoxFuture<'_, Result<Vec<RevocationEntry>, StorageError>>;
+    /// Certificate inventory, see `CertificateQuery`.
+    fn find_certificates<'a>(
+        &'a self,
+        query: &'a CertificateQuery,
+    ) -> BoxFuture<'a, Result<CertificatePage, StorageError>>;
+    /// Increases the CRL number counter and returns the new value.
+    fn increase_crl_number(&self) -> BoxFuture<'_, Result<u64, StorageError>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files;
+    use picky::key::PrivateKey;
+    use picky::x509::certificate::CertificateBuilder;
+    use picky::x509::date::UTCDate;
+    use picky::x509::name::{DirectoryName, GeneralName, GeneralNames};
+
+    fn certificate_entry(common_name: &str, dns_names: &[&str], serial: u8) -> CertificateEntry {
+        let key = PrivateKey::from_pem_str(test_files::RSA_2048_PK_1).unwrap();
+        let dns_names = dns_names
+            .iter()
+            .map(|dns_name| GeneralName::new_dns_name(*dns_name).unwrap())
+            .collect::<Vec<GeneralName>>();
+        let cert = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2021, 1, 1).unwrap(), UTCDate::ymd(2031, 1, 1).unwrap())
+            .self_signed(DirectoryName::new_common_name(common_name), &key)
+            .subject_alt_name(GeneralNames::from(dns_names))
+            .serial_number(vec![serial])
+            .build()
+            .expect("certificate");
+
+        CertificateEntry {
+            name: common_name.to_owned(),
+            cert: cert.to_der().unwrap(),
+            key_identifier: hex::encode(cert.subject_key_identifier().unwrap()),
+            serial_number: hex::encode([serial]),
+            key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn dns_name_filter_matches_names_one_by_one() {
+        let sql_config = Config {
+            backend: BackendType::Sql,
+            sql_database_url: "sqlite::memory:".to_owned(),
+            sql_max_connections: 1,
+            ..Config::default()
+        };
+        let storages = vec![
+            ("memory", Box::new(MemoryStorage::new()) as BoxedPickyStorage),
+            ("sql", Box::new(SqlStorage::new(&sql_config).await) as BoxedPickyStorage),
+        ];
+
+        for (backend, storage) in storages {
+            storage
+                .store(certificate_entry("web", &["www.example.com", "api.example.com"], 1))
+                .await
+                .unwrap();
+            storage
+                .store(certificate_entry("mail", &["mail.example.org"], 2))
+                .await
+                .unwrap();
+
+            for (dns_name, expected) in [
+                ("WWW.Example", vec!["web"]),
+                ("api.", vec!["web"]),
+                ("example", vec!["mail", "web"]),
+                // a needle spanning two names of the same certificate
+                ("com\napi", vec![]),
+                ("_", vec![]),
+            ] {
+                let query = CertificateQuery {
+                    dns_name: Some(dns_name.to_owned()),
+                    ..CertificateQuery::default()
+                };
+                let page = storage.find_certificates(&query).await.unwrap();
+                let mut subjects = page
+                    .certificates
+                    .iter()
+                    .map(|summary| summary.subject.as_str())
+                    .collect::<Vec<&str>>();
+                subjects.sort_unstable();
+                assert_eq!(subjects, expected, "{} backend, dns name {:?}", backend, dns_name);
+                assert_eq!(page.total, expected.len());
+            }
+        }
+    }
 }
@@ -1,15 +1,18 @@
 mod model;
 
 use crate::addressing::{encode_to_alternative_addresses, encode_to_canonical_address};
-use crate::db::{CertificateEntry, PickyStorage, StorageError, SCHEMA_LAST_VERSION};
+use crate::db::{
+    CertificateEntry, CertificatePage, CertificateQuery, CertificateSummary, PickyStorage, RevocationEntry,
+    StorageError, SCHEMA_LAST_VERSION,
+};
 use futures::future::BoxFuture;
 use futures::stream::StreamExt;
 use futures::FutureExt;
 use model::*;
 use mongodm::mongo::bson::oid::ObjectId;
 use mongodm::mongo::bson::spec::BinarySubtype;
-use mongodm::mongo::bson::{doc, Binary, Bson};
-use mongodm::mongo::options::{ClientOptions, ReadPreference, ReplaceOptions, SelectionCriteria};
+use mongodm::mongo::bson::{doc, Binary, Bson, Document};
+use mongodm::mongo::options::{ClientOptions, FindOptions, ReadPreference, ReplaceOptions, SelectionCriteria};
 use mongodm::mongo::{Client, Database};
 use mongodm::{f, ToRepository};
 use picky::x509::Cert;
@@ -165,6 +168,7 @@ impl MongoStorage {
                                     .to_string(),
                                 cert: cert_der,
                                 key_identifier: hex::encode(cert.subject_key_identifier().expect("cert key id")),
+                                serial_number: hex::encode(cert.serial_number().as_unsigned_bytes_be()),
                                 key: key_pkcs10,
                             })
                             .await
@@ -185,8 +189,57 @@ impl MongoStorage {
             }
         }
 
+        storage.index_certificates().await.expect("index certificates");
+
         storage
     }
+
+    /// Fills in inventory fields of certificates stored by older versions.
+    async fn index_certificates(&self) -> Result<(), MongoStorageError> {
+        let repository = self.repository::<Certificate>();
+
+        let mut unindexed = Vec::new();
+        let mut cursor = repository
+            .find(doc!(f!(not_before in Certificate): { "$exists": false }), None)
+            .await?;
+        while let Some(certificate) = cursor.next().await {
+            unindexed.push(certificate?);
+        }
+
+        if unindexed.is_empty() {
+            return Ok(());
+        }
+
+        log::info!("indexing {} certificates...", unindexed.len());
+
+        for certificate in unindexed {
+            let der = match certificate.value {
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes: bin,
+                }) => bin,
+                unexpected => {
+                    log::warn!(
+                        "certificate {} left out of inventory: expected binary DB content but got {}",
+                        certificate.key,
+                        unexpected
+                    );
+                    continue;
+                }
+            };
+
+            match certificate_model(certificate.key.clone(), der) {
+                Ok(indexed) => {
+                    repository
+                        .replace_one(doc!(f!(key in Certificate): &certificate.key), &indexed, None)
+                        .await?;
+                }
+                Err(e) => log::warn!("certificate {} left out of inventory: {}", certificate.key, e),
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl PickyStorage for MongoStorage {
@@ -207,6 +260,7 @@ impl PickyStorage for MongoStorage {
         let name = entry.name;
         let cert = entry.cert;
         let key_identifier = entry.key_identifier;
+        let serial_number = entry.serial_number;
         let key = entry.key;
 
         async move {
@@ -227,13 +281,8 @@ impl PickyStorage for MongoStorage {
                 .await?;
 
             let query = doc! {f!(key in Certificate): &addressing_hash };
-            let certificate = Certificate {
-                key: addressing_hash.clone(),
-                value: Bson::Binary(Binary {
-                    subtype: BinarySubtype::Generic,
-                    bytes: cert,
-                }),
-            };
+            let certificate = certificate_model(addressing_hash.clone(), cert)
+                .map_err(|description| MongoStorageError::Other { description })?;
             self.repository::<Certificate>()
                 .replace_one(
                     query,
@@ -255,6 +304,19 @@ impl PickyStorage for MongoStorage {
                 )
                 .await?;
 
+            let query = doc! { f!(key in SerialNumber): &serial_number };
+            let serial_number = SerialNumber {
+                key: serial_number,
+                value: addressing_hash.clone(),
+            };
+            self.repository::<SerialNumber>()
+                .replace_one(
+                    query,
+                    &serial_number,
+                    Some(ReplaceOptions::builder().upsert(true).build()),
+                )
+                .await?;
+
             for alternative_address in alternative_addresses.into_iter() {
                 let query = doc! { f!(key in HashLookupEntry): &alternative_address };
                 let alternative_key_identifier = HashLookupEntry {
@@ -385,6 +447,24 @@ impl PickyStorage for MongoStorage {
         .boxed()
     }
 
+    fn get_addressing_hash_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            let addressing_hash = self
+                .repository::<SerialNumber>()
+                .find_one(doc!(f!(key in SerialNumber): serial_number), None)
+                .await?
+                .ok_or_else(|| MongoStorageError::Other {
+                    description: format!("addressing hash not found by serial number \"{}\"", serial_number),
+                })?
+                .value;
+            Ok(addressing_hash)
+        }
+        .boxed()
+    }
+
     fn increase_issued_authenticode_timestamps_counter(&self) -> BoxFuture<'_, Result<(), StorageError>> {
         async move {
             let repository = self.repository::<IssuedTimestampsCounter>();
@@ -407,4 +487,195 @@ impl PickyStorage for MongoStorage {
         }
         .boxed()
     }
+
+    fn revoke(&self, entry: RevocationEntry) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            let query = doc! { f!(key in Revocation): &entry.serial_number };
+            let revocation = Revocation {
+                key: entry.serial_number,
+                revocation_date: entry.revocation_date,
+                reason: i32::from(entry.reason),
+            };
+            self.repository::<Revocation>()
+                .replace_one(query, &revocation, Some(ReplaceOptions::builder().upsert(true).build()))
+                .await?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_revocation_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RevocationEntry>, StorageError>> {
+        async move {
+            let revocation = self
+                .repository::<Revocation>()
+                .find_one(doc!(f!(key in Revocation): serial_number), None)
+                .await?;
+            Ok(revocation.map(revocation_entry_from_model).transpose()?)
+        }
+        .boxed()
+    }
+
+    fn get_revocations(&self) -> BoxFuture<'_, Result<Vec<RevocationEntry>, StorageError>> {
+        async move {
+            let mut revocations = Vec::new();
+            let mut cursor = self.repository::<Revocation>().find(doc!(), None).await?;
+            while let Some(revocation) = cursor.next().await {
+                revocations.push(revocation_entry_from_model(revocation?)?);
+            }
+            Ok(revocations)
+        }
+        .boxed()
+    }
+
+    fn find_certificates<'a>(
+        &'a self,
+        query: &'a CertificateQuery,
+    ) -> BoxFuture<'a, Result<CertificatePage, StorageError>> {
+        async move {
+            let mut issued = doc! { "$exists": true };
+            if let Some(issued_after) = query.issued_after {
+                issued.insert("$gte", issued_after);
+            }
+            if let Some(issued_before) = query.issued_before {
+                issued.insert("$lte", issued_before);
+            }
+
+            let mut filter = doc! { f!(not_before in Certificate): issued };
+            if let Some(common_name) = &query.common_name {
+                filter.insert(f!(subject in Certificate), contains(common_name));
+            }
+            if let Some(dns_name) = &query.dns_name {
+                filter.insert(f!(dns_names in Certificate), contains(dns_name));
+            }
+            if let Some(issuer) = &query.issuer {
+                filter.insert(f!(issuer in Certificate), contains(issuer));
+            }
+            if let Some(serial_number) = &query.serial_number {
+                filter.insert(f!(serial_number in Certificate), serial_number);
+            }
+            if query.expires_after.is_some() || query.expires_before.is_some() {
+                let mut expires = Document::new();
+                if let Some(expires_after) = query.expires_after {
+                    expires.insert("$gte", expires_after);
+                }
+                if let Some(expires_before) = query.expires_before {
+                    expires.insert("$lte", expires_before);
+                }
+                filter.insert(f!(not_after in Certificate), expires);
+            }
+
+            let repository = self.repository::<Certificate>();
+            let total = repository.count_documents(filter.clone(), None).await?;
+
+            let options = FindOptions::builder()
+                .sort(doc! { f!(not_before in Certificate): -1, f!(key in Certificate): 1 })
+                .skip(i64::try_from(query.offset()).map_err(|e| MongoStorageError::Other {
+                    description: format!("invalid page: {}", e),
+                })?)
+                .limit(i64::try_from(query.page_size).map_err(|e| MongoStorageError::Other {
+                    description: format!("invalid page size: {}", e),
+                })?)
+                .build();
+
+            let mut certificates = Vec::new();
+            let mut cursor = repository.find(filter, options).await?;
+            while let Some(certificate) = cursor.next().await {
+                certificates.push(certificate_summary_from_model(certificate?)?);
+            }
+
+            Ok(CertificatePage {
+                total: usize::try_from(total).map_err(|e| MongoStorageError::Other {
+                    description: format!("invalid certificate count: {}", e),
+                })?,
+                page: query.page,
+                page_size: query.page_size,
+                certificates,
+            })
+        }
+        .boxed()
+    }
+
+    fn increase_crl_number(&self) -> BoxFuture<'_, Result<u64, StorageError>> {
+        async move {
+            let repository = self.repository::<CrlNumberCounter>();
+            let counter = if let Some(crl_number_counter) = repository.find_one(doc!(), None).await? {
+                crl_number_counter.counter + 1
+            } else {
+                1
+            };
+
+            let crl_number_counter = CrlNumberCounter { counter };
+            repository
+                .replace_one(
+                    doc! {},
+                    &crl_number_counter,
+                    Some(ReplaceOptions::builder().upsert(true).build()),
+                )
+                .await?;
+
+            Ok(u64::try_from(counter).map_err(|e| MongoStorageError::Other {
+                description: format!("invalid crl number: {}", e),
+            })?)
+        }
+        .boxed()
+    }
+}
+
+fn certificate_model(addressing_hash: String, der: Vec<u8>) -> Result<Certificate, String> {
+    let summary = CertificateSummary::from_der(addressing_hash, &der)?;
+    Ok(Certificate {
+        key: summary.addressing_hash,
+        value: Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: der,
+        }),
+        subject: Some(summary.subject),
+        issuer: Some(summary.issuer),
+        serial_number: Some(summary.serial_number),
+        dns_names: summary.dns_names,
+        not_before: Some(summary.not_before),
+        not_after: Some(summary.not_after),
+    })
+}
+
+fn certificate_summary_from_model(certificate: Certificate) -> Result<CertificateSummary, MongoStorageError> {
+    let key = certificate.key;
+    let missing = |field: &str| MongoStorageError::Other {
+        description: format!("certificate {} is missing inventory field {}", key, field),
+    };
+    Ok(CertificateSummary {
+        subject: certificate.subject.ok_or_else(|| missing("subject"))?,
+        issuer: certificate.issuer.ok_or_else(|| missing("issuer"))?,
+        serial_number: certificate.serial_number.ok_or_else(|| missing("serial_number"))?,
+        dns_names: certificate.dns_names,
+        not_before: certificate.not_before.ok_or_else(|| missing("not_before"))?,
+        not_after: certificate.not_after.ok_or_else(|| missing("not_after"))?,
+        addressing_hash: key,
+    })
+}
+
+/// Case-insensitive substring regular expression.
+fn contains(needle: &str) -> Document {
+    let mut pattern = String::with_capacity(needle.len());
+    for c in needle.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    doc! { "$regex": pattern, "$options": "i" }
+}
+
+fn revocation_entry_from_model(revocation: Revocation) -> Result<RevocationEntry, MongoStorageError> {
+    Ok(RevocationEntry {
+        serial_number: revocation.key,
+        revocation_date: revocation.revocation_date,
+        reason: u8::try_from(revocation.reason).map_err(|e| MongoStorageError::Other {
+            description: format!("invalid revocation reason: {}", e),
+        })?,
+    })
 }
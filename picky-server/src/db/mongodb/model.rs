@@ -74,6 +74,30 @@ impl mongodm::Model for KeyIdentifier {
     type CollConf = KeyIdCollConf;
 }
 
+// == serial number == //
+
+pub struct SerialNumberCollConf;
+
+impl mongodm::CollectionConfig for SerialNumberCollConf {
+    fn collection_name() -> &'static str {
+        "serial_number_store"
+    }
+
+    fn indexes() -> Indexes {
+        Indexes::new().with(Index::new("key").with_option(IndexOption::Unique))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SerialNumber {
+    pub key: String,
+    pub value: String,
+}
+
+impl mongodm::Model for SerialNumber {
+    type CollConf = SerialNumberCollConf;
+}
+
 // == certificate == //
 
 pub struct CertCollConf;
@@ -84,14 +108,26 @@ impl mongodm::CollectionConfig for CertCollConf {
     }
 
     fn indexes() -> Indexes {
-        Indexes::new().with(Index::new("key").with_option(IndexOption::Unique))
+        Indexes::new()
+            .with(Index::new("key").with_option(IndexOption::Unique))
+            .with(Index::new("serial_number"))
+            .with(Index::new("not_before"))
+            .with(Index::new("not_after"))
     }
 }
 
+/// Inventory fields are missing from certificates stored by older versions until `MongoStorage::new` indexes them.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Certificate {
     pub key: String,
     pub value: Bson,
+    pub subject: Option<String>,
+    pub issuer: Option<String>,
+    pub serial_number: Option<String>,
+    #[serde(default)]
+    pub dns_names: Vec<String>,
+    pub not_before: Option<i64>,
+    pub not_after: Option<i64>,
 }
 
 impl mongodm::Model for Certificate {
@@ -159,3 +195,45 @@ pub struct IssuedTimestampsCounter {
 impl mongodm::Model for IssuedTimestampsCounter {
     type CollConf = TimestampCollConf;
 }
+
+// == revocation == //
+
+pub struct RevocationCollConf;
+
+impl mongodm::CollectionConfig for RevocationCollConf {
+    fn collection_name() -> &'static str {
+        "revocation_store"
+    }
+
+    fn indexes() -> Indexes {
+        Indexes::new().with(Index::new("key").with_option(IndexOption::Unique))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Revocation {
+    pub key: String,
+    pub revocation_date: i64,
+    pub reason: i32,
+}
+
+impl mongodm::Model for Revocation {
+    type CollConf = RevocationCollConf;
+}
+
+pub struct CrlNumberCollConf;
+
+impl mongodm::CollectionConfig for CrlNumberCollConf {
+    fn collection_name() -> &'static str {
+        "crl_number_counter"
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrlNumberCounter {
+    pub counter: i64,
+}
+
+impl mongodm::Model for CrlNumberCounter {
+    type CollConf = CrlNumberCollConf;
+}
@@ -0,0 +1,298 @@
+use picky::x509::name::DirectoryName;
+use picky::x509::Cert;
+use picky_asn1_x509::{ExtensionView, GeneralName};
+use serde::Serialize;
+use std::convert::TryFrom;
+use time::OffsetDateTime;
+
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Certificate inventory filter.
+///
+/// Text filters are case-insensitive substring matches, dates are Unix timestamps (bounds are inclusive).
+/// Most recently issued certificates come first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateQuery {
+    /// Subject common name
+    pub common_name: Option<String>,
+    /// Any DNS subject alternative name
+    pub dns_name: Option<String>,
+    /// Issuer common name
+    pub issuer: Option<String>,
+    /// Lowercase hexadecimal serial number without leading zeros (exact match)
+    pub serial_number: Option<String>,
+    pub issued_after: Option<i64>,
+    pub issued_before: Option<i64>,
+    pub expires_after: Option<i64>,
+    pub expires_before: Option<i64>,
+    /// First page is 1
+    pub page: usize,
+    pub page_size: usize,
+}
+
+impl Default for CertificateQuery {
+    fn default() -> Self {
+        Self {
+            common_name: None,
+            dns_name: None,
+            issuer: None,
+            serial_number: None,
+            issued_after: None,
+            issued_before: None,
+            expires_after: None,
+            expires_before: None,
+            page: 1,
+            page_size: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertificateSummary {
+    /// Canonical address, as accepted by `GET /cert/<multihash>`
+    pub addressing_hash: String,
+    pub subject: String,
+    pub issuer: String,
+    pub serial_number: String,
+    pub dns_names: Vec<String>,
+    pub not_before: i64,
+    pub not_after: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertificatePage {
+    /// Number of certificates matching the query, all pages included
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub certificates: Vec<CertificateSummary>,
+}
+
+impl CertificateQuery {
+    /// Number of matching certificates preceding the requested page.
+    pub fn offset(&self) -> usize {
+        self.page.saturating_sub(1).saturating_mul(self.page_size)
+    }
+
+    /// Filters `(addressing hash, DER)` pairs in memory and returns the requested page.
+    pub fn apply(&self, certificates: impl IntoIterator<Item = (String, Vec<u8>)>) -> CertificatePage {
+        let mut matching = certificates
+            .into_iter()
+            .filter_map(
+                |(addressing_hash, der)| match CertificateSummary::from_der(addressing_hash, &der) {
+                    Ok(summary) => Some(summary),
+                    Err(e) => {
+                        log::warn!("certificate skipped from inventory: {}", e);
+                        None
+                    }
+                },
+            )
+            .filter(|summary| self.matches(summary))
+            .collect::<Vec<CertificateSummary>>();
+
+        matching.sort_by(|lhs, rhs| {
+            rhs.not_before
+                .cmp(&lhs.not_before)
+                .then_with(|| lhs.addressing_hash.cmp(&rhs.addressing_hash))
+        });
+
+        let total = matching.len();
+        let certificates = matching.into_iter().skip(self.offset()).take(self.page_size).collect();
+
+        CertificatePage {
+            total,
+            page: self.page,
+            page_size: self.page_size,
+            certificates,
+        }
+    }
+
+    fn matches(&self, summary: &CertificateSummary) -> bool {
+        fn contains(haystack: &str, needle: &str) -> bool {
+            haystack.to_lowercase().contains(&needle.to_lowercase())
+        }
+
+        fn within(value: i64, lower: Option<i64>, upper: Option<i64>) -> bool {
+            lower.iter().all(|lower| value >= *lower) && upper.iter().all(|upper| value <= *upper)
+        }
+
+        self.common_name.iter().all(|cn| contains(&summary.subject, cn))
+            && self
+                .dns_name
+                .iter()
+                .all(|dns_name| summary.dns_names.iter().any(|name| contains(name, dns_name)))
+            && self.issuer.iter().all(|issuer| contains(&summary.issuer, issuer))
+            && self
+                .serial_number
+                .iter()
+                .all(|serial_number| summary.serial_number == *serial_number)
+            && within(summary.not_before, self.issued_after, self.issued_before)
+            && within(summary.not_after, self.expires_after, self.expires_before)
+    }
+}
+
+impl CertificateSummary {
+    /// Extracts inventory fields from a certificate.
+    ///
+    /// Database backends store these next to the DER so that filters and pagination run in their queries.
+    pub fn from_der(addressing_hash: String, der: &[u8]) -> Result<Self, String> {
+        let cert = Cert::from_der(der).map_err(|e| format!("couldn't decode {}: {}", addressing_hash, e))?;
+
+        let unix_timestamp = |date| {
+            OffsetDateTime::try_from(date)
+                .map(|date| date.unix_timestamp())
+                .map_err(|e| format!("invalid validity date for {}: {}", addressing_hash, e))
+        };
+        let not_before = unix_timestamp(cert.valid_not_before())?;
+        let not_after = unix_timestamp(cert.valid_not_after())?;
+
+        let mut dns_names = Vec::new();
+        for extension in cert.extensions() {
+            if let ExtensionView::SubjectAltName(subject_alt_name) = extension.extn_value() {
+                for name in subject_alt_name.0 {
+                    if let GeneralName::DnsName(dns_name) = name {
+                        dns_names.push(dns_name.to_string());
+                    }
+                }
+            }
+        }
+
+        Ok(CertificateSummary {
+            subject: common_name_or_dn(&cert.subject_name()),
+            issuer: common_name_or_dn(&cert.issuer_name()),
+            serial_number: hex::encode(cert.serial_number().as_unsigned_bytes_be()),
+            dns_names,
+            not_before,
+            not_after,
+            addressing_hash,
+        })
+    }
+}
+
+fn common_name_or_dn(name: &DirectoryName) -> String {
+    match name.find_common_name() {
+        Some(common_name) => common_name.to_string(),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addressing::encode_to_canonical_address;
+    use crate::test_files;
+    use picky::key::PrivateKey;
+    use picky::x509::certificate::CertificateBuilder;
+    use picky::x509::date::UTCDate;
+    use picky::x509::name::{GeneralName as PickyGeneralName, GeneralNames};
+
+    fn certificate(
+        common_name: &str,
+        dns_name: &str,
+        issued: UTCDate,
+        expires: UTCDate,
+        serial: u8,
+    ) -> (String, Vec<u8>) {
+        let key = PrivateKey::from_pem_str(test_files::RSA_2048_PK_1).unwrap();
+        let der = CertificateBuilder::new()
+            .validity(issued, expires)
+            .self_signed(DirectoryName::new_common_name(common_name), &key)
+            .subject_alt_name(GeneralNames::from(vec![
+                PickyGeneralName::new_dns_name(dns_name).unwrap()
+            ]))
+            .serial_number(vec![serial])
+            .build()
+            .expect("certificate")
+            .to_der()
+            .unwrap();
+        (encode_to_canonical_address(&der), der)
+    }
+
+    fn inventory() -> Vec<(String, Vec<u8>)> {
+        vec![
+            certificate(
+                "api.example.com",
+                "api.example.com",
+                UTCDate::ymd(2021, 1, 1).unwrap(),
+                UTCDate::ymd(2022, 1, 1).unwrap(),
+                1,
+            ),
+            certificate(
+                "www.example.com",
+                "web.example.com",
+                UTCDate::ymd(2021, 6, 1).unwrap(),
+                UTCDate::ymd(2021, 7, 1).unwrap(),
+                2,
+            ),
+            certificate(
+                "mail.example.org",
+                "mail.example.org",
+                UTCDate::ymd(2021, 3, 1).unwrap(),
+                UTCDate::ymd(2021, 9, 1).unwrap(),
+                3,
+            ),
+        ]
+    }
+
+    fn subjects(page: &CertificatePage) -> Vec<&str> {
+        page.certificates
+            .iter()
+            .map(|summary| summary.subject.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn filters_and_pagination() {
+        let page = CertificateQuery::default().apply(inventory());
+        assert_eq!(page.total, 3);
+        assert_eq!(
+            subjects(&page),
+            ["www.example.com", "mail.example.org", "api.example.com"]
+        );
+        assert_eq!(page.certificates[0].dns_names, ["web.example.com"]);
+        assert_eq!(page.certificates[0].serial_number, "02");
+
+        let query = CertificateQuery {
+            common_name: Some("EXAMPLE.com".to_owned()),
+            ..CertificateQuery::default()
+        };
+        assert_eq!(
+            subjects(&query.apply(inventory())),
+            ["www.example.com", "api.example.com"]
+        );
+
+        let query = CertificateQuery {
+            dns_name: Some("web.".to_owned()),
+            ..CertificateQuery::default()
+        };
+        assert_eq!(subjects(&query.apply(inventory())), ["www.example.com"]);
+
+        let query = CertificateQuery {
+            serial_number: Some("03".to_owned()),
+            issuer: Some("mail".to_owned()),
+            ..CertificateQuery::default()
+        };
+        assert_eq!(subjects(&query.apply(inventory())), ["mail.example.org"]);
+
+        let june_2021 = OffsetDateTime::try_from(UTCDate::ymd(2021, 6, 1).unwrap())
+            .unwrap()
+            .unix_timestamp();
+        let query = CertificateQuery {
+            issued_before: Some(june_2021 - 1),
+            expires_after: Some(june_2021),
+            expires_before: Some(june_2021 + 30 * 86_400 * 4),
+            ..CertificateQuery::default()
+        };
+        assert_eq!(subjects(&query.apply(inventory())), ["mail.example.org"]);
+
+        let query = CertificateQuery {
+            page: 2,
+            page_size: 2,
+            ..CertificateQuery::default()
+        };
+        let page = query.apply(inventory());
+        assert_eq!(page.total, 3);
+        assert_eq!(subjects(&page), ["api.example.com"]);
+    }
+}
@@ -0,0 +1,817 @@
+use crate::addressing::{encode_to_alternative_addresses, encode_to_canonical_address};
+use crate::config::Config;
+use crate::db::{
+    CertificateEntry, CertificatePage, CertificateQuery, CertificateSummary, PickyStorage, RevocationEntry,
+    StorageError,
+};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use sqlx::any::{Any, AnyArguments, AnyPool, AnyPoolOptions, AnyRow};
+use sqlx::query::Query;
+use sqlx::{Row, Transaction};
+use std::convert::TryFrom;
+use thiserror::Error;
+
+/// Schema migrations, applied in order. A migration is never modified once released.
+///
+/// Statements must be understood by both SQLite and PostgreSQL.
+/// DER documents are stored base64-encoded, DNS names of a certificate are separated by line feeds
+/// in `certificate_store` and stored one per row in `dns_name_store` for filtering.
+const MIGRATIONS: &[&[&str]] = &[
+    // v1
+    &[
+        "CREATE TABLE certificate_store (addressing_hash TEXT PRIMARY KEY, certificate TEXT NOT NULL)",
+        "CREATE TABLE key_store (addressing_hash TEXT PRIMARY KEY, key TEXT NOT NULL)",
+        "CREATE TABLE name_store (name TEXT PRIMARY KEY, addressing_hash TEXT NOT NULL)",
+        "CREATE TABLE key_identifier_store (key_identifier TEXT PRIMARY KEY, addressing_hash TEXT NOT NULL)",
+        "CREATE TABLE serial_number_store (serial_number TEXT PRIMARY KEY, addressing_hash TEXT NOT NULL)",
+        "CREATE TABLE hash_lookup_store (lookup_key TEXT PRIMARY KEY, addressing_hash TEXT NOT NULL)",
+        "CREATE TABLE revocation_store \
+         (serial_number TEXT PRIMARY KEY, revocation_date BIGINT NOT NULL, reason INTEGER NOT NULL)",
+        "CREATE TABLE counter_store (name TEXT PRIMARY KEY, value BIGINT NOT NULL)",
+    ],
+    // v2: certificate inventory fields, filled in by `SqlStorage::index_certificates` for existing rows
+    &[
+        "ALTER TABLE certificate_store ADD COLUMN subject TEXT",
+        "ALTER TABLE certificate_store ADD COLUMN issuer TEXT",
+        "ALTER TABLE certificate_store ADD COLUMN serial_number TEXT",
+        "ALTER TABLE certificate_store ADD COLUMN dns_names TEXT",
+        "ALTER TABLE certificate_store ADD COLUMN not_before BIGINT",
+        "ALTER TABLE certificate_store ADD COLUMN not_after BIGINT",
+        "CREATE INDEX certificate_store_serial_number ON certificate_store (serial_number)",
+        "CREATE INDEX certificate_store_not_before ON certificate_store (not_before)",
+        "CREATE INDEX certificate_store_not_after ON certificate_store (not_after)",
+    ],
+    // v3: DNS names are matched one by one, existing rows are indexed again by `SqlStorage::index_certificates`
+    &[
+        "CREATE TABLE dns_name_store \
+         (addressing_hash TEXT NOT NULL, dns_name TEXT NOT NULL, PRIMARY KEY (addressing_hash, dns_name))",
+        "UPDATE certificate_store SET not_before = NULL",
+    ],
+];
+
+const TIMESTAMPS_COUNTER: &str = "timestamps_counter";
+const CRL_NUMBER_COUNTER: &str = "crl_number";
+
+#[derive(Debug, Error)]
+pub enum SqlStorageError {
+    #[error("sql error: {}", source)]
+    SqlError { source: sqlx::Error },
+
+    #[error("generic error: {}", description)]
+    Other { description: String },
+}
+
+impl From<String> for SqlStorageError {
+    fn from(description: String) -> Self {
+        SqlStorageError::Other { description }
+    }
+}
+
+impl From<sqlx::Error> for SqlStorageError {
+    fn from(source: sqlx::Error) -> Self {
+        SqlStorageError::SqlError { source }
+    }
+}
+
+impl From<sqlx::Error> for StorageError {
+    fn from(source: sqlx::Error) -> Self {
+        StorageError::Sql {
+            source: SqlStorageError::SqlError { source },
+        }
+    }
+}
+
+/// SQLite (`sqlite:` URLs) and PostgreSQL (`postgres:` URLs) storage.
+pub struct SqlStorage {
+    pool: AnyPool,
+}
+
+impl SqlStorage {
+    pub async fn new(config: &Config) -> Self {
+        let pool = AnyPoolOptions::new()
+            .max_connections(config.sql_max_connections)
+            .connect(&config.sql_database_url)
+            .await
+            .expect("sql connection pool");
+
+        let storage = SqlStorage { pool };
+        storage.migrate().await.expect("sql schema migration");
+        storage.index_certificates().await.expect("sql certificate indexing");
+        storage
+    }
+
+    async fn migrate(&self) -> Result<(), SqlStorageError> {
+        sqlx::query("CREATE TABLE IF NOT EXISTS schema_migrations (version INTEGER PRIMARY KEY)")
+            .execute(&self.pool)
+            .await?;
+
+        let current_version = self.schema_version().await?;
+        let last_version = MIGRATIONS.len();
+
+        if current_version > last_version {
+            return Err(SqlStorageError::Other {
+                description: format!("unsupported schema version: v{}", current_version),
+            });
+        }
+
+        if current_version == last_version {
+            log::info!("detected database using supported v{} schema", current_version);
+            return Ok(());
+        }
+
+        for (version, statements) in MIGRATIONS.iter().enumerate().skip(current_version) {
+            let version = version + 1;
+            log::info!("migrate database to v{}...", version);
+
+            let mut tx = self.pool.begin().await?;
+            for statement in statements.iter() {
+                sqlx::query(statement).execute(&mut tx).await?;
+            }
+            sqlx::query("INSERT INTO schema_migrations (version) VALUES ($1)")
+                .bind(i32::try_from(version).map_err(|e| format!("invalid schema version: {}", e))?)
+                .execute(&mut tx)
+                .await?;
+            tx.commit().await?;
+        }
+
+        log::info!("migrated to v{} successfully!", last_version);
+
+        Ok(())
+    }
+
+    async fn schema_version(&self) -> Result<usize, SqlStorageError> {
+        let version: Option<i32> = sqlx::query("SELECT MAX(version) FROM schema_migrations")
+            .fetch_one(&self.pool)
+            .await?
+            .try_get(0)?;
+        Ok(usize::try_from(version.unwrap_or(0)).map_err(|e| format!("invalid schema version: {}", e))?)
+    }
+
+    /// Fills in inventory fields of certificates stored before the v3 schema.
+    async fn index_certificates(&self) -> Result<(), SqlStorageError> {
+        let rows = sqlx::query("SELECT addressing_hash, certificate FROM certificate_store WHERE not_before IS NULL")
+            .fetch_all(&self.pool)
+            .await?;
+
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        log::info!("indexing {} certificates...", rows.len());
+
+        for row in rows {
+            let addressing_hash: String = row.try_get(0)?;
+            let encoded: String = row.try_get(1)?;
+            let summary = base64::decode(encoded)
+                .map_err(|e| format!("couldn't decode stored cert {}: {}", addressing_hash, e))
+                .and_then(|der| CertificateSummary::from_der(addressing_hash.clone(), &der));
+
+            match summary {
+                Ok(summary) => {
+                    let mut tx = self.pool.begin().await?;
+                    h_replace_dns_names(&mut tx, &summary.addressing_hash, &summary.dns_names).await?;
+                    sqlx::query(
+                        "UPDATE certificate_store SET subject = $1, issuer = $2, serial_number = $3, \
+                         dns_names = $4, not_before = $5, not_after = $6 WHERE addressing_hash = $7",
+                    )
+                    .bind(summary.subject)
+                    .bind(summary.issuer)
+                    .bind(summary.serial_number)
+                    .bind(summary.dns_names.join("\n"))
+                    .bind(summary.not_before)
+                    .bind(summary.not_after)
+                    .bind(summary.addressing_hash)
+                    .execute(&mut tx)
+                    .await?;
+                    tx.commit().await?;
+                }
+                Err(e) => log::warn!("certificate {} left out of inventory: {}", addressing_hash, e),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn h_get_value(&self, query: &str, key: &str) -> Result<Option<String>, SqlStorageError> {
+        let row = sqlx::query(query).bind(key).fetch_optional(&self.pool).await?;
+        Ok(row.map(|row| row.try_get(0)).transpose()?)
+    }
+
+    async fn h_get_der(&self, query: &str, hash: &str, type_err: &'static str) -> Result<Vec<u8>, SqlStorageError> {
+        let encoded = self
+            .h_get_value(query, hash)
+            .await?
+            .ok_or_else(|| format!("{} not found", type_err))?;
+        Ok(base64::decode(encoded).map_err(|e| format!("couldn't decode stored {}: {}", type_err, e))?)
+    }
+
+    async fn h_get_addressing_hash(&self, query: &str, key: &str, kind: &str) -> Result<String, SqlStorageError> {
+        Ok(self
+            .h_get_value(query, key)
+            .await?
+            .ok_or_else(|| format!("addressing hash not found by {} \"{}\"", kind, key))?)
+    }
+
+    /// Increases the named counter and returns the new value.
+    async fn h_increase_counter(&self, name: &str) -> Result<i64, SqlStorageError> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO counter_store (name, value) VALUES ($1, 1) \
+             ON CONFLICT (name) DO UPDATE SET value = counter_store.value + 1",
+        )
+        .bind(name)
+        .execute(&mut tx)
+        .await?;
+        let counter: i64 = sqlx::query("SELECT value FROM counter_store WHERE name = $1")
+            .bind(name)
+            .fetch_one(&mut tx)
+            .await?
+            .try_get(0)?;
+        tx.commit().await?;
+        Ok(counter)
+    }
+}
+
+impl PickyStorage for SqlStorage {
+    fn health(&self) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            sqlx::query("SELECT 1")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| SqlStorageError::Other {
+                    description: format!("ping to sql database failed: {}", e),
+                })?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn store(&self, entry: CertificateEntry) -> BoxFuture<'_, Result<(), StorageError>> {
+        let name = entry.name;
+        let cert = entry.cert;
+        let key_identifier = entry.key_identifier;
+        let serial_number = entry.serial_number;
+        let key = entry.key;
+
+        async move {
+            let addressing_hash = encode_to_canonical_address(&cert);
+
+            let alternative_addresses = encode_to_alternative_addresses(&cert).map_err(|e| SqlStorageError::Other {
+                description: format!("couldn't encode alternative addresses: {}", e),
+            })?;
+
+            let summary = CertificateSummary::from_der(addressing_hash.clone(), &cert)
+                .map_err(|description| SqlStorageError::Other { description })?;
+
+            let mut tx = self.pool.begin().await?;
+
+            h_replace_dns_names(&mut tx, &addressing_hash, &summary.dns_names).await?;
+
+            sqlx::query(
+                "INSERT INTO certificate_store \
+                 (addressing_hash, certificate, subject, issuer, serial_number, dns_names, not_before, not_after) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+                 ON CONFLICT (addressing_hash) DO UPDATE SET certificate = excluded.certificate, \
+                 subject = excluded.subject, issuer = excluded.issuer, serial_number = excluded.serial_number, \
+                 dns_names = excluded.dns_names, not_before = excluded.not_before, not_after = excluded.not_after",
+            )
+            .bind(&addressing_hash)
+            .bind(base64::encode(&cert))
+            .bind(summary.subject)
+            .bind(summary.issuer)
+            .bind(summary.serial_number)
+            .bind(summary.dns_names.join("\n"))
+            .bind(summary.not_before)
+            .bind(summary.not_after)
+            .execute(&mut tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO name_store (name, addressing_hash) VALUES ($1, $2) \
+                 ON CONFLICT (name) DO UPDATE SET addressing_hash = excluded.addressing_hash",
+            )
+            .bind(&name)
+            .bind(&addressing_hash)
+            .execute(&mut tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO key_identifier_store (key_identifier, addressing_hash) VALUES ($1, $2) \
+                 ON CONFLICT (key_identifier) DO UPDATE SET addressing_hash = excluded.addressing_hash",
+            )
+            .bind(&key_identifier)
+            .bind(&addressing_hash)
+            .execute(&mut tx)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO serial_number_store (serial_number, addressing_hash) VALUES ($1, $2) \
+                 ON CONFLICT (serial_number) DO UPDATE SET addressing_hash = excluded.addressing_hash",
+            )
+            .bind(&serial_number)
+            .bind(&addressing_hash)
+            .execute(&mut tx)
+            .await?;
+
+            for alternative_address in alternative_addresses.into_iter() {
+                sqlx::query(
+                    "INSERT INTO hash_lookup_store (lookup_key, addressing_hash) VALUES ($1, $2) \
+                     ON CONFLICT (lookup_key) DO UPDATE SET addressing_hash = excluded.addressing_hash",
+                )
+                .bind(alternative_address)
+                .bind(&addressing_hash)
+                .execute(&mut tx)
+                .await?;
+            }
+
+            if let Some(key) = key {
+                sqlx::query(
+                    "INSERT INTO key_store (addressing_hash, key) VALUES ($1, $2) \
+                     ON CONFLICT (addressing_hash) DO UPDATE SET key = excluded.key",
+                )
+                .bind(&addressing_hash)
+                .bind(base64::encode(&key))
+                .execute(&mut tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_cert_by_addressing_hash<'a>(&'a self, hash: &'a str) -> BoxFuture<'a, Result<Vec<u8>, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_der(
+                    "SELECT certificate FROM certificate_store WHERE addressing_hash = $1",
+                    hash,
+                    "cert",
+                )
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn get_key_by_addressing_hash<'a>(&'a self, hash: &'a str) -> BoxFuture<'a, Result<Vec<u8>, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_der("SELECT key FROM key_store WHERE addressing_hash = $1", hash, "key")
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn get_addressing_hash_by_name<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_addressing_hash("SELECT addressing_hash FROM name_store WHERE name = $1", name, "name")
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn increase_issued_authenticode_timestamps_counter(&self) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            self.h_increase_counter(TIMESTAMPS_COUNTER).await?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_addressing_hash_by_key_identifier<'a>(
+        &'a self,
+        key_identifier: &'a str,
+    ) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_addressing_hash(
+                    "SELECT addressing_hash FROM key_identifier_store WHERE key_identifier = $1",
+                    key_identifier,
+                    "key identifier",
+                )
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn lookup_addressing_hash<'a>(&'a self, lookup_key: &'a str) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_addressing_hash(
+                    "SELECT addressing_hash FROM hash_lookup_store WHERE lookup_key = $1",
+                    lookup_key,
+                    "lookup key",
+                )
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn get_addressing_hash_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<String, StorageError>> {
+        async move {
+            Ok(self
+                .h_get_addressing_hash(
+                    "SELECT addressing_hash FROM serial_number_store WHERE serial_number = $1",
+                    serial_number,
+                    "serial number",
+                )
+                .await?)
+        }
+        .boxed()
+    }
+
+    fn revoke(&self, entry: RevocationEntry) -> BoxFuture<'_, Result<(), StorageError>> {
+        async move {
+            sqlx::query(
+                "INSERT INTO revocation_store (serial_number, revocation_date, reason) VALUES ($1, $2, $3) \
+                 ON CONFLICT (serial_number) DO UPDATE \
+                 SET revocation_date = excluded.revocation_date, reason = excluded.reason",
+            )
+            .bind(entry.serial_number)
+            .bind(entry.revocation_date)
+            .bind(i32::from(entry.reason))
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn get_revocation_by_serial_number<'a>(
+        &'a self,
+        serial_number: &'a str,
+    ) -> BoxFuture<'a, Result<Option<RevocationEntry>, StorageError>> {
+        async move {
+            let row = sqlx::query(
+                "SELECT serial_number, revocation_date, reason FROM revocation_store WHERE serial_number = $1",
+            )
+            .bind(serial_number)
+            .fetch_optional(&self.pool)
+            .await?;
+            Ok(row.map(|row| revocation_entry_from_row(&row)).transpose()?)
+        }
+        .boxed()
+    }
+
+    fn get_revocations(&self) -> BoxFuture<'_, Result<Vec<RevocationEntry>, StorageError>> {
+        async move {
+            let rows = sqlx::query("SELECT serial_number, revocation_date, reason FROM revocation_store")
+                .fetch_all(&self.pool)
+                .await?;
+            Ok(rows
+                .iter()
+                .map(revocation_entry_from_row)
+                .collect::<Result<Vec<RevocationEntry>, SqlStorageError>>()?)
+        }
+        .boxed()
+    }
+
+    fn find_certificates<'a>(
+        &'a self,
+        query: &'a CertificateQuery,
+    ) -> BoxFuture<'a, Result<CertificatePage, StorageError>> {
+        async move {
+            let mut conditions = vec!["not_before IS NOT NULL".to_owned()];
+            let mut values = Vec::new();
+            let mut filter = |condition: &str, value: SqlValue| {
+                values.push(value);
+                conditions.push(condition.replace('?', &format!("${}", values.len())));
+            };
+
+            if let Some(common_name) = &query.common_name {
+                filter("LOWER(subject) LIKE ? ESCAPE '\\'", SqlValue::contains(common_name));
+            }
+            if let Some(dns_name) = &query.dns_name {
+                filter(
+                    "EXISTS (SELECT 1 FROM dns_name_store WHERE dns_name_store.addressing_hash = \
+                     certificate_store.addressing_hash AND LOWER(dns_name_store.dns_name) LIKE ? ESCAPE '\\')",
+                    SqlValue::contains(dns_name),
+                );
+            }
+            if let Some(issuer) = &query.issuer {
+                filter("LOWER(issuer) LIKE ? ESCAPE '\\'", SqlValue::contains(issuer));
+            }
+            if let Some(serial_number) = &query.serial_number {
+                filter("serial_number = ?", SqlValue::Text(serial_number.clone()));
+            }
+            if let Some(issued_after) = query.issued_after {
+                filter("not_before >= ?", SqlValue::Integer(issued_after));
+            }
+            if let Some(issued_before) = query.issued_before {
+                filter("not_before <= ?", SqlValue::Integer(issued_before));
+            }
+            if let Some(expires_after) = query.expires_after {
+                filter("not_after >= ?", SqlValue::Integer(expires_after));
+            }
+            if let Some(expires_before) = query.expires_before {
+                filter("not_after <= ?", SqlValue::Integer(expires_before));
+            }
+
+            let condition = conditions.join(" AND ");
+
+            let count_statement = format!("SELECT COUNT(*) FROM certificate_store WHERE {}", condition);
+            let total: i64 = bind_values(sqlx::query(&count_statement), &values)
+                .fetch_one(&self.pool)
+                .await?
+                .try_get(0)?;
+
+            let page_statement = format!(
+                "SELECT addressing_hash, subject, issuer, serial_number, dns_names, not_before, not_after \
+                 FROM certificate_store WHERE {} ORDER BY not_before DESC, addressing_hash LIMIT ${} OFFSET ${}",
+                condition,
+                values.len() + 1,
+                values.len() + 2
+            );
+            let limit = i64::try_from(query.page_size).map_err(|e| SqlStorageError::Other {
+                description: format!("invalid page size: {}", e),
+            })?;
+            let offset = i64::try_from(query.offset()).map_err(|e| SqlStorageError::Other {
+                description: format!("invalid page: {}", e),
+            })?;
+            let rows = bind_values(sqlx::query(&page_statement), &values)
+                .bind(limit)
+                .bind(offset)
+                .fetch_all(&self.pool)
+                .await?;
+
+            Ok(CertificatePage {
+                total: usize::try_from(total).map_err(|e| SqlStorageError::Other {
+                    description: format!("invalid certificate count: {}", e),
+                })?,
+                page: query.page,
+                page_size: query.page_size,
+                certificates: rows
+                    .iter()
+                    .map(certificate_summary_from_row)
+                    .collect::<Result<Vec<CertificateSummary>, SqlStorageError>>()?,
+            })
+        }
+        .boxed()
+    }
+
+    fn increase_crl_number(&self) -> BoxFuture<'_, Result<u64, StorageError>> {
+        async move {
+            let counter = self.h_increase_counter(CRL_NUMBER_COUNTER).await?;
+            Ok(u64::try_from(counter).map_err(|e| SqlStorageError::Other {
+                description: format!("invalid crl number: {}", e),
+            })?)
+        }
+        .boxed()
+    }
+}
+
+/// Replaces the DNS names matched by the `dns_name` inventory filter.
+async fn h_replace_dns_names(
+    tx: &mut Transaction<'_, Any>,
+    addressing_hash: &str,
+    dns_names: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM dns_name_store WHERE addressing_hash = $1")
+        .bind(addressing_hash)
+        .execute(&mut *tx)
+        .await?;
+
+    for dns_name in dns_names {
+        sqlx::query("INSERT INTO dns_name_store (addressing_hash, dns_name) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(addressing_hash)
+            .bind(dns_name)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Bound value of a dynamically built statement.
+enum SqlValue {
+    Text(String),
+    Integer(i64),
+}
+
+impl SqlValue {
+    /// Case-insensitive substring `LIKE` pattern, compared against a `LOWER` column.
+    fn contains(needle: &str) -> Self {
+        let mut pattern = String::from("%");
+        for c in needle.to_lowercase().chars() {
+            if matches!(c, '\\' | '%' | '_') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push('%');
+        SqlValue::Text(pattern)
+    }
+}
+
+fn bind_values<'q>(
+    mut query: Query<'q, Any, AnyArguments<'q>>,
+    values: &'q [SqlValue],
+) -> Query<'q, Any, AnyArguments<'q>> {
+    for value in values {
+        query = match value {
+            SqlValue::Text(text) => query.bind(text),
+            SqlValue::Integer(integer) => query.bind(integer),
+        };
+    }
+    query
+}
+
+fn certificate_summary_from_row(row: &AnyRow) -> Result<CertificateSummary, SqlStorageError> {
+    let dns_names: String = row.try_get(4)?;
+    Ok(CertificateSummary {
+        addressing_hash: row.try_get(0)?,
+        subject: row.try_get(1)?,
+        issuer: row.try_get(2)?,
+        serial_number: row.try_get(3)?,
+        dns_names: dns_names
+            .split('\n')
+            .filter(|dns_name| !dns_name.is_empty())
+            .map(str::to_owned)
+            .collect(),
+        not_before: row.try_get(5)?,
+        not_after: row.try_get(6)?,
+    })
+}
+
+fn revocation_entry_from_row(row: &AnyRow) -> Result<RevocationEntry, SqlStorageError> {
+    let reason: i32 = row.try_get(2)?;
+    Ok(RevocationEntry {
+        serial_number: row.try_get(0)?,
+        revocation_date: row.try_get(1)?,
+        reason: u8::try_from(reason).map_err(|e| SqlStorageError::Other {
+            description: format!("invalid revocation reason: {}", e),
+        })?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackendType;
+    use crate::test_files;
+    use picky::key::PrivateKey;
+    use picky::x509::certificate::CertificateBuilder;
+    use picky::x509::date::UTCDate;
+    use picky::x509::name::DirectoryName;
+
+    fn config() -> Config {
+        Config {
+            backend: BackendType::Sql,
+            sql_database_url: "sqlite::memory:".to_owned(),
+            // each connection would otherwise open its own in-memory database
+            sql_max_connections: 1,
+            ..Config::default()
+        }
+    }
+
+    fn certificate_entry() -> CertificateEntry {
+        let key = PrivateKey::from_pem_str(test_files::RSA_2048_PK_1).unwrap();
+        let cert = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2021, 1, 1).unwrap(), UTCDate::ymd(2031, 1, 1).unwrap())
+            .self_signed(DirectoryName::new_common_name("Picky Root CA"), &key)
+            .ca(true)
+            .serial_number(vec![0x2a])
+            .build()
+            .expect("certificate");
+
+        CertificateEntry {
+            name: "Picky Root CA".to_owned(),
+            cert: cert.to_der().unwrap(),
+            key_identifier: hex::encode(cert.subject_key_identifier().unwrap()),
+            serial_number: "2a".to_owned(),
+            key: Some(key.to_pkcs8().unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn sqlite_storage() {
+        let storage = SqlStorage::new(&config()).await;
+        storage.health().await.expect("health");
+
+        let entry = certificate_entry();
+        storage.store(entry.clone()).await.expect("store");
+        storage.store(entry.clone()).await.expect("store again");
+
+        let hash = storage.get_addressing_hash_by_name(&entry.name).await.expect("by name");
+        assert_eq!(hash, encode_to_canonical_address(&entry.cert));
+        assert_eq!(storage.get_cert_by_addressing_hash(&hash).await.unwrap(), entry.cert);
+        assert_eq!(
+            storage.get_key_by_addressing_hash(&hash).await.unwrap(),
+            entry.key.clone().unwrap()
+        );
+        assert_eq!(
+            storage
+                .get_addressing_hash_by_key_identifier(&entry.key_identifier)
+                .await
+                .unwrap(),
+            hash
+        );
+        assert_eq!(storage.get_addressing_hash_by_serial_number("2a").await.unwrap(), hash);
+        for alternative_address in encode_to_alternative_addresses(&entry.cert).unwrap() {
+            assert_eq!(
+                storage.lookup_addressing_hash(&alternative_address).await.unwrap(),
+                hash
+            );
+        }
+        assert!(storage.get_addressing_hash_by_name("unknown").await.is_err());
+
+        let query = CertificateQuery {
+            serial_number: Some("2a".to_owned()),
+            ..CertificateQuery::default()
+        };
+        let page = storage.find_certificates(&query).await.expect("find certificates");
+        assert_eq!(page.total, 1);
+        assert_eq!(page.certificates[0].addressing_hash, hash);
+        assert_eq!(page.certificates[0].subject, "Picky Root CA");
+
+        let query = CertificateQuery {
+            common_name: Some("root_ca".to_owned()),
+            ..CertificateQuery::default()
+        };
+        assert_eq!(storage.find_certificates(&query).await.unwrap().total, 0);
+
+        let query = CertificateQuery {
+            common_name: Some("ROOT".to_owned()),
+            expires_after: Some(1_924_905_600),
+            page: 2,
+            ..CertificateQuery::default()
+        };
+        let page = storage.find_certificates(&query).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert!(page.certificates.is_empty());
+
+        storage.increase_issued_authenticode_timestamps_counter().await.unwrap();
+        assert_eq!(storage.increase_crl_number().await.unwrap(), 1);
+        assert_eq!(storage.increase_crl_number().await.unwrap(), 2);
+
+        let revocation = RevocationEntry {
+            serial_number: "2a".to_owned(),
+            revocation_date: 1_609_459_200,
+            reason: 1,
+        };
+        storage.revoke(revocation.clone()).await.unwrap();
+        assert_eq!(
+            storage.get_revocation_by_serial_number("2a").await.unwrap(),
+            Some(revocation.clone())
+        );
+        assert_eq!(storage.get_revocation_by_serial_number("2b").await.unwrap(), None);
+        assert_eq!(storage.get_revocations().await.unwrap(), vec![revocation]);
+    }
+
+    #[tokio::test]
+    async fn migrations_are_applied_once() {
+        let storage = SqlStorage::new(&config()).await;
+        assert_eq!(storage.schema_version().await.unwrap(), MIGRATIONS.len());
+
+        storage.migrate().await.expect("no pending migration");
+        assert_eq!(storage.schema_version().await.unwrap(), MIGRATIONS.len());
+    }
+
+    #[tokio::test]
+    async fn unsupported_schema_version_err() {
+        let storage = SqlStorage::new(&config()).await;
+        let next_version = MIGRATIONS.len() + 1;
+        sqlx::query("INSERT INTO schema_migrations (version) VALUES ($1)")
+            .bind(i32::try_from(next_version).unwrap())
+            .execute(&storage.pool)
+            .await
+            .unwrap();
+
+        let err = storage.migrate().await.expect_err("unsupported schema version");
+        assert_eq!(
+            err.to_string(),
+            format!("generic error: unsupported schema version: v{}", next_version)
+        );
+    }
+
+    #[tokio::test]
+    async fn certificates_stored_before_v2_are_indexed() {
+        let storage = SqlStorage::new(&config()).await;
+
+        let entry = certificate_entry();
+        let hash = encode_to_canonical_address(&entry.cert);
+        sqlx::query("INSERT INTO certificate_store (addressing_hash, certificate) VALUES ($1, $2)")
+            .bind(&hash)
+            .bind(base64::encode(&entry.cert))
+            .execute(&storage.pool)
+            .await
+            .unwrap();
+
+        let page = storage.find_certificates(&CertificateQuery::default()).await.unwrap();
+        assert_eq!(page.total, 0);
+
+        storage.index_certificates().await.expect("index certificates");
+        let page = storage.find_certificates(&CertificateQuery::default()).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.certificates[0].addressing_hash, hash);
+        assert_eq!(page.certificates[0].serial_number, "2a");
+    }
+}
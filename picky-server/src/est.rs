@@ -0,0 +1,232 @@
+//! EST (RFC 7030) enrollment.
+//!
+//! Requests and responses are base64-encoded DER (RFC 7030 #4), routes live in the HTTP controller.
+
+use crate::config::EstConfig;
+use crate::http::utils::percent_decode;
+use picky::x509::pkcs7::Pkcs7;
+use picky::x509::{Cert, Csr};
+
+pub const PKCS7_CERTS_ONLY_CONTENT_TYPE: &str = "application/pkcs7-mime; smime-type=certs-only";
+pub const CSR_ATTRS_CONTENT_TYPE: &str = "application/csrattrs";
+pub const PROXY_SECRET_HEADER: &str = "Picky-Proxy-Secret";
+
+/// `CsrAttrs ::= SEQUENCE { id-at-commonName }`
+///
+/// The subject common name is the DNS name certified by picky.
+const CSR_ATTRS: [u8; 7] = [0x30, 0x05, 0x06, 0x03, 0x55, 0x04, 0x03];
+
+/// Authenticated EST client.
+pub enum EstIdentity {
+    /// HTTP basic authentication
+    User(String),
+    /// Client certificate issued by this server
+    Device(Box<Cert>),
+}
+
+/// Checks the credentials of an `Authorization: Basic` header value.
+pub fn authenticate_user(config: &EstConfig, authorization: &str) -> Result<String, String> {
+    let (username, password) = parse_basic_credentials(authorization)?;
+
+    let authenticated = config
+        .users
+        .iter()
+        .any(|user| user.username == username && constant_time_eq(user.password.as_bytes(), password.as_bytes()));
+
+    if authenticated {
+        Ok(username)
+    } else {
+        Err(format!("invalid credentials for user {}", username))
+    }
+}
+
+/// Whether the request was forwarded by the TLS terminating proxy (`proxy_secret_header` is the value of the
+/// `Picky-Proxy-Secret` header).
+///
+/// Forwarded client certificates can't be trusted when no proxy secret is configured.
+pub fn is_forwarded_by_proxy(config: &EstConfig, proxy_secret_header: Option<&str>) -> bool {
+    match (&config.proxy_secret, proxy_secret_header) {
+        (Some(secret), Some(header)) => constant_time_eq(secret.as_bytes(), header.as_bytes()),
+        _ => false,
+    }
+}
+
+/// Certificate forwarded by the TLS terminating proxy (URL-encoded PEM, e.g.: nginx `$ssl_client_escaped_cert`).
+///
+/// Its chain is not checked here.
+pub fn decode_client_certificate(header_value: &str) -> Result<Cert, String> {
+    let pem = percent_decode(header_value)?;
+    Cert::from_pem_str(&pem).map_err(|e| format!("couldn't parse client certificate: {}", e))
+}
+
+pub fn decode_csr(body: &[u8]) -> Result<Csr, String> {
+    let der = decode_base64_body(body)?;
+    let csr = Csr::from_der(&der).map_err(|e| format!("couldn't parse CSR: {}", e))?;
+    csr.verify().map_err(|e| format!("invalid CSR signature: {}", e))?;
+    Ok(csr)
+}
+
+/// Subject of the CSR must be identical to the certificate being renewed (RFC 7030 #4.2.2).
+///
+/// Subject alternative names are derived from the subject by picky.
+pub fn check_reenrollment(current: &Cert, csr: &Csr) -> Result<(), String> {
+    if current.subject_name() == csr.subject_name() {
+        Ok(())
+    } else {
+        Err(format!(
+            "CSR subject {} doesn't match the certificate being renewed ({})",
+            csr.subject_name(),
+            current.subject_name()
+        ))
+    }
+}
+
+/// Base64-encoded PKCS#7 certs-only response.
+pub fn certs_only_response<'a>(certificates: impl IntoIterator<Item = &'a Cert>) -> Result<String, String> {
+    let der = Pkcs7::from_certificates(certificates)
+        .and_then(|pkcs7| pkcs7.to_der())
+        .map_err(|e| format!("couldn't encode certs-only response: {}", e))?;
+    Ok(base64::encode(der))
+}
+
+pub fn csr_attributes_response() -> String {
+    base64::encode(CSR_ATTRS)
+}
+
+fn parse_basic_credentials(authorization: &str) -> Result<(String, String), String> {
+    let encoded = match authorization.split_once(' ') {
+        Some((scheme, encoded)) if unicase::eq_ascii(scheme, "basic") => encoded.trim(),
+        _ => return Err("basic authorization is expected".to_owned()),
+    };
+
+    let decoded = base64::decode(encoded).map_err(|e| format!("invalid basic credentials: {}", e))?;
+    let decoded = String::from_utf8(decoded).map_err(|_| "basic credentials are not utf-8".to_owned())?;
+    let (username, password) = decoded
+        .split_once(':')
+        .ok_or_else(|| "basic credentials must be of the form username:password".to_owned())?;
+
+    Ok((username.to_owned(), password.to_owned()))
+}
+
+fn decode_base64_body(body: &[u8]) -> Result<Vec<u8>, String> {
+    let body = body
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<u8>>();
+    base64::decode(body).map_err(|e| format!("body is not valid base64: {}", e))
+}
+
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EstUser;
+    use crate::test_files;
+    use picky::hash::HashAlgorithm;
+    use picky::key::PrivateKey;
+    use picky::pem::Pem;
+    use picky::signature::SignatureAlgorithm;
+    use picky::x509::certificate::CertificateBuilder;
+    use picky::x509::date::UTCDate;
+    use picky::x509::name::DirectoryName;
+
+    fn private_key(pem: &str) -> PrivateKey {
+        PrivateKey::from_pem(&pem.parse::<Pem>().expect("pem")).expect("private key")
+    }
+
+    fn generate_csr(common_name: &str, key: &PrivateKey) -> Csr {
+        Csr::generate(
+            DirectoryName::new_common_name(common_name),
+            key,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+        )
+        .expect("csr")
+    }
+
+    #[test]
+    fn basic_authentication() {
+        let config = EstConfig {
+            users: vec![EstUser {
+                username: "router".to_owned(),
+                password: "p4ss:word".to_owned(),
+            }],
+            ..EstConfig::default()
+        };
+
+        let header = format!("Basic {}", base64::encode("router:p4ss:word"));
+        assert_eq!(authenticate_user(&config, &header).unwrap(), "router");
+
+        let header = format!("Basic {}", base64::encode("router:p4ss"));
+        assert_eq!(
+            authenticate_user(&config, &header).unwrap_err(),
+            "invalid credentials for user router"
+        );
+
+        assert_eq!(
+            authenticate_user(&config, "Bearer token").unwrap_err(),
+            "basic authorization is expected"
+        );
+    }
+
+    #[test]
+    fn proxy_forwarding() {
+        let mut config = EstConfig::default();
+        assert!(!is_forwarded_by_proxy(&config, Some("")));
+
+        config.proxy_secret = Some("s3cr3t".to_owned());
+        assert!(is_forwarded_by_proxy(&config, Some("s3cr3t")));
+        assert!(!is_forwarded_by_proxy(&config, Some("s3cr3")));
+        assert!(!is_forwarded_by_proxy(&config, None));
+    }
+
+    #[test]
+    fn csr_decoding_and_reenrollment() {
+        let key = private_key(test_files::RSA_2048_PK_1);
+        let csr_der = generate_csr("device.example.com", &key).to_der().expect("csr der");
+        let body = base64::encode(csr_der)
+            .as_bytes()
+            .chunks(64)
+            .map(|line| format!("{}\r\n", std::str::from_utf8(line).unwrap()))
+            .collect::<String>();
+        let csr = decode_csr(body.as_bytes()).expect("decode csr");
+
+        let current = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2021, 1, 1).unwrap(), UTCDate::ymd(2031, 1, 1).unwrap())
+            .self_signed(DirectoryName::new_common_name("device.example.com"), &key)
+            .build()
+            .expect("certificate");
+        check_reenrollment(&current, &csr).expect("same subject");
+
+        let other =
+            decode_csr(base64::encode(generate_csr("other.example.com", &key).to_der().unwrap()).as_bytes()).unwrap();
+        assert!(check_reenrollment(&current, &other).is_err());
+
+        let pem = current.to_pem().expect("pem").to_string();
+        let escaped = pem.replace('\n', "%0A").replace(' ', "%20");
+        assert_eq!(
+            decode_client_certificate(&escaped).expect("client certificate"),
+            current
+        );
+    }
+
+    #[test]
+    fn certs_only_and_csr_attributes() {
+        let key = private_key(test_files::RSA_2048_PK_2);
+        let cert = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2021, 1, 1).unwrap(), UTCDate::ymd(2031, 1, 1).unwrap())
+            .self_signed(DirectoryName::new_common_name("Picky Root CA"), &key)
+            .ca(true)
+            .build()
+            .expect("certificate");
+
+        let response = certs_only_response(std::slice::from_ref(&cert)).expect("certs-only");
+        let pkcs7 = Pkcs7::from_der(&base64::decode(response).unwrap()).expect("pkcs7");
+        assert_eq!(pkcs7.decode_certificates(), vec![cert]);
+
+        assert_eq!(csr_attributes_response(), "MAUGA1UEAw==");
+    }
+}
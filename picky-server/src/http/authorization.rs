@@ -1,5 +1,6 @@
-use crate::config::Config;
+use crate::config::{Config, ProvisionerConfig};
 use crate::utils::{unix_epoch, PathOr};
+use picky::jose::jwk::JwkSet;
 use picky::jose::jwt::{JwtDate, JwtSig, JwtValidator};
 use picky::key::PublicKey;
 use picky::pem::Pem;
@@ -33,7 +34,70 @@ impl From<&str> for AuthorizationMethod {
     }
 }
 
-pub fn check_authorization(config: &Config, req: &Request<Body>) -> Result<JwtSig<serde_json::Value>, String> {
+/// Validated provisioner token.
+pub struct Authorization {
+    pub token: JwtSig<serde_json::Value>,
+    /// Named provisioner whose profile applies, `None` when validated using `provisioner_public_key`
+    pub provisioner: Option<ProvisionerConfig>,
+}
+
+pub fn check_authorization(config: &Config, req: &Request<Body>) -> Result<Authorization, String> {
+    let encoded_token = bearer_token(req)?;
+    let now = JwtDate::new_with_leeway(unix_epoch() as i64, 10);
+    let validator = JwtValidator::strict(&now);
+
+    if let Some(provisioner) = find_provisioner(config, encoded_token)? {
+        let decoded = match (&provisioner.jwks, &provisioner.public_key) {
+            (Some(jwks), _) => JwtSig::decode_with_jwk_set(encoded_token, &load_jwk_set(jwks)?, &validator),
+            (None, Some(public_key)) => JwtSig::decode(encoded_token, &load_public_key(public_key)?, &validator),
+            (None, None) => return Err(format!("provisioner {} has no key", provisioner.name)),
+        };
+        let token = decoded.map_err(|e| format!("couldn't validate json web token: {}", e))?;
+
+        return Ok(Authorization {
+            token,
+            provisioner: Some(provisioner.clone()),
+        });
+    }
+
+    if !config.provisioners.is_empty() && !config.provisioner_public_key_fallback {
+        return Err("token doesn't match any provisioner".to_owned());
+    }
+
+    let public_key = load_public_key(
+        config
+            .provisioner_public_key
+            .as_ref()
+            .ok_or_else(|| "provisioner public key is missing".to_owned())?,
+    )?;
+
+    let token = JwtSig::decode(encoded_token, &public_key, &validator)
+        .map_err(|e| format!("couldn't validate json web token: {}", e))?;
+
+    Ok(Authorization {
+        token,
+        provisioner: None,
+    })
+}
+
+/// Administration endpoints only accept tokens signed by the key of `admin_public_key`.
+pub fn check_admin_authorization(config: &Config, req: &Request<Body>) -> Result<JwtSig<serde_json::Value>, String> {
+    let encoded_token = bearer_token(req)?;
+    let now = JwtDate::new_with_leeway(unix_epoch() as i64, 10);
+    let validator = JwtValidator::strict(&now);
+
+    let public_key = load_public_key(
+        config
+            .admin_public_key
+            .as_ref()
+            .ok_or_else(|| "admin public key is missing".to_owned())?,
+    )?;
+
+    JwtSig::decode(encoded_token, &public_key, &validator)
+        .map_err(|e| format!("couldn't validate json web token: {}", e))
+}
+
+fn bearer_token(req: &Request<Body>) -> Result<&str, String> {
     let header = match req.headers().get(header::AUTHORIZATION) {
         Some(h) => h,
         None => return Err("Authorization header is missing".to_owned()),
@@ -48,36 +112,69 @@ pub fn check_authorization(config: &Config, req: &Request<Body>) -> Result<JwtSi
     if auth_vec.len() < 2 {
         return Err(format!("Authorization header wrong format: {}", auth_str));
     }
-    let method = AuthorizationMethod::from(auth_vec[0]);
-    match method {
-        AuthorizationMethod::Bearer => {
-            let public_key = match config
-                .provisioner_public_key
-                .as_ref()
-                .ok_or_else(|| "provisioner public key is missing".to_owned())?
-            {
-                PathOr::Path(path) => {
-                    let pem_str = std::fs::read_to_string(path)
-                        .map_err(|e| format!("couldn't read provisioner public key: {}", e))?;
-                    let pem = pem_str
-                        .parse::<Pem>()
-                        .map_err(|e| format!("couldn't parse provisioner public key pem: {}", e))?;
-                    Cow::Owned(
-                        PublicKey::from_pem(&pem)
-                            .map_err(|e| format!("couldn't parse provisioner public key: {}", e))?,
-                    )
+    match AuthorizationMethod::from(auth_vec[0]) {
+        AuthorizationMethod::Bearer => Ok(auth_vec[1]),
+        AuthorizationMethod::Unknown => Err(format!("Unknown authorization method: {}", auth_vec[0])),
+    }
+}
+
+/// Provisioners are selected by `kid` first (provisioner name or key of its JWKS), then by `iss`.
+///
+/// Token is not validated yet. Provisioner keys are loaded along with the configuration.
+fn find_provisioner<'a>(config: &'a Config, encoded_token: &str) -> Result<Option<&'a ProvisionerConfig>, String> {
+    if config.provisioners.is_empty() {
+        return Ok(None);
+    }
+
+    let unverified = JwtSig::<serde_json::Value>::decode_dangerous(encoded_token, &JwtValidator::no_check())
+        .map_err(|e| format!("couldn't decode json web token: {}", e))?;
+
+    if let Some(kid) = unverified.header.kid.as_deref() {
+        for provisioner in &config.provisioners {
+            if provisioner.name == kid {
+                return Ok(Some(provisioner));
+            }
+
+            if let Some(jwks) = &provisioner.jwks {
+                if load_jwk_set(jwks)?.find_by_kid(kid).is_some() {
+                    return Ok(Some(provisioner));
                 }
-                PathOr::Some(key) => Cow::Borrowed(key),
-            };
-
-            Ok(JwtSig::decode(
-                auth_vec[1],
-                &public_key,
-                &JwtValidator::strict(&JwtDate::new_with_leeway(unix_epoch() as i64, 10)),
-            )
-            .map_err(|e| format!("couldn't validate json web token: {}", e))?)
+            }
         }
-        AuthorizationMethod::Unknown => Err(format!("Unknown authorization method: {}", auth_vec[0])),
+    }
+
+    if let Some(iss) = unverified.claims.get("iss").and_then(|iss| iss.as_str()) {
+        return Ok(config.provisioners.iter().find(|provisioner| provisioner.name == iss));
+    }
+
+    Ok(None)
+}
+
+fn load_public_key(public_key: &PathOr<PublicKey>) -> Result<Cow<'_, PublicKey>, String> {
+    match public_key {
+        PathOr::Path(path) => {
+            let pem_str =
+                std::fs::read_to_string(path).map_err(|e| format!("couldn't read provisioner public key: {}", e))?;
+            let pem = pem_str
+                .parse::<Pem>()
+                .map_err(|e| format!("couldn't parse provisioner public key pem: {}", e))?;
+            Ok(Cow::Owned(PublicKey::from_pem(&pem).map_err(|e| {
+                format!("couldn't parse provisioner public key: {}", e)
+            })?))
+        }
+        PathOr::Some(key) => Ok(Cow::Borrowed(key)),
+    }
+}
+
+fn load_jwk_set(jwks: &PathOr<JwkSet>) -> Result<Cow<'_, JwkSet>, String> {
+    match jwks {
+        PathOr::Path(path) => {
+            let json = std::fs::read_to_string(path).map_err(|e| format!("couldn't read provisioner JWKS: {}", e))?;
+            Ok(Cow::Owned(
+                JwkSet::from_json(&json).map_err(|e| format!("couldn't parse provisioner JWKS: {}", e))?,
+            ))
+        }
+        PathOr::Some(jwk_set) => Ok(Cow::Borrowed(jwk_set)),
     }
 }
 
@@ -86,6 +183,7 @@ mod tests {
     use super::*;
     use crate::config::BackendType;
     use crate::utils::unix_epoch;
+    use picky::jose::jwk::Jwk;
     use picky::jose::jws::JwsAlg;
     use picky::key::{PrivateKey, PublicKey};
     use picky::pem::Pem;
@@ -163,4 +261,133 @@ mod tests {
         let err = check_authorization(&config, &saphir_req).err().expect("auth err");
         assert_eq!(err, "provisioner public key is missing");
     }
+
+    #[test]
+    fn admin_token_authorized() {
+        let admin_key = get_private_key_2();
+        let provisioner_key = get_private_key_1();
+
+        let mut config = config(Some(provisioner_key.to_public_key()));
+        let err = check_admin_authorization(&config, &build_saphir_req(&get_provider_token(&admin_key)))
+            .err()
+            .expect("auth err");
+        assert_eq!(err, "admin public key is missing");
+
+        config.admin_public_key = Some(PathOr::Some(admin_key.to_public_key()));
+        check_admin_authorization(&config, &build_saphir_req(&get_provider_token(&admin_key))).expect("admin auth");
+
+        // provisioner tokens don't grant administration rights
+        let err = check_admin_authorization(&config, &build_saphir_req(&get_provider_token(&provisioner_key)))
+            .err()
+            .expect("auth err");
+        assert_eq!(
+            err,
+            "couldn't validate json web token: JWS error: signature error: invalid signature"
+        );
+    }
+
+    fn provisioner(name: &str) -> ProvisionerConfig {
+        ProvisionerConfig {
+            name: name.to_owned(),
+            public_key: None,
+            jwks: None,
+            san_patterns: Vec::new(),
+            max_x509_duration_secs: None,
+            key_usage: None,
+            extended_key_usage: None,
+            key_types: Vec::new(),
+        }
+    }
+
+    fn encode_token(private_key: &PrivateKey, kid: Option<&str>, iss: Option<&str>) -> String {
+        let mut claims = serde_json::to_value(ProviderClaims {
+            x509_duration_secs: 7_776_000,
+            sub: "CoolSubject".to_owned(),
+            nbf: unix_epoch(),
+            exp: unix_epoch() + 10,
+        })
+        .unwrap();
+        if let Some(iss) = iss {
+            claims["iss"] = iss.into();
+        }
+        let mut jwt = JwtSig::new(JwsAlg::RS256, claims);
+        jwt.header.kid = kid.map(str::to_owned);
+        jwt.encode(private_key).expect("jwt encode")
+    }
+
+    #[test]
+    fn provisioner_selected_by_kid() {
+        let key_1 = get_private_key_1();
+        let key_2 = get_private_key_2();
+
+        let mut jwk = Jwk::from_public_key(&key_2.to_public_key()).unwrap();
+        jwk.kid = Some("key-2".to_owned());
+
+        let mut config = config(Some(key_1.to_public_key()));
+        config.provisioners = vec![
+            ProvisionerConfig {
+                public_key: Some(PathOr::Some(key_1.to_public_key())),
+                ..provisioner("servers")
+            },
+            ProvisionerConfig {
+                jwks: Some(PathOr::Some(JwkSet { keys: vec![jwk] })),
+                ..provisioner("devices")
+            },
+        ];
+
+        let token = encode_token(&key_1, Some("servers"), None);
+        let authorization = check_authorization(&config, &build_saphir_req(&token)).expect("auth");
+        assert_eq!(authorization.provisioner.unwrap().name, "servers");
+
+        let token = encode_token(&key_2, Some("key-2"), None);
+        let authorization = check_authorization(&config, &build_saphir_req(&token)).expect("auth");
+        assert_eq!(authorization.provisioner.unwrap().name, "devices");
+
+        let token = encode_token(&key_1, Some("key-2"), None);
+        let err = check_authorization(&config, &build_saphir_req(&token))
+            .err()
+            .expect("auth err");
+        assert_eq!(
+            err,
+            "couldn't validate json web token: JWS error: signature error: invalid signature"
+        );
+
+        let token = encode_token(&key_1, Some("unknown"), None);
+        let err = check_authorization(&config, &build_saphir_req(&token))
+            .err()
+            .expect("auth err");
+        assert_eq!(err, "token doesn't match any provisioner");
+
+        // unknown provisioner only falls back to `provisioner_public_key` when explicitly allowed
+        config.provisioner_public_key_fallback = true;
+        let authorization = check_authorization(&config, &build_saphir_req(&token)).expect("auth");
+        assert!(authorization.provisioner.is_none());
+    }
+
+    #[test]
+    fn provisioner_selected_by_iss() {
+        let key_2 = get_private_key_2();
+
+        let mut config = config(None);
+        config.provisioners = vec![ProvisionerConfig {
+            public_key: Some(PathOr::Some(key_2.to_public_key())),
+            ..provisioner("devices")
+        }];
+
+        let token = encode_token(&key_2, None, Some("devices"));
+        let authorization = check_authorization(&config, &build_saphir_req(&token)).expect("auth");
+        assert_eq!(authorization.provisioner.unwrap().name, "devices");
+
+        let token = encode_token(&key_2, None, Some("servers"));
+        let err = check_authorization(&config, &build_saphir_req(&token))
+            .err()
+            .expect("auth err");
+        assert_eq!(err, "token doesn't match any provisioner");
+
+        config.provisioner_public_key_fallback = true;
+        let err = check_authorization(&config, &build_saphir_req(&token))
+            .err()
+            .expect("auth err");
+        assert_eq!(err, "provisioner public key is missing");
+    }
 }
@@ -1,34 +1,48 @@
+use crate::acme::challenge::{Http01Challenge, InternalNetworks};
+use crate::acme::{AcmeError, AcmeResponse, AcmeState, REPLAY_NONCE_HEADER};
 use crate::addressing::{convert_to_canonical_base, CANONICAL_HASH_CODE};
-use crate::config::{CertKeyPair, Config};
-use crate::db::{get_storage, BoxedPickyStorage, CertificateEntry, PickyStorage};
-use crate::http::authorization::{check_authorization, ProviderClaims};
-use crate::http::utils::{Format, StatusCodeResult};
+use crate::config::{CertKeyPair, Config, ProvisionerConfig};
+use crate::db::{
+    get_storage, BoxedPickyStorage, CertificateEntry, CertificateQuery, PickyStorage, RevocationEntry, MAX_PAGE_SIZE,
+};
+use crate::est::{self, EstIdentity, CSR_ATTRS_CONTENT_TYPE, PKCS7_CERTS_ONLY_CONTENT_TYPE};
+use crate::http::authorization::{check_admin_authorization, check_authorization, ProviderClaims};
+use crate::http::utils::{percent_decode, Format, SaphirRequestExt, StatusCodeResult};
 use crate::logging::build_logger_config;
-use crate::picky_controller::Picky;
+use crate::picky_controller::{LeafExtensions, Picky};
 use crate::utils::{GreedyError, PathOr};
 use log4rs::Handle;
-use picky::key::PrivateKey;
+use picky::hash::HashAlgorithm;
+use picky::key::{EcCurve, EdCurve, PrivateKey};
 use picky::pem::{parse_pem, to_pem, Pem};
+use picky::signature::SignatureAlgorithm;
 use picky::ssh::certificate::{SshCertKeyType, SshCertType, SshCertificateBuilder};
 use picky::ssh::private_key::SshPrivateKey;
 use picky::ssh::public_key::SshPublicKey;
 use picky::ssh::sshtime::SshTime;
+use picky::x509::crl::CrlBuilder;
 use picky::x509::date::UTCDate;
+use picky::x509::ocsp::{OcspCertStatus, OcspRequest, OcspResponse, OcspResponseBuilder};
 use picky::x509::pkcs7::authenticode::{Attribute, AuthenticodeSignatureBuilder};
 use picky::x509::pkcs7::timestamp::TimestampRequest;
-use picky::x509::{Cert, Csr};
+use picky::x509::{Cert, Crl, Csr};
+use picky_asn1_x509::{CrlReason, OcspResponseStatus};
 use saphir::prelude::*;
 use saphir::response::Builder as ResponseBuilder;
 use serde_json::{self, Value};
 use std::borrow::Cow;
 use std::convert::TryFrom;
 use time::OffsetDateTime;
-use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+const CRL_VALIDITY_DAYS: i64 = 1;
 
 pub struct ServerController {
     storage: BoxedPickyStorage,
     config: RwLock<Config>,
     log_handle: Handle,
+    acme: AcmeState,
+    crl_cache: CrlCache,
 }
 
 impl ServerController {
@@ -39,6 +53,8 @@ impl ServerController {
             storage,
             config: RwLock::new(config),
             log_handle,
+            acme: AcmeState::new(vec![Box::new(Http01Challenge::default())]),
+            crl_cache: CrlCache::default(),
         })
     }
 
@@ -65,6 +81,7 @@ impl ServerController {
 
         let (cert, der) = extract_cert_from_request(&req).await.bad_request()?;
         let ski = hex::encode(cert.subject_key_identifier().bad_request_desc("couldn't fetch SKI")?);
+        let serial_number = hex::encode(cert.serial_number().as_unsigned_bytes_be());
         let issuer_name = cert
             .issuer_name()
             .find_common_name()
@@ -88,6 +105,7 @@ impl ServerController {
                 name: subject_name.clone(),
                 cert: der,
                 key_identifier: ski,
+                serial_number,
                 key: None,
             })
             .await
@@ -114,19 +132,24 @@ impl ServerController {
 
     #[post("/sign")]
     async fn cert_signature_request(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
-        let (locked_subject_name, x509_duration_secs) = match check_authorization(&*self.read_conf().await, &req) {
-            Ok(token) => {
-                let provider_claims: ProviderClaims = serde_json::from_value(token.claims).bad_request()?;
-                (provider_claims.sub, provider_claims.x509_duration_secs)
-            }
-            Err(e) => {
-                log::error!("authorization failed: {}", e);
-                return Err(StatusCode::UNAUTHORIZED);
-            }
-        };
+        let (locked_subject_name, x509_duration_secs, provisioner) =
+            match check_authorization(&*self.read_conf().await, &req) {
+                Ok(authorization) => {
+                    let provider_claims: ProviderClaims =
+                        serde_json::from_value(authorization.token.claims).bad_request()?;
+                    (
+                        provider_claims.sub,
+                        provider_claims.x509_duration_secs,
+                        authorization.provisioner,
+                    )
+                }
+                Err(e) => {
+                    log::error!("authorization failed: {}", e);
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+            };
 
         let req = req.load_body().await.bad_request()?;
-
         let csr = extract_csr_from_request(&req).await.bad_request()?;
 
         let subject_name = csr
@@ -144,12 +167,38 @@ impl ServerController {
             return Err(StatusCode::UNAUTHORIZED);
         }
 
+        let (dns_names, x509_duration_secs, extensions) = match &provisioner {
+            Some(provisioner) => {
+                let dns_names = match provisioner.check_csr(&csr) {
+                    Ok(dns_names) => dns_names,
+                    Err(e) => {
+                        log::error!("CSR rejected: {}", e);
+                        return Err(StatusCode::FORBIDDEN);
+                    }
+                };
+                (
+                    dns_names,
+                    provisioner.x509_duration_secs(x509_duration_secs),
+                    provisioner.leaf_extensions(),
+                )
+            }
+            None => (vec![subject_name], x509_duration_secs, LeafExtensions::default()),
+        };
+
         // Sign CSR
         let conf = self.read_conf().await;
         let ca_name = format!("{} Authority", &conf.realm);
-        let signed_cert = sign_certificate(&ca_name, csr, &conf, self.storage.as_ref(), x509_duration_secs)
-            .await
-            .internal_error()?;
+        let signed_cert = sign_certificate_for_dns_names(
+            &ca_name,
+            csr,
+            &dns_names,
+            &extensions,
+            &conf,
+            self.storage.as_ref(),
+            x509_duration_secs,
+        )
+        .await
+        .internal_error()?;
         drop(conf); // release lock early
 
         let builder = match Format::response_format(&req).unwrap_or(Format::PemFile) {
@@ -182,19 +231,9 @@ impl ServerController {
 
     #[get("/cert/<multihash>")]
     async fn get_cert(&self, multihash: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
-        let addressing_hash_any_base = multihash;
-        let (addressing_hash, hash) = convert_to_canonical_base(&addressing_hash_any_base).internal_error()?;
-        let canonical_address = if hash == CANONICAL_HASH_CODE {
-            addressing_hash
-        } else {
-            let converted = self
-                .storage
-                .lookup_addressing_hash(&addressing_hash)
-                .await
-                .internal_error_desc("address lookup failed")?;
-            log::info!("converted cert address {} -> {}", addressing_hash_any_base, converted);
-            converted
-        };
+        let canonical_address = find_canonical_address(self.storage.as_ref(), &multihash)
+            .await
+            .internal_error()?;
 
         let cert_der = match self.storage.get_cert_by_addressing_hash(&canonical_address).await {
             Ok(cert_der) => cert_der,
@@ -228,6 +267,28 @@ impl ServerController {
         Ok(builder.body(chain.join("\n")))
     }
 
+    #[get("/certs")]
+    async fn get_certs(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        if let Err(e) = check_admin_authorization(&*self.read_conf().await, &req) {
+            log::error!("authorization failed: {}", e);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let query = parse_certificate_query(
+            req.uri().query().unwrap_or_default(),
+            OffsetDateTime::now_utc().unix_timestamp(),
+        )
+        .bad_request()?;
+
+        let page = self.storage.find_certificates(&query).await.internal_error()?;
+        let json = serde_json::to_string(&page).internal_error()?;
+
+        Ok(ResponseBuilder::new()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(json)
+            .status(StatusCode::OK))
+    }
+
     #[get("/reload")]
     async fn reload_yaml_conf(&self) -> (&'static str, StatusCode) {
         match self.reload_yaml_conf_impl().await {
@@ -284,7 +345,10 @@ impl ServerController {
             .internal_error()?;
 
         let digest = timestamp_request.digest();
-        let picky_server_hash = config.signing_algorithm.hash_algorithm();
+        let picky_server_hash = config
+            .signing_algorithm
+            .hash_algorithm()
+            .internal_error_desc("configured signing algorithm can't be used for authenticode timestamping")?;
 
         let attributes = vec![
             Attribute::new_content_type_pkcs7(),
@@ -294,6 +358,7 @@ impl ServerController {
 
         let authenticode_signature = AuthenticodeSignatureBuilder::new()
             .digest_algorithm(picky_server_hash)
+            .signature_algorithm(config.signing_algorithm)
             .signing_key(&intermediate_pk)
             .content_info(timestamp_request.into_content())
             .authenticated_attributes(attributes)
@@ -321,6 +386,130 @@ impl ServerController {
         Ok(response)
     }
 
+    #[get("/ocsp/<request>")]
+    async fn ocsp_get(&self, request: String) -> Result<ResponseBuilder, StatusCode> {
+        let request = percent_decode(&request)
+            .and_then(|request| base64::decode(request).map_err(|e| e.to_string()))
+            .and_then(|der| OcspRequest::from_der(&der).map_err(|e| e.to_string()));
+        self.ocsp_respond(request).await
+    }
+
+    #[post("/ocsp")]
+    async fn ocsp_post(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let req = req.load_body().await.bad_request()?;
+
+        let request = match Format::request_format(&req) {
+            Ok(Format::OcspRequest) => OcspRequest::from_der(req.body()).map_err(|e| e.to_string()),
+            Ok(unexpected) => Err(format!("unexpected request format: {}", unexpected)),
+            Err(e) => Err(e),
+        };
+        self.ocsp_respond(request).await
+    }
+
+    #[post("/revoke")]
+    async fn revoke(&self, req: Request) -> Result<StatusCode, StatusCode> {
+        #[derive(serde::Deserialize)]
+        struct RevokeRequest {
+            serial_number: Option<String>,
+            multihash: Option<String>,
+            #[serde(default)]
+            reason: u8,
+        }
+
+        if let Err(e) = check_admin_authorization(&*self.read_conf().await, &req) {
+            log::error!("authorization failed: {}", e);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        let req = req.load_body().await.bad_request()?;
+        let revoke_request: RevokeRequest = serde_json::from_slice(req.body()).bad_request()?;
+
+        // removeFromCRL is only meaningful in delta CRLs and can't be used to revoke a certificate
+        crl_reason_from_code(revoke_request.reason)
+            .filter(|reason| *reason != CrlReason::RemoveFromCrl)
+            .bad_request_desc("invalid revocation reason code")?;
+
+        let serial_number = match (revoke_request.serial_number, revoke_request.multihash) {
+            (Some(serial_number), None) => normalize_serial_number(&serial_number).bad_request()?,
+            (None, Some(multihash)) => {
+                let canonical_address = find_canonical_address(self.storage.as_ref(), &multihash)
+                    .await
+                    .bad_request()?;
+                let cert_der = match self.storage.get_cert_by_addressing_hash(&canonical_address).await {
+                    Ok(cert_der) => cert_der,
+                    Err(e) => {
+                        log::error!("couldn't fetch certificate using hash {}: {}", canonical_address, e);
+                        return Err(StatusCode::NOT_FOUND);
+                    }
+                };
+                let cert = Cert::from_der(&cert_der).internal_error()?;
+
+                let issuer_name = cert
+                    .issuer_name()
+                    .find_common_name()
+                    .bad_request_desc("couldn't find issuer common name")?
+                    .to_string();
+                if issuer_name != format!("{} Authority", &self.read_conf().await.realm) {
+                    log::error!("this certificate was not signed by the CA of this server.");
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+
+                hex::encode(cert.serial_number().as_unsigned_bytes_be())
+            }
+            _ => {
+                log::error!("either a serial number or a multihash is expected");
+                return Err(StatusCode::BAD_REQUEST);
+            }
+        };
+
+        // only certificates saved by this server can be revoked
+        self.storage
+            .get_addressing_hash_by_serial_number(&serial_number)
+            .await
+            .not_found_desc("unknown serial number")?;
+
+        self.storage
+            .revoke(RevocationEntry {
+                serial_number,
+                revocation_date: OffsetDateTime::now_utc().unix_timestamp(),
+                reason: revoke_request.reason,
+            })
+            .await
+            .internal_error()?;
+
+        Ok(StatusCode::OK)
+    }
+
+    #[get("/crl")]
+    async fn get_crl(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let config = self.read_conf().await;
+        let crl = self
+            .crl_cache
+            .get(&config, self.storage.as_ref())
+            .await
+            .internal_error()?;
+        drop(config); // release lock early
+
+        match Format::response_format(&req).unwrap_or(Format::PkixCrlBinary) {
+            Format::PemFile => {
+                let pem = crl.to_pem().internal_error_desc("couldn't get crl pem")?;
+                Ok(ResponseBuilder::new()
+                    .header(header::CONTENT_TYPE, "application/x-pem-file")
+                    .body(pem.to_string()))
+            }
+            Format::PkixCrlBinary => {
+                let der = crl.to_der().internal_error_desc("couldn't get crl der")?;
+                Ok(ResponseBuilder::new()
+                    .header(header::CONTENT_TYPE, "application/pkix-crl")
+                    .body(der))
+            }
+            unexpected => {
+                log::error!("unexpected response format: {}", unexpected);
+                Err(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+
     #[post("/ssh/sign")]
     async fn sign_ssh_key(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
         // nbf and exp never reads but we need them for deserialization
@@ -337,7 +526,7 @@ impl ServerController {
         }
 
         let sign_request: SshSignRequest = match check_authorization(&*self.read_conf().await, &req) {
-            Ok(token) => serde_json::from_value(token.claims).bad_request()?,
+            Ok(authorization) => serde_json::from_value(authorization.token.claims).bad_request()?,
             Err(e) => {
                 log::error!("authorization failed: {}", e);
                 return Err(StatusCode::UNAUTHORIZED);
@@ -392,9 +581,309 @@ impl ServerController {
 
         Ok(ResponseBuilder::new().body(pub_key).status(StatusCode::OK))
     }
+
+    #[get("/acme/directory")]
+    async fn acme_directory(&self) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        Ok(self.acme_respond(Ok(self.acme.directory(&base_url))))
+    }
+
+    #[head("/acme/new-nonce")]
+    async fn acme_new_nonce_head(&self) -> Result<ResponseBuilder, StatusCode> {
+        self.acme_base_url().await?;
+        Ok(self.acme_respond(Ok(AcmeResponse::no_content(200))))
+    }
+
+    #[get("/acme/new-nonce")]
+    async fn acme_new_nonce(&self) -> Result<ResponseBuilder, StatusCode> {
+        self.acme_base_url().await?;
+        Ok(self.acme_respond(Ok(AcmeResponse::no_content(204))))
+    }
+
+    #[post("/acme/new-account")]
+    async fn acme_new_account(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.new_account(&base_url, req.body())))
+    }
+
+    #[post("/acme/account/<id>")]
+    async fn acme_account(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.account(&base_url, &id, req.body())))
+    }
+
+    #[post("/acme/account/<id>/orders")]
+    async fn acme_account_orders(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.account_orders(&base_url, &id, req.body())))
+    }
+
+    #[post("/acme/new-order")]
+    async fn acme_new_order(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        let internal_networks = self.acme_internal_networks().await?;
+        Ok(self.acme_respond(self.acme.new_order(&base_url, req.body(), &internal_networks)))
+    }
+
+    #[post("/acme/order/<id>")]
+    async fn acme_order(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.order(&base_url, &id, req.body())))
+    }
+
+    #[post("/acme/order/<id>/finalize")]
+    async fn acme_finalize(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+
+        let conf = self.read_conf().await;
+        let config: &Config = &conf;
+        let storage = self.storage.as_ref();
+        let acme_config = config.acme.as_ref().not_found()?;
+        let x509_duration_secs = acme_config.x509_duration_secs;
+        let provisioner = config
+            .find_provisioner(acme_config.provisioner.as_deref())
+            .internal_error()?;
+
+        let result = self
+            .acme
+            .finalize(&base_url, &id, req.body(), move |csr, dns_names| {
+                issue_acme_certificate(csr, dns_names, provisioner, config, storage, x509_duration_secs)
+            })
+            .await;
+
+        Ok(self.acme_respond(result))
+    }
+
+    #[post("/acme/authz/<id>")]
+    async fn acme_authorization(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.authorization(&base_url, &id, req.body())))
+    }
+
+    #[post("/acme/chall/<id>")]
+    async fn acme_challenge(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        let internal_networks = self.acme_internal_networks().await?;
+        Ok(self.acme_respond(
+            self.acme
+                .challenge(&base_url, &id, req.body(), &internal_networks)
+                .await,
+        ))
+    }
+
+    #[post("/acme/cert/<id>")]
+    async fn acme_certificate(&self, id: String, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let base_url = self.acme_base_url().await?;
+        let req = req.load_body().await.bad_request()?;
+        Ok(self.acme_respond(self.acme.certificate(&base_url, &id, req.body())))
+    }
+
+    #[get("/.well-known/est/cacerts")]
+    async fn est_ca_certs(&self) -> Result<ResponseBuilder, StatusCode> {
+        let conf = self.read_conf().await;
+        conf.est.as_ref().not_found_desc("EST is not enabled")?;
+
+        let ca_name = format!("{} Authority", conf.realm);
+        let chain = find_ca_certificates(self.storage.as_ref(), &ca_name)
+            .await
+            .internal_error()?;
+        drop(conf);
+
+        let body = est::certs_only_response(&chain).internal_error()?;
+        Ok(est_respond(PKCS7_CERTS_ONLY_CONTENT_TYPE, body))
+    }
+
+    #[post("/.well-known/est/simpleenroll")]
+    async fn est_simple_enroll(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let identity = match self.est_authenticate(&req).await {
+            Ok(identity) => identity,
+            Err(response) => return Ok(response),
+        };
+
+        let req = req.load_body().await.bad_request()?;
+        let csr = est::decode_csr(req.body()).bad_request()?;
+
+        self.est_enroll(csr, &identity).await
+    }
+
+    #[post("/.well-known/est/simplereenroll")]
+    async fn est_simple_reenroll(&self, req: Request) -> Result<ResponseBuilder, StatusCode> {
+        let identity = match self.est_authenticate(&req).await {
+            Ok(identity) => identity,
+            Err(response) => return Ok(response),
+        };
+
+        let current = match &identity {
+            EstIdentity::Device(cert) => cert,
+            EstIdentity::User(username) => {
+                log::error!(
+                    "{} can't renew a certificate without client certificate authentication",
+                    username
+                );
+                return Err(StatusCode::FORBIDDEN);
+            }
+        };
+
+        let req = req.load_body().await.bad_request()?;
+        let csr = est::decode_csr(req.body()).bad_request()?;
+        est::check_reenrollment(current, &csr).bad_request()?;
+
+        self.est_enroll(csr, &identity).await
+    }
+
+    #[get("/.well-known/est/csrattrs")]
+    async fn est_csr_attributes(&self) -> Result<ResponseBuilder, StatusCode> {
+        self.read_conf()
+            .await
+            .est
+            .as_ref()
+            .not_found_desc("EST is not enabled")?;
+
+        Ok(est_respond(CSR_ATTRS_CONTENT_TYPE, est::csr_attributes_response()))
+    }
 }
 
 impl ServerController {
+    async fn acme_base_url(&self) -> Result<String, StatusCode> {
+        self.read_conf()
+            .await
+            .acme
+            .as_ref()
+            .map(|acme| acme.base_url.clone())
+            .not_found_desc("ACME is not enabled")
+    }
+
+    async fn acme_internal_networks(&self) -> Result<InternalNetworks, StatusCode> {
+        self.read_conf()
+            .await
+            .acme
+            .as_ref()
+            .map(|acme| acme.internal_networks())
+            .not_found_desc("ACME is not enabled")
+    }
+
+    /// Every ACME response carries a fresh nonce (RFC 8555 #6.5).
+    fn acme_respond(&self, response: Result<AcmeResponse, AcmeError>) -> ResponseBuilder {
+        let response = response.unwrap_or_else(|e| {
+            log::warn!("ACME request failed: {}", e);
+            AcmeResponse::from(e)
+        });
+
+        let mut builder = ResponseBuilder::new()
+            .header(REPLAY_NONCE_HEADER, self.acme.new_nonce())
+            .header(header::CACHE_CONTROL, "no-store");
+
+        if let Some(location) = response.location {
+            builder = builder.header(header::LOCATION, location);
+        }
+
+        if let Some(link) = response.link {
+            builder = builder.header(header::LINK, link);
+        }
+
+        if !response.body.is_empty() {
+            builder = builder
+                .header(header::CONTENT_TYPE, response.content_type)
+                .body(response.body);
+        }
+
+        builder.status(StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR))
+    }
+
+    /// Client certificate, when forwarded by the trusted proxy, takes precedence over HTTP basic authentication.
+    async fn est_authenticate(&self, req: &Request) -> Result<EstIdentity, ResponseBuilder> {
+        let conf = self.read_conf().await;
+        let est_config = match &conf.est {
+            Some(est_config) => est_config,
+            None => {
+                log::error!("EST is not enabled");
+                return Err(ResponseBuilder::new().status(StatusCode::NOT_FOUND));
+            }
+        };
+
+        let client_cert = est_config
+            .client_cert_header
+            .as_deref()
+            .and_then(|header_name| req.get_header_string_value(header_name));
+
+        let identity = if let Some(client_cert) = client_cert {
+            let proxy_secret = req.get_header_string_value(est::PROXY_SECRET_HEADER);
+            if !est::is_forwarded_by_proxy(est_config, proxy_secret.as_deref()) {
+                Err("client certificate wasn't forwarded by the trusted proxy".to_owned())
+            } else {
+                match est::decode_client_certificate(&client_cert) {
+                    Ok(cert) => verify_est_client_certificate(&cert, &conf, self.storage.as_ref())
+                        .await
+                        .map(|()| EstIdentity::Device(Box::new(cert))),
+                    Err(e) => Err(e),
+                }
+            }
+        } else if let Some(authorization) = req.get_header_string_value(header::AUTHORIZATION.as_str()) {
+            est::authenticate_user(est_config, &authorization).map(EstIdentity::User)
+        } else {
+            Err("Authorization header is missing".to_owned())
+        };
+
+        identity.map_err(|e| {
+            log::error!("EST authentication failed: {}", e);
+            ResponseBuilder::new()
+                .header(header::WWW_AUTHENTICATE, format!("Basic realm=\"{}\"", conf.realm))
+                .status(StatusCode::UNAUTHORIZED)
+        })
+    }
+
+    async fn est_enroll(&self, csr: Csr, identity: &EstIdentity) -> Result<ResponseBuilder, StatusCode> {
+        let subject_name = csr.subject_name().to_string();
+        match identity {
+            EstIdentity::User(username) => log::info!("EST enrollment of {} by {}", subject_name, username),
+            EstIdentity::Device(cert) => {
+                log::info!("EST enrollment of {} by {}", subject_name, cert.subject_name())
+            }
+        }
+
+        let conf = self.read_conf().await;
+        let signed_cert = enroll_est_certificate(csr, identity, &conf, self.storage.as_ref()).await?;
+        drop(conf); // release lock early
+
+        let body = est::certs_only_response(std::iter::once(&signed_cert)).internal_error()?;
+        Ok(est_respond(PKCS7_CERTS_ONLY_CONTENT_TYPE, body))
+    }
+
+    async fn ocsp_respond(&self, request: Result<OcspRequest, String>) -> Result<ResponseBuilder, StatusCode> {
+        let response = match request {
+            Ok(request) => {
+                let config = self.read_conf().await;
+                respond_to_ocsp_request(&config, self.storage.as_ref(), &request)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("couldn't respond to OCSP request: {}", e);
+                        OcspResponse::unsuccessful(OcspResponseStatus::InternalError)
+                    })
+            }
+            Err(e) => {
+                log::error!("malformed OCSP request: {}", e);
+                OcspResponse::unsuccessful(OcspResponseStatus::MalformedRequest)
+            }
+        };
+
+        let der = response
+            .to_der()
+            .internal_error_desc("couldn't serialize OCSP response")?;
+
+        Ok(ResponseBuilder::new()
+            .header(header::CONTENT_TYPE, "application/ocsp-response")
+            .body(der)
+            .status(StatusCode::OK))
+    }
+
     async fn reload_yaml_conf_impl(&self) -> Result<(), String> {
         match Config::init_yaml() {
             Ok(new_conf) => {
@@ -421,28 +910,341 @@ impl ServerController {
                 }
                 *old_conf = new_conf;
 
-                log::info!("reloaded successfully");
-                Ok(())
+                log::info!("reloaded successfully");
+                Ok(())
+            }
+            Err(e) => Err(format!("couldn't reload config: {}", e)),
+        }
+    }
+}
+
+async fn find_canonical_address(storage: &dyn PickyStorage, multihash: &str) -> Result<String, String> {
+    let (addressing_hash, hash) = convert_to_canonical_base(multihash)?;
+    if hash == CANONICAL_HASH_CODE {
+        Ok(addressing_hash)
+    } else {
+        let converted = storage
+            .lookup_addressing_hash(&addressing_hash)
+            .await
+            .map_err(|e| format!("address lookup failed: {}", e))?;
+        log::info!("converted cert address {} -> {}", multihash, converted);
+        Ok(converted)
+    }
+}
+
+async fn get_main_private_key(config: &Config, storage: &dyn PickyStorage) -> Result<(PrivateKey, String), String> {
+    let root_name = format!("{} Root CA", config.realm);
+    let root_hash = storage
+        .get_addressing_hash_by_name(&root_name)
+        .await
+        .map_err(|e| format!("error while fetching root: {}", e))?;
+    let root_key_der = storage
+        .get_key_by_addressing_hash(&root_hash)
+        .await
+        .map_err(|e| format!("couldn't fetch root CA private key: {}", e))?;
+    Ok((
+        Picky::parse_pk_from_magic_der(&root_key_der).map_err(|e| format!("Failed to get root private key: {}", e))?,
+        root_hash,
+    ))
+}
+
+async fn respond_to_ocsp_request(
+    config: &Config,
+    storage: &dyn PickyStorage,
+    request: &OcspRequest,
+) -> Result<OcspResponse, String> {
+    let intermediate_name = format!("{} Authority", config.realm);
+    let intermediate_hash = storage
+        .get_addressing_hash_by_name(&intermediate_name)
+        .await
+        .map_err(|e| format!("couldn't fetch intermediate cert: {}", e))?;
+    let intermediate_cert_der = storage
+        .get_cert_by_addressing_hash(&intermediate_hash)
+        .await
+        .map_err(|e| format!("couldn't get intermediate cert der: {}", e))?;
+    let intermediate_cert =
+        Cert::from_der(&intermediate_cert_der).map_err(|e| format!("couldn't deserialize intermediate cert: {}", e))?;
+
+    // A dedicated OCSP responder is used when available, otherwise responses are signed by the intermediate CA.
+    let responder_name = format!("{} OCSP Responder", config.realm);
+    let (responder_hash, delegated) = match storage.get_addressing_hash_by_name(&responder_name).await {
+        Ok(responder_hash) => (responder_hash, true),
+        Err(_) => (intermediate_hash, false),
+    };
+    let responder_cert_der = storage
+        .get_cert_by_addressing_hash(&responder_hash)
+        .await
+        .map_err(|e| format!("couldn't get OCSP responder cert der: {}", e))?;
+    let responder_cert =
+        Cert::from_der(&responder_cert_der).map_err(|e| format!("couldn't deserialize OCSP responder cert: {}", e))?;
+    let responder_pk_der = storage
+        .get_key_by_addressing_hash(&responder_hash)
+        .await
+        .map_err(|e| format!("couldn't fetch OCSP responder private key: {}", e))?;
+    let responder_pk = Picky::parse_pk_from_magic_der(&responder_pk_der).map_err(|e| e.to_string())?;
+
+    let mut responses = Vec::new();
+    for cert_id in request.cert_ids() {
+        // cert ids using an unsupported hash algorithm can't identify a certificate issued by us
+        let issued_by_us = cert_id.is_issued_by(&intermediate_cert).unwrap_or(false);
+
+        let status = if issued_by_us {
+            let serial_number = hex::encode(cert_id.serial_number().as_unsigned_bytes_be());
+            let revocation = storage
+                .get_revocation_by_serial_number(&serial_number)
+                .await
+                .map_err(|e| format!("couldn't fetch revocation status: {}", e))?;
+
+            if let Some(revocation) = revocation {
+                OcspCertStatus::Revoked {
+                    revocation_time: revocation_date(&revocation)?,
+                    reason: crl_reason_from_code(revocation.reason).filter(|reason| *reason != CrlReason::Unspecified),
+                }
+            } else {
+                match storage.get_addressing_hash_by_serial_number(&serial_number).await {
+                    Ok(_) => OcspCertStatus::Good,
+                    Err(_) => OcspCertStatus::Unknown,
+                }
+            }
+        } else {
+            OcspCertStatus::Unknown
+        };
+
+        responses.push((cert_id, status));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let this_update = UTCDate::from(now);
+    let next_update = UTCDate::from(now + time::Duration::days(CRL_VALIDITY_DAYS));
+
+    let builder = OcspResponseBuilder::new();
+    builder
+        .responder(responder_cert, &responder_pk)
+        .produced_at(this_update.clone())
+        .signature_hash_type(signature_algorithm_for_key(config, &responder_pk)?);
+
+    if delegated {
+        builder.include_responder_cert();
+    }
+
+    if let Some(nonce) = request.nonce() {
+        builder.nonce(nonce);
+    }
+
+    for (cert_id, status) in responses {
+        builder.response(cert_id, status, this_update.clone(), Some(next_update.clone()));
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("couldn't build OCSP response: {}", e))
+}
+
+/// The configured signing algorithm only applies to RSA keys: other key types have a single suitable algorithm.
+fn signature_algorithm_for_key(config: &Config, key: &PrivateKey) -> Result<SignatureAlgorithm, String> {
+    if let Some(curve) = key.ec_curve() {
+        let hash = match curve {
+            EcCurve::NistP256 => HashAlgorithm::SHA2_256,
+            EcCurve::NistP384 => HashAlgorithm::SHA2_384,
+            EcCurve::NistP521 => HashAlgorithm::SHA2_512,
+        };
+        return Ok(SignatureAlgorithm::Ecdsa(hash));
+    }
+
+    match key.ed_curve() {
+        Some(EdCurve::Ed25519) => Ok(SignatureAlgorithm::Ed25519),
+        None => match config.signing_algorithm {
+            algorithm @ SignatureAlgorithm::RsaPkcs1v15(_) | algorithm @ SignatureAlgorithm::RsaPss(_) => Ok(algorithm),
+            _ => Ok(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256)),
+        },
+    }
+}
+
+/// Last published CRL.
+///
+/// A new CRL (with a new CRL number) is only signed when the revocation list changed or when the cached one
+/// reached its nextUpdate time.
+#[derive(Default)]
+struct CrlCache {
+    cached: Mutex<Option<CachedCrl>>,
+}
+
+struct CachedCrl {
+    crl: Crl,
+    revocations: Vec<RevocationEntry>,
+    next_update: OffsetDateTime,
+}
+
+impl CrlCache {
+    async fn get(&self, config: &Config, storage: &dyn PickyStorage) -> Result<Crl, String> {
+        let mut revocations = storage
+            .get_revocations()
+            .await
+            .map_err(|e| format!("couldn't fetch revocations: {}", e))?;
+        revocations.sort_by(|a, b| a.serial_number.cmp(&b.serial_number));
+
+        let mut cached = self.cached.lock().await;
+
+        if let Some(cached) = cached.as_ref() {
+            if cached.revocations == revocations && OffsetDateTime::now_utc() < cached.next_update {
+                return Ok(cached.crl.clone());
+            }
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let next_update = now + time::Duration::days(CRL_VALIDITY_DAYS);
+        let crl = generate_crl(config, storage, &revocations, now, next_update).await?;
+
+        *cached = Some(CachedCrl {
+            crl: crl.clone(),
+            revocations,
+            next_update,
+        });
+
+        Ok(crl)
+    }
+}
+
+async fn generate_crl(
+    config: &Config,
+    storage: &dyn PickyStorage,
+    revocations: &[RevocationEntry],
+    this_update: OffsetDateTime,
+    next_update: OffsetDateTime,
+) -> Result<Crl, String> {
+    let ca_name = format!("{} Authority", config.realm);
+    let ca_hash = storage
+        .get_addressing_hash_by_name(&ca_name)
+        .await
+        .map_err(|e| format!("couldn't fetch CA: {}", e))?;
+
+    let ca_cert_der = storage
+        .get_cert_by_addressing_hash(&ca_hash)
+        .await
+        .map_err(|e| format!("couldn't get CA cert der: {}", e))?;
+    let ca_cert = Cert::from_der(&ca_cert_der).map_err(|e| format!("couldn't deserialize CA cert: {}", e))?;
+
+    let ca_pk_der = storage
+        .get_key_by_addressing_hash(&ca_hash)
+        .await
+        .map_err(|e| format!("couldn't fetch CA private key: {}", e))?;
+    let ca_pk = Picky::parse_pk_from_magic_der(&ca_pk_der).map_err(|e| e.to_string())?;
+
+    let crl_number = storage
+        .increase_crl_number()
+        .await
+        .map_err(|e| format!("couldn't increase CRL number: {}", e))?;
+    let crl_number = crl_number
+        .to_be_bytes()
+        .iter()
+        .skip_while(|byte| **byte == 0)
+        .copied()
+        .collect::<Vec<u8>>();
+
+    let builder = CrlBuilder::new();
+    builder
+        .issuer_cert(&ca_cert, &ca_pk)
+        .this_update(this_update.into())
+        .next_update(next_update.into())
+        .crl_number(crl_number)
+        .signature_hash_type(config.signing_algorithm);
+
+    for revocation in revocations {
+        let serial_number =
+            hex::decode(&revocation.serial_number).map_err(|e| format!("invalid stored serial number: {}", e))?;
+        let revocation_date = revocation_date(revocation)?;
+
+        // As per RFC 5280, the reason code extension should be absent instead of using the unspecified value
+        match crl_reason_from_code(revocation.reason) {
+            Some(CrlReason::Unspecified) | None => builder.revoke(serial_number, revocation_date),
+            Some(reason) => builder.revoke_with_reason(serial_number, revocation_date, reason),
+        };
+    }
+
+    builder.build().map_err(|e| format!("couldn't build CRL: {}", e))
+}
+
+fn revocation_date(revocation: &RevocationEntry) -> Result<UTCDate, String> {
+    OffsetDateTime::from_unix_timestamp(revocation.revocation_date)
+        .map(UTCDate::from)
+        .map_err(|e| format!("invalid stored revocation date: {}", e))
+}
+
+fn crl_reason_from_code(code: u8) -> Option<CrlReason> {
+    match code {
+        0 => Some(CrlReason::Unspecified),
+        1 => Some(CrlReason::KeyCompromise),
+        2 => Some(CrlReason::CaCompromise),
+        3 => Some(CrlReason::AffiliationChanged),
+        4 => Some(CrlReason::Superseded),
+        5 => Some(CrlReason::CessationOfOperation),
+        6 => Some(CrlReason::CertificateHold),
+        8 => Some(CrlReason::RemoveFromCrl),
+        9 => Some(CrlReason::PrivilegeWithdrawn),
+        10 => Some(CrlReason::AaCompromise),
+        _ => None,
+    }
+}
+
+/// Serial numbers are stored as lowercase hexadecimal strings without leading zeros.
+fn normalize_serial_number(serial_number: &str) -> Result<String, String> {
+    let bytes = hex::decode(serial_number.replace(':', "")).map_err(|e| format!("invalid serial number: {}", e))?;
+    if bytes.is_empty() {
+        return Err("empty serial number".to_owned());
+    }
+    let first_significant = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len() - 1);
+    Ok(hex::encode(&bytes[first_significant..]))
+}
+
+/// Parses `GET /certs` query string parameters.
+///
+/// `expiring_within_days` is a shortcut for an expiry window starting at `now` (Unix timestamp).
+fn parse_certificate_query(query_string: &str, now: i64) -> Result<CertificateQuery, String> {
+    fn timestamp(value: &str) -> Result<i64, String> {
+        value
+            .parse()
+            .map_err(|e| format!("invalid Unix timestamp '{}': {}", value, e))
+    }
+
+    fn count(name: &str, value: &str) -> Result<usize, String> {
+        match value.parse() {
+            Ok(0) | Err(_) => Err(format!("{} must be a positive integer", name)),
+            Ok(count) => Ok(count),
+        }
+    }
+
+    let mut query = CertificateQuery::default();
+
+    for pair in query_string.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(&value.replace('+', " "))?;
+
+        match name {
+            "cn" => query.common_name = Some(value),
+            "san" => query.dns_name = Some(value),
+            "issuer" => query.issuer = Some(value),
+            "serial_number" => query.serial_number = Some(normalize_serial_number(&value)?),
+            "issued_after" => query.issued_after = Some(timestamp(&value)?),
+            "issued_before" => query.issued_before = Some(timestamp(&value)?),
+            "expires_after" => query.expires_after = Some(timestamp(&value)?),
+            "expires_before" => query.expires_before = Some(timestamp(&value)?),
+            "expiring_within_days" => {
+                let days = i64::try_from(count(name, &value)?).map_err(|e| e.to_string())?;
+                query.expires_after = Some(now);
+                query.expires_before = Some(now.saturating_add(days.saturating_mul(86_400)));
             }
-            Err(e) => Err(format!("couldn't reload config: {}", e)),
+            "page" => query.page = count(name, &value)?,
+            "page_size" => {
+                query.page_size = count(name, &value)?;
+                if query.page_size > MAX_PAGE_SIZE {
+                    return Err(format!("page_size can't exceed {}", MAX_PAGE_SIZE));
+                }
+            }
+            unknown => return Err(format!("unknown query parameter: {}", unknown)),
         }
     }
-}
 
-async fn get_main_private_key(config: &Config, storage: &dyn PickyStorage) -> Result<(PrivateKey, String), String> {
-    let root_name = format!("{} Root CA", config.realm);
-    let root_hash = storage
-        .get_addressing_hash_by_name(&root_name)
-        .await
-        .map_err(|e| format!("error while fetching root: {}", e))?;
-    let root_key_der = storage
-        .get_key_by_addressing_hash(&root_hash)
-        .await
-        .map_err(|e| format!("couldn't fetch root CA private key: {}", e))?;
-    Ok((
-        Picky::parse_pk_from_magic_der(&root_key_der).map_err(|e| format!("Failed to get root private key: {}", e))?,
-        root_hash,
-    ))
+    Ok(query)
 }
 
 async fn extract_cert_from_request(req: &Request<Bytes>) -> Result<(Cert, Vec<u8>), GreedyError> {
@@ -500,6 +1302,35 @@ async fn sign_certificate(
     storage: &dyn PickyStorage,
     duration_secs: u64,
 ) -> Result<Cert, String> {
+    let dns_name = csr
+        .subject_name()
+        .find_common_name()
+        .ok_or("couldn't find signed cert subject common name")?
+        .to_string();
+
+    sign_certificate_for_dns_names(
+        ca_name,
+        csr,
+        std::slice::from_ref(&dns_name),
+        &LeafExtensions::default(),
+        config,
+        storage,
+        duration_secs,
+    )
+    .await
+}
+
+async fn sign_certificate_for_dns_names(
+    ca_name: &str,
+    csr: Csr,
+    dns_names: &[String],
+    extensions: &LeafExtensions,
+    config: &Config,
+    storage: &dyn PickyStorage,
+    duration_secs: u64,
+) -> Result<Cert, String> {
+    let name = dns_names.first().ok_or("no DNS name to certify")?.clone();
+
     let ca_hash = storage
         .get_addressing_hash_by_name(ca_name)
         .await
@@ -517,21 +1348,16 @@ async fn sign_certificate(
         .map_err(|e| format!("couldn't fetch CA private key: {}", e))?;
     let ca_pk = Picky::parse_pk_from_magic_der(&ca_pk_der).map_err(|e| e.to_string())?;
 
-    let dns_name = csr
-        .subject_name()
-        .find_common_name()
-        .ok_or("couldn't find signed cert subject common name")?
-        .to_string();
-
     let signed_cert = Picky::generate_leaf_from_csr(
         csr,
         &ca_cert,
         &ca_pk,
         config.signing_algorithm,
-        &dns_name,
+        dns_names,
         time::Duration::seconds(
             i64::try_from(duration_secs).map_err(|e| format!("invalid x509 duration (too big?): {}", e))?,
         ),
+        extensions,
     )
     .map_err(|e| format!("couldn't generate leaf certificate: {}", e))?;
 
@@ -544,21 +1370,140 @@ async fn sign_certificate(
                 .subject_key_identifier()
                 .map_err(|e| format!("couldn't get SKI: {}", e))?,
         );
+        let serial_number = hex::encode(signed_cert.serial_number().as_unsigned_bytes_be());
 
         storage
             .store(CertificateEntry {
-                name: dns_name.clone(),
+                name: name.clone(),
                 cert: cert_der,
                 key_identifier: ski,
+                serial_number,
                 key: None,
             })
             .await
-            .map_err(|e| format!("insertion error for leaf {}: {}", dns_name, e))?;
+            .map_err(|e| format!("insertion error for leaf {}: {}", name, e))?;
     }
 
     Ok(signed_cert)
 }
 
+/// Returns the PEM chain of the issued certificate.
+///
+/// DNS names are the validated order identifiers, the provisioner profile (if any) must allow them.
+async fn issue_acme_certificate(
+    csr: Csr,
+    dns_names: Vec<String>,
+    provisioner: Option<&ProvisionerConfig>,
+    config: &Config,
+    storage: &dyn PickyStorage,
+    duration_secs: u64,
+) -> Result<String, String> {
+    let (duration_secs, extensions) = match provisioner {
+        Some(provisioner) => {
+            provisioner.check_csr(&csr)?;
+            (
+                provisioner.x509_duration_secs(duration_secs),
+                provisioner.leaf_extensions(),
+            )
+        }
+        None => (duration_secs, LeafExtensions::default()),
+    };
+
+    let ca_name = format!("{} Authority", config.realm);
+    let signed_cert =
+        sign_certificate_for_dns_names(&ca_name, csr, &dns_names, &extensions, config, storage, duration_secs).await?;
+    let cert_pem = signed_cert
+        .to_pem()
+        .map_err(|e| format!("couldn't get certificate pem: {}", e))?;
+
+    let mut chain = vec![cert_pem.to_string()];
+    chain.extend(find_ca_chain(storage, &ca_name).await?);
+
+    Ok(chain.join("\n"))
+}
+
+async fn find_ca_certificates(storage: &dyn PickyStorage, ca_name: &str) -> Result<Vec<Cert>, String> {
+    find_ca_chain(storage, ca_name)
+        .await?
+        .iter()
+        .map(|pem| Cert::from_pem_str(pem).map_err(|e| format!("couldn't parse CA certificate: {}", e)))
+        .collect()
+}
+
+/// Basic authentication users may only enroll names allowed by the EST provisioner.
+async fn enroll_est_certificate(
+    csr: Csr,
+    identity: &EstIdentity,
+    config: &Config,
+    storage: &dyn PickyStorage,
+) -> Result<Cert, StatusCode> {
+    let est_config = config.est.as_ref().not_found()?;
+    let provisioner = config
+        .find_provisioner(est_config.provisioner.as_deref())
+        .internal_error()?;
+
+    let ca_name = format!("{} Authority", &config.realm);
+    match provisioner {
+        Some(provisioner) => {
+            let dns_names = match provisioner.check_csr(&csr) {
+                Ok(dns_names) => dns_names,
+                Err(e) => {
+                    log::error!("CSR rejected: {}", e);
+                    return Err(StatusCode::FORBIDDEN);
+                }
+            };
+            sign_certificate_for_dns_names(
+                &ca_name,
+                csr,
+                &dns_names,
+                &provisioner.leaf_extensions(),
+                config,
+                storage,
+                provisioner.x509_duration_secs(est_config.x509_duration_secs),
+            )
+            .await
+        }
+        None if matches!(identity, EstIdentity::User(_)) => {
+            log::error!("CSR rejected: EST users can't enroll without an EST provisioner");
+            return Err(StatusCode::FORBIDDEN);
+        }
+        None => sign_certificate(&ca_name, csr, config, storage, est_config.x509_duration_secs).await,
+    }
+    .internal_error()
+}
+
+/// Forwarded client certificates must be valid, issued by this server and not revoked.
+async fn verify_est_client_certificate(cert: &Cert, config: &Config, storage: &dyn PickyStorage) -> Result<(), String> {
+    let ca_name = format!("{} Authority", config.realm);
+    let chain = find_ca_certificates(storage, &ca_name).await?;
+
+    cert.verifier()
+        .chain(chain.iter())
+        .exact_date(&UTCDate::now())
+        .verify()
+        .map_err(|e| format!("untrusted client certificate: {}", e))?;
+
+    let serial_number = hex::encode(cert.serial_number().as_unsigned_bytes_be());
+    let revocation = storage
+        .get_revocation_by_serial_number(&serial_number)
+        .await
+        .map_err(|e| format!("couldn't fetch revocation status: {}", e))?;
+    if revocation.is_some() {
+        return Err(format!("client certificate {} is revoked", serial_number));
+    }
+
+    Ok(())
+}
+
+/// EST responses are base64-encoded (RFC 7030 #4).
+fn est_respond(content_type: &'static str, body: String) -> ResponseBuilder {
+    ResponseBuilder::new()
+        .header(header::CONTENT_TYPE, content_type)
+        .header("Content-Transfer-Encoding", "base64")
+        .body(body)
+        .status(StatusCode::OK)
+}
+
 async fn find_ca_chain(storage: &dyn PickyStorage, ca_name: &str) -> Result<Vec<String>, String> {
     let ca_hash = storage
         .get_addressing_hash_by_name(ca_name)
@@ -621,6 +1566,8 @@ async fn generate_root_ca(config: &Config, storage: &dyn PickyStorage) -> Result
         .subject_key_identifier()
         .map_err(|e| format!("couldn't fetch subject key identifier: {}", e))?;
 
+    let serial_number = hex::encode(root.serial_number().as_unsigned_bytes_be());
+
     let cert_der = root
         .to_der()
         .map_err(|e| format!("couldn't serialize root certificate into der: {}", e))?;
@@ -634,6 +1581,7 @@ async fn generate_root_ca(config: &Config, storage: &dyn PickyStorage) -> Result
             name,
             cert: cert_der,
             key_identifier: hex::encode(ski),
+            serial_number,
             key: Some(pk_pkcs8),
         })
         .await
@@ -675,6 +1623,8 @@ async fn generate_intermediate_ca(config: &Config, storage: &dyn PickyStorage) -
         .subject_key_identifier()
         .map_err(|e| format!("couldn't fetch key id: {}", e))?;
 
+    let serial_number = hex::encode(intermediate_cert.serial_number().as_unsigned_bytes_be());
+
     let cert_der = intermediate_cert
         .to_der()
         .map_err(|e| format!("couldn't serialize intermediate certificate into der: {}", e))?;
@@ -688,6 +1638,7 @@ async fn generate_intermediate_ca(config: &Config, storage: &dyn PickyStorage) -
             name: intermediate_name,
             cert: cert_der,
             key_identifier: hex::encode(ski),
+            serial_number,
             key: Some(pk_pkcs8),
         })
         .await
@@ -724,6 +1675,7 @@ async fn inject_config_provided_cert(
         cert.subject_key_identifier()
             .map_err(|e| format!("couldn't parse fetch subject key identifier: {}", e))?,
     );
+    let serial_number = hex::encode(cert.serial_number().as_unsigned_bytes_be());
     let subject_name = cert
         .subject_name()
         .find_common_name()
@@ -757,6 +1709,7 @@ async fn inject_config_provided_cert(
             name: subject_name,
             cert: cert_der,
             key_identifier: ski,
+            serial_number,
             key: Some(key_der),
         })
         .await
@@ -804,6 +1757,14 @@ async fn init_storage_from_config(storage: &dyn PickyStorage, config: &Config) -
         }
     }
 
+    if let Some(ocsp_responder_cert_key_pair) = &config.ocsp_responder {
+        log::info!("inject OCSP responder provided by settings");
+        let expected = format!("{} OCSP Responder", config.realm);
+        if let Err(e) = inject_config_provided_cert(&expected, ocsp_responder_cert_key_pair, storage).await {
+            return Err(format!("couldn't inject OCSP responder: {}", e));
+        }
+    }
+
     Ok(())
 }
 
@@ -811,10 +1772,9 @@ async fn init_storage_from_config(storage: &dyn PickyStorage, config: &Config) -
 mod tests {
     use super::*;
     use crate::config::BackendType;
-    use picky::hash::HashAlgorithm;
-    use picky::signature::SignatureAlgorithm;
-    use picky::x509::date::UTCDate;
     use picky::x509::name::DirectoryName;
+    use picky::x509::ocsp::OcspRequestBuilder;
+    use picky_asn1_x509::{ExtensionView, GeneralName};
     use tokio_test::block_on;
 
     fn config() -> Config {
@@ -873,4 +1833,464 @@ mod tests {
             .verify()
             .expect("couldn't validate ca chain");
     }
+
+    #[test]
+    fn acme_certificate_chain() {
+        let config = config();
+        let storage = block_on(get_storage(&config));
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("example.com"),
+            &pk,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+        )
+        .expect("couldn't generate csr");
+
+        let dns_names = vec!["example.com".to_owned(), "www.example.com".to_owned()];
+        let chain_pem = block_on(issue_acme_certificate(
+            csr.clone(),
+            dns_names.clone(),
+            None,
+            &config,
+            storage.as_ref(),
+            7_776_000,
+        ))
+        .expect("couldn't issue certificate");
+
+        let chain = parse_pem_chain(&chain_pem);
+        assert_eq!(chain.len(), 3);
+
+        let subject_alt_names = chain[0]
+            .extensions()
+            .iter()
+            .find_map(|ext| match ext.extn_value() {
+                ExtensionView::SubjectAltName(san) => Some(san.0),
+                _ => None,
+            })
+            .expect("subject alt name")
+            .into_iter()
+            .map(|name| match name {
+                GeneralName::DnsName(dns_name) => dns_name.to_string(),
+                _ => panic!("unexpected name"),
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(subject_alt_names, dns_names);
+
+        chain[0]
+            .verifier()
+            .chain(chain[1..].iter())
+            .exact_date(&UTCDate::now())
+            .verify()
+            .expect("couldn't validate ca chain");
+
+        // the profile of the ACME provisioner applies
+        let mut provisioner = ProvisionerConfig {
+            name: "acme".to_owned(),
+            public_key: None,
+            jwks: None,
+            san_patterns: vec!["*.example.org".to_owned()],
+            max_x509_duration_secs: None,
+            key_usage: None,
+            extended_key_usage: Some(vec![crate::config::ExtendedKeyUsagePurpose::ServerAuth]),
+            key_types: Vec::new(),
+        };
+
+        let err = block_on(issue_acme_certificate(
+            csr.clone(),
+            dns_names.clone(),
+            Some(&provisioner),
+            &config,
+            storage.as_ref(),
+            7_776_000,
+        ))
+        .unwrap_err();
+        assert_eq!(err, "example.com is not allowed by provisioner acme");
+
+        provisioner.san_patterns = vec!["example.com".to_owned(), "*.example.com".to_owned()];
+        let chain_pem = block_on(issue_acme_certificate(
+            csr,
+            dns_names,
+            Some(&provisioner),
+            &config,
+            storage.as_ref(),
+            7_776_000,
+        ))
+        .expect("couldn't issue certificate");
+        let chain = parse_pem_chain(&chain_pem);
+        let extended_key_usage = chain[0].extended_key_usage().expect("extended key usage");
+        assert!(extended_key_usage.contains(picky::oids::kp_server_auth()));
+        assert!(!extended_key_usage.contains(picky::oids::kp_client_auth()));
+    }
+
+    #[test]
+    fn est_enrollment_policy() {
+        use crate::config::{EstConfig, ProvisionerConfig};
+
+        let mut config = config();
+        let storage = block_on(get_storage(&config));
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+        let generate_csr = |common_name: &str| {
+            Csr::generate(
+                DirectoryName::new_common_name(common_name),
+                &pk,
+                SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+            )
+            .expect("couldn't generate csr")
+        };
+        let router = EstIdentity::User("router".to_owned());
+
+        // users can't enroll without a provisioner restricting names
+        config.est = Some(EstConfig::default());
+        let err = block_on(enroll_est_certificate(
+            generate_csr("router.devices.example.com"),
+            &router,
+            &config,
+            storage.as_ref(),
+        ))
+        .unwrap_err();
+        assert_eq!(err, StatusCode::FORBIDDEN);
+
+        config.provisioners = vec![ProvisionerConfig {
+            name: "est".to_owned(),
+            public_key: None,
+            jwks: None,
+            san_patterns: vec!["*.devices.example.com".to_owned()],
+            max_x509_duration_secs: None,
+            key_usage: None,
+            extended_key_usage: None,
+            key_types: Vec::new(),
+        }];
+        config.est = Some(EstConfig {
+            provisioner: Some("est".to_owned()),
+            ..EstConfig::default()
+        });
+
+        let err = block_on(enroll_est_certificate(
+            generate_csr("ca.example.com"),
+            &router,
+            &config,
+            storage.as_ref(),
+        ))
+        .unwrap_err();
+        assert_eq!(err, StatusCode::FORBIDDEN);
+
+        let cert = block_on(enroll_est_certificate(
+            generate_csr("router.devices.example.com"),
+            &router,
+            &config,
+            storage.as_ref(),
+        ))
+        .expect("couldn't enroll certificate");
+        assert_eq!(cert.subject_name().to_string(), "CN=router.devices.example.com");
+    }
+
+    #[test]
+    fn provisioner_profile_extensions() {
+        use crate::config::{ExtendedKeyUsagePurpose, KeyUsageFlag, ProvisionerConfig};
+
+        let config = config();
+        let storage = block_on(get_storage(&config));
+        let ca_name = format!("{} Authority", config.realm);
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        let provisioner = ProvisionerConfig {
+            name: "devices".to_owned(),
+            public_key: None,
+            jwks: None,
+            san_patterns: vec!["*.devices.example.com".to_owned()],
+            max_x509_duration_secs: Some(86_400),
+            key_usage: Some(vec![KeyUsageFlag::DigitalSignature, KeyUsageFlag::KeyAgreement]),
+            extended_key_usage: Some(vec![ExtendedKeyUsagePurpose::ClientAuth]),
+            key_types: Vec::new(),
+        };
+
+        let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("sensor.devices.example.com"),
+            &pk,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+        )
+        .expect("couldn't generate csr");
+
+        let dns_names = provisioner.check_csr(&csr).expect("CSR should be allowed");
+        let signed_cert = block_on(sign_certificate_for_dns_names(
+            &ca_name,
+            csr,
+            &dns_names,
+            &provisioner.leaf_extensions(),
+            &config,
+            storage.as_ref(),
+            provisioner.x509_duration_secs(7_776_000),
+        ))
+        .expect("couldn't sign certificate");
+
+        let key_usage = signed_cert.key_usage().expect("key usage");
+        assert!(key_usage.digital_signature());
+        assert!(key_usage.key_agreement());
+        assert!(!key_usage.key_encipherment());
+
+        let extended_key_usage = signed_cert.extended_key_usage().expect("extended key usage");
+        assert!(extended_key_usage.contains(picky::oids::kp_client_auth()));
+        assert!(!extended_key_usage.contains(picky::oids::kp_server_auth()));
+
+        let valid_to = OffsetDateTime::try_from(signed_cert.valid_not_after()).expect("valid_not_after");
+        assert!((valid_to - OffsetDateTime::now_utc()).whole_seconds() <= 86_400);
+    }
+
+    #[test]
+    fn certificate_inventory() {
+        let config = config();
+        let storage = block_on(get_storage(&config));
+        let ca_name = format!("{} Authority", config.realm);
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        for (common_name, duration_secs) in [("short.example.com", 86_400), ("long.example.com", 7_776_000)] {
+            let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+            let csr = Csr::generate(
+                DirectoryName::new_common_name(common_name),
+                &pk,
+                SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+            )
+            .expect("couldn't generate csr");
+            block_on(sign_certificate(
+                &ca_name,
+                csr,
+                &config,
+                storage.as_ref(),
+                duration_secs,
+            ))
+            .expect("couldn't sign certificate");
+        }
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        let query = parse_certificate_query("issuer=Picky+Authority&expiring_within_days=30", now).unwrap();
+        let page = block_on(storage.find_certificates(&query)).expect("find certificates");
+        assert_eq!(page.total, 1);
+        assert_eq!(page.certificates[0].subject, "short.example.com");
+        assert_eq!(page.certificates[0].dns_names, ["short.example.com"]);
+
+        let query = parse_certificate_query("san=%2Eexample.com&page_size=1&page=2", now).unwrap();
+        let page = block_on(storage.find_certificates(&query)).expect("find certificates");
+        assert_eq!(page.total, 2);
+        assert_eq!(page.certificates.len(), 1);
+
+        let query = parse_certificate_query("", now).unwrap();
+        let page = block_on(storage.find_certificates(&query)).expect("find certificates");
+        assert_eq!(page.total, 4); // root and intermediate CAs included
+
+        let query = parse_certificate_query("serial_number=00:0A&issued_after=0", now).unwrap();
+        assert_eq!(query.serial_number.as_deref(), Some("0a"));
+        assert_eq!(query.issued_after, Some(0));
+
+        assert!(parse_certificate_query("page=0", now).is_err());
+        assert!(parse_certificate_query("page_size=1000", now).is_err());
+        assert!(parse_certificate_query("expires_before=tomorrow", now).is_err());
+        assert!(parse_certificate_query("subject=x", now).is_err());
+    }
+
+    fn parse_pem_chain(chain_pem: &str) -> Vec<Cert> {
+        chain_pem
+            .split_inclusive("-----END CERTIFICATE-----")
+            .filter(|pem| pem.contains("-----BEGIN CERTIFICATE-----"))
+            .map(|pem| Cert::from_pem_str(pem.trim()).expect("couldn't parse cert pem"))
+            .collect()
+    }
+
+    #[test]
+    fn ocsp_responses() {
+        let mut config = config();
+        config.save_certificate = true;
+        let storage = block_on(get_storage(&config));
+
+        let ca_name = format!("{} Authority", config.realm);
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("Mister Bushido"),
+            &pk,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+        )
+        .expect("couldn't generate csr");
+        let signed_cert = block_on(sign_certificate(
+            &ca_name,
+            csr.clone(),
+            &config,
+            storage.as_ref(),
+            7_776_000,
+        ))
+        .expect("couldn't sign certificate");
+
+        let ca_hash = block_on(storage.get_addressing_hash_by_name(&ca_name)).expect("ca hash");
+        let ca_cert_der = block_on(storage.get_cert_by_addressing_hash(&ca_hash)).expect("ca cert der");
+        let ca_cert = Cert::from_der(&ca_cert_der).expect("ca cert");
+
+        // a certificate signed by the authority without being saved is unknown
+        config.save_certificate = false;
+        let unsaved_cert = block_on(sign_certificate(&ca_name, csr, &config, storage.as_ref(), 7_776_000))
+            .expect("couldn't sign certificate");
+
+        for (cert, expected_status) in [
+            (&signed_cert, OcspCertStatus::Good),
+            (&unsaved_cert, OcspCertStatus::Unknown),
+        ] {
+            let request = OcspRequestBuilder::new()
+                .cert(cert, &ca_cert)
+                .build()
+                .expect("couldn't build ocsp request");
+
+            let response = block_on(respond_to_ocsp_request(&config, storage.as_ref(), &request))
+                .expect("couldn't respond to ocsp request");
+
+            let now = UTCDate::now();
+            let status = response
+                .verifier()
+                .cert(cert)
+                .issuer(&ca_cert)
+                .exact_date(&now)
+                .request(&request)
+                .verify()
+                .expect("couldn't verify ocsp response");
+            assert_eq!(status, expected_status);
+
+            let after_next_update =
+                UTCDate::from(OffsetDateTime::now_utc() + time::Duration::days(CRL_VALIDITY_DAYS + 1));
+            response
+                .verifier()
+                .cert(cert)
+                .issuer(&ca_cert)
+                .exact_date(&after_next_update)
+                .request(&request)
+                .verify()
+                .expect_err("ocsp response should have expired");
+        }
+    }
+
+    #[test]
+    fn signature_algorithm_follows_key_type() {
+        let mut config = config();
+        config.signing_algorithm = SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_384);
+
+        let rsa_key = Picky::generate_private_key(2048).expect("couldn't generate rsa key");
+        assert_eq!(
+            signature_algorithm_for_key(&config, &rsa_key).unwrap(),
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_384)
+        );
+
+        let ec_key = PrivateKey::generate_ec(EcCurve::NistP384).expect("couldn't generate ec key");
+        assert_eq!(
+            signature_algorithm_for_key(&config, &ec_key).unwrap(),
+            SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_384)
+        );
+
+        let ed_key = PrivateKey::generate_ed25519().expect("couldn't generate ed25519 key");
+        assert_eq!(
+            signature_algorithm_for_key(&config, &ed_key).unwrap(),
+            SignatureAlgorithm::Ed25519
+        );
+
+        config.signing_algorithm = SignatureAlgorithm::Ed25519;
+        assert_eq!(
+            signature_algorithm_for_key(&config, &rsa_key).unwrap(),
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256)
+        );
+    }
+
+    #[test]
+    fn revoke_and_publish_crl() {
+        let mut config = config();
+        config.save_certificate = true;
+        let storage = block_on(get_storage(&config));
+
+        let ca_name = format!("{} Authority", config.realm);
+
+        block_on(generate_root_ca(&config, storage.as_ref())).expect("couldn't generate root ca");
+        block_on(generate_intermediate_ca(&config, storage.as_ref())).expect("couldn't generate intermediate ca");
+
+        let pk = Picky::generate_private_key(2048).expect("couldn't generate private key");
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("Mister Bushido"),
+            &pk,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+        )
+        .expect("couldn't generate csr");
+        let signed_cert = block_on(sign_certificate(&ca_name, csr, &config, storage.as_ref(), 7_776_000))
+            .expect("couldn't sign certificate");
+
+        let ca_hash = block_on(storage.get_addressing_hash_by_name(&ca_name)).expect("ca hash");
+        let ca_cert_der = block_on(storage.get_cert_by_addressing_hash(&ca_hash)).expect("ca cert der");
+        let ca_cert = Cert::from_der(&ca_cert_der).expect("ca cert");
+
+        let crl_cache = CrlCache::default();
+        let crl = block_on(crl_cache.get(&config, storage.as_ref())).expect("couldn't generate crl");
+        crl.verify(&ca_cert).expect("couldn't verify crl");
+        assert_eq!(crl.crl_number().unwrap().as_unsigned_bytes_be(), [1]);
+        assert!(!crl.is_revoked(&signed_cert));
+
+        // unchanged CRL isn't signed again
+        let cached_crl = block_on(crl_cache.get(&config, storage.as_ref())).expect("couldn't get cached crl");
+        assert_eq!(cached_crl, crl);
+
+        let serial_number = hex::encode(signed_cert.serial_number().as_unsigned_bytes_be());
+        block_on(storage.revoke(RevocationEntry {
+            serial_number: normalize_serial_number(&format!("00{}", serial_number)).unwrap(),
+            revocation_date: OffsetDateTime::now_utc().unix_timestamp(),
+            reason: CrlReason::KeyCompromise as u8,
+        }))
+        .expect("couldn't revoke certificate");
+
+        let crl = block_on(crl_cache.get(&config, storage.as_ref())).expect("couldn't generate crl");
+        crl.verify(&ca_cert).expect("couldn't verify crl");
+        assert_eq!(crl.crl_number().unwrap().as_unsigned_bytes_be(), [2]);
+        assert!(crl.is_revoked(&signed_cert));
+        assert_eq!(
+            crl.revocation_reason(signed_cert.serial_number()),
+            Some(CrlReason::KeyCompromise)
+        );
+
+        let request = OcspRequestBuilder::new()
+            .cert(&signed_cert, &ca_cert)
+            .build()
+            .expect("couldn't build ocsp request");
+        let response = block_on(respond_to_ocsp_request(&config, storage.as_ref(), &request))
+            .expect("couldn't respond to ocsp request");
+        let status = response
+            .verifier()
+            .cert(&signed_cert)
+            .issuer(&ca_cert)
+            .exact_date(&UTCDate::now())
+            .request(&request)
+            .verify()
+            .expect("couldn't verify ocsp response");
+        assert!(matches!(
+            status,
+            OcspCertStatus::Revoked {
+                reason: Some(CrlReason::KeyCompromise),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn serial_number_normalization() {
+        assert_eq!(normalize_serial_number("00:8F:0a").unwrap(), "8f0a");
+        assert_eq!(normalize_serial_number("0000").unwrap(), "00");
+        assert!(normalize_serial_number("").is_err());
+        assert!(normalize_serial_number("xyz").is_err());
+    }
 }
@@ -170,6 +170,8 @@ pub enum Format {
     PkixCertBase64,
     Pkcs10Binary,
     Pkcs10Base64,
+    OcspRequest,
+    PkixCrlBinary,
 }
 
 impl fmt::Display for Format {
@@ -181,6 +183,8 @@ impl fmt::Display for Format {
             Format::PkixCertBase64 => write!(f, "base64-encoded pkix-cert"),
             Format::Pkcs10Binary => write!(f, "binary-encoded pkcs10"),
             Format::Pkcs10Base64 => write!(f, "base64-encoded pkcs10"),
+            Format::OcspRequest => write!(f, "ocsp request"),
+            Format::PkixCrlBinary => write!(f, "binary-encoded pkix-crl"),
         }
     }
 }
@@ -217,11 +221,36 @@ impl Format {
             "application/pkix-cert-base64" => Ok(Self::PkixCertBase64),
             "application/pkcs10" => Ok(Self::Pkcs10Binary),
             "application/pkcs10-base64" => Ok(Self::Pkcs10Base64),
+            "application/ocsp-request" => Ok(Self::OcspRequest),
+            "application/pkix-crl" => Ok(Self::PkixCrlBinary),
             unsupported => Err(format!("unsupported format: {}", unsupported)),
         }
     }
 }
 
+// === url === //
+
+/// Decodes percent-encoded characters (e.g.: `%2F` for `/`) of an URL path segment.
+pub fn percent_decode(segment: &str) -> Result<String, String> {
+    let mut decoded = Vec::with_capacity(segment.len());
+    let mut bytes = segment.bytes();
+
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let hex = [
+                bytes.next().ok_or("truncated percent-encoding")?,
+                bytes.next().ok_or("truncated percent-encoding")?,
+            ];
+            let hex = std::str::from_utf8(&hex).map_err(|e| format!("invalid percent-encoding: {}", e))?;
+            decoded.push(u8::from_str_radix(hex, 16).map_err(|e| format!("invalid percent-encoding: {}", e))?);
+        } else {
+            decoded.push(byte);
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|e| format!("invalid utf-8 after percent-decoding: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,4 +327,15 @@ mod tests {
         let err = Format::response_format(&new_saphir_request(vec![])).err().unwrap();
         assert_eq!(err, "Accept header is missing");
     }
+
+    #[test]
+    fn percent_decode_path_segment() {
+        assert_eq!(
+            percent_decode("MEMwQTA%2FMD0wOzAJBgUr").unwrap(),
+            "MEMwQTA/MD0wOzAJBgUr"
+        );
+        assert_eq!(percent_decode("ab%2bcd%3D%3D").unwrap(), "ab+cd==");
+        assert_eq!(percent_decode("no-escape").unwrap(), "no-escape");
+        assert_eq!(percent_decode("ab%2").unwrap_err(), "truncated percent-encoding");
+    }
 }
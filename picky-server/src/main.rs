@@ -1,9 +1,12 @@
+mod acme;
 mod addressing;
 mod config;
 mod db;
+mod est;
 mod http;
 mod logging;
 mod picky_controller;
+mod provisioner;
 mod utils;
 
 use crate::config::Config;
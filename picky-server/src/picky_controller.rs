@@ -4,7 +4,7 @@ use picky::signature::SignatureAlgorithm;
 use picky::x509::certificate::{Cert, CertError, CertificateBuilder};
 use picky::x509::csr::Csr;
 use picky::x509::date::UTCDate;
-use picky::x509::extension::KeyUsage;
+use picky::x509::extension::{ExtendedKeyUsage, KeyUsage};
 use picky::x509::name::{DirectoryName, GeneralName, GeneralNames};
 use picky_asn1::restricted_string::CharSetError;
 use thiserror::Error;
@@ -52,6 +52,26 @@ impl From<CertError> for PickyError {
     }
 }
 
+/// Usage extensions of leaf certificates.
+#[derive(Clone, Debug)]
+pub struct LeafExtensions {
+    pub key_usage: KeyUsage,
+    pub extended_key_usage: ExtendedKeyUsage,
+}
+
+impl Default for LeafExtensions {
+    fn default() -> Self {
+        let mut key_usage = KeyUsage::default();
+        key_usage.set_digital_signature(true);
+        key_usage.set_key_encipherment(true);
+
+        Self {
+            key_usage,
+            extended_key_usage: vec![oids::kp_server_auth(), oids::kp_client_auth()].into(),
+        }
+    }
+}
+
 pub struct Picky;
 impl Picky {
     pub fn generate_root(
@@ -114,33 +134,33 @@ impl Picky {
         issuer_cert: &Cert,
         issuer_key: &PrivateKey,
         signature_hash_type: SignatureAlgorithm,
-        dns_name: &str,
+        dns_names: &[String],
         validity_duration: time::Duration,
+        extensions: &LeafExtensions,
     ) -> Result<Cert, PickyError> {
         // validity
         let now = time::OffsetDateTime::now_utc();
         let valid_from = UTCDate::from(now - time::Duration::minutes(INITIAL_VALIDITY_MARGIN_MINUTES));
         let valid_to = UTCDate::from(now + validity_duration);
 
-        let mut key_usage = KeyUsage::default();
-        key_usage.set_digital_signature(true);
-        key_usage.set_key_encipherment(true);
-
-        let eku = vec![oids::kp_server_auth(), oids::kp_client_auth()];
-
-        let dns_gn = GeneralName::new_dns_name(dns_name).map_err(|e| PickyError::InvalidCharSet {
-            source: e,
-            input: dns_name.to_owned(),
-        })?;
-        let san = GeneralNames::new(dns_gn);
+        let dns_gns = dns_names
+            .iter()
+            .map(|dns_name| {
+                GeneralName::new_dns_name(dns_name.as_str()).map_err(|e| PickyError::InvalidCharSet {
+                    source: e,
+                    input: dns_name.clone(),
+                })
+            })
+            .collect::<Result<Vec<GeneralName>, PickyError>>()?;
+        let san = GeneralNames::from(dns_gns);
 
         CertificateBuilder::new()
             .validity(valid_from, valid_to)
             .subject_from_csr(csr)
             .issuer_cert(issuer_cert, issuer_key)
             .signature_hash_type(signature_hash_type)
-            .key_usage(key_usage)
-            .extended_key_usage(eku.into())
+            .key_usage(extensions.key_usage.clone())
+            .extended_key_usage(extensions.extended_key_usage.clone())
             .subject_alt_name(san)
             .build()
             .map_err(|e| PickyError::Certificate { source: e })
@@ -0,0 +1,237 @@
+//! Issuance policy of named provisioners.
+
+use crate::config::{ExtendedKeyUsagePurpose, KeyType, KeyUsageFlag, ProvisionerConfig};
+use crate::picky_controller::LeafExtensions;
+use picky::oids;
+use picky::x509::extension::KeyUsage;
+use picky::x509::Csr;
+use picky_asn1_x509::{ExtensionView, GeneralName, PublicKey as InnerPublicKey, SubjectPublicKeyInfo};
+
+impl ProvisionerConfig {
+    /// Returns the DNS names to certify, common name first.
+    pub fn check_csr(&self, csr: &Csr) -> Result<Vec<String>, String> {
+        let key_type = key_type(csr);
+        if !self.key_types.is_empty() && !self.key_types.contains(&key_type) {
+            return Err(format!(
+                "{:?} keys are not allowed by provisioner {}",
+                key_type, self.name
+            ));
+        }
+
+        let common_name = csr
+            .subject_name()
+            .find_common_name()
+            .ok_or("couldn't find CSR subject common name")?
+            .to_string();
+        let mut dns_names = vec![common_name];
+
+        for extension in csr.requested_extensions() {
+            if let ExtensionView::SubjectAltName(subject_alt_name) = extension.extn_value() {
+                for name in subject_alt_name.0 {
+                    match name {
+                        GeneralName::DnsName(dns_name) => {
+                            let dns_name = dns_name.to_string();
+                            if !dns_names.iter().any(|name| name.eq_ignore_ascii_case(&dns_name)) {
+                                dns_names.push(dns_name);
+                            }
+                        }
+                        _ => return Err("only DNS subject alternative names are supported".to_owned()),
+                    }
+                }
+            }
+        }
+
+        if let Some(dns_name) = dns_names.iter().find(|dns_name| !self.allows_dns_name(dns_name)) {
+            return Err(format!("{} is not allowed by provisioner {}", dns_name, self.name));
+        }
+
+        Ok(dns_names)
+    }
+
+    pub fn x509_duration_secs(&self, requested_secs: u64) -> u64 {
+        match self.max_x509_duration_secs {
+            Some(max_secs) => requested_secs.min(max_secs),
+            None => requested_secs,
+        }
+    }
+
+    pub fn leaf_extensions(&self) -> LeafExtensions {
+        let mut extensions = LeafExtensions::default();
+
+        if let Some(flags) = &self.key_usage {
+            let mut key_usage = KeyUsage::default();
+            for flag in flags {
+                match flag {
+                    KeyUsageFlag::DigitalSignature => key_usage.set_digital_signature(true),
+                    KeyUsageFlag::ContentCommitment => key_usage.set_content_commitment(true),
+                    KeyUsageFlag::KeyEncipherment => key_usage.set_key_encipherment(true),
+                    KeyUsageFlag::DataEncipherment => key_usage.set_data_encipherment(true),
+                    KeyUsageFlag::KeyAgreement => key_usage.set_key_agreement(true),
+                }
+            }
+            extensions.key_usage = key_usage;
+        }
+
+        if let Some(purposes) = &self.extended_key_usage {
+            extensions.extended_key_usage = purposes
+                .iter()
+                .map(|purpose| match purpose {
+                    ExtendedKeyUsagePurpose::ServerAuth => oids::kp_server_auth(),
+                    ExtendedKeyUsagePurpose::ClientAuth => oids::kp_client_auth(),
+                    ExtendedKeyUsagePurpose::CodeSigning => oids::kp_code_signing(),
+                    ExtendedKeyUsagePurpose::EmailProtection => oids::kp_email_protection(),
+                    ExtendedKeyUsagePurpose::TimeStamping => oids::kp_time_stamping(),
+                    ExtendedKeyUsagePurpose::OcspSigning => oids::kp_ocsp_signing(),
+                })
+                .collect::<Vec<_>>()
+                .into();
+        }
+
+        extensions
+    }
+
+    fn allows_dns_name(&self, dns_name: &str) -> bool {
+        self.san_patterns.is_empty()
+            || self
+                .san_patterns
+                .iter()
+                .any(|pattern| dns_name_matches(pattern, dns_name))
+    }
+}
+
+/// Labels are compared case-insensitively, a `*` label matches exactly one label.
+fn dns_name_matches(pattern: &str, dns_name: &str) -> bool {
+    let pattern_labels = pattern.split('.').collect::<Vec<&str>>();
+    let name_labels = dns_name.split('.').collect::<Vec<&str>>();
+
+    pattern_labels.len() == name_labels.len()
+        && pattern_labels
+            .iter()
+            .zip(name_labels.iter())
+            .all(|(pattern_label, name_label)| {
+                !name_label.is_empty() && (*pattern_label == "*" || pattern_label.eq_ignore_ascii_case(name_label))
+            })
+}
+
+fn key_type(csr: &Csr) -> KeyType {
+    let spki: &SubjectPublicKeyInfo = csr.public_key().as_ref();
+    match spki.subject_public_key {
+        InnerPublicKey::Rsa(_) => KeyType::Rsa,
+        InnerPublicKey::Ec(_) => KeyType::Ec,
+        InnerPublicKey::Ed(_) => KeyType::Ed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_files;
+    use picky::hash::HashAlgorithm;
+    use picky::key::PrivateKey;
+    use picky::signature::SignatureAlgorithm;
+    use picky::x509::csr::Attribute;
+    use picky::x509::name::{DirectoryName, GeneralName, GeneralNames};
+    use picky::x509::Extension;
+
+    fn provisioner() -> ProvisionerConfig {
+        ProvisionerConfig {
+            name: "devices".to_owned(),
+            public_key: None,
+            jwks: None,
+            san_patterns: vec!["*.devices.example.com".to_owned(), "gateway.example.com".to_owned()],
+            max_x509_duration_secs: Some(86_400),
+            key_usage: Some(vec![KeyUsageFlag::DigitalSignature]),
+            extended_key_usage: Some(vec![ExtendedKeyUsagePurpose::ClientAuth]),
+            key_types: vec![KeyType::Rsa],
+        }
+    }
+
+    fn generate_csr(common_name: &str, dns_names: &[&str], key: &PrivateKey) -> Csr {
+        let san = dns_names
+            .iter()
+            .map(|dns_name| GeneralName::new_dns_name(*dns_name).unwrap())
+            .collect::<Vec<GeneralName>>();
+        let attributes = vec![Attribute::new_extension_request(vec![Extension::new_subject_alt_name(
+            GeneralNames::from(san),
+        )])];
+
+        Csr::generate_with_attributes(
+            DirectoryName::new_common_name(common_name),
+            key,
+            SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256),
+            attributes,
+        )
+        .expect("csr")
+    }
+
+    #[test]
+    fn dns_name_patterns() {
+        assert!(dns_name_matches("*.example.com", "www.example.com"));
+        assert!(dns_name_matches("*.example.com", "WWW.Example.com"));
+        assert!(!dns_name_matches("*.example.com", "example.com"));
+        assert!(!dns_name_matches("*.example.com", "a.b.example.com"));
+        assert!(!dns_name_matches("*.example.com", ".example.com"));
+        assert!(dns_name_matches("gateway.example.com", "gateway.example.com"));
+        assert!(!dns_name_matches("gateway.example.com", "gateway.example.org"));
+    }
+
+    #[test]
+    fn csr_within_profile() {
+        let key = PrivateKey::from_pem_str(test_files::RSA_2048_PK_1).unwrap();
+        let csr = generate_csr(
+            "sensor.devices.example.com",
+            &["gateway.example.com", "SENSOR.devices.example.com"],
+            &key,
+        );
+
+        let dns_names = provisioner().check_csr(&csr).expect("allowed CSR");
+        assert_eq!(dns_names, ["sensor.devices.example.com", "gateway.example.com"]);
+    }
+
+    #[test]
+    fn csr_outside_profile() {
+        let key = PrivateKey::from_pem_str(test_files::RSA_2048_PK_1).unwrap();
+
+        let csr = generate_csr("www.example.com", &[], &key);
+        assert_eq!(
+            provisioner().check_csr(&csr).unwrap_err(),
+            "www.example.com is not allowed by provisioner devices"
+        );
+
+        let csr = generate_csr("sensor.devices.example.com", &["admin.example.com"], &key);
+        assert_eq!(
+            provisioner().check_csr(&csr).unwrap_err(),
+            "admin.example.com is not allowed by provisioner devices"
+        );
+
+        let mut provisioner = provisioner();
+        provisioner.key_types = vec![KeyType::Ec];
+        let csr = generate_csr("sensor.devices.example.com", &[], &key);
+        assert_eq!(
+            provisioner.check_csr(&csr).unwrap_err(),
+            "Rsa keys are not allowed by provisioner devices"
+        );
+    }
+
+    #[test]
+    fn profile_templates() {
+        let provisioner = provisioner();
+        assert_eq!(provisioner.x509_duration_secs(7_776_000), 86_400);
+        assert_eq!(provisioner.x509_duration_secs(3_600), 3_600);
+
+        let extensions = provisioner.leaf_extensions();
+        assert!(extensions.key_usage.digital_signature());
+        assert!(!extensions.key_usage.key_encipherment());
+        assert!(extensions.extended_key_usage.contains(oids::kp_client_auth()));
+        assert_eq!(extensions.extended_key_usage.iter().count(), 1);
+
+        let extensions = ProvisionerConfig {
+            key_usage: None,
+            extended_key_usage: None,
+            ..provisioner
+        }
+        .leaf_extensions();
+        assert!(extensions.key_usage.key_encipherment());
+        assert_eq!(extensions.extended_key_usage.iter().count(), 2);
+    }
+}
@@ -1,4 +1,5 @@
 use base64::DecodeError;
+use picky::jose::jwk::JwkSet;
 use picky::key::{PrivateKey, PublicKey};
 use picky::pem::{Pem, PemError};
 use picky::x509::certificate::CertError;
@@ -142,3 +143,49 @@ macro_rules! path_or_impl_serde {
 path_or_impl_serde!(Cert);
 path_or_impl_serde!(PrivateKey);
 path_or_impl_serde!(PublicKey);
+
+impl<'de> de::Deserialize<'de> for PathOr<JwkSet> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct V;
+        impl<'de> de::Visitor<'de> for V {
+            type Value = PathOr<JwkSet>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a path or some json-formatted JwkSet")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                if v.trim_start().starts_with('{') {
+                    let jwk_set = JwkSet::from_json(v).map_err(|e| E::custom(format!("JwkSet from json: {}", e)))?;
+                    Ok(PathOr::Some(jwk_set))
+                } else {
+                    Ok(PathOr::Path(PathBuf::from(v)))
+                }
+            }
+        }
+
+        deserializer.deserialize_str(V)
+    }
+}
+
+impl Serialize for PathOr<JwkSet> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let s = match self {
+            PathOr::Path(path) => path.to_string_lossy().into_owned(),
+            PathOr::Some(jwk_set) => jwk_set
+                .to_json()
+                .map_err(|e| ser::Error::custom(format!("JwkSet to json: {}", e)))?,
+        };
+
+        serializer.serialize_str(&s)
+    }
+}
@@ -56,14 +56,12 @@ impl TryFrom<HashAlgorithm> for ShaVariant {
         match v {
             HashAlgorithm::MD5 => Ok(ShaVariant::MD5),
             HashAlgorithm::SHA1 => Ok(ShaVariant::SHA1),
+            HashAlgorithm::SHA2_224 => Ok(ShaVariant::SHA2_224),
             HashAlgorithm::SHA2_256 => Ok(ShaVariant::SHA2_256),
             HashAlgorithm::SHA2_384 => Ok(ShaVariant::SHA2_384),
             HashAlgorithm::SHA2_512 => Ok(ShaVariant::SHA2_512),
             HashAlgorithm::SHA3_384 => Ok(ShaVariant::SHA3_384),
             HashAlgorithm::SHA3_512 => Ok(ShaVariant::SHA3_512),
-            _ => Err(UnsupportedHashAlgorithmError {
-                algorithm: format!("{:?}", v),
-            }),
         }
     }
 }
@@ -75,6 +73,7 @@ impl TryFrom<ShaVariant> for HashAlgorithm {
         match v {
             ShaVariant::MD5 => Ok(HashAlgorithm::MD5),
             ShaVariant::SHA1 => Ok(HashAlgorithm::SHA1),
+            ShaVariant::SHA2_224 => Ok(HashAlgorithm::SHA2_224),
             ShaVariant::SHA2_256 => Ok(HashAlgorithm::SHA2_256),
             ShaVariant::SHA2_384 => Ok(HashAlgorithm::SHA2_384),
             ShaVariant::SHA2_512 => Ok(HashAlgorithm::SHA2_512),
@@ -102,16 +101,15 @@ impl HashAlgorithm {
     }
 
     pub fn output_size(self) -> usize {
-        use digest::generic_array::typenum::Unsigned;
         match self {
-            Self::MD5 => <md5::Md5 as Digest>::OutputSize::to_usize(),
-            Self::SHA1 => <sha1::Sha1 as Digest>::OutputSize::to_usize(),
-            Self::SHA2_224 => <sha2::Sha224 as Digest>::OutputSize::to_usize(),
-            Self::SHA2_256 => <sha2::Sha256 as Digest>::OutputSize::to_usize(),
-            Self::SHA2_384 => <sha2::Sha384 as Digest>::OutputSize::to_usize(),
-            Self::SHA2_512 => <sha2::Sha512 as Digest>::OutputSize::to_usize(),
-            Self::SHA3_384 => <sha3::Sha3_384 as Digest>::OutputSize::to_usize(),
-            Self::SHA3_512 => <sha3::Sha3_512 as Digest>::OutputSize::to_usize(),
+            Self::MD5 => md5::Md5::output_size(),
+            Self::SHA1 => sha1::Sha1::output_size(),
+            Self::SHA2_224 => sha2::Sha224::output_size(),
+            Self::SHA2_256 => sha2::Sha256::output_size(),
+            Self::SHA2_384 => sha2::Sha384::output_size(),
+            Self::SHA2_512 => sha2::Sha512::output_size(),
+            Self::SHA3_384 => sha3::Sha3_384::output_size(),
+            Self::SHA3_512 => sha3::Sha3_512::output_size(),
         }
     }
 }
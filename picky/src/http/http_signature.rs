@@ -1,7 +1,7 @@
 use crate::hash::HashAlgorithm;
 use crate::http::http_request::{HttpRequest, HttpRequestError};
 use crate::key::{PrivateKey, PublicKey};
-use crate::signature::{SignatureAlgorithm, SignatureError};
+use crate::signature::{RsaPssParams, SignatureAlgorithm, SignatureError};
 use base64::{DecodeError, URL_SAFE_NO_PAD};
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -166,9 +166,10 @@ impl HttpSigAlgorithm {
         self.as_custom().is_some()
     }
 
+    /// Empty for a known algorithm that has no HTTP signature name.
     pub fn as_str(&self) -> &str {
         match self {
-            Self::Known(algo) => to_http_sig_algo_str(*algo),
+            Self::Known(algo) => to_http_sig_algo_str(*algo).unwrap_or_default(),
             Self::Custom(name) => name,
         }
     }
@@ -259,11 +260,9 @@ impl ToString for HttpSignature {
                     acc.push(format!("{}=\"{}\"", HTTP_SIGNATURE_ALGORITHM, algorithm_name));
                 }
                 Some(HttpSigAlgorithm::Known(algorithm)) => {
-                    acc.push(format!(
-                        "{}=\"{}\"",
-                        HTTP_SIGNATURE_ALGORITHM,
-                        to_http_sig_algo_str(*algorithm)
-                    ));
+                    if let Some(algorithm_name) = to_http_sig_algo_str(*algorithm) {
+                        acc.push(format!("{}=\"{}\"", HTTP_SIGNATURE_ALGORITHM, algorithm_name));
+                    }
                 }
                 None => {}
             }
@@ -541,6 +540,14 @@ impl<'a> HttpSignatureBuilder<'a> {
 
         drop(inner);
 
+        if to_http_sig_algo_str(signature_type).is_none() {
+            return Err(HttpSignatureError::Signature {
+                source: SignatureError::UnsupportedAlgorithm {
+                    algorithm: format!("{:?}", signature_type),
+                },
+            });
+        }
+
         let signature_binary =
             match signing_string_generation {
                 SigningStringGenMethod::PreGenerated(signing_string) => {
@@ -829,8 +836,24 @@ const HTTP_SIG_ALGO_RSA_SHA2_512: &str = "rsa-sha2-512";
 const HTTP_SIG_ALGO_RSA_SHA3_384: &str = "rsa-sha3-384";
 const HTTP_SIG_ALGO_RSA_SHA3_512: &str = "rsa-sha3-512";
 
-fn to_http_sig_algo_str(algo: SignatureAlgorithm) -> &'static str {
-    match algo {
+const HTTP_SIG_ALGO_RSA_PSS_MD5: &str = "rsa-pss-md5";
+const HTTP_SIG_ALGO_RSA_PSS_SHA_1: &str = "rsa-pss-sha1";
+const HTTP_SIG_ALGO_RSA_PSS_SHA_224: &str = "rsa-pss-sha224";
+const HTTP_SIG_ALGO_RSA_PSS_SHA_256: &str = "rsa-pss-sha256";
+const HTTP_SIG_ALGO_RSA_PSS_SHA_384: &str = "rsa-pss-sha384";
+const HTTP_SIG_ALGO_RSA_PSS_SHA_512: &str = "rsa-pss-sha512";
+const HTTP_SIG_ALGO_RSA_PSS_SHA3_384: &str = "rsa-pss-sha3-384";
+const HTTP_SIG_ALGO_RSA_PSS_SHA3_512: &str = "rsa-pss-sha3-512";
+
+const HTTP_SIG_ALGO_ECDSA_SHA_256: &str = "ecdsa-sha256";
+const HTTP_SIG_ALGO_ECDSA_SHA_384: &str = "ecdsa-sha384";
+const HTTP_SIG_ALGO_ECDSA_SHA_512: &str = "ecdsa-sha512";
+
+const HTTP_SIG_ALGO_ED25519: &str = "ed25519";
+
+/// Returns `None` for algorithms without an HTTP signature name (ECDSA is only defined with SHA-2).
+fn to_http_sig_algo_str(algo: SignatureAlgorithm) -> Option<&'static str> {
+    let name = match algo {
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::MD5) => HTTP_SIG_ALGO_RSA_MD5,
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA1) => HTTP_SIG_ALGO_RSA_SHA_1,
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_224) => HTTP_SIG_ALGO_RSA_SHA_224,
@@ -839,7 +862,25 @@ fn to_http_sig_algo_str(algo: SignatureAlgorithm) -> &'static str {
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_512) => HTTP_SIG_ALGO_RSA_SHA_512,
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA3_384) => HTTP_SIG_ALGO_RSA_SHA3_384,
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA3_512) => HTTP_SIG_ALGO_RSA_SHA3_512,
-    }
+        // Algorithm name only carries the hash algorithm: MGF1 hash and salt length are expected
+        // to follow the RsaPssParams::new convention.
+        SignatureAlgorithm::RsaPss(params) => match params.hash_algorithm {
+            HashAlgorithm::MD5 => HTTP_SIG_ALGO_RSA_PSS_MD5,
+            HashAlgorithm::SHA1 => HTTP_SIG_ALGO_RSA_PSS_SHA_1,
+            HashAlgorithm::SHA2_224 => HTTP_SIG_ALGO_RSA_PSS_SHA_224,
+            HashAlgorithm::SHA2_256 => HTTP_SIG_ALGO_RSA_PSS_SHA_256,
+            HashAlgorithm::SHA2_384 => HTTP_SIG_ALGO_RSA_PSS_SHA_384,
+            HashAlgorithm::SHA2_512 => HTTP_SIG_ALGO_RSA_PSS_SHA_512,
+            HashAlgorithm::SHA3_384 => HTTP_SIG_ALGO_RSA_PSS_SHA3_384,
+            HashAlgorithm::SHA3_512 => HTTP_SIG_ALGO_RSA_PSS_SHA3_512,
+        },
+        SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_256) => HTTP_SIG_ALGO_ECDSA_SHA_256,
+        SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_384) => HTTP_SIG_ALGO_ECDSA_SHA_384,
+        SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_512) => HTTP_SIG_ALGO_ECDSA_SHA_512,
+        SignatureAlgorithm::Ecdsa(_) => return None,
+        SignatureAlgorithm::Ed25519 => HTTP_SIG_ALGO_ED25519,
+    };
+    Some(name)
 }
 
 fn from_http_sig_algo_str(s: &str) -> Option<SignatureAlgorithm> {
@@ -860,6 +901,18 @@ fn from_http_sig_algo_str(s: &str) -> Option<SignatureAlgorithm> {
         }
         HTTP_SIG_ALGO_RSA_SHA3_384 => Some(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA3_384)),
         HTTP_SIG_ALGO_RSA_SHA3_512 => Some(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA3_512)),
+        HTTP_SIG_ALGO_RSA_PSS_MD5 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::MD5))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA_1 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA1))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA_224 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA2_224))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA_256 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA2_256))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA_384 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA2_384))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA_512 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA2_512))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA3_384 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA3_384))),
+        HTTP_SIG_ALGO_RSA_PSS_SHA3_512 => Some(SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA3_512))),
+        HTTP_SIG_ALGO_ECDSA_SHA_256 => Some(SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_256)),
+        HTTP_SIG_ALGO_ECDSA_SHA_384 => Some(SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_384)),
+        HTTP_SIG_ALGO_ECDSA_SHA_512 => Some(SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_512)),
+        HTTP_SIG_ALGO_ED25519 => Some(SignatureAlgorithm::Ed25519),
         _ => None,
     }
 }
@@ -869,8 +922,11 @@ fn is_algo_compatible_with_key(algo: SignatureAlgorithm, key: &PublicKey) -> boo
 
     let key_algo = Into::<String>::into(key.as_inner().algorithm.oid());
     match algo {
-        // Currently, SignatureHashType only contains RSA methods, so this is a an auto-win
-        _ if key_algo == RSA_ENCRYPTION => true,
+        // Any hash algorithm is fine for generic RSA and EC keys
+        SignatureAlgorithm::RsaPkcs1v15(_) if key_algo == RSA_ENCRYPTION => true,
+        SignatureAlgorithm::RsaPss(_) if key_algo == RSA_ENCRYPTION || key_algo == RSASSA_PSS => true,
+        SignatureAlgorithm::Ecdsa(_) if key_algo == EC_PUBLIC_KEY => true,
+        SignatureAlgorithm::Ed25519 if key_algo == ED25519 => true,
 
         // Otherwise we need to check for specific hash algorithm
         SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA1) if key_algo == SHA1_WITH_RSA_ENCRYPTION => true,
@@ -893,6 +949,7 @@ fn is_algo_compatible_with_key(algo: SignatureAlgorithm, key: &PublicKey) -> boo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::key::EcCurve;
     use crate::pem::Pem;
     use http::method::Method;
     use http::{header, request};
@@ -1291,6 +1348,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ecdsa_requires_sha2() {
+        let private_key = PrivateKey::generate_ec(EcCurve::NistP256).expect("generate ec key");
+        let err = HttpSignatureBuilder::new()
+            .key_id("my-ec-key")
+            .signature_method(&private_key, SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA1))
+            .pre_generated_signing_string("date: Tue, 07 Jun 2014 20:51:35 GMT")
+            .build()
+            .err()
+            .expect("build http signature");
+        assert_eq!(err.to_string(), "signature error: unsupported algorithm: Ecdsa(SHA1)");
+
+        let http_signature = HttpSignatureBuilder::new()
+            .key_id("my-ec-key")
+            .signature_method(&private_key, SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_384))
+            .pre_generated_signing_string("date: Tue, 07 Jun 2014 20:51:35 GMT")
+            .build()
+            .expect("build http signature");
+        assert_eq!(http_signature.algorithm.unwrap().as_str(), "ecdsa-sha384");
+
+        let http_signature = HttpSignature::from_str(
+            "Signature keyId=\"my-ec-key\",algorithm=\"ecdsa-sha1\",headers=\"date\",signature=\"GARBAGE\"",
+        )
+        .expect("from str");
+        assert!(http_signature.algorithm.unwrap().is_custom());
+    }
+
     const HTTP_SIGNATURE_UNKNOWN_ALGO: &str = "Signature keyId=\"my-rsa-key\",algorithm=\"magical-algo\",\
                                                headers=\"(request-target)\",signature=\"GARBAGE\"";
 
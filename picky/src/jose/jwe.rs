@@ -2,20 +2,33 @@
 //!
 //! See [RFC7516](https://tools.ietf.org/html/rfc7516).
 
-use crate::jose::jwk::Jwk;
-use crate::key::{PrivateKey, PublicKey};
+use crate::jose::jwk::{Jwk, JwkEcCurve, JwkError, JwkKeyType, JwkOkpCurve};
+use crate::jose::merge_header_parts;
+use crate::key::ec::{self, with_curve_crate};
+use crate::key::{EcCurve, PrivateKey, PublicKey};
+use aes::{Aes128, Aes192, Aes256};
 use aes_gcm::aead::generic_array::typenum::Unsigned;
 use aes_gcm::{AeadCore, AeadInPlace, Aes128Gcm, Aes256Gcm, NewAead};
 use base64::DecodeError;
+use block_modes::block_padding::Pkcs7;
+use block_modes::{BlockMode, Cbc};
 use digest::generic_array::GenericArray;
+use hmac::digest::KeyInit;
+use hmac::{Hmac, Mac};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::rngs::OsRng;
 use rand::RngCore;
 use rsa::{PaddingScheme, PublicKey as RsaPublicKeyInterface, RsaPrivateKey, RsaPublicKey};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Sha256, Sha384, Sha512};
 use std::borrow::Cow;
 use thiserror::Error;
 
 type Aes192Gcm = aes_gcm::AesGcm<aes_gcm::aes::Aes192, aes_gcm::aead::generic_array::typenum::U12>;
 
+const AES_BLOCK_SIZE: usize = 16;
+
 // === error type === //
 
 #[derive(Debug, Error)]
@@ -29,6 +42,18 @@ pub enum JweError {
     #[error("AES-GCM error (opaque)")]
     AesGcm,
 
+    /// AES-CBC-HMAC-SHA2 error (opaque)
+    #[error("AES-CBC-HMAC-SHA2 error (opaque)")]
+    AesCbcHmac,
+
+    /// AES Key Wrap error (opaque)
+    #[error("AES Key Wrap error (opaque)")]
+    AesKeyWrap,
+
+    /// Key agreement error
+    #[error("key agreement error: {context}")]
+    KeyAgreement { context: String },
+
     /// Json error
     #[error("JSON error: {source}")]
     Json { source: serde_json::Error },
@@ -37,6 +62,10 @@ pub enum JweError {
     #[error("Key error: {source}")]
     Key { source: crate::key::KeyError },
 
+    /// JWK error
+    #[error("JWK error: {source}")]
+    Jwk { source: JwkError },
+
     /// Invalid token encoding
     #[error("input isn't a valid token string: {input}")]
     InvalidEncoding { input: String },
@@ -56,6 +85,26 @@ pub enum JweError {
     #[error("unsupported algorithm: {algorithm}")]
     UnsupportedAlgorithm { algorithm: String },
 
+    /// A header parameter required by the algorithm is missing
+    #[error("missing header parameter: {parameter}")]
+    MissingHeaderParameter { parameter: &'static str },
+
+    /// JWE has no recipient
+    #[error("JWE has no recipient")]
+    NoRecipient,
+
+    /// Algorithm determining the CEK itself used along with other recipients
+    #[error("{algorithm} algorithm can only be used with a single recipient")]
+    SingleRecipientAlgorithm { algorithm: String },
+
+    /// Flattened JSON serialization of a JWE with several recipients
+    #[error("flattened JSON serialization requires a single recipient, got {count}")]
+    FlattenedSerialization { count: usize },
+
+    /// A header parameter is present in several parts of the JOSE header
+    #[error("duplicated header parameter: {parameter}")]
+    DuplicatedHeaderParameter { parameter: String },
+
     /// Invalid size
     #[error("invalid size for {ty}: expected {expected}, got {got}")]
     InvalidSize {
@@ -89,6 +138,12 @@ impl From<crate::key::KeyError> for JweError {
     }
 }
 
+impl From<JwkError> for JweError {
+    fn from(e: JwkError) -> Self {
+        Self::Jwk { source: e }
+    }
+}
+
 impl From<DecodeError> for JweError {
     fn from(e: DecodeError) -> Self {
         Self::Base64Decoding { source: e }
@@ -118,17 +173,17 @@ pub enum JweAlg {
     #[serde(rename = "RSA-OAEP-256")]
     RsaOaep256,
 
-    /// AES Key Wrap with default initial value using 128 bit key (unsupported)
+    /// AES Key Wrap with default initial value using 128 bit key
     ///
     /// Recommended by RFC
     #[serde(rename = "A128KW")]
     AesKeyWrap128,
 
-    /// AES Key Wrap with default initial value using 192 bit key (unsupported)
+    /// AES Key Wrap with default initial value using 192 bit key
     #[serde(rename = "A192KW")]
     AesKeyWrap192,
 
-    /// AES Key Wrap with default initial value using 256 bit key (unsupported)
+    /// AES Key Wrap with default initial value using 256 bit key
     ///
     /// Recommended by RFC
     #[serde(rename = "A256KW")]
@@ -138,13 +193,15 @@ pub enum JweAlg {
     #[serde(rename = "dir")]
     Direct,
 
-    /// Elliptic Curve Diffie-Hellman Ephemeral Static key agreement using Concat KDF (unsupported)
+    /// Elliptic Curve Diffie-Hellman Ephemeral Static key agreement using Concat KDF
     ///
     /// Recommended+ by RFC
+    ///
+    /// Additional header used: "epk", "apu", "apv"
     #[serde(rename = "ECDH-ES")]
     EcdhEs,
 
-    /// ECDH-ES using Concat KDF and CEK wrapped with "A128KW" (unsupported)
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A128KW"
     ///
     /// Recommended by RFC
     ///
@@ -152,13 +209,13 @@ pub enum JweAlg {
     #[serde(rename = "ECDH-ES+A128KW")]
     EcdhEsAesKeyWrap128,
 
-    /// ECDH-ES using Concat KDF and CEK wrapped with "A192KW" (unsupported)
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A192KW"
     ///
     /// Additional header used: "epk", "apu", "apv"
     #[serde(rename = "ECDH-ES+A192KW")]
     EcdhEsAesKeyWrap192,
 
-    /// ECDH-ES using Concat KDF and CEK wrapped with "A256KW" (unsupported)
+    /// ECDH-ES using Concat KDF and CEK wrapped with "A256KW"
     ///
     /// Recommended by RFC
     ///
@@ -167,6 +224,25 @@ pub enum JweAlg {
     EcdhEsAesKeyWrap256,
 }
 
+impl JweAlg {
+    /// Size of the Key Encryption Key for algorithms relying on AES Key Wrap
+    fn key_wrap_size(self) -> Option<usize> {
+        match self {
+            Self::AesKeyWrap128 | Self::EcdhEsAesKeyWrap128 => Some(16),
+            Self::AesKeyWrap192 | Self::EcdhEsAesKeyWrap192 => Some(24),
+            Self::AesKeyWrap256 | Self::EcdhEsAesKeyWrap256 => Some(32),
+            _ => None,
+        }
+    }
+
+    fn is_ecdh_es(self) -> bool {
+        matches!(
+            self,
+            Self::EcdhEs | Self::EcdhEsAesKeyWrap128 | Self::EcdhEsAesKeyWrap192 | Self::EcdhEsAesKeyWrap256
+        )
+    }
+}
+
 // === JWE header === //
 
 /// `enc` header parameter values for JWE to encrypt content
@@ -174,17 +250,17 @@ pub enum JweAlg {
 /// [JSON Web Algorithms (JWA) draft-ietf-jose-json-web-algorithms-40 #5](https://www.rfc-editor.org/rfc/rfc7518.html#section-5.1)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum JweEnc {
-    /// AES_128_CBC_HMAC_SHA_256 authenticated encryption algorithm. (unsupported)
+    /// AES_128_CBC_HMAC_SHA_256 authenticated encryption algorithm.
     ///
     /// Required by RFC
     #[serde(rename = "A128CBC-HS256")]
     Aes128CbcHmacSha256,
 
-    /// AES_192_CBC_HMAC_SHA_384 authenticated encryption algorithm. (unsupported)
+    /// AES_192_CBC_HMAC_SHA_384 authenticated encryption algorithm.
     #[serde(rename = "A192CBC-HS384")]
     Aes192CbcHmacSha384,
 
-    /// AES_256_CBC_HMAC_SHA_512 authenticated encryption algorithm. (unsupported)
+    /// AES_256_CBC_HMAC_SHA_512 authenticated encryption algorithm.
     ///
     /// Required by RFC
     #[serde(rename = "A256CBC-HS512")]
@@ -208,27 +284,36 @@ pub enum JweEnc {
 }
 
 impl JweEnc {
+    /// Size of the Content Encryption Key
+    ///
+    /// For AES-CBC-HMAC-SHA2 algorithms, this includes both the MAC key and the encryption key.
     pub fn key_size(self) -> usize {
         match self {
-            Self::Aes128CbcHmacSha256 | Self::Aes128Gcm => <Aes128Gcm as NewAead>::KeySize::to_usize(),
-            Self::Aes192CbcHmacSha384 | Self::Aes192Gcm => <Aes192Gcm as NewAead>::KeySize::to_usize(),
-            Self::Aes256CbcHmacSha512 | Self::Aes256Gcm => <Aes256Gcm as NewAead>::KeySize::to_usize(),
+            Self::Aes128CbcHmacSha256 => 32,
+            Self::Aes192CbcHmacSha384 => 48,
+            Self::Aes256CbcHmacSha512 => 64,
+            Self::Aes128Gcm => <Aes128Gcm as NewAead>::KeySize::to_usize(),
+            Self::Aes192Gcm => <Aes192Gcm as NewAead>::KeySize::to_usize(),
+            Self::Aes256Gcm => <Aes256Gcm as NewAead>::KeySize::to_usize(),
         }
     }
 
     pub fn nonce_size(self) -> usize {
         match self {
-            Self::Aes128CbcHmacSha256 | Self::Aes128Gcm => <Aes128Gcm as AeadCore>::NonceSize::to_usize(),
-            Self::Aes192CbcHmacSha384 | Self::Aes192Gcm => <Aes192Gcm as AeadCore>::NonceSize::to_usize(),
-            Self::Aes256CbcHmacSha512 | Self::Aes256Gcm => <Aes256Gcm as AeadCore>::NonceSize::to_usize(),
+            Self::Aes128CbcHmacSha256 | Self::Aes192CbcHmacSha384 | Self::Aes256CbcHmacSha512 => AES_BLOCK_SIZE,
+            Self::Aes128Gcm => <Aes128Gcm as AeadCore>::NonceSize::to_usize(),
+            Self::Aes192Gcm => <Aes192Gcm as AeadCore>::NonceSize::to_usize(),
+            Self::Aes256Gcm => <Aes256Gcm as AeadCore>::NonceSize::to_usize(),
         }
     }
 
     pub fn tag_size(self) -> usize {
         match self {
-            Self::Aes128CbcHmacSha256 | Self::Aes128Gcm => <Aes128Gcm as AeadCore>::TagSize::to_usize(),
-            Self::Aes192CbcHmacSha384 | Self::Aes192Gcm => <Aes192Gcm as AeadCore>::TagSize::to_usize(),
-            Self::Aes256CbcHmacSha512 | Self::Aes256Gcm => <Aes256Gcm as AeadCore>::TagSize::to_usize(),
+            // authentication tag is the HMAC output truncated to the MAC key length
+            Self::Aes128CbcHmacSha256 | Self::Aes192CbcHmacSha384 | Self::Aes256CbcHmacSha512 => self.key_size() / 2,
+            Self::Aes128Gcm => <Aes128Gcm as AeadCore>::TagSize::to_usize(),
+            Self::Aes192Gcm => <Aes192Gcm as AeadCore>::TagSize::to_usize(),
+            Self::Aes256Gcm => <Aes256Gcm as AeadCore>::TagSize::to_usize(),
         }
     }
 }
@@ -247,6 +332,24 @@ pub struct JweHeader {
     /// This must be a *symmetric* Authenticated Encryption with Associated Data (AEAD) algorithm.
     pub enc: JweEnc,
 
+    /// Ephemeral Public Key
+    ///
+    /// Public key created by the originator for use in ECDH-ES key agreement algorithms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub epk: Option<Jwk>,
+
+    /// Agreement PartyUInfo
+    ///
+    /// base64url-encoded information about the producer, used in ECDH-ES key derivation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apu: Option<String>,
+
+    /// Agreement PartyVInfo
+    ///
+    /// base64url-encoded information about the recipient, used in ECDH-ES key derivation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apv: Option<String>,
+
     // -- common with JWS -- //
     /// JWK Set URL
     ///
@@ -311,6 +414,9 @@ impl JweHeader {
         Self {
             alg,
             enc,
+            epk: None,
+            apu: None,
+            apv: None,
             jku: None,
             jwk: None,
             typ: None,
@@ -342,6 +448,8 @@ impl Jwe {
     }
 
     /// Encode with CEK encrypted and included in the token using asymmetric cryptography.
+    ///
+    /// RSA keys are used with RSAES algorithms and elliptic curve keys with ECDH-ES algorithms.
     pub fn encode(self, asymmetric_key: &PublicKey) -> Result<String, JweError> {
         encode_impl(self, EncoderMode::Asymetric(asymmetric_key))
     }
@@ -351,6 +459,104 @@ impl Jwe {
         encode_impl(self, EncoderMode::Direct(cek))
     }
 
+    /// Encode with a random CEK wrapped using the provided Key Encryption Key (AES Key Wrap algorithms).
+    pub fn encode_key_wrap(self, kek: &[u8]) -> Result<String, JweError> {
+        encode_impl(self, EncoderMode::KeyWrap(kek))
+    }
+
+    /// Encode for the recipient described by the provided JWK.
+    ///
+    /// In addition to what `encode` supports, X25519 keys can be used with ECDH-ES algorithms
+    /// and symmetric keys with "dir" and AES Key Wrap algorithms.
+    pub fn encode_with_jwk(self, jwk: &Jwk) -> Result<String, JweError> {
+        encode_impl(self, EncoderMode::Jwk(jwk))
+    }
+
+    /// Encode using the JSON serialization for one or more recipients sharing the same CEK.
+    ///
+    /// Members of `header` are integrity protected, except for `alg` (and `epk`) which are set in each
+    /// per-recipient header instead. "dir" and "ECDH-ES" can only be used with a single recipient.
+    ///
+    /// When provided, `aad` is integrity protected along with the protected header.
+    pub fn encode_json(
+        self,
+        recipients: &[JweRecipientKey<'_>],
+        unprotected_header: Option<Map<String, Value>>,
+        aad: Option<&[u8]>,
+    ) -> Result<JweJson, JweError> {
+        if recipients.is_empty() {
+            return Err(JweError::NoRecipient);
+        }
+
+        if recipients.len() > 1 {
+            if let Some(recipient) = recipients
+                .iter()
+                .find(|recipient| matches!(recipient.alg, JweAlg::Direct | JweAlg::EcdhEs))
+            {
+                return Err(JweError::SingleRecipientAlgorithm {
+                    algorithm: format!("{:?}", recipient.alg),
+                });
+            }
+        }
+
+        let mut protected_header = match serde_json::to_value(&self.header)? {
+            Value::Object(members) => members,
+            _ => Map::new(),
+        };
+        protected_header.remove("alg");
+        protected_header.remove("epk");
+
+        let protected_header_base64 = if protected_header.is_empty() {
+            String::new()
+        } else {
+            base64::encode_config(&serde_json::to_vec(&protected_header)?, base64::URL_SAFE_NO_PAD)
+        };
+
+        let mut cek: Option<Vec<u8>> = None;
+        let mut json_recipients = Vec::with_capacity(recipients.len());
+
+        for recipient in recipients {
+            let mut recipient_header = recipient.header.clone().unwrap_or_default();
+            recipient_header.insert("alg".to_owned(), serde_json::to_value(recipient.alg)?);
+
+            let mut header = h_merge_header_parts(&protected_header, unprotected_header.as_ref(), &recipient_header)?;
+            let (encrypted_key, recipient_cek) = h_encrypt_key(&mut header, recipient.mode.clone(), cek.as_deref())?;
+
+            if let Some(epk) = &header.epk {
+                recipient_header.insert("epk".to_owned(), serde_json::to_value(epk)?);
+            }
+
+            if cek.is_none() {
+                cek = Some(recipient_cek.into_owned());
+            }
+
+            json_recipients.push(JweRecipient {
+                header,
+                unprotected_header: Some(recipient_header),
+                encrypted_key,
+            });
+        }
+
+        let cek = cek.expect("at least one recipient");
+        let content_aad = h_json_content_aad(&protected_header_base64, aad);
+        let enc = json_recipients[0].header.enc;
+        let EncryptedContent {
+            initialization_vector,
+            ciphertext,
+            authentication_tag,
+        } = h_encrypt_content(enc, &cek, content_aad.as_bytes(), self.payload)?;
+
+        Ok(JweJson {
+            unprotected_header,
+            recipients: json_recipients,
+            aad: aad.map(<[u8]>::to_vec),
+            initialization_vector,
+            ciphertext,
+            authentication_tag,
+            protected_header_base64,
+        })
+    }
+
     /// Encode with CEK encrypted and included in the token using asymmetric cryptography.
     pub fn decode(encoded_token: &str, key: &PrivateKey) -> Result<Jwe, JweError> {
         decode_impl(encoded_token, DecoderMode::Normal(key))
@@ -360,6 +566,320 @@ impl Jwe {
     pub fn decode_direct(encoded_token: &str, cek: &[u8]) -> Result<Jwe, JweError> {
         decode_impl(encoded_token, DecoderMode::Direct(cek))
     }
+
+    /// Decode with CEK wrapped using the provided Key Encryption Key (AES Key Wrap algorithms).
+    pub fn decode_key_wrap(encoded_token: &str, kek: &[u8]) -> Result<Jwe, JweError> {
+        decode_impl(encoded_token, DecoderMode::KeyWrap(kek))
+    }
+
+    /// Decode using the private or symmetric key described by the provided JWK.
+    ///
+    /// See `encode_with_jwk` for supported keys.
+    pub fn decode_with_jwk(encoded_token: &str, jwk: &Jwk) -> Result<Jwe, JweError> {
+        decode_impl(encoded_token, DecoderMode::Jwk(jwk))
+    }
+}
+
+// === JSON serialization === //
+
+/// JWE using the JSON serialization, possibly holding several recipients.
+///
+/// Both general and flattened syntaxes are supported, see [RFC7516 section 7.2](https://tools.ietf.org/html/rfc7516#section-7.2).
+#[derive(Debug, Clone, PartialEq)]
+pub struct JweJson {
+    /// Header parameters shared by all recipients and not integrity protected
+    pub unprotected_header: Option<Map<String, Value>>,
+
+    pub recipients: Vec<JweRecipient>,
+
+    /// Additional Authenticated Data, integrity protected along with the protected header
+    pub aad: Option<Vec<u8>>,
+
+    pub initialization_vector: Vec<u8>,
+
+    pub ciphertext: Vec<u8>,
+
+    pub authentication_tag: Vec<u8>,
+
+    /// Kept as is since it is part of the content encryption AAD
+    protected_header_base64: String,
+}
+
+/// Recipient of a JWE using the JSON serialization.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JweRecipient {
+    /// JOSE header for this recipient (union of the protected, shared unprotected and per-recipient header parameters)
+    pub header: JweHeader,
+
+    /// Per-recipient header parameters, not integrity protected
+    pub unprotected_header: Option<Map<String, Value>>,
+
+    pub encrypted_key: Vec<u8>,
+}
+
+/// Key used to encrypt the CEK for a recipient of `Jwe::encode_json`.
+#[derive(Debug, Clone)]
+pub struct JweRecipientKey<'a> {
+    alg: JweAlg,
+    header: Option<Map<String, Value>>,
+    mode: EncoderMode<'a>,
+}
+
+impl<'a> JweRecipientKey<'a> {
+    /// CEK encrypted using RSAES algorithms, or agreed upon using ECDH-ES algorithms with elliptic curve keys.
+    pub fn asymmetric(alg: JweAlg, public_key: &'a PublicKey) -> Self {
+        Self::new(alg, EncoderMode::Asymetric(public_key))
+    }
+
+    /// Provided symmetric key is the CEK ("dir" algorithm).
+    pub fn direct(cek: &'a [u8]) -> Self {
+        Self::new(JweAlg::Direct, EncoderMode::Direct(cek))
+    }
+
+    /// CEK wrapped using the provided Key Encryption Key (AES Key Wrap algorithms).
+    pub fn key_wrap(alg: JweAlg, kek: &'a [u8]) -> Self {
+        Self::new(alg, EncoderMode::KeyWrap(kek))
+    }
+
+    /// CEK encrypted for the recipient described by the provided JWK (see `Jwe::encode_with_jwk`).
+    pub fn jwk(alg: JweAlg, jwk: &'a Jwk) -> Self {
+        Self::new(alg, EncoderMode::Jwk(jwk))
+    }
+
+    /// Additional per-recipient header parameters (e.g. `kid`).
+    pub fn header(mut self, header: Map<String, Value>) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    fn new(alg: JweAlg, mode: EncoderMode<'a>) -> Self {
+        Self {
+            alg,
+            header: None,
+            mode,
+        }
+    }
+}
+
+impl JweJson {
+    /// Integrity protected header parameters.
+    pub fn protected_header(&self) -> Result<Map<String, Value>, JweError> {
+        h_decode_header_part(&self.protected_header_base64)
+    }
+
+    /// Decode using the first recipient whose CEK can be decrypted with the given private key.
+    pub fn decode(&self, key: &PrivateKey) -> Result<Jwe, JweError> {
+        self.decode_impl(DecoderMode::Normal(key))
+    }
+
+    /// Decode with provided CEK (a symmetric key).
+    pub fn decode_direct(&self, cek: &[u8]) -> Result<Jwe, JweError> {
+        self.decode_impl(DecoderMode::Direct(cek))
+    }
+
+    /// Decode using the first recipient whose CEK can be unwrapped with the given Key Encryption Key.
+    pub fn decode_key_wrap(&self, kek: &[u8]) -> Result<Jwe, JweError> {
+        self.decode_impl(DecoderMode::KeyWrap(kek))
+    }
+
+    /// Decode using the first recipient whose CEK can be decrypted with the key described by the provided JWK.
+    pub fn decode_with_jwk(&self, jwk: &Jwk) -> Result<Jwe, JweError> {
+        self.decode_impl(DecoderMode::Jwk(jwk))
+    }
+
+    /// Parses a JWE using either the general or the flattened JSON serialization.
+    pub fn from_json(json: &str) -> Result<Self, JweError> {
+        let (shared, raw_recipients) = match serde_json::from_str::<RawJweJson>(json)? {
+            RawJweJson::General(raw) => (raw.shared, raw.recipients),
+            RawJweJson::Flattened(raw) => (raw.shared, vec![raw.recipient]),
+        };
+
+        let protected_header_base64 = shared.protected.unwrap_or_default();
+        let protected_header = h_decode_header_part(&protected_header_base64)?;
+
+        let recipients = raw_recipients
+            .into_iter()
+            .map(|raw_recipient| {
+                let recipient_header = raw_recipient.header.clone().unwrap_or_default();
+                Ok(JweRecipient {
+                    header: h_merge_header_parts(&protected_header, shared.unprotected.as_ref(), &recipient_header)?,
+                    unprotected_header: raw_recipient.header,
+                    encrypted_key: base64::decode_config(&raw_recipient.encrypted_key, base64::URL_SAFE_NO_PAD)?,
+                })
+            })
+            .collect::<Result<Vec<_>, JweError>>()?;
+
+        let aad = shared
+            .aad
+            .map(|aad| base64::decode_config(&aad, base64::URL_SAFE_NO_PAD))
+            .transpose()?;
+
+        Ok(Self {
+            unprotected_header: shared.unprotected,
+            recipients,
+            aad,
+            initialization_vector: base64::decode_config(&shared.iv, base64::URL_SAFE_NO_PAD)?,
+            ciphertext: base64::decode_config(&shared.ciphertext, base64::URL_SAFE_NO_PAD)?,
+            authentication_tag: base64::decode_config(&shared.tag, base64::URL_SAFE_NO_PAD)?,
+            protected_header_base64,
+        })
+    }
+
+    /// Serializes using the general JSON syntax.
+    pub fn to_json_general(&self) -> Result<String, JweError> {
+        let raw = RawJweGeneral {
+            shared: self.raw_shared(),
+            recipients: self.recipients.iter().map(RawJweRecipient::from).collect(),
+        };
+        Ok(serde_json::to_string(&raw)?)
+    }
+
+    /// Serializes using the flattened JSON syntax. There must be exactly one recipient.
+    pub fn to_json_flattened(&self) -> Result<String, JweError> {
+        let recipient = match self.recipients.as_slice() {
+            [recipient] => recipient,
+            recipients => {
+                return Err(JweError::FlattenedSerialization {
+                    count: recipients.len(),
+                })
+            }
+        };
+
+        let raw = RawJweFlattened {
+            shared: self.raw_shared(),
+            recipient: RawJweRecipient::from(recipient),
+        };
+        Ok(serde_json::to_string(&raw)?)
+    }
+
+    fn decode_impl(&self, mode: DecoderMode<'_>) -> Result<Jwe, JweError> {
+        let content_aad = h_json_content_aad(&self.protected_header_base64, self.aad.as_deref());
+
+        let mut last_error = JweError::NoRecipient;
+
+        for recipient in &self.recipients {
+            let payload = h_decrypt_key(&recipient.header, mode.clone(), &recipient.encrypted_key).and_then(|cek| {
+                h_decrypt_content(
+                    recipient.header.enc,
+                    &cek,
+                    content_aad.as_bytes(),
+                    &self.initialization_vector,
+                    self.ciphertext.clone(),
+                    &self.authentication_tag,
+                )
+            });
+
+            match payload {
+                Ok(payload) => {
+                    return Ok(Jwe {
+                        header: recipient.header.clone(),
+                        payload,
+                    })
+                }
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    fn raw_shared(&self) -> RawJweShared {
+        RawJweShared {
+            protected: Some(self.protected_header_base64.clone()).filter(|protected| !protected.is_empty()),
+            unprotected: self.unprotected_header.clone(),
+            aad: self
+                .aad
+                .as_ref()
+                .map(|aad| base64::encode_config(aad, base64::URL_SAFE_NO_PAD)),
+            iv: base64::encode_config(&self.initialization_vector, base64::URL_SAFE_NO_PAD),
+            ciphertext: base64::encode_config(&self.ciphertext, base64::URL_SAFE_NO_PAD),
+            tag: base64::encode_config(&self.authentication_tag, base64::URL_SAFE_NO_PAD),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawJweShared {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unprotected: Option<Map<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aad: Option<String>,
+    iv: String,
+    ciphertext: String,
+    tag: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawJweRecipient {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header: Option<Map<String, Value>>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    encrypted_key: String,
+}
+
+impl From<&JweRecipient> for RawJweRecipient {
+    fn from(recipient: &JweRecipient) -> Self {
+        Self {
+            header: recipient.unprotected_header.clone(),
+            encrypted_key: base64::encode_config(&recipient.encrypted_key, base64::URL_SAFE_NO_PAD),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawJweGeneral {
+    #[serde(flatten)]
+    shared: RawJweShared,
+    recipients: Vec<RawJweRecipient>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawJweFlattened {
+    #[serde(flatten)]
+    shared: RawJweShared,
+    #[serde(flatten)]
+    recipient: RawJweRecipient,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawJweJson {
+    General(RawJweGeneral),
+    Flattened(RawJweFlattened),
+}
+
+fn h_decode_header_part(header_base64: &str) -> Result<Map<String, Value>, JweError> {
+    if header_base64.is_empty() {
+        return Ok(Map::new());
+    }
+
+    let header_json = base64::decode_config(header_base64, base64::URL_SAFE_NO_PAD)?;
+    Ok(serde_json::from_slice(&header_json)?)
+}
+
+/// Builds the JOSE header of a recipient from the protected, shared unprotected and per-recipient parts
+fn h_merge_header_parts(
+    protected_header: &Map<String, Value>,
+    unprotected_header: Option<&Map<String, Value>>,
+    recipient_header: &Map<String, Value>,
+) -> Result<JweHeader, JweError> {
+    let header = merge_header_parts(&[Some(protected_header), unprotected_header, Some(recipient_header)])
+        .map_err(|parameter| JweError::DuplicatedHeaderParameter { parameter })?;
+    Ok(serde_json::from_value(Value::Object(header))?)
+}
+
+/// Additional Authenticated Data used for content encryption with the JSON serialization
+fn h_json_content_aad(protected_header_base64: &str, aad: Option<&[u8]>) -> String {
+    match aad {
+        Some(aad) => [
+            protected_header_base64,
+            &base64::encode_config(aad, base64::URL_SAFE_NO_PAD),
+        ]
+        .join("."),
+        None => protected_header_base64.to_owned(),
+    }
 }
 
 // encoder
@@ -368,13 +888,57 @@ impl Jwe {
 enum EncoderMode<'a> {
     Asymetric(&'a PublicKey),
     Direct(&'a [u8]),
+    KeyWrap(&'a [u8]),
+    KeyAgreement(EcdhPublicKey<'a>),
+    Jwk(&'a Jwk),
 }
 
 fn encode_impl(jwe: Jwe, mode: EncoderMode) -> Result<String, JweError> {
     let mut header = jwe.header;
+
+    let (encrypted_key, jwe_cek) = h_encrypt_key(&mut header, mode, None)?;
+
+    // protected header is serialized last since key management may update it (`alg`, `epk`)
     let protected_header_base64 = base64::encode_config(&serde_json::to_vec(&header)?, base64::URL_SAFE_NO_PAD);
+    let encrypted_key_base64 = base64::encode_config(&encrypted_key, base64::URL_SAFE_NO_PAD);
+
+    // The Additional Authenticated Data value used for both AES-GCM and AES-CBC-HMAC-SHA2.
+    let aad = protected_header_base64.as_bytes();
+    let EncryptedContent {
+        initialization_vector,
+        ciphertext,
+        authentication_tag,
+    } = h_encrypt_content(header.enc, &jwe_cek, aad, jwe.payload)?;
+
+    let initialization_vector_base64 = base64::encode_config(&initialization_vector, base64::URL_SAFE_NO_PAD);
+    let ciphertext_base64 = base64::encode_config(&ciphertext, base64::URL_SAFE_NO_PAD);
+    let authentication_tag_base64 = base64::encode_config(&authentication_tag, base64::URL_SAFE_NO_PAD);
+
+    Ok([
+        protected_header_base64,
+        encrypted_key_base64,
+        initialization_vector_base64,
+        ciphertext_base64,
+        authentication_tag_base64,
+    ]
+    .join("."))
+}
 
-    let (encrypted_key_base64, jwe_cek) = match mode {
+/// Key management: determines the CEK and the JWE Encrypted Key, updating the header if required.
+///
+/// `shared_cek` is the CEK already used for other recipients (JSON serialization) and is encrypted
+/// instead of a new random one. It is ignored by "dir" and "ECDH-ES" since they determine the CEK themselves.
+fn h_encrypt_key<'a>(
+    header: &mut JweHeader,
+    mode: EncoderMode<'a>,
+    shared_cek: Option<&[u8]>,
+) -> Result<(Vec<u8>, Cow<'a, [u8]>), JweError> {
+    let new_cek = |enc: JweEnc| match shared_cek {
+        Some(cek) => cek.to_vec(),
+        None => h_generate_cek(enc),
+    };
+
+    match mode {
         EncoderMode::Direct(symmetric_key) => {
             if symmetric_key.len() != header.enc.key_size() {
                 return Err(JweError::InvalidSize {
@@ -387,72 +951,107 @@ fn encode_impl(jwe: Jwe, mode: EncoderMode) -> Result<String, JweError> {
             // Override `alg` header with "dir"
             header.alg = JweAlg::Direct;
 
-            (
-                base64::encode_config(&[], base64::URL_SAFE_NO_PAD),
-                Cow::Borrowed(symmetric_key),
-            )
+            Ok((Vec::new(), Cow::Borrowed(symmetric_key)))
+        }
+        EncoderMode::Asymetric(public_key) if header.alg.is_ecdh_es() => {
+            let (curve, point) = ec::public_key_components(public_key)?;
+            h_ecdh_es_encrypt_key(header, EcdhPublicKey::Ec { curve, point }, new_cek)
         }
         EncoderMode::Asymetric(public_key) => {
-            // Currently, only rsa is supported
             let rsa_public_key = RsaPublicKey::try_from(public_key)?;
 
-            let mut rng = rand::rngs::OsRng;
+            let mut rng = OsRng;
 
-            let mut symmetric_key = vec![0u8; header.enc.key_size()];
-            rng.fill_bytes(&mut symmetric_key);
+            let symmetric_key = new_cek(header.enc);
 
             let padding = match header.alg {
                 JweAlg::RsaPkcs1v15 => PaddingScheme::new_pkcs1v15_encrypt(),
                 JweAlg::RsaOaep => PaddingScheme::new_oaep::<sha1::Sha1>(),
                 JweAlg::RsaOaep256 => PaddingScheme::new_oaep::<sha2::Sha256>(),
-                unsupported => {
-                    return Err(JweError::UnsupportedAlgorithm {
-                        algorithm: format!("{:?}", unsupported),
-                    })
-                }
+                unsupported => return Err(h_unsupported_algorithm(unsupported)),
             };
 
             let encrypted_key = rsa_public_key.encrypt(&mut rng, padding, &symmetric_key)?;
 
-            (
-                base64::encode_config(&encrypted_key, base64::URL_SAFE_NO_PAD),
-                Cow::Owned(symmetric_key),
-            )
+            Ok((encrypted_key, Cow::Owned(symmetric_key)))
         }
-    };
-
-    let mut buffer = jwe.payload;
-    let nonce = <aes_gcm::aead::Nonce<Aes128Gcm> as From<[u8; 12]>>::from(rand::random()); // 96-bits nonce for all AES-GCM variants
-    let aad = protected_header_base64.as_bytes(); // The Additional Authenticated Data value used for AES-GCM.
-    let authentication_tag = match header.enc {
-        JweEnc::Aes128Gcm => {
-            Aes128Gcm::new(GenericArray::from_slice(&jwe_cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+        EncoderMode::KeyWrap(kek) => {
+            let symmetric_key = new_cek(header.enc);
+            let encrypted_key = h_aes_key_wrap(header.alg, kek, &symmetric_key)?;
+            Ok((encrypted_key, Cow::Owned(symmetric_key)))
         }
-        JweEnc::Aes192Gcm => {
-            Aes192Gcm::new(GenericArray::from_slice(&jwe_cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+        EncoderMode::KeyAgreement(public_key) => h_ecdh_es_encrypt_key(header, public_key, new_cek),
+        EncoderMode::Jwk(jwk) => {
+            let (encrypted_key, cek) = match &jwk.key {
+                JwkKeyType::Oct(oct) => {
+                    let key = oct.key()?;
+                    let mode = if header.alg == JweAlg::Direct {
+                        EncoderMode::Direct(&key)
+                    } else {
+                        EncoderMode::KeyWrap(&key)
+                    };
+                    let (encrypted_key, cek) = h_encrypt_key(header, mode, shared_cek)?;
+                    (encrypted_key, cek.into_owned())
+                }
+                JwkKeyType::Okp(okp) if okp.curve() == JwkOkpCurve::X25519 => {
+                    let public_key = okp.public_key()?;
+                    let mode = EncoderMode::KeyAgreement(EcdhPublicKey::X25519(&public_key));
+                    let (encrypted_key, cek) = h_encrypt_key(header, mode, shared_cek)?;
+                    (encrypted_key, cek.into_owned())
+                }
+                _ => {
+                    let public_key = jwk.to_public_key()?;
+                    let (encrypted_key, cek) = h_encrypt_key(header, EncoderMode::Asymetric(&public_key), shared_cek)?;
+                    (encrypted_key, cek.into_owned())
+                }
+            };
+
+            Ok((encrypted_key, Cow::Owned(cek)))
         }
-        JweEnc::Aes256Gcm => {
-            Aes256Gcm::new(GenericArray::from_slice(&jwe_cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+    }
+}
+
+struct EncryptedContent {
+    initialization_vector: Vec<u8>,
+    ciphertext: Vec<u8>,
+    authentication_tag: Vec<u8>,
+}
+
+fn h_encrypt_content(enc: JweEnc, cek: &[u8], aad: &[u8], plaintext: Vec<u8>) -> Result<EncryptedContent, JweError> {
+    let mut buffer = plaintext;
+
+    let (initialization_vector, authentication_tag) = match enc {
+        JweEnc::Aes128Gcm | JweEnc::Aes192Gcm | JweEnc::Aes256Gcm => {
+            // 96-bits nonce for all AES-GCM variants
+            let nonce = <aes_gcm::aead::Nonce<Aes128Gcm> as From<[u8; 12]>>::from(rand::random());
+            let authentication_tag = match enc {
+                JweEnc::Aes128Gcm => {
+                    Aes128Gcm::new(GenericArray::from_slice(cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+                }
+                JweEnc::Aes192Gcm => {
+                    Aes192Gcm::new(GenericArray::from_slice(cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+                }
+                _ => {
+                    Aes256Gcm::new(GenericArray::from_slice(cek)).encrypt_in_place_detached(&nonce, aad, &mut buffer)?
+                }
+            };
+            (nonce.to_vec(), authentication_tag.to_vec())
         }
-        unsupported => {
-            return Err(JweError::UnsupportedAlgorithm {
-                algorithm: format!("{:?}", unsupported),
-            })
+        JweEnc::Aes128CbcHmacSha256 | JweEnc::Aes192CbcHmacSha384 | JweEnc::Aes256CbcHmacSha512 => {
+            let mut initialization_vector = vec![0u8; AES_BLOCK_SIZE];
+            OsRng.fill_bytes(&mut initialization_vector);
+            let (ciphertext, authentication_tag) =
+                h_aes_cbc_hmac_encrypt(enc, cek, &initialization_vector, aad, &buffer)?;
+            buffer = ciphertext;
+            (initialization_vector, authentication_tag)
         }
     };
 
-    let initialization_vector_base64 = base64::encode_config(nonce.as_slice(), base64::URL_SAFE_NO_PAD);
-    let ciphertext_base64 = base64::encode_config(&buffer, base64::URL_SAFE_NO_PAD);
-    let authentication_tag_base64 = base64::encode_config(&authentication_tag, base64::URL_SAFE_NO_PAD);
-
-    Ok([
-        protected_header_base64,
-        encrypted_key_base64,
-        initialization_vector_base64,
-        ciphertext_base64,
-        authentication_tag_base64,
-    ]
-    .join("."))
+    Ok(EncryptedContent {
+        initialization_vector,
+        ciphertext: buffer,
+        authentication_tag,
+    })
 }
 
 // decoder
@@ -461,6 +1060,11 @@ fn encode_impl(jwe: Jwe, mode: EncoderMode) -> Result<String, JweError> {
 enum DecoderMode<'a> {
     Normal(&'a PrivateKey),
     Direct(&'a [u8]),
+    KeyWrap(&'a [u8]),
+    KeyAgreement(EcdhPrivateKey<'a>),
+    /// Either "dir" or AES Key Wrap depending on `alg` header
+    Symmetric(&'a [u8]),
+    Jwk(&'a Jwk),
 }
 
 struct Parts<'a> {
@@ -494,8 +1098,35 @@ fn decode_impl(encoded_token: &str, mode: DecoderMode<'_>) -> Result<Jwe, JweErr
 
     let header = serde_json::from_slice::<JweHeader>(&parts.protected_header)?;
 
+    let jwe_cek = h_decrypt_key(&header, mode, &parts.encrypted_key)?;
+
+    // The Additional Authenticated Data value used for both AES-GCM and AES-CBC-HMAC-SHA2.
+    let aad = parts.protected_header_base64.as_bytes();
+    let payload = h_decrypt_content(
+        header.enc,
+        &jwe_cek,
+        aad,
+        &parts.initialization_vector,
+        parts.ciphertext,
+        &parts.authentication_tag,
+    )?;
+
+    Ok(Jwe { header, payload })
+}
+
+/// Key management: determines the CEK from the JWE Encrypted Key and the header
+fn h_decrypt_key<'a>(
+    header: &JweHeader,
+    mode: DecoderMode<'a>,
+    encrypted_key: &[u8],
+) -> Result<Cow<'a, [u8]>, JweError> {
     let jwe_cek = match mode {
         DecoderMode::Direct(symmetric_key) => Cow::Borrowed(symmetric_key),
+        DecoderMode::Normal(private_key) if header.alg.is_ecdh_es() => {
+            let (curve, secret) = ec::private_key_components(private_key)?;
+            let private_key = EcdhPrivateKey::Ec { curve, secret };
+            Cow::Owned(h_ecdh_es_decrypt_key(header, private_key, encrypted_key)?)
+        }
         DecoderMode::Normal(private_key) => {
             let rsa_private_key = RsaPrivateKey::try_from(private_key)?;
 
@@ -503,78 +1134,466 @@ fn decode_impl(encoded_token: &str, mode: DecoderMode<'_>) -> Result<Jwe, JweErr
                 JweAlg::RsaPkcs1v15 => PaddingScheme::new_pkcs1v15_encrypt(),
                 JweAlg::RsaOaep => PaddingScheme::new_oaep::<sha1::Sha1>(),
                 JweAlg::RsaOaep256 => PaddingScheme::new_oaep::<sha2::Sha256>(),
-                unsupported => {
-                    return Err(JweError::UnsupportedAlgorithm {
-                        algorithm: format!("{:?}", unsupported),
-                    })
-                }
+                unsupported => return Err(h_unsupported_algorithm(unsupported)),
             };
 
-            let decrypted_key = rsa_private_key.decrypt(padding, &parts.encrypted_key)?;
+            let decrypted_key = rsa_private_key.decrypt(padding, encrypted_key)?;
 
             Cow::Owned(decrypted_key)
         }
+        DecoderMode::KeyWrap(kek) => Cow::Owned(h_aes_key_unwrap(header.alg, kek, encrypted_key)?),
+        DecoderMode::KeyAgreement(private_key) => {
+            Cow::Owned(h_ecdh_es_decrypt_key(header, private_key, encrypted_key)?)
+        }
+        DecoderMode::Symmetric(symmetric_key) if header.alg == JweAlg::Direct => Cow::Borrowed(symmetric_key),
+        DecoderMode::Symmetric(kek) => Cow::Owned(h_aes_key_unwrap(header.alg, kek, encrypted_key)?),
+        DecoderMode::Jwk(jwk) => {
+            let jwe_cek = match &jwk.key {
+                JwkKeyType::Oct(oct) => {
+                    let key = oct.key()?;
+                    h_decrypt_key(header, DecoderMode::Symmetric(&key), encrypted_key)?.into_owned()
+                }
+                JwkKeyType::Okp(okp) if okp.curve() == JwkOkpCurve::X25519 => {
+                    let secret = okp.secret()?.ok_or(JwkError::MissingMember { member: "d" })?;
+                    let mode = DecoderMode::KeyAgreement(EcdhPrivateKey::X25519(&secret));
+                    h_decrypt_key(header, mode, encrypted_key)?.into_owned()
+                }
+                _ => {
+                    let private_key = jwk.to_private_key()?;
+                    h_decrypt_key(header, DecoderMode::Normal(&private_key), encrypted_key)?.into_owned()
+                }
+            };
+
+            Cow::Owned(jwe_cek)
+        }
     };
 
-    if jwe_cek.len() != header.enc.key_size() {
+    Ok(jwe_cek)
+}
+
+/// Checks the sizes of the content encryption parameters and decrypts the content
+fn h_decrypt_content(
+    enc: JweEnc,
+    cek: &[u8],
+    aad: &[u8],
+    initialization_vector: &[u8],
+    ciphertext: Vec<u8>,
+    authentication_tag: &[u8],
+) -> Result<Vec<u8>, JweError> {
+    if cek.len() != enc.key_size() {
         return Err(JweError::InvalidSize {
             ty: "symmetric key",
-            expected: header.enc.key_size(),
-            got: jwe_cek.len(),
+            expected: enc.key_size(),
+            got: cek.len(),
         });
     }
 
-    if parts.initialization_vector.len() != header.enc.nonce_size() {
+    if initialization_vector.len() != enc.nonce_size() {
         return Err(JweError::InvalidSize {
             ty: "initialization vector (nonce)",
-            expected: header.enc.nonce_size(),
-            got: parts.initialization_vector.len(),
+            expected: enc.nonce_size(),
+            got: initialization_vector.len(),
         });
     }
 
-    if parts.authentication_tag.len() != header.enc.tag_size() {
+    if authentication_tag.len() != enc.tag_size() {
         return Err(JweError::InvalidSize {
             ty: "authentication tag",
-            expected: header.enc.tag_size(),
-            got: parts.authentication_tag.len(),
+            expected: enc.tag_size(),
+            got: authentication_tag.len(),
         });
     }
 
-    let mut buffer = parts.ciphertext;
-    let nonce = GenericArray::from_slice(&parts.initialization_vector);
-    let aad = parts.protected_header_base64.as_bytes(); // The Additional Authenticated Data value used for AES-GCM.
-    match header.enc {
-        JweEnc::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(&jwe_cek)).decrypt_in_place_detached(
-            nonce,
-            aad,
-            &mut buffer,
-            GenericArray::from_slice(&parts.authentication_tag),
-        )?,
-        JweEnc::Aes192Gcm => Aes192Gcm::new(GenericArray::from_slice(&jwe_cek)).decrypt_in_place_detached(
-            nonce,
-            aad,
-            &mut buffer,
-            GenericArray::from_slice(&parts.authentication_tag),
-        )?,
-        JweEnc::Aes256Gcm => Aes256Gcm::new(GenericArray::from_slice(&jwe_cek)).decrypt_in_place_detached(
-            nonce,
-            aad,
-            &mut buffer,
-            GenericArray::from_slice(&parts.authentication_tag),
-        )?,
-        unsupported => {
-            return Err(JweError::UnsupportedAlgorithm {
-                algorithm: format!("{:?}", unsupported),
-            })
+    let mut buffer = ciphertext;
+    match enc {
+        JweEnc::Aes128Gcm | JweEnc::Aes192Gcm | JweEnc::Aes256Gcm => {
+            let nonce = GenericArray::from_slice(initialization_vector);
+            let tag = GenericArray::from_slice(authentication_tag);
+            match enc {
+                JweEnc::Aes128Gcm => Aes128Gcm::new(GenericArray::from_slice(cek)).decrypt_in_place_detached(
+                    nonce,
+                    aad,
+                    &mut buffer,
+                    tag,
+                )?,
+                JweEnc::Aes192Gcm => Aes192Gcm::new(GenericArray::from_slice(cek)).decrypt_in_place_detached(
+                    nonce,
+                    aad,
+                    &mut buffer,
+                    tag,
+                )?,
+                _ => Aes256Gcm::new(GenericArray::from_slice(cek)).decrypt_in_place_detached(
+                    nonce,
+                    aad,
+                    &mut buffer,
+                    tag,
+                )?,
+            }
         }
+        JweEnc::Aes128CbcHmacSha256 | JweEnc::Aes192CbcHmacSha384 | JweEnc::Aes256CbcHmacSha512 => {
+            buffer = h_aes_cbc_hmac_decrypt(enc, cek, initialization_vector, aad, &buffer, authentication_tag)?;
+        }
+    }
+
+    Ok(buffer)
+}
+
+fn h_unsupported_algorithm<A: std::fmt::Debug>(algorithm: A) -> JweError {
+    JweError::UnsupportedAlgorithm {
+        algorithm: format!("{:?}", algorithm),
+    }
+}
+
+fn h_generate_cek(enc: JweEnc) -> Vec<u8> {
+    let mut cek = vec![0u8; enc.key_size()];
+    OsRng.fill_bytes(&mut cek);
+    cek
+}
+
+// AES Key Wrap (RFC 3394) as described in RFC 7518 section 4.4.
+
+fn h_check_kek_size(alg: JweAlg, kek: &[u8]) -> Result<(), JweError> {
+    let expected = alg.key_wrap_size().ok_or_else(|| h_unsupported_algorithm(alg))?;
+
+    if kek.len() == expected {
+        Ok(())
+    } else {
+        Err(JweError::InvalidSize {
+            ty: "key encryption key",
+            expected,
+            got: kek.len(),
+        })
+    }
+}
+
+fn h_aes_key_wrap(alg: JweAlg, kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, JweError> {
+    h_check_kek_size(alg, kek)?;
+
+    let wrapped_key = match kek.len() {
+        16 => aes_kw::KekAes128::try_from(kek).and_then(|kek| kek.wrap_vec(cek)),
+        24 => aes_kw::KekAes192::try_from(kek).and_then(|kek| kek.wrap_vec(cek)),
+        _ => aes_kw::KekAes256::try_from(kek).and_then(|kek| kek.wrap_vec(cek)),
+    };
+
+    wrapped_key.map_err(|_| JweError::AesKeyWrap)
+}
+
+fn h_aes_key_unwrap(alg: JweAlg, kek: &[u8], wrapped_key: &[u8]) -> Result<Vec<u8>, JweError> {
+    h_check_kek_size(alg, kek)?;
+
+    let cek = match kek.len() {
+        16 => aes_kw::KekAes128::try_from(kek).and_then(|kek| kek.unwrap_vec(wrapped_key)),
+        24 => aes_kw::KekAes192::try_from(kek).and_then(|kek| kek.unwrap_vec(wrapped_key)),
+        _ => aes_kw::KekAes256::try_from(kek).and_then(|kek| kek.unwrap_vec(wrapped_key)),
     };
 
-    Ok(Jwe {
-        header,
-        payload: buffer,
+    cek.map_err(|_| JweError::AesKeyWrap)
+}
+
+// ECDH-ES key agreement as described in RFC 7518 section 4.6 (and RFC 8037 for X25519).
+
+/// Recipient public key for ECDH-ES (SEC1 encoded point for NIST curves)
+#[derive(Debug, Clone)]
+enum EcdhPublicKey<'a> {
+    Ec { curve: EcCurve, point: &'a [u8] },
+    X25519(&'a [u8]),
+}
+
+/// Recipient private key for ECDH-ES
+#[derive(Clone)]
+enum EcdhPrivateKey<'a> {
+    Ec { curve: EcCurve, secret: &'a [u8] },
+    X25519(&'a [u8]),
+}
+
+const X25519_KEY_SIZE: usize = 32;
+
+/// Generates an ephemeral key pair and returns it as a JWK along with the agreed upon shared secret
+fn h_ecdh_es_ephemeral_agreement(public_key: EcdhPublicKey) -> Result<(Jwk, Vec<u8>), JweError> {
+    match public_key {
+        EcdhPublicKey::Ec { curve, point } => with_curve_crate!(curve, m => {
+            let public_key = m::PublicKey::from_sec1_bytes(point).map_err(|_| JweError::KeyAgreement {
+                context: "invalid recipient public key".to_owned(),
+            })?;
+
+            let ephemeral_secret = m::ecdh::EphemeralSecret::random(&mut OsRng);
+            let shared_secret = ephemeral_secret.diffie_hellman(&public_key);
+
+            let ephemeral_point = ephemeral_secret.public_key().to_encoded_point(false);
+            let (x, y) = ephemeral_point
+                .x()
+                .zip(ephemeral_point.y())
+                .expect("uncompressed point has both coordinates");
+            let epk = Jwk::new(JwkKeyType::new_ec_key(JwkEcCurve::from(curve), x, y));
+
+            Ok((epk, shared_secret.raw_secret_bytes().to_vec()))
+        }),
+        EcdhPublicKey::X25519(public_key) => {
+            let public_key = h_x25519_key(public_key)?;
+
+            let ephemeral_secret = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
+            let ephemeral_public_key = x25519_dalek::PublicKey::from(&ephemeral_secret);
+            let shared_secret = ephemeral_secret.diffie_hellman(&x25519_dalek::PublicKey::from(public_key));
+            if !shared_secret.was_contributory() {
+                return Err(JweError::KeyAgreement {
+                    context: "non-contributory X25519 shared secret".to_owned(),
+                });
+            }
+
+            let epk = Jwk::new(JwkKeyType::new_okp_key(
+                JwkOkpCurve::X25519,
+                ephemeral_public_key.as_bytes(),
+            ));
+
+            Ok((epk, shared_secret.as_bytes().to_vec()))
+        }
+    }
+}
+
+/// Computes the shared secret agreed upon with the originator ephemeral public key
+fn h_ecdh_es_static_agreement(private_key: EcdhPrivateKey, epk: &Jwk) -> Result<Vec<u8>, JweError> {
+    match private_key {
+        EcdhPrivateKey::Ec { curve, secret } => {
+            let epk = match &epk.key {
+                JwkKeyType::Ec(epk) if epk.curve() == JwkEcCurve::from(curve) => epk,
+                _ => {
+                    return Err(JweError::KeyAgreement {
+                        context: format!("ephemeral public key is not a {:?} key", curve),
+                    })
+                }
+            };
+            let point = epk.encoded_point()?;
+
+            with_curve_crate!(curve, m => {
+                let secret_key = m::SecretKey::from_slice(secret).map_err(|_| JweError::KeyAgreement {
+                    context: "invalid private key".to_owned(),
+                })?;
+                let public_key = m::PublicKey::from_sec1_bytes(&point).map_err(|_| JweError::KeyAgreement {
+                    context: "invalid ephemeral public key".to_owned(),
+                })?;
+
+                let shared_secret = m::ecdh::diffie_hellman(secret_key.to_nonzero_scalar(), public_key.as_affine());
+
+                Ok(shared_secret.raw_secret_bytes().to_vec())
+            })
+        }
+        EcdhPrivateKey::X25519(secret) => {
+            let epk = match &epk.key {
+                JwkKeyType::Okp(epk) if epk.curve() == JwkOkpCurve::X25519 => epk,
+                _ => {
+                    return Err(JweError::KeyAgreement {
+                        context: "ephemeral public key is not a X25519 key".to_owned(),
+                    })
+                }
+            };
+            let public_key = h_x25519_key(&epk.public_key()?)?;
+
+            let static_secret = x25519_dalek::StaticSecret::from(h_x25519_key(secret)?);
+            let shared_secret = static_secret.diffie_hellman(&x25519_dalek::PublicKey::from(public_key));
+            if !shared_secret.was_contributory() {
+                return Err(JweError::KeyAgreement {
+                    context: "non-contributory X25519 shared secret".to_owned(),
+                });
+            }
+
+            Ok(shared_secret.as_bytes().to_vec())
+        }
+    }
+}
+
+fn h_x25519_key(key: &[u8]) -> Result<[u8; X25519_KEY_SIZE], JweError> {
+    key.try_into().map_err(|_| JweError::InvalidSize {
+        ty: "X25519 key",
+        expected: X25519_KEY_SIZE,
+        got: key.len(),
     })
 }
 
+/// Derives the key produced by the key agreement: the CEK itself for "ECDH-ES", the KEK otherwise
+fn h_ecdh_es_derive_key(shared_secret: &[u8], header: &JweHeader) -> Result<Vec<u8>, JweError> {
+    // AlgorithmID is the `enc` value in Direct Key Agreement mode, and the `alg` value otherwise
+    let (algorithm_id, key_size) = match header.alg {
+        JweAlg::EcdhEs => (serde_json::to_value(header.enc)?, header.enc.key_size()),
+        alg => (
+            serde_json::to_value(alg)?,
+            alg.key_wrap_size().ok_or_else(|| h_unsupported_algorithm(alg))?,
+        ),
+    };
+    let algorithm_id = algorithm_id.as_str().unwrap_or_default().as_bytes();
+
+    let apu = match &header.apu {
+        Some(apu) => base64::decode_config(apu, base64::URL_SAFE_NO_PAD)?,
+        None => Vec::new(),
+    };
+    let apv = match &header.apv {
+        Some(apv) => base64::decode_config(apv, base64::URL_SAFE_NO_PAD)?,
+        None => Vec::new(),
+    };
+
+    // OtherInfo: AlgorithmID || PartyUInfo || PartyVInfo || SuppPubInfo (key data length in bits)
+    let mut other_info = Vec::new();
+    for field in [algorithm_id, apu.as_slice(), apv.as_slice()] {
+        other_info.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(field);
+    }
+    other_info.extend_from_slice(&(key_size as u32 * 8).to_be_bytes());
+
+    let mut key = vec![0u8; key_size];
+    concat_kdf::derive_key_into::<Sha256>(shared_secret, &other_info, &mut key).map_err(|e| {
+        JweError::KeyAgreement {
+            context: format!("Concat KDF failed: {}", e),
+        }
+    })?;
+
+    Ok(key)
+}
+
+fn h_ecdh_es_encrypt_key<F>(
+    header: &mut JweHeader,
+    public_key: EcdhPublicKey,
+    new_cek: F,
+) -> Result<(Vec<u8>, Cow<'static, [u8]>), JweError>
+where
+    F: FnOnce(JweEnc) -> Vec<u8>,
+{
+    if !header.alg.is_ecdh_es() {
+        return Err(h_unsupported_algorithm(header.alg));
+    }
+
+    let (epk, shared_secret) = h_ecdh_es_ephemeral_agreement(public_key)?;
+    header.epk = Some(epk);
+
+    let derived_key = h_ecdh_es_derive_key(&shared_secret, header)?;
+
+    if header.alg == JweAlg::EcdhEs {
+        // Direct Key Agreement: derived key is the CEK and the JWE Encrypted Key is empty
+        Ok((Vec::new(), Cow::Owned(derived_key)))
+    } else {
+        let cek = new_cek(header.enc);
+        let encrypted_key = h_aes_key_wrap(header.alg, &derived_key, &cek)?;
+        Ok((encrypted_key, Cow::Owned(cek)))
+    }
+}
+
+fn h_ecdh_es_decrypt_key(
+    header: &JweHeader,
+    private_key: EcdhPrivateKey,
+    encrypted_key: &[u8],
+) -> Result<Vec<u8>, JweError> {
+    if !header.alg.is_ecdh_es() {
+        return Err(h_unsupported_algorithm(header.alg));
+    }
+
+    let epk = header
+        .epk
+        .as_ref()
+        .ok_or(JweError::MissingHeaderParameter { parameter: "epk" })?;
+
+    let shared_secret = h_ecdh_es_static_agreement(private_key, epk)?;
+    let derived_key = h_ecdh_es_derive_key(&shared_secret, header)?;
+
+    if header.alg == JweAlg::EcdhEs {
+        Ok(derived_key)
+    } else {
+        h_aes_key_unwrap(header.alg, &derived_key, encrypted_key)
+    }
+}
+
+// AES_CBC_HMAC_SHA2 authenticated encryption as described in RFC 7518 section 5.2.
+// The CEK is the concatenation of the MAC key and the encryption key.
+
+fn h_aes_cbc_hmac_encrypt(
+    enc: JweEnc,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), JweError> {
+    let (mac_key, enc_key) = cek.split_at(enc.key_size() / 2);
+
+    let ciphertext = match enc {
+        JweEnc::Aes128CbcHmacSha256 => Cbc::<Aes128, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .encrypt_vec(plaintext),
+        JweEnc::Aes192CbcHmacSha384 => Cbc::<Aes192, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .encrypt_vec(plaintext),
+        JweEnc::Aes256CbcHmacSha512 => Cbc::<Aes256, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .encrypt_vec(plaintext),
+        unsupported => return Err(h_unsupported_algorithm(unsupported)),
+    };
+
+    let mut tag = match enc {
+        JweEnc::Aes128CbcHmacSha256 => h_aes_cbc_hmac_mac::<Hmac<Sha256>>(mac_key, aad, iv, &ciphertext)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+        JweEnc::Aes192CbcHmacSha384 => h_aes_cbc_hmac_mac::<Hmac<Sha384>>(mac_key, aad, iv, &ciphertext)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+        _ => h_aes_cbc_hmac_mac::<Hmac<Sha512>>(mac_key, aad, iv, &ciphertext)
+            .finalize()
+            .into_bytes()
+            .to_vec(),
+    };
+    tag.truncate(enc.tag_size());
+
+    Ok((ciphertext, tag))
+}
+
+fn h_aes_cbc_hmac_decrypt(
+    enc: JweEnc,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, JweError> {
+    let (mac_key, enc_key) = cek.split_at(enc.key_size() / 2);
+
+    // authentication tag is checked (in constant time) before any decryption attempt
+    let tag_check = match enc {
+        JweEnc::Aes128CbcHmacSha256 => {
+            h_aes_cbc_hmac_mac::<Hmac<Sha256>>(mac_key, aad, iv, ciphertext).verify_truncated_left(tag)
+        }
+        JweEnc::Aes192CbcHmacSha384 => {
+            h_aes_cbc_hmac_mac::<Hmac<Sha384>>(mac_key, aad, iv, ciphertext).verify_truncated_left(tag)
+        }
+        JweEnc::Aes256CbcHmacSha512 => {
+            h_aes_cbc_hmac_mac::<Hmac<Sha512>>(mac_key, aad, iv, ciphertext).verify_truncated_left(tag)
+        }
+        unsupported => return Err(h_unsupported_algorithm(unsupported)),
+    };
+    tag_check.map_err(|_| JweError::AesCbcHmac)?;
+
+    let plaintext = match enc {
+        JweEnc::Aes128CbcHmacSha256 => Cbc::<Aes128, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .decrypt_vec(ciphertext),
+        JweEnc::Aes192CbcHmacSha384 => Cbc::<Aes192, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .decrypt_vec(ciphertext),
+        _ => Cbc::<Aes256, Pkcs7>::new_from_slices(enc_key, iv)
+            .map_err(|_| JweError::AesCbcHmac)?
+            .decrypt_vec(ciphertext),
+    };
+
+    plaintext.map_err(|_| JweError::AesCbcHmac)
+}
+
+fn h_aes_cbc_hmac_mac<M: Mac + KeyInit>(mac_key: &[u8], aad: &[u8], iv: &[u8], ciphertext: &[u8]) -> M {
+    // AL: number of bits in AAD expressed as a 64-bit unsigned big-endian integer
+    let aad_length = (aad.len() as u64 * 8).to_be_bytes();
+
+    let mut mac = <M as KeyInit>::new_from_slice(mac_key).expect("HMAC accepts keys of any size");
+    for part in [aad, iv, ciphertext, aad_length.as_slice()] {
+        Mac::update(&mut mac, part);
+    }
+    mac
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -651,6 +1670,259 @@ mod tests {
         assert_eq!(err.to_string(), "AES-GCM error (opaque)");
     }
 
+    #[test]
+    fn rsa_oaep_aes_128_cbc_hmac_sha_256() {
+        let payload = "何だと？……無駄な努力だ？……百も承知だ！だがな、勝つ望みがある時ばかり、戦うのとは訳が違うぞ！"
+            .as_bytes()
+            .to_vec();
+
+        let private_key = get_private_key_1();
+        let public_key = private_key.to_public_key();
+
+        let jwe = Jwe::new(JweAlg::RsaOaep, JweEnc::Aes128CbcHmacSha256, payload);
+        let encoded = jwe.clone().encode(&public_key).unwrap();
+
+        let decoded = Jwe::decode(&encoded, &private_key).unwrap();
+
+        assert_eq!(jwe.payload, decoded.payload);
+        assert_eq!(jwe.header, decoded.header);
+    }
+
+    #[test]
+    fn direct_aes_cbc_hmac_sha2() {
+        let payload = "さあ、取れ、取るがいい！".as_bytes().to_vec();
+
+        for (enc, key) in [
+            (JweEnc::Aes128CbcHmacSha256, [7u8; 32].as_slice()),
+            (JweEnc::Aes192CbcHmacSha384, [7u8; 48].as_slice()),
+            (JweEnc::Aes256CbcHmacSha512, [7u8; 64].as_slice()),
+        ] {
+            let jwe = Jwe::new(JweAlg::Direct, enc, payload.clone());
+            let encoded = jwe.clone().encode_direct(key).unwrap();
+
+            let decoded = Jwe::decode_direct(&encoded, key).unwrap();
+
+            assert_eq!(jwe.payload, decoded.payload);
+            assert_eq!(jwe.header, decoded.header);
+        }
+    }
+
+    #[test]
+    fn direct_aes_128_cbc_hmac_sha_256_tampered() {
+        let key = [7u8; 32];
+        let jwe = Jwe::new(
+            JweAlg::Direct,
+            JweEnc::Aes128CbcHmacSha256,
+            "和解をしよう？".as_bytes().to_vec(),
+        );
+        let encoded = jwe.encode_direct(&key).unwrap();
+
+        let mut parts: Vec<String> = encoded.split('.').map(str::to_owned).collect();
+        let mut tag = base64::decode_config(&parts[4], base64::URL_SAFE_NO_PAD).unwrap();
+        tag[0] ^= 1;
+        parts[4] = base64::encode_config(&tag, base64::URL_SAFE_NO_PAD);
+        let tampered = parts.join(".");
+
+        let err = Jwe::decode_direct(&tampered, &key).err().unwrap();
+        assert_eq!(err.to_string(), "AES-CBC-HMAC-SHA2 error (opaque)");
+
+        let err = Jwe::decode_direct(&encoded, &[8u8; 32]).err().unwrap();
+        assert_eq!(err.to_string(), "AES-CBC-HMAC-SHA2 error (opaque)");
+    }
+
+    #[test]
+    fn direct_aes_256_cbc_hmac_sha_512_interop() {
+        // token produced by another JOSE implementation
+        const TOKEN: &str = "eyJhbGciOiJkaXIiLCJlbmMiOiJBMjU2Q0JDLUhTNTEyIn0..MQRmbqzQIYrYhJHsA8fPgQ.8kksULNqOFfvQ8ib0-NeyQ.PO19yZAlkWTKvKifIEI5tKHKszDC0drTMC68282fRXw";
+
+        let key: Vec<u8> = (0..64).collect();
+        let decoded = Jwe::decode_direct(TOKEN, &key).unwrap();
+
+        assert_eq!(decoded.header.enc, JweEnc::Aes256CbcHmacSha512);
+        assert_eq!(decoded.payload, b"hello");
+    }
+
+    #[test]
+    fn rfc7518_aes_128_cbc_hmac_sha_256_test_vector() {
+        // See: https://tools.ietf.org/html/rfc7518#appendix-B.1
+
+        let cek: Vec<u8> = (0..32).collect();
+        let plaintext = b"A cipher system must not be required to be secret, and it must be able to fall into the hands of the enemy without inconvenience";
+        let iv = hex::decode("1af38c2dc2b96ffdd86694092341bc04").unwrap();
+        let aad = b"The second principle of Auguste Kerckhoffs";
+
+        let (ciphertext, tag) = h_aes_cbc_hmac_encrypt(JweEnc::Aes128CbcHmacSha256, &cek, &iv, aad, plaintext).unwrap();
+
+        assert_eq!(
+            hex::encode(&ciphertext),
+            "c80edfa32ddf39d5ef00c0b468834279a2e46a1b8049f792f76bfe54b903a9c9\
+             a94ac9b47ad2655c5f10f9aef71427e2fc6f9b3f399a221489f16362c7032336\
+             09d45ac69864e3321cf82935ac4096c86e133314c54019e8ca7980dfa4b9cf1b\
+             384c486f3a54c51078158ee5d79de59fbd34d848b3d69550a67646344427ade5\
+             4b8851ffb598f7f80074b9473c82e2db"
+        );
+        assert_eq!(hex::encode(&tag), "652c3fa36b0a7c5b3219fab3a30bc1c4");
+
+        let decrypted = h_aes_cbc_hmac_decrypt(JweEnc::Aes128CbcHmacSha256, &cek, &iv, aad, &ciphertext, &tag).unwrap();
+        assert_eq!(decrypted, plaintext);
+    }
+
+    #[test]
+    fn direct_overrides_alg_header() {
+        let jwe = Jwe::new(JweAlg::RsaOaep, JweEnc::Aes128Gcm, b"payload".to_vec());
+        let encoded = jwe.encode_direct(&[7u8; 16]).unwrap();
+
+        let decoded = Jwe::decode_direct(&encoded, &[7u8; 16]).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::Direct);
+    }
+
+    #[test]
+    fn rfc7516_example_using_aes_key_wrap_and_aes_cbc_hmac_sha2() {
+        // See: https://tools.ietf.org/html/rfc7516#appendix-A.3
+
+        const TOKEN: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0.6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ.AxY8DCtDaGlsbGljb3RoZQ.KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY.U0m_YmjN04DJvceFICbCVQ";
+
+        let jwk = Jwk::from_json(r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#).unwrap();
+        let kek = jwk.key.as_oct().unwrap().key().unwrap();
+
+        let decoded = Jwe::decode_key_wrap(TOKEN, &kek).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::AesKeyWrap128);
+        assert_eq!(decoded.header.enc, JweEnc::Aes128CbcHmacSha256);
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        let decoded = Jwe::decode_with_jwk(TOKEN, &jwk).unwrap();
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+    }
+
+    #[test]
+    fn aes_key_wrap() {
+        let payload = "無駄な努力だ？".as_bytes().to_vec();
+        let kek = [42u8; 32];
+
+        let jwe = Jwe::new(JweAlg::AesKeyWrap256, JweEnc::Aes256Gcm, payload);
+        let encoded = jwe.clone().encode_key_wrap(&kek).unwrap();
+
+        let decoded = Jwe::decode_key_wrap(&encoded, &kek).unwrap();
+        assert_eq!(jwe.payload, decoded.payload);
+        assert_eq!(jwe.header, decoded.header);
+
+        let err = Jwe::decode_key_wrap(&encoded, &[43u8; 32]).err().unwrap();
+        assert_eq!(err.to_string(), "AES Key Wrap error (opaque)");
+
+        let err = Jwe::decode_key_wrap(&encoded, &[42u8; 16]).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid size for key encryption key: expected 32, got 16"
+        );
+
+        let err = Jwe::new(JweAlg::RsaOaep, JweEnc::Aes256Gcm, Vec::new())
+            .encode_key_wrap(&kek)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "unsupported algorithm: RsaOaep");
+    }
+
+    const P256_PRIVATE_JWK: &str = r#"{"crv":"P-256","x":"shCQUwKvhZB-FxtskLABwAcnhvthjjQp1hbfszCX0-w","y":"f8KDIVGc0YuIKEFfcO8fpQPOsBPsGKbMJjrb002ylfQ","d":"0oVKusYFJRjis5eDChFIp5jAK00mtCGY6Jn8DrnWA8I","kty":"EC"}"#;
+    const X25519_PRIVATE_JWK: &str = r#"{"crv":"X25519","x":"8ovIum2jTU71hCplkNKVQdKvpN-5XIK8-PyNMJFDYCA","d":"8JY9Y9QRqz6Ub4HPFWZJSHwxC8bKXruDDBbUOXh2gk8","kty":"OKP"}"#;
+
+    #[test]
+    fn rfc7518_ecdh_es_key_derivation() {
+        // See: https://tools.ietf.org/html/rfc7518#appendix-C
+
+        let bob_key = Jwk::from_json(
+            r#"{"kty":"EC","crv":"P-256","x":"weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ","y":"e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck","d":"VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"}"#,
+        )
+        .unwrap()
+        .to_private_key()
+        .unwrap();
+
+        let header: JweHeader = serde_json::from_str(
+            r#"{"alg":"ECDH-ES","enc":"A128GCM","apu":"QWxpY2U","apv":"Qm9i","epk":{"kty":"EC","crv":"P-256","x":"gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0","y":"SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps"}}"#,
+        )
+        .unwrap();
+
+        let (curve, secret) = ec::private_key_components(&bob_key).unwrap();
+        let private_key = EcdhPrivateKey::Ec { curve, secret };
+        let cek = h_ecdh_es_decrypt_key(&header, private_key, &[]).unwrap();
+
+        assert_eq!(
+            base64::encode_config(&cek, base64::URL_SAFE_NO_PAD),
+            "VqqN6vgjbSBcIijNcacQGg"
+        );
+    }
+
+    #[test]
+    fn ecdh_es_nist_curves() {
+        for curve in [EcCurve::NistP256, EcCurve::NistP384, EcCurve::NistP521] {
+            let private_key = PrivateKey::generate_ec(curve).unwrap();
+            let public_key = private_key.to_public_key();
+
+            for alg in [JweAlg::EcdhEs, JweAlg::EcdhEsAesKeyWrap128, JweAlg::EcdhEsAesKeyWrap256] {
+                let mut jwe = Jwe::new(alg, JweEnc::Aes256Gcm, b"Live long and prosper.".to_vec());
+                jwe.header.apu = Some(base64::encode_config("Alice", base64::URL_SAFE_NO_PAD));
+                let encoded = jwe.clone().encode(&public_key).unwrap();
+
+                let decoded = Jwe::decode(&encoded, &private_key).unwrap();
+                assert_eq!(decoded.payload, jwe.payload);
+                assert_eq!(decoded.header.apu, jwe.header.apu);
+                assert!(decoded.header.epk.unwrap().key.is_ec());
+            }
+        }
+    }
+
+    #[test]
+    fn ecdh_es_x25519() {
+        let jwk = Jwk::from_json(X25519_PRIVATE_JWK).unwrap();
+
+        for alg in [JweAlg::EcdhEs, JweAlg::EcdhEsAesKeyWrap192] {
+            let jwe = Jwe::new(alg, JweEnc::Aes128CbcHmacSha256, b"Live long and prosper.".to_vec());
+            let encoded = jwe.clone().encode_with_jwk(&jwk).unwrap();
+
+            let decoded = Jwe::decode_with_jwk(&encoded, &jwk).unwrap();
+            assert_eq!(decoded.payload, jwe.payload);
+            assert_eq!(decoded.header.alg, alg);
+        }
+    }
+
+    #[test]
+    fn ecdh_es_interop() {
+        // tokens produced by another JOSE implementation
+        const X25519_TOKEN: &str = "eyJhbGciOiJFQ0RILUVTK0ExMjhLVyIsImVuYyI6IkExMjhHQ00iLCJhcHUiOiJRV3hwWTJVIiwiYXB2IjoiUW05aSIsImVwayI6eyJjcnYiOiJYMjU1MTkiLCJ4IjoiVE1nYVI2UlZuMEdzclFZS253dWtSY0xPXzdGTTM1WkxIN1FhaFNaTGZTOCIsImt0eSI6Ik9LUCJ9fQ.Le2PbBeK3zlzCm6I0NoK-AMEn_VYH6xF.B2z0ayO7q-Gpp_pp.OYEsfXqRVEvnLOw2bV6iBM4pa49b-A.OGK_i8o3dvtW8wjxqTk95Q";
+        const P256_TOKEN: &str = "eyJhbGciOiJFQ0RILUVTIiwiZW5jIjoiQTEyOENCQy1IUzI1NiIsImVwayI6eyJjcnYiOiJQLTI1NiIsIngiOiJFR2hoZnFiWGVZYmo0cHp3QWpQSjMzVlBaUmRWeTBLUGFRYmRqcG5FbXhRIiwieSI6IllJV0VvSm5NaTlfbjJpSnpoSk5GVHV4T3VjWC1pQmR3elczamxLVkgySTAiLCJrdHkiOiJFQyJ9fQ..cg-16Q7_fIN7xMNXc2TsRw.G0y0INBaHkSX0QryXd0LEZmAgbLCAtyOF4FJ1s7VUgE.KqngKsR6m3CqyKCfvF_tDg";
+
+        let x25519_jwk = Jwk::from_json(X25519_PRIVATE_JWK).unwrap();
+        let decoded = Jwe::decode_with_jwk(X25519_TOKEN, &x25519_jwk).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::EcdhEsAesKeyWrap128);
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        let p256_key = Jwk::from_json(P256_PRIVATE_JWK).unwrap().to_private_key().unwrap();
+        let decoded = Jwe::decode(P256_TOKEN, &p256_key).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::EcdhEs);
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        // ephemeral key on another curve than the recipient key
+        let other_key = PrivateKey::generate_ec(EcCurve::NistP384).unwrap();
+        let err = Jwe::decode(P256_TOKEN, &other_key).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "key agreement error: ephemeral public key is not a NistP384 key"
+        );
+    }
+
+    #[test]
+    fn ecdh_es_missing_epk() {
+        let private_key = Jwk::from_json(P256_PRIVATE_JWK).unwrap().to_private_key().unwrap();
+        let jwe = Jwe::new(JweAlg::EcdhEs, JweEnc::Aes128Gcm, b"payload".to_vec());
+        let encoded = jwe.encode(&private_key.to_public_key()).unwrap();
+
+        let mut parts: Vec<&str> = encoded.split('.').collect();
+        let header = base64::encode_config(r#"{"alg":"ECDH-ES","enc":"A128GCM"}"#, base64::URL_SAFE_NO_PAD);
+        parts[0] = &header;
+
+        let err = Jwe::decode(&parts.join("."), &private_key).err().unwrap();
+        assert_eq!(err.to_string(), "missing header parameter: epk");
+    }
+
     #[test]
     #[ignore = "this is not directly using picky code"]
     fn rfc7516_example_using_rsaes_oaep_and_aes_gcm() {
@@ -723,4 +1995,137 @@ mod tests {
 
         assert_eq!(token, "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ.OKOawDo13gRp2ojaHV7LFpZcgV7T6DVZKTyKOMTYUmKoTCVJRgckCL9kiMT03JGeipsEdY3mx_etLbbWSrFr05kLzcSr4qKAq7YN7e9jwQRb23nfa6c9d-StnImGyFDbSv04uVuxIp5Zms1gNxKKK2Da14B8S4rzVRltdYwam_lDp5XnZAYpQdb76FdIKLaVmqgfwX7XWRxv2322i-vDxRfqNzo_tETKzpVLzfiwQyeyPGLBIO56YJ7eObdv0je81860ppamavo35UgoRdbYaBcoh9QcfylQr66oc6vFWXRcZ_ZT2LawVCWTIy3brGPi6UklfCpIMfIjf7iGdXKHzg.48V1_ALb6US04U3b.5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6jiSdiwkIr3ajwQzaBtQD_A.XFBoMYUZodetZdvTiFvSkQ");
     }
+
+    const JSON_GENERAL_EXAMPLE: &str = r#"{"protected":"eyJlbmMiOiJBMTI4R0NNIn0","iv":"mDeVgwO_wIGEmemE","ciphertext":"oGLj4F26WKkQV-45EJf3dYFFRDanxg","tag":"yo_avWzkLOhkEqLm3UTQvw","aad":"cGlja3k","unprotected":{"cty":"text/plain"},"recipients":[{"header":{"alg":"ECDH-ES+A128KW","kid":"x25519-key","epk":{"crv":"X25519","x":"kmCbkPmUlFMvZwGsezBKxLY34mYKN9DgPqf0ad7rZiY","kty":"OKP"}},"encrypted_key":"1atRlC3i4yzI-VR4U3IerWFJfwv-Y0fc"},{"header":{"alg":"A128KW","kid":"kw-key"},"encrypted_key":"xRik6FsqBfjYKq9Hm1RoWgNvznYUr-Jp"}]}"#;
+    const JSON_FLATTENED_EXAMPLE: &str = r#"{"protected":"eyJlbmMiOiJBMTI4Q0JDLUhTMjU2In0","iv":"pcxgtlzHkOb09zm8nejQ7Q","ciphertext":"upxgmrUfATc9o1mPUaS-ZuSZWA2qwws7BN63tIHvCOo","tag":"B3kZuV_7CjM1T8U-p8_FDQ","header":{"alg":"A128KW","kid":"kw-key"},"encrypted_key":"Cn5xX5vrs-lK-_zqrpEbrNbYsyF5nor3RtXw0Uy92SCl0LgFAsf_lw"}"#;
+
+    fn json_example_kek() -> Vec<u8> {
+        base64::decode_config("GawgguFyGrWKav7AX4VKUg", base64::URL_SAFE_NO_PAD).unwrap()
+    }
+
+    fn kid_header(kid: &str) -> Map<String, Value> {
+        let mut header = Map::new();
+        header.insert("kid".to_owned(), Value::String(kid.to_owned()));
+        header
+    }
+
+    #[test]
+    fn json_general_serialization() {
+        let jwe = JweJson::from_json(JSON_GENERAL_EXAMPLE).unwrap();
+        assert_eq!(jwe.recipients.len(), 2);
+        assert_eq!(jwe.aad.as_deref(), Some(b"picky".as_slice()));
+        assert_eq!(jwe.protected_header().unwrap().len(), 1);
+
+        let x25519_jwk = Jwk::from_json(X25519_PRIVATE_JWK).unwrap();
+        let decoded = jwe.decode_with_jwk(&x25519_jwk).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::EcdhEsAesKeyWrap128);
+        assert_eq!(decoded.header.enc, JweEnc::Aes128Gcm);
+        assert_eq!(decoded.header.cty.as_deref(), Some("text/plain"));
+        assert_eq!(decoded.header.kid.as_deref(), Some("x25519-key"));
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        let decoded = jwe.decode_key_wrap(&json_example_kek()).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::AesKeyWrap128);
+        assert_eq!(decoded.header.kid.as_deref(), Some("kw-key"));
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        // round trip
+        let jwe_2 = JweJson::from_json(&jwe.to_json_general().unwrap()).unwrap();
+        assert_eq!(jwe, jwe_2);
+
+        let err = jwe.to_json_flattened().err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "flattened JSON serialization requires a single recipient, got 2"
+        );
+
+        // tampered AAD
+        let mut tampered = jwe;
+        tampered.aad = Some(b"pick".to_vec());
+        let err = tampered.decode_key_wrap(&json_example_kek()).err().unwrap();
+        assert_eq!(err.to_string(), "AES-GCM error (opaque)");
+    }
+
+    #[test]
+    fn json_flattened_serialization() {
+        let jwe = JweJson::from_json(JSON_FLATTENED_EXAMPLE).unwrap();
+        assert_eq!(jwe.recipients.len(), 1);
+        assert_eq!(jwe.aad, None);
+
+        let decoded = jwe.decode_key_wrap(&json_example_kek()).unwrap();
+        assert_eq!(decoded.header.enc, JweEnc::Aes128CbcHmacSha256);
+        assert_eq!(decoded.header.kid.as_deref(), Some("kw-key"));
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+
+        let jwe_2 = JweJson::from_json(&jwe.to_json_flattened().unwrap()).unwrap();
+        assert_eq!(jwe, jwe_2);
+    }
+
+    #[test]
+    fn json_multiple_recipients() {
+        let payload = b"Live long and prosper.".to_vec();
+        let private_key = get_private_key_1();
+        let public_key = private_key.to_public_key();
+        let kek = json_example_kek();
+
+        let recipients = [
+            JweRecipientKey::asymmetric(JweAlg::RsaOaep256, &public_key).header(kid_header("rsa-key")),
+            JweRecipientKey::key_wrap(JweAlg::AesKeyWrap128, &kek).header(kid_header("kw-key")),
+        ];
+
+        let mut jwe = Jwe::new(JweAlg::RsaOaep, JweEnc::Aes256Gcm, payload.clone());
+        jwe.header.typ = Some("example".to_owned());
+        let jwe = jwe.encode_json(&recipients, None, Some(b"picky")).unwrap();
+
+        let protected_header = jwe.protected_header().unwrap();
+        assert_eq!(protected_header.get("typ"), Some(&Value::from("example")));
+        assert_eq!(protected_header.get("alg"), None);
+
+        let jwe = JweJson::from_json(&jwe.to_json_general().unwrap()).unwrap();
+
+        let decoded = jwe.decode(&private_key).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::RsaOaep256);
+        assert_eq!(decoded.header.kid.as_deref(), Some("rsa-key"));
+        assert_eq!(decoded.header.typ.as_deref(), Some("example"));
+        assert_eq!(decoded.payload, payload);
+
+        let decoded = jwe.decode_key_wrap(&kek).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::AesKeyWrap128);
+        assert_eq!(decoded.payload, payload);
+
+        jwe.decode(&get_private_key_2()).err().unwrap();
+    }
+
+    #[test]
+    fn json_invalid_recipients() {
+        let jwe = Jwe::new(JweAlg::Direct, JweEnc::Aes128Gcm, b"Live long and prosper.".to_vec());
+        let kek = json_example_kek();
+
+        let err = jwe.clone().encode_json(&[], None, None).err().unwrap();
+        assert_eq!(err.to_string(), "JWE has no recipient");
+
+        let recipients = [
+            JweRecipientKey::direct(&kek),
+            JweRecipientKey::key_wrap(JweAlg::AesKeyWrap128, &kek),
+        ];
+        let err = jwe.clone().encode_json(&recipients, None, None).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Direct algorithm can only be used with a single recipient"
+        );
+
+        let recipients = [JweRecipientKey::key_wrap(JweAlg::AesKeyWrap128, &kek).header(kid_header("kw-key"))];
+        let err = jwe
+            .clone()
+            .encode_json(&recipients, Some(kid_header("shared")), None)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "duplicated header parameter: kid");
+
+        // a single "dir" recipient is fine
+        let encoded = jwe.encode_json(&[JweRecipientKey::direct(&kek)], None, None).unwrap();
+        let decoded = encoded.decode_direct(&kek).unwrap();
+        assert_eq!(decoded.header.alg, JweAlg::Direct);
+        assert_eq!(decoded.payload, b"Live long and prosper.");
+    }
 }
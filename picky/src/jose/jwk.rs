@@ -2,11 +2,14 @@
 //!
 //! See [RFC7517](https://tools.ietf.org/html/rfc7517).
 
+use crate::hash::HashAlgorithm;
 use crate::jose::jwe::{JweAlg, JweEnc};
-use crate::jose::jws::JwsAlg;
-use crate::key::PublicKey;
+use crate::jose::jws::{JwsAlg, JwsHeader};
+use crate::key::{ec, ed, EcCurve, EdCurve, KeyError, PrivateKey, PublicKey};
 use base64::DecodeError;
-use picky_asn1_x509::SubjectPublicKeyInfo;
+use picky_asn1::wrapper::OctetStringAsn1Container;
+use picky_asn1_x509::PrivateKeyValue;
+use rsa::BigUint;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -25,6 +28,14 @@ pub enum JwkError {
     /// unsupported algorithm
     #[error("unsupported algorithm: {algorithm}")]
     UnsupportedAlgorithm { algorithm: &'static str },
+
+    /// key error
+    #[error("key error: {source}")]
+    Key { source: KeyError },
+
+    /// a required member is missing
+    #[error("required member `{member}` is missing")]
+    MissingMember { member: &'static str },
 }
 
 impl From<serde_json::Error> for JwkError {
@@ -39,6 +50,12 @@ impl From<DecodeError> for JwkError {
     }
 }
 
+impl From<KeyError> for JwkError {
+    fn from(e: KeyError) -> Self {
+        Self::Key { source: e }
+    }
+}
+
 // === key type === //
 
 /// Algorithm type for JWK
@@ -47,39 +64,68 @@ impl From<DecodeError> for JwkError {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(tag = "kty")]
 pub enum JwkKeyType {
-    /// Elliptic Curve (unsupported)
+    /// Elliptic Curve
     ///
     /// Recommended+ by RFC
     #[serde(rename = "EC")]
-    Ec,
-    /// Elliptic Curve
+    Ec(JwkEcKey),
+    /// RSA
     ///
     /// Required by RFC
     #[serde(rename = "RSA")]
-    Rsa(JwkPublicRsaKey),
-    /// Octet sequence (used to represent symmetric keys) (unsupported)
+    Rsa(JwkRsaKey),
+    /// Octet sequence (used to represent symmetric keys)
     ///
     /// Required by RFC
     #[serde(rename = "oct")]
-    Oct,
+    Oct(JwkOctKey),
+    /// Octet string key pairs (Edwards and Montgomery curves)
+    ///
+    /// See [RFC8037](https://tools.ietf.org/html/rfc8037#section-2)
+    #[serde(rename = "OKP")]
+    Okp(JwkOkpKey),
 }
 
 impl JwkKeyType {
     pub fn new_rsa_key(modulus: &[u8], public_exponent: &[u8]) -> Self {
-        Self::Rsa(JwkPublicRsaKey {
-            n: base64::encode_config(modulus, base64::URL_SAFE_NO_PAD),
-            e: base64::encode_config(public_exponent, base64::URL_SAFE_NO_PAD),
+        Self::Rsa(JwkRsaKey {
+            n: h_encode(modulus),
+            e: h_encode(public_exponent),
+            ..JwkRsaKey::default()
         })
     }
 
     pub fn new_rsa_key_from_base64_url(modulus: String, public_exponent: String) -> Self {
-        Self::Rsa(JwkPublicRsaKey {
+        Self::Rsa(JwkRsaKey {
             n: modulus,
             e: public_exponent,
+            ..JwkRsaKey::default()
+        })
+    }
+
+    /// Coordinates are big-endian and must be as long as the curve field size.
+    pub fn new_ec_key(curve: JwkEcCurve, x: &[u8], y: &[u8]) -> Self {
+        Self::Ec(JwkEcKey {
+            crv: curve,
+            x: h_encode(x),
+            y: h_encode(y),
+            d: None,
+        })
+    }
+
+    pub fn new_okp_key(curve: JwkOkpCurve, public_key: &[u8]) -> Self {
+        Self::Okp(JwkOkpKey {
+            crv: curve,
+            x: h_encode(public_key),
+            d: None,
         })
     }
 
-    pub fn as_rsa(&self) -> Option<&JwkPublicRsaKey> {
+    pub fn new_oct_key(key: &[u8]) -> Self {
+        Self::Oct(JwkOctKey { k: h_encode(key) })
+    }
+
+    pub fn as_rsa(&self) -> Option<&JwkRsaKey> {
         match self {
             JwkKeyType::Rsa(rsa) => Some(rsa),
             _ => None,
@@ -89,6 +135,113 @@ impl JwkKeyType {
     pub fn is_rsa(&self) -> bool {
         self.as_rsa().is_some()
     }
+
+    pub fn as_ec(&self) -> Option<&JwkEcKey> {
+        match self {
+            JwkKeyType::Ec(ec) => Some(ec),
+            _ => None,
+        }
+    }
+
+    pub fn is_ec(&self) -> bool {
+        self.as_ec().is_some()
+    }
+
+    pub fn as_okp(&self) -> Option<&JwkOkpKey> {
+        match self {
+            JwkKeyType::Okp(okp) => Some(okp),
+            _ => None,
+        }
+    }
+
+    pub fn is_okp(&self) -> bool {
+        self.as_okp().is_some()
+    }
+
+    pub fn as_oct(&self) -> Option<&JwkOctKey> {
+        match self {
+            JwkKeyType::Oct(oct) => Some(oct),
+            _ => None,
+        }
+    }
+
+    pub fn is_oct(&self) -> bool {
+        self.as_oct().is_some()
+    }
+
+    /// Returns `true` if private key material is present (always `true` for symmetric keys).
+    pub fn is_private(&self) -> bool {
+        match self {
+            JwkKeyType::Ec(ec) => ec.d.is_some(),
+            JwkKeyType::Rsa(rsa) => rsa.d.is_some(),
+            JwkKeyType::Oct(_) => true,
+            JwkKeyType::Okp(okp) => okp.d.is_some(),
+        }
+    }
+}
+
+// === curves === //
+
+/// Curves for `EC` keys
+///
+/// See [RFC7518 #6.2.1.1](https://tools.ietf.org/html/rfc7518#section-6.2.1.1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JwkEcCurve {
+    #[serde(rename = "P-256")]
+    P256,
+    #[serde(rename = "P-384")]
+    P384,
+    #[serde(rename = "P-521")]
+    P521,
+}
+
+impl From<EcCurve> for JwkEcCurve {
+    fn from(curve: EcCurve) -> Self {
+        match curve {
+            EcCurve::NistP256 => Self::P256,
+            EcCurve::NistP384 => Self::P384,
+            EcCurve::NistP521 => Self::P521,
+        }
+    }
+}
+
+impl From<JwkEcCurve> for EcCurve {
+    fn from(curve: JwkEcCurve) -> Self {
+        match curve {
+            JwkEcCurve::P256 => Self::NistP256,
+            JwkEcCurve::P384 => Self::NistP384,
+            JwkEcCurve::P521 => Self::NistP521,
+        }
+    }
+}
+
+/// Subtypes for `OKP` keys
+///
+/// See [RFC8037 #2](https://tools.ietf.org/html/rfc8037#section-2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum JwkOkpCurve {
+    Ed25519,
+    Ed448,
+    X25519,
+    X448,
+}
+
+impl From<EdCurve> for JwkOkpCurve {
+    fn from(curve: EdCurve) -> Self {
+        match curve {
+            EdCurve::Ed25519 => Self::Ed25519,
+        }
+    }
+}
+
+impl JwkOkpCurve {
+    /// Returns the signature curve, `None` for `Ed448` (unsupported) and key agreement curves (`X25519` and `X448`).
+    pub fn to_ed_curve(self) -> Option<EdCurve> {
+        match self {
+            JwkOkpCurve::Ed25519 => Some(EdCurve::Ed25519),
+            JwkOkpCurve::Ed448 | JwkOkpCurve::X25519 | JwkOkpCurve::X448 => None,
+        }
+    }
 }
 
 // === public key use === //
@@ -150,6 +303,7 @@ pub struct Jwk {
     pub key: JwkKeyType,
 
     /// Identifies the algorithm intended for use with the key.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alg: Option<Jwa>,
 
     /// Public Key Use
@@ -221,16 +375,56 @@ impl Jwk {
 
         match &public_key.as_inner().subject_public_key {
             SerdePublicKey::Rsa(BitStringAsn1Container(rsa)) => Ok(Self::new(JwkKeyType::new_rsa_key(
-                rsa.modulus.as_signed_bytes_be(),
-                rsa.public_exponent.as_signed_bytes_be(),
+                rsa.modulus.as_unsigned_bytes_be(),
+                rsa.public_exponent.as_unsigned_bytes_be(),
             ))),
-            SerdePublicKey::Ec(_) => Err(JwkError::UnsupportedAlgorithm {
-                algorithm: "elliptic curves",
-            }),
-            SerdePublicKey::Ed(_) => Err(JwkError::UnsupportedAlgorithm {
-                algorithm: "edwards curves",
-            }),
+            SerdePublicKey::Ec(_) => {
+                let (curve, point) = ec::public_key_components(public_key)?;
+                let point = ec::decompress_public_point(curve, point)?;
+                let (x, y) = point[1..].split_at(curve.field_size());
+                Ok(Self::new(JwkKeyType::new_ec_key(curve.into(), x, y)))
+            }
+            SerdePublicKey::Ed(_) => {
+                let (curve, point) = ed::public_key_components(public_key)?;
+                Ok(Self::new(JwkKeyType::new_okp_key(curve.into(), point)))
+            }
+        }
+    }
+
+    /// Builds a private JWK (public members are included as well).
+    ///
+    /// **Beware**: private JWKs should never be published.
+    pub fn from_private_key(private_key: &PrivateKey) -> Result<Self, JwkError> {
+        let mut jwk = Self::from_public_key(&private_key.to_public_key())?;
+
+        match (&mut jwk.key, &private_key.as_inner().private_key) {
+            (JwkKeyType::Rsa(jwk_key), PrivateKeyValue::RSA(OctetStringAsn1Container(rsa))) => {
+                jwk_key.d = Some(h_encode(rsa.private_exponent.as_unsigned_bytes_be()));
+                jwk_key.p = Some(h_encode(rsa.prime_1.as_unsigned_bytes_be()));
+                jwk_key.q = Some(h_encode(rsa.prime_2.as_unsigned_bytes_be()));
+                jwk_key.dp = Some(h_encode(rsa.exponent_1.as_unsigned_bytes_be()));
+                jwk_key.dq = Some(h_encode(rsa.exponent_2.as_unsigned_bytes_be()));
+                jwk_key.qi = Some(h_encode(rsa.coefficient.as_unsigned_bytes_be()));
+            }
+            (JwkKeyType::Ec(jwk_key), PrivateKeyValue::EC(_)) => {
+                let (curve, secret) = ec::private_key_components(private_key)?;
+                // RFC 7518 requires the full field size, leading zeros included
+                let mut padded_secret = vec![0; curve.field_size().saturating_sub(secret.len())];
+                padded_secret.extend_from_slice(secret);
+                jwk_key.d = Some(h_encode(&padded_secret));
+            }
+            (JwkKeyType::Okp(jwk_key), PrivateKeyValue::ED(_)) => {
+                let (_, secret) = ed::private_key_components(private_key)?;
+                jwk_key.d = Some(h_encode(secret));
+            }
+            _ => {
+                return Err(JwkError::UnsupportedAlgorithm {
+                    algorithm: "mismatched private and public keys",
+                })
+            }
         }
+
+        Ok(jwk)
     }
 
     pub fn to_json(&self) -> Result<String, JwkError> {
@@ -243,18 +437,146 @@ impl Jwk {
 
     pub fn to_public_key(&self) -> Result<PublicKey, JwkError> {
         match &self.key {
-            JwkKeyType::Rsa(rsa) => {
-                let spki = SubjectPublicKeyInfo::new_rsa_key(rsa.modulus()?.into(), rsa.public_exponent()?.into());
-                Ok(spki.into())
+            JwkKeyType::Rsa(rsa) => Ok(PublicKey::from_rsa_components(
+                &BigUint::from_bytes_be(&rsa.modulus()?),
+                &BigUint::from_bytes_be(&rsa.public_exponent()?),
+            )),
+            JwkKeyType::Ec(ec) => Ok(PublicKey::from_ec_encoded_point(ec.crv.into(), &ec.encoded_point()?)?),
+            JwkKeyType::Okp(okp) => {
+                let curve = h_okp_ed_curve(okp.crv)?;
+                Ok(PublicKey::from_ed_encoded_point(curve, &okp.public_key()?)?)
             }
-            JwkKeyType::Ec => Err(JwkError::UnsupportedAlgorithm {
-                algorithm: "elliptic curves",
+            JwkKeyType::Oct(_) => Err(JwkError::UnsupportedAlgorithm {
+                algorithm: "octet sequence",
             }),
-            JwkKeyType::Oct => Err(JwkError::UnsupportedAlgorithm {
+        }
+    }
+
+    /// Builds a private key from a private JWK.
+    ///
+    /// For RSA keys, the primes (`p` and `q`) are required.
+    pub fn to_private_key(&self) -> Result<PrivateKey, JwkError> {
+        match &self.key {
+            JwkKeyType::Rsa(rsa) => {
+                let private_exponent = rsa.private_exponent()?.ok_or(JwkError::MissingMember { member: "d" })?;
+                let prime_1 = h_decode_opt(&rsa.p)?.ok_or(JwkError::MissingMember { member: "p" })?;
+                let prime_2 = h_decode_opt(&rsa.q)?.ok_or(JwkError::MissingMember { member: "q" })?;
+                Ok(PrivateKey::from_rsa_components(
+                    &BigUint::from_bytes_be(&rsa.modulus()?),
+                    &BigUint::from_bytes_be(&rsa.public_exponent()?),
+                    &BigUint::from_bytes_be(&private_exponent),
+                    &[BigUint::from_bytes_be(&prime_1), BigUint::from_bytes_be(&prime_2)],
+                )?)
+            }
+            JwkKeyType::Ec(ec) => {
+                let secret = ec.secret()?.ok_or(JwkError::MissingMember { member: "d" })?;
+                Ok(PrivateKey::from_ec_components(
+                    ec.crv.into(),
+                    &secret,
+                    Some(&ec.encoded_point()?),
+                )?)
+            }
+            JwkKeyType::Okp(okp) => {
+                let curve = h_okp_ed_curve(okp.crv)?;
+                let secret = okp.secret()?.ok_or(JwkError::MissingMember { member: "d" })?;
+                Ok(PrivateKey::from_ed_components(
+                    curve,
+                    &secret,
+                    Some(&okp.public_key()?),
+                )?)
+            }
+            JwkKeyType::Oct(_) => Err(JwkError::UnsupportedAlgorithm {
                 algorithm: "octet sequence",
             }),
         }
     }
+
+    /// Computes the JWK thumbprint as defined by [RFC7638](https://tools.ietf.org/html/rfc7638).
+    ///
+    /// Only the required members of the key are hashed: private members and optional parameters are ignored.
+    pub fn thumbprint(&self, hash_algorithm: HashAlgorithm) -> Vec<u8> {
+        // required members in lexicographic order, without whitespace
+        let members: &[(&str, &str)] = match &self.key {
+            JwkKeyType::Ec(ec) => &[
+                ("crv", h_ec_curve_name(ec.crv)),
+                ("kty", "EC"),
+                ("x", &ec.x),
+                ("y", &ec.y),
+            ],
+            JwkKeyType::Rsa(rsa) => &[("e", &rsa.e), ("kty", "RSA"), ("n", &rsa.n)],
+            JwkKeyType::Oct(oct) => &[("k", &oct.k), ("kty", "oct")],
+            JwkKeyType::Okp(okp) => &[("crv", h_okp_curve_name(okp.crv)), ("kty", "OKP"), ("x", &okp.x)],
+        };
+
+        let json = members
+            .iter()
+            .map(|(name, value)| format!("\"{}\":{}", name, serde_json::Value::from(*value)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        hash_algorithm.digest(format!("{{{}}}", json).as_bytes())
+    }
+
+    /// base64url-encoded JWK thumbprint, often used as key ID.
+    pub fn thumbprint_base64url(&self, hash_algorithm: HashAlgorithm) -> String {
+        h_encode(&self.thumbprint(hash_algorithm))
+    }
+
+    /// Checks whether this key may be used to verify a JWS using the given algorithm.
+    ///
+    /// The key type (and curve) must match the algorithm, and `use`, `key_ops` and `alg`
+    /// members must allow signature verification with this algorithm when present.
+    pub fn is_suitable_for_jws(&self, alg: JwsAlg) -> bool {
+        if matches!(self.key_use, Some(key_use) if key_use != JwkPubKeyUse::Signature) {
+            return false;
+        }
+
+        if matches!(&self.key_ops, Some(key_ops) if !key_ops.contains(&JwkKeyOps::Verify)) {
+            return false;
+        }
+
+        match self.alg {
+            None => {}
+            Some(Jwa::Sig(key_alg)) if key_alg == alg => {}
+            Some(_) => return false,
+        }
+
+        match (&self.key, alg) {
+            (JwkKeyType::Oct(_), JwsAlg::HS256 | JwsAlg::HS384 | JwsAlg::HS512) => true,
+            (
+                JwkKeyType::Rsa(_),
+                JwsAlg::RS256 | JwsAlg::RS384 | JwsAlg::RS512 | JwsAlg::PS256 | JwsAlg::PS384 | JwsAlg::PS512,
+            ) => true,
+            (JwkKeyType::Ec(ec), JwsAlg::ES256) => ec.crv == JwkEcCurve::P256,
+            (JwkKeyType::Ec(ec), JwsAlg::ES384) => ec.crv == JwkEcCurve::P384,
+            (JwkKeyType::Ec(ec), JwsAlg::ES512) => ec.crv == JwkEcCurve::P521,
+            (JwkKeyType::Okp(okp), JwsAlg::EdDSA) => okp.crv.to_ed_curve().is_some(),
+            _ => false,
+        }
+    }
+}
+
+fn h_ec_curve_name(curve: JwkEcCurve) -> &'static str {
+    match curve {
+        JwkEcCurve::P256 => "P-256",
+        JwkEcCurve::P384 => "P-384",
+        JwkEcCurve::P521 => "P-521",
+    }
+}
+
+fn h_okp_curve_name(curve: JwkOkpCurve) -> &'static str {
+    match curve {
+        JwkOkpCurve::Ed25519 => "Ed25519",
+        JwkOkpCurve::Ed448 => "Ed448",
+        JwkOkpCurve::X25519 => "X25519",
+        JwkOkpCurve::X448 => "X448",
+    }
+}
+
+fn h_okp_ed_curve(curve: JwkOkpCurve) -> Result<EdCurve, JwkError> {
+    curve.to_ed_curve().ok_or(JwkError::UnsupportedAlgorithm {
+        algorithm: h_okp_curve_name(curve),
+    })
 }
 
 // === jwk set === //
@@ -276,30 +598,191 @@ impl JwkSet {
     pub fn to_json_pretty(&self) -> Result<String, JwkError> {
         Ok(serde_json::to_string_pretty(self)?)
     }
+
+    pub fn find_by_kid(&self, kid: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.kid.as_deref() == Some(kid))
+    }
+
+    pub fn find_by_x5t(&self, x5t: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.x5t.as_deref() == Some(x5t))
+    }
+
+    pub fn find_by_x5t_s256(&self, x5t_s256: &str) -> Option<&Jwk> {
+        self.keys.iter().find(|jwk| jwk.x5t_s256.as_deref() == Some(x5t_s256))
+    }
+
+    /// Returns the keys that may be used to verify a JWS with the given header.
+    ///
+    /// Keys are matched using `kid`, then `x5t`, then `x5t#S256` header parameters.
+    /// When none of these parameters is present, all the keys suitable for the algorithm are returned.
+    pub fn select_for_jws(&self, header: &JwsHeader) -> Vec<&Jwk> {
+        let suitable_keys = self
+            .keys
+            .iter()
+            .filter(|jwk| jwk.is_suitable_for_jws(header.alg))
+            .collect::<Vec<_>>();
+
+        if header.kid.is_none() && header.x5t.is_none() && header.x5t_s256.is_none() {
+            return suitable_keys;
+        }
+
+        let matching_keys = |header_value: &Option<String>, jwk_value: fn(&Jwk) -> &Option<String>| {
+            suitable_keys
+                .iter()
+                .copied()
+                .filter(|jwk| header_value.is_some() && jwk_value(jwk) == header_value)
+                .collect::<Vec<_>>()
+        };
+
+        [
+            matching_keys(&header.kid, |jwk| &jwk.kid),
+            matching_keys(&header.x5t, |jwk| &jwk.x5t),
+            matching_keys(&header.x5t_s256, |jwk| &jwk.x5t_s256),
+        ]
+        .into_iter()
+        .find(|keys| !keys.is_empty())
+        .unwrap_or_default()
+    }
 }
 
-// === public rsa key === //
+// === rsa key === //
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub struct JwkPublicRsaKey {
+/// RSA key members
+///
+/// Private members are only present in private JWKs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JwkRsaKey {
     n: String,
     e: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    d: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    p: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    q: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dq: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    qi: Option<String>,
 }
 
-impl JwkPublicRsaKey {
+/// Former name of `JwkRsaKey`, when only public keys were supported.
+pub type JwkPublicRsaKey = JwkRsaKey;
+
+impl JwkRsaKey {
     pub fn modulus(&self) -> Result<Vec<u8>, JwkError> {
-        base64::decode_config(&self.n, base64::URL_SAFE_NO_PAD).map_err(JwkError::from)
+        h_decode(&self.n)
     }
 
     pub fn public_exponent(&self) -> Result<Vec<u8>, JwkError> {
-        base64::decode_config(&self.e, base64::URL_SAFE_NO_PAD).map_err(JwkError::from)
+        h_decode(&self.e)
+    }
+
+    pub fn private_exponent(&self) -> Result<Option<Vec<u8>>, JwkError> {
+        h_decode_opt(&self.d)
+    }
+}
+
+// === ec key === //
+
+/// Elliptic curve key members
+///
+/// `d` is only present in private JWKs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JwkEcKey {
+    crv: JwkEcCurve,
+    x: String,
+    y: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    d: Option<String>,
+}
+
+impl JwkEcKey {
+    pub fn curve(&self) -> JwkEcCurve {
+        self.crv
+    }
+
+    pub fn x_coordinate(&self) -> Result<Vec<u8>, JwkError> {
+        h_decode(&self.x)
+    }
+
+    pub fn y_coordinate(&self) -> Result<Vec<u8>, JwkError> {
+        h_decode(&self.y)
+    }
+
+    /// Uncompressed SEC1 encoded point
+    pub fn encoded_point(&self) -> Result<Vec<u8>, JwkError> {
+        let mut point = vec![0x04];
+        point.extend(self.x_coordinate()?);
+        point.extend(self.y_coordinate()?);
+        Ok(point)
+    }
+
+    pub fn secret(&self) -> Result<Option<Vec<u8>>, JwkError> {
+        h_decode_opt(&self.d)
+    }
+}
+
+// === okp key === //
+
+/// Octet key pair members
+///
+/// `d` is only present in private JWKs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JwkOkpKey {
+    crv: JwkOkpCurve,
+    x: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    d: Option<String>,
+}
+
+impl JwkOkpKey {
+    pub fn curve(&self) -> JwkOkpCurve {
+        self.crv
+    }
+
+    pub fn public_key(&self) -> Result<Vec<u8>, JwkError> {
+        h_decode(&self.x)
+    }
+
+    pub fn secret(&self) -> Result<Option<Vec<u8>>, JwkError> {
+        h_decode_opt(&self.d)
     }
 }
 
+// === oct key === //
+
+/// Symmetric key
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JwkOctKey {
+    k: String,
+}
+
+impl JwkOctKey {
+    pub fn key(&self) -> Result<Vec<u8>, JwkError> {
+        h_decode(&self.k)
+    }
+}
+
+fn h_encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+fn h_decode(data: &str) -> Result<Vec<u8>, JwkError> {
+    base64::decode_config(data, base64::URL_SAFE_NO_PAD).map_err(JwkError::from)
+}
+
+fn h_decode_opt(data: &Option<String>) -> Result<Option<Vec<u8>>, JwkError> {
+    data.as_deref().map(h_decode).transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::jose::jws::JwsAlg;
+    use crate::hash::HashAlgorithm;
+    use crate::jose::jws::{JwsAlg, JwsHeader};
 
     const RSA_MODULUS: &str = "rpJjxW0nNZiq1mPC3ZAxqf9qNjmKurP7XuKrpWrfv3IOUldqChQVPNg8zCvDOMZIO-ZDuRmVH\
                                EZ5E1vz5auHNACnpl6AvDGJ-4qyX42vfUDMNZx8i86d7bQpwJkO_MVMLj8qMGmTVbQ8zqVw2z\
@@ -403,4 +886,200 @@ mod tests {
         let decoded = JwkSet::from_json(&encoded).unwrap();
         pretty_assertions::assert_eq!(decoded, expected);
     }
+
+    // RFC 7517 appendix A.2
+    const EC_PRIVATE_JWK: &str = r#"{"kty":"EC",
+        "crv":"P-256",
+        "x":"MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4",
+        "y":"4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM",
+        "d":"870MB6gfuTJ4HtUnUvYMyJpr5eUZNP4Bk43bVdj3eAE",
+        "use":"enc",
+        "kid":"1"}"#;
+
+    #[test]
+    fn ec_key() {
+        let jwk = Jwk::from_json(EC_PRIVATE_JWK).unwrap();
+        assert_eq!(jwk.key_use, Some(JwkPubKeyUse::Encryption));
+        assert_eq!(jwk.key.as_ec().unwrap().curve(), JwkEcCurve::P256);
+        assert!(jwk.key.is_private());
+
+        let private_key = jwk.to_private_key().unwrap();
+        assert_eq!(private_key.ec_curve(), Some(EcCurve::NistP256));
+        let exported = Jwk::from_private_key(&private_key).unwrap();
+        assert_eq!(exported.key, jwk.key);
+
+        let public_jwk = Jwk::from_public_key(&jwk.to_public_key().unwrap()).unwrap();
+        assert!(!public_jwk.key.is_private());
+        assert_eq!(
+            public_jwk.to_json().unwrap(),
+            r#"{"kty":"EC","crv":"P-256","x":"MKBCTNIcKUSDii11ySs3526iDZ8AiTo7Tu6KPAqv7D4","y":"4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyM"}"#
+        );
+
+        let err = public_jwk.to_private_key().err().unwrap();
+        assert_eq!(err.to_string(), "required member `d` is missing");
+    }
+
+    #[test]
+    fn ec_key_point_not_on_curve_err() {
+        let mut jwk = Jwk::from_json(EC_PRIVATE_JWK).unwrap();
+        if let JwkKeyType::Ec(ec) = &mut jwk.key {
+            ec.y = "4Etl6SRW2YiLUrN5vfvVHuhp7x8PxltmWWlbbM4IFyQ".to_owned();
+        }
+        let err = jwk.to_public_key().err().unwrap();
+        assert_eq!(err.to_string(), "key error: EC error: invalid public point");
+    }
+
+    // RFC 8037 appendix A.1
+    const OKP_PRIVATE_JWK: &str = r#"{"kty":"OKP","crv":"Ed25519",
+        "d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        "x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#;
+
+    #[test]
+    fn okp_key() {
+        let jwk = Jwk::from_json(OKP_PRIVATE_JWK).unwrap();
+        let private_key = jwk.to_private_key().unwrap();
+        assert_eq!(private_key.ed_curve(), Some(EdCurve::Ed25519));
+        assert_eq!(Jwk::from_private_key(&private_key).unwrap().key, jwk.key);
+
+        let public_jwk = Jwk::from_public_key(&private_key.to_public_key()).unwrap();
+        assert_eq!(
+            public_jwk.to_json().unwrap(),
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+    }
+
+    #[test]
+    fn okp_unsupported_curve_err() {
+        // RFC 8037 appendix A.6
+        let jwk = Jwk::from_json(r#"{"kty":"OKP","crv":"X25519","x":"hSDwCYkwp1R0i33ctD73Wg2_Og0mOBr066SpjqqbTmo"}"#)
+            .unwrap();
+        assert_eq!(jwk.key.as_okp().unwrap().curve(), JwkOkpCurve::X25519);
+        let err = jwk.to_public_key().err().unwrap();
+        assert_eq!(err.to_string(), "unsupported algorithm: X25519");
+    }
+
+    #[test]
+    fn rsa_private_key() {
+        let pem = crate::test_files::RSA_2048_PK_1.parse::<crate::pem::Pem>().unwrap();
+        let private_key = PrivateKey::from_pem(&pem).unwrap();
+
+        let jwk = Jwk::from_private_key(&private_key).unwrap();
+        let rsa = jwk.key.as_rsa().unwrap();
+        assert_ne!(rsa.modulus().unwrap()[0], 0);
+        assert!(rsa.private_exponent().unwrap().is_some());
+
+        let decoded = Jwk::from_json(&jwk.to_json().unwrap()).unwrap();
+        assert_eq!(decoded.to_private_key().unwrap(), private_key);
+        assert_eq!(decoded.to_public_key().unwrap(), private_key.to_public_key());
+
+        let public_jwk = Jwk::from_public_key(&private_key.to_public_key()).unwrap();
+        assert!(!public_jwk.key.is_private());
+        assert!(!public_jwk.to_json().unwrap().contains(r#""d""#));
+    }
+
+    #[test]
+    fn oct_key() {
+        let jwk = Jwk::from_json(r#"{"kty":"oct","k":"GawgguFyGrWKav7AX4VKUg"}"#).unwrap();
+        assert_eq!(
+            jwk.key.as_oct().unwrap().key().unwrap(),
+            base64::decode_config("GawgguFyGrWKav7AX4VKUg", base64::URL_SAFE_NO_PAD).unwrap()
+        );
+        assert_eq!(
+            jwk.key,
+            JwkKeyType::new_oct_key(&jwk.key.as_oct().unwrap().key().unwrap())
+        );
+
+        let err = jwk.to_public_key().err().unwrap();
+        assert_eq!(err.to_string(), "unsupported algorithm: octet sequence");
+    }
+
+    #[test]
+    fn rsa_thumbprint() {
+        // RFC 7638 section 3.1
+        let jwk = Jwk::new(JwkKeyType::new_rsa_key_from_base64_url(
+            "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw".to_owned(),
+            "AQAB".to_owned(),
+        ));
+        assert_eq!(
+            jwk.thumbprint_base64url(HashAlgorithm::SHA2_256),
+            "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs"
+        );
+    }
+
+    #[test]
+    fn okp_thumbprint() {
+        // RFC 8037 appendix A.3, private members are ignored
+        let jwk = Jwk::from_json(OKP_PRIVATE_JWK).unwrap();
+        assert_eq!(
+            jwk.thumbprint_base64url(HashAlgorithm::SHA2_256),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+    }
+
+    #[test]
+    fn select_for_jws() {
+        let ec_jwk = Jwk {
+            kid: Some("ec".to_owned()),
+            ..Jwk::from_json(EC_PRIVATE_JWK).unwrap()
+        };
+        let mut jwk_set = get_jwk_set();
+        jwk_set.keys.push(ec_jwk.clone());
+        jwk_set.keys.push(Jwk {
+            key_use: Some(JwkPubKeyUse::Signature),
+            ..ec_jwk
+        });
+
+        let rsa_kid = jwk_set.keys[0].kid.clone().unwrap();
+        let header = JwsHeader {
+            kid: Some(rsa_kid.clone()),
+            ..JwsHeader::new(JwsAlg::RS256)
+        };
+        assert_eq!(jwk_set.select_for_jws(&header), vec![&jwk_set.keys[0]]);
+        assert_eq!(jwk_set.find_by_kid(&rsa_kid), Some(&jwk_set.keys[0]));
+
+        // `alg` member of the JWK doesn't allow RS384
+        let header = JwsHeader {
+            kid: Some(rsa_kid),
+            ..JwsHeader::new(JwsAlg::RS384)
+        };
+        assert!(jwk_set.select_for_jws(&header).is_empty());
+
+        // falls back to `x5t` when `kid` doesn't match
+        let header = JwsHeader {
+            kid: Some("unknown".to_owned()),
+            x5t: Some(X509_SHA1_THUMBPRINT.to_owned()),
+            ..JwsHeader::new(JwsAlg::RS256)
+        };
+        assert_eq!(jwk_set.select_for_jws(&header), vec![&jwk_set.keys[0]]);
+
+        // `x5t` takes precedence over `x5t#S256`
+        jwk_set.keys.push(Jwk {
+            kid: Some("rsa-s256".to_owned()),
+            x5t: None,
+            x5t_s256: Some("other-thumbprint".to_owned()),
+            ..jwk_set.keys[0].clone()
+        });
+        let header = JwsHeader {
+            x5t: Some(X509_SHA1_THUMBPRINT.to_owned()),
+            x5t_s256: Some("other-thumbprint".to_owned()),
+            ..JwsHeader::new(JwsAlg::RS256)
+        };
+        assert_eq!(jwk_set.select_for_jws(&header), vec![&jwk_set.keys[0]]);
+
+        // falls back to `x5t#S256` when `x5t` doesn't match
+        let header = JwsHeader {
+            x5t: Some("unknown".to_owned()),
+            x5t_s256: Some("other-thumbprint".to_owned()),
+            ..JwsHeader::new(JwsAlg::RS256)
+        };
+        assert_eq!(jwk_set.select_for_jws(&header), vec![&jwk_set.keys[3]]);
+
+        // first EC key is intended for encryption only
+        let header = JwsHeader::new(JwsAlg::ES256);
+        assert_eq!(jwk_set.select_for_jws(&header), vec![&jwk_set.keys[2]]);
+
+        // curve doesn't match
+        let header = JwsHeader::new(JwsAlg::ES384);
+        assert!(jwk_set.select_for_jws(&header).is_empty());
+    }
 }
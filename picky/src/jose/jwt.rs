@@ -1,5 +1,6 @@
 use super::jwe::Jwe;
 use crate::jose::jwe::{JweAlg, JweEnc, JweError, JweHeader};
+use crate::jose::jwk::{Jwk, JwkSet};
 use crate::jose::jws::{Jws, JwsAlg, JwsError, JwsHeader};
 use crate::key::{PrivateKey, PublicKey};
 use core::fmt;
@@ -291,6 +292,16 @@ where
         let encoded = jws.encode(private_key)?;
         Ok(encoded)
     }
+
+    /// Encode using an HMAC-based algorithm (`HS256`, `HS384` or `HS512`) and a shared secret.
+    pub fn encode_hmac(self, key: &[u8]) -> Result<String, JwtError> {
+        let jws = Jws {
+            header: self.header,
+            payload: serde_json::to_vec(&self.claims)?,
+        };
+        let encoded = jws.encode_hmac(key)?;
+        Ok(encoded)
+    }
 }
 
 impl<C> Jwt<JwsHeader, C>
@@ -306,6 +317,28 @@ where
         })
     }
 
+    /// Validate using validator and the key of the JWK set selected from the header (`kid`, `x5t#S256` or `x5t`).
+    pub fn decode_with_jwk_set(
+        encoded_token: &str,
+        jwk_set: &JwkSet,
+        validator: &JwtValidator,
+    ) -> Result<Self, JwtError> {
+        let jws = Jws::decode_with_jwk_set(encoded_token, jwk_set)?;
+        Ok(Jwt {
+            header: jws.header,
+            claims: h_decode_and_validate_claims(&jws.payload, validator)?,
+        })
+    }
+
+    /// Validate using validator and shared secret. Only HMAC-based algorithms are accepted.
+    pub fn decode_hmac(encoded_token: &str, key: &[u8], validator: &JwtValidator) -> Result<Self, JwtError> {
+        let jws = Jws::decode_hmac(encoded_token, key)?;
+        Ok(Jwt {
+            header: jws.header,
+            claims: h_decode_and_validate_claims(&jws.payload, validator)?,
+        })
+    }
+
     /// Unsafe JWT decoding method. Signature isn't checked at all.
     pub fn decode_dangerous(encoded_token: &str, validator: &JwtValidator) -> Result<Self, JwtError> {
         let jws = Jws::decode_without_validation(encoded_token)?;
@@ -339,6 +372,26 @@ where
         let encoded = jwe.encode_direct(cek)?;
         Ok(encoded)
     }
+
+    /// Encode with a random CEK wrapped using the provided Key Encryption Key (AES Key Wrap algorithms).
+    pub fn encode_key_wrap(self, kek: &[u8]) -> Result<String, JwtError> {
+        let jwe = Jwe {
+            header: self.header,
+            payload: serde_json::to_vec(&self.claims)?,
+        };
+        let encoded = jwe.encode_key_wrap(kek)?;
+        Ok(encoded)
+    }
+
+    /// Encode for the recipient described by the provided JWK.
+    pub fn encode_with_jwk(self, jwk: &Jwk) -> Result<String, JwtError> {
+        let jwe = Jwe {
+            header: self.header,
+            payload: serde_json::to_vec(&self.claims)?,
+        };
+        let encoded = jwe.encode_with_jwk(jwk)?;
+        Ok(encoded)
+    }
 }
 
 impl<C> Jwt<JweHeader, C>
@@ -362,6 +415,24 @@ where
             claims: h_decode_and_validate_claims(&jwe.payload, validator)?,
         })
     }
+
+    /// Decode with CEK wrapped using the provided Key Encryption Key (AES Key Wrap algorithms).
+    pub fn decode_key_wrap(encoded_token: &str, kek: &[u8], validator: &JwtValidator) -> Result<Self, JwtError> {
+        let jwe = Jwe::decode_key_wrap(encoded_token, kek)?;
+        Ok(Jwt {
+            header: jwe.header,
+            claims: h_decode_and_validate_claims(&jwe.payload, validator)?,
+        })
+    }
+
+    /// Decode using the private or symmetric key described by the provided JWK.
+    pub fn decode_with_jwk(encoded_token: &str, jwk: &Jwk, validator: &JwtValidator) -> Result<Self, JwtError> {
+        let jwe = Jwe::decode_with_jwk(encoded_token, jwk)?;
+        Ok(Jwt {
+            header: jwe.header,
+            claims: h_decode_and_validate_claims(&jwe.payload, validator)?,
+        })
+    }
 }
 
 fn h_decode_and_validate_claims<C: DeserializeOwned>(
@@ -524,6 +595,55 @@ mod tests {
         assert_eq!(jwt.claims["iat"].as_i64().expect("iat"), 1516239022);
     }
 
+    #[test]
+    fn jws_hmac_sha256() {
+        let key = crate::hash::HashAlgorithm::SHA2_256.digest(b"magic_password");
+        let jwt = JwtSig::new(JwsAlg::HS256, get_strongly_typed_claims());
+        let encoded = jwt.encode_hmac(&key).unwrap();
+        let decoded = JwtSig::<MyClaims>::decode_hmac(&encoded, &key, &NO_CHECK_VALIDATOR).unwrap();
+        assert_eq!(decoded.claims, get_strongly_typed_claims());
+
+        let public_key = get_private_key_1().to_public_key();
+        let err = JwtSig::<MyClaims>::decode(&encoded, &public_key, &NO_CHECK_VALIDATOR)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "JWS error: HS256 algorithm can't be used with an asymmetric key"
+        );
+    }
+
+    #[test]
+    fn jws_ecdsa_and_eddsa() {
+        use crate::key::{EcCurve, EdCurve};
+
+        for (alg, private_key) in [
+            (JwsAlg::ES256, PrivateKey::generate_ec(EcCurve::NistP256).unwrap()),
+            (JwsAlg::EdDSA, PrivateKey::generate_ed(EdCurve::Ed25519).unwrap()),
+        ] {
+            let encoded = JwtSig::new(alg, get_strongly_typed_claims())
+                .encode(&private_key)
+                .unwrap();
+            let decoded =
+                JwtSig::<MyClaims>::decode(&encoded, &private_key.to_public_key(), &NO_CHECK_VALIDATOR).unwrap();
+            assert_eq!(decoded.header.alg, alg);
+            assert_eq!(decoded.claims, get_strongly_typed_claims());
+        }
+    }
+
+    #[test]
+    fn decode_jws_with_jwk_set() {
+        let jwk = crate::jose::jwk::Jwk::from_public_key(&get_private_key_1().to_public_key()).unwrap();
+        let jwk_set = JwkSet { keys: vec![jwk] };
+        let jwt = JwtSig::<MyClaims>::decode_with_jwk_set(
+            crate::test_files::JOSE_JWT_SIG_EXAMPLE,
+            &jwk_set,
+            &NO_CHECK_VALIDATOR,
+        )
+        .unwrap();
+        assert_eq!(jwt.claims, get_strongly_typed_claims());
+    }
+
     #[test]
     fn jwe_direct_aes_256_gcm() {
         let claims = get_strongly_typed_claims();
@@ -534,6 +654,27 @@ mod tests {
         assert_eq!(decoded.claims, get_strongly_typed_claims());
     }
 
+    #[test]
+    fn jwe_aes_key_wrap() {
+        let claims = get_strongly_typed_claims();
+        let kek = [42u8; 16];
+        let jwt = Jwt::new_encrypted(JweAlg::AesKeyWrap128, JweEnc::Aes128CbcHmacSha256, claims);
+        let encoded = jwt.encode_key_wrap(&kek).unwrap();
+        let decoded = Jwt::<_, MyClaims>::decode_key_wrap(&encoded, &kek, &NO_CHECK_VALIDATOR).unwrap();
+        assert_eq!(decoded.claims, get_strongly_typed_claims());
+    }
+
+    #[test]
+    fn jwe_ecdh_es_with_jwk() {
+        let private_key = PrivateKey::generate_ec(crate::key::EcCurve::NistP256).unwrap();
+        let jwk = Jwk::from_private_key(&private_key).unwrap();
+        let claims = get_strongly_typed_claims();
+        let jwt = Jwt::new_encrypted(JweAlg::EcdhEsAesKeyWrap128, JweEnc::Aes128Gcm, claims);
+        let encoded = jwt.encode_with_jwk(&jwk).unwrap();
+        let decoded = Jwt::<_, MyClaims>::decode_with_jwk(&encoded, &jwk, &NO_CHECK_VALIDATOR).unwrap();
+        assert_eq!(decoded.claims, get_strongly_typed_claims());
+    }
+
     #[derive(Serialize, Deserialize)]
     struct MyExpirableClaims {
         exp: i64,
@@ -12,3 +12,20 @@ pub mod jwe;
 pub mod jwk;
 pub mod jws;
 pub mod jwt;
+
+use serde_json::{Map, Value};
+
+/// Merges the parts of a JOSE header split across several JSON objects (JSON serialization).
+///
+/// Header parameter names must be disjoint: the first duplicated name is returned otherwise.
+pub(crate) fn merge_header_parts(parts: &[Option<&Map<String, Value>>]) -> Result<Map<String, Value>, String> {
+    let mut header = Map::new();
+
+    for (name, value) in parts.iter().flatten().flat_map(|part| part.iter()) {
+        if header.insert(name.clone(), value.clone()).is_some() {
+            return Err(name.clone());
+        }
+    }
+
+    Ok(header)
+}
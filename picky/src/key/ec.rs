@@ -0,0 +1,155 @@
+//! Elliptic curves (NIST prime curves) keys
+
+use super::{KeyError, PrivateKey, PublicKey};
+use oid::ObjectIdentifier;
+use picky_asn1::wrapper::OctetStringAsn1Container;
+use picky_asn1_x509::{oids, AlgorithmIdentifier, AlgorithmIdentifierParameters, EcParameters, PrivateKeyValue};
+
+/// Dispatch to the RustCrypto crate implementing the given curve.
+///
+/// Inside `$body`, `$m` is an alias to the `p256`, `p384` or `p521` crate.
+macro_rules! with_curve_crate {
+    ($curve:expr, $m:ident => $body:expr) => {
+        match $curve {
+            $crate::key::EcCurve::NistP256 => {
+                use p256 as $m;
+                $body
+            }
+            $crate::key::EcCurve::NistP384 => {
+                use p384 as $m;
+                $body
+            }
+            $crate::key::EcCurve::NistP521 => {
+                use p521 as $m;
+                $body
+            }
+        }
+    };
+}
+
+pub(crate) use with_curve_crate;
+
+/// Supported NIST elliptic curves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EcCurve {
+    /// NIST P-256 (secp256r1, prime256v1)
+    NistP256,
+    /// NIST P-384 (secp384r1)
+    NistP384,
+    /// NIST P-521 (secp521r1)
+    NistP521,
+}
+
+impl EcCurve {
+    pub fn oid(self) -> ObjectIdentifier {
+        match self {
+            EcCurve::NistP256 => oids::secp256r1(),
+            EcCurve::NistP384 => oids::secp384r1(),
+            EcCurve::NistP521 => oids::secp521r1(),
+        }
+    }
+
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        let oid_string: String = oid.into();
+        match oid_string.as_str() {
+            oids::SECP256R1 => Some(EcCurve::NistP256),
+            oids::SECP384R1 => Some(EcCurve::NistP384),
+            oids::SECP521R1 => Some(EcCurve::NistP521),
+            _ => None,
+        }
+    }
+
+    /// Size in bytes of a field element (and therefore of a scalar or a coordinate)
+    pub fn field_size(self) -> usize {
+        match self {
+            EcCurve::NistP256 => 32,
+            EcCurve::NistP384 => 48,
+            EcCurve::NistP521 => 66,
+        }
+    }
+
+    pub(crate) fn from_parameters(parameters: &EcParameters) -> Result<Self, KeyError> {
+        match parameters {
+            EcParameters::NamedCurve(oid) => Self::from_oid(&oid.0).ok_or(KeyError::UnsupportedAlgorithm {
+                algorithm: "unknown elliptic curve",
+            }),
+        }
+    }
+
+    pub(crate) fn from_algorithm_identifier(algorithm: &AlgorithmIdentifier) -> Result<Self, KeyError> {
+        match algorithm.parameters() {
+            AlgorithmIdentifierParameters::Ec(parameters) if algorithm.is_a(oids::ec_public_key()) => {
+                Self::from_parameters(parameters)
+            }
+            _ => Err(KeyError::UnsupportedAlgorithm {
+                algorithm: "not an elliptic curve key",
+            }),
+        }
+    }
+}
+
+impl From<EcCurve> for EcParameters {
+    fn from(curve: EcCurve) -> Self {
+        EcParameters::NamedCurve(curve.oid().into())
+    }
+}
+
+/// Computes the uncompressed SEC1 encoded public point associated to the given secret scalar.
+pub(crate) fn public_point_from_secret(curve: EcCurve, secret: &[u8]) -> Result<Vec<u8>, KeyError> {
+    with_curve_crate!(curve, m => {
+        let signing_key = m::ecdsa::SigningKey::from_slice(secret).map_err(|_| KeyError::EC {
+            context: "invalid secret scalar".to_owned(),
+        })?;
+        let verifying_key = m::ecdsa::VerifyingKey::from(&signing_key);
+        Ok(verifying_key.to_encoded_point(false).as_bytes().to_vec())
+    })
+}
+
+/// Generates a random secret scalar and returns it alongside its uncompressed SEC1 encoded public point.
+pub(crate) fn generate_secret(curve: EcCurve) -> (Vec<u8>, Vec<u8>) {
+    use rand::rngs::OsRng;
+
+    with_curve_crate!(curve, m => {
+        let signing_key = m::ecdsa::SigningKey::random(&mut OsRng);
+        let verifying_key = m::ecdsa::VerifyingKey::from(&signing_key);
+        let public_point = verifying_key.to_encoded_point(false).as_bytes().to_vec();
+        (signing_key.to_bytes().to_vec(), public_point)
+    })
+}
+
+/// Checks that the SEC1 encoded point is on the curve and returns its uncompressed form.
+pub(crate) fn decompress_public_point(curve: EcCurve, point: &[u8]) -> Result<Vec<u8>, KeyError> {
+    with_curve_crate!(curve, m => {
+        let verifying_key = m::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(|_| KeyError::EC {
+            context: "invalid public point".to_owned(),
+        })?;
+        Ok(verifying_key.to_encoded_point(false).as_bytes().to_vec())
+    })
+}
+
+pub(crate) fn private_key_components(private_key: &PrivateKey) -> Result<(EcCurve, &[u8]), KeyError> {
+    match &private_key.as_inner().private_key {
+        PrivateKeyValue::EC(OctetStringAsn1Container(key)) => {
+            let curve = EcCurve::from_algorithm_identifier(&private_key.as_inner().private_key_algorithm)?;
+            Ok((curve, key.private_key.as_slice()))
+        }
+        _ => Err(KeyError::UnsupportedAlgorithm {
+            algorithm: "not an elliptic curve key",
+        }),
+    }
+}
+
+pub(crate) fn public_key_components(public_key: &PublicKey) -> Result<(EcCurve, &[u8]), KeyError> {
+    use picky_asn1_x509::PublicKey as InnerPublicKey;
+
+    let spki = public_key.as_inner();
+    match &spki.subject_public_key {
+        InnerPublicKey::Ec(point) => {
+            let curve = EcCurve::from_algorithm_identifier(&spki.algorithm)?;
+            Ok((curve, point.payload_view()))
+        }
+        _ => Err(KeyError::UnsupportedAlgorithm {
+            algorithm: "not an elliptic curve key",
+        }),
+    }
+}
@@ -0,0 +1,133 @@
+//! Edwards curves (EdDSA) keys
+
+use super::{KeyError, PrivateKey, PublicKey};
+use oid::ObjectIdentifier;
+use picky_asn1::wrapper::OctetStringAsn1Container;
+use picky_asn1_x509::{oids, AlgorithmIdentifier, PrivateKeyValue};
+
+/// Supported Edwards curves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdCurve {
+    /// Ed25519 (edwards25519)
+    Ed25519,
+}
+
+impl EdCurve {
+    pub fn oid(self) -> ObjectIdentifier {
+        match self {
+            EdCurve::Ed25519 => oids::ed25519(),
+        }
+    }
+
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        let oid_string: String = oid.into();
+        match oid_string.as_str() {
+            oids::ED25519 => Some(EdCurve::Ed25519),
+            _ => None,
+        }
+    }
+
+    /// Size in bytes of both secret and public keys
+    pub fn key_size(self) -> usize {
+        match self {
+            EdCurve::Ed25519 => ed25519_dalek::SECRET_KEY_LENGTH,
+        }
+    }
+
+    pub(crate) fn from_algorithm_identifier(algorithm: &AlgorithmIdentifier) -> Result<Self, KeyError> {
+        Self::from_oid(algorithm.oid()).ok_or(KeyError::UnsupportedAlgorithm {
+            algorithm: "not an edwards curve key",
+        })
+    }
+
+    pub(crate) fn to_algorithm_identifier(self) -> AlgorithmIdentifier {
+        match self {
+            EdCurve::Ed25519 => AlgorithmIdentifier::new_ed25519(),
+        }
+    }
+
+    pub(crate) fn check_key_size(self, key: &[u8]) -> Result<(), KeyError> {
+        if key.len() == self.key_size() {
+            Ok(())
+        } else {
+            Err(KeyError::ED {
+                context: format!(
+                    "invalid {:?} key size: expected {}, got {}",
+                    self,
+                    self.key_size(),
+                    key.len()
+                ),
+            })
+        }
+    }
+}
+
+/// Computes the encoded public key associated to the given secret key.
+pub(crate) fn public_key_from_secret(curve: EdCurve, secret: &[u8]) -> Result<Vec<u8>, KeyError> {
+    curve.check_key_size(secret)?;
+
+    match curve {
+        EdCurve::Ed25519 => {
+            let signing_key =
+                ed25519_dalek::SigningKey::try_from(secret).map_err(|e| KeyError::ED { context: e.to_string() })?;
+            Ok(signing_key.verifying_key().to_bytes().to_vec())
+        }
+    }
+}
+
+/// Generates a random secret key and returns it alongside its encoded public key.
+pub(crate) fn generate_secret(curve: EdCurve) -> Result<(Vec<u8>, Vec<u8>), KeyError> {
+    use rand::rngs::OsRng;
+
+    match curve {
+        EdCurve::Ed25519 => {
+            let signing_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+            let public_key = signing_key.verifying_key().to_bytes().to_vec();
+            Ok((signing_key.to_bytes().to_vec(), public_key))
+        }
+    }
+}
+
+/// Checks that the encoded public key is a valid point on the curve.
+pub(crate) fn check_public_key(curve: EdCurve, public_key: &[u8]) -> Result<(), KeyError> {
+    curve.check_key_size(public_key)?;
+
+    let is_valid = match curve {
+        EdCurve::Ed25519 => ed25519_dalek::VerifyingKey::try_from(public_key).is_ok(),
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(KeyError::ED {
+            context: "invalid public key".to_owned(),
+        })
+    }
+}
+
+pub(crate) fn private_key_components(private_key: &PrivateKey) -> Result<(EdCurve, &[u8]), KeyError> {
+    match &private_key.as_inner().private_key {
+        PrivateKeyValue::ED(OctetStringAsn1Container(secret)) => {
+            let curve = EdCurve::from_algorithm_identifier(&private_key.as_inner().private_key_algorithm)?;
+            Ok((curve, secret.as_slice()))
+        }
+        _ => Err(KeyError::UnsupportedAlgorithm {
+            algorithm: "not an edwards curve key",
+        }),
+    }
+}
+
+pub(crate) fn public_key_components(public_key: &PublicKey) -> Result<(EdCurve, &[u8]), KeyError> {
+    use picky_asn1_x509::PublicKey as InnerPublicKey;
+
+    let spki = public_key.as_inner();
+    match &spki.subject_public_key {
+        InnerPublicKey::Ed(key) => {
+            let curve = EdCurve::from_algorithm_identifier(&spki.algorithm)?;
+            Ok((curve, key.payload_view()))
+        }
+        _ => Err(KeyError::UnsupportedAlgorithm {
+            algorithm: "not an edwards curve key",
+        }),
+    }
+}
@@ -29,6 +29,11 @@ mod test_files {
     pub const RSA_2048_PK_1: &str = include_str!("../../test_assets/private_keys/rsa-2048-pk_1.key");
     pub const RSA_2048_PK_7: &str = include_str!("../../test_assets/private_keys/rsa-2048-pk_7.key");
     pub const RSA_4096_PK_3: &str = include_str!("../../test_assets/private_keys/rsa-4096-pk_3.key");
+    pub const EC_P256_PK: &str = include_str!("../../test_assets/private_keys/ec-p256-pk.key");
+    pub const EC_P256_SEC1_PK: &str = include_str!("../../test_assets/private_keys/ec-p256-sec1.key");
+    pub const EC_P384_PK: &str = include_str!("../../test_assets/private_keys/ec-p384-pk.key");
+    pub const EC_P521_PK: &str = include_str!("../../test_assets/private_keys/ec-p521-pk.key");
+    pub const ED25519_PK: &str = include_str!("../../test_assets/private_keys/ed25519-pk.key");
 
     cfg_if::cfg_if! { if  #[cfg(feature = "pkcs7")]  {
         pub const PKCS7: &str = include_str!("../../test_assets/pkcs7.p7b");
@@ -48,10 +53,13 @@ mod test_files {
 
         pub const INTERMEDIATE_CA: &str = include_str!("../../test_assets/intermediate_ca.crt");
         pub const ROOT_CA: &str = include_str!("../../test_assets/root_ca.crt");
+        pub const RSA_PSS_ROOT_CA: &str = include_str!("../../test_assets/rsa_pss_root_ca.crt");
 
         pub const PSDIAG_ROOT: &str = include_str!("../../test_assets/authenticode-psdiagnostics/1_psdiag_root.pem");
         pub const PSDIAG_INTER: &str = include_str!("../../test_assets/authenticode-psdiagnostics/2_psdiag_inter.pem");
         pub const PSDIAG_LEAF: &str = include_str!("../../test_assets/authenticode-psdiagnostics/3_psdiag_leaf.pem");
+
+        pub const MKCERT_ALL_ROOT_CA: &str = include_str!("../../test_assets/mkcert_all_root_ca_2019_10.txt");
     }}
 
     cfg_if::cfg_if! { if #[cfg(feature = "jose")] {
@@ -1,9 +1,14 @@
 //! Signature algorithms supported by picky
 
 use crate::hash::HashAlgorithm;
-use crate::key::{KeyError, PrivateKey, PublicKey};
-use picky_asn1_x509::{oids, AlgorithmIdentifier};
-use rsa::{PublicKey as _, RsaPrivateKey, RsaPublicKey};
+use crate::key::ec::with_curve_crate;
+use crate::key::{ec, ed, EdCurve, KeyError, PrivateKey, PublicKey};
+use picky_asn1_x509::{
+    oids, AlgorithmIdentifier, AlgorithmIdentifierParameters, MaskGenAlgorithm, RsassaPssParameters, ShaVariant,
+};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rsa::{BigUint, PublicKey as _, PublicKeyParts, RsaPrivateKey, RsaPublicKey};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -18,6 +23,10 @@ pub enum SignatureError {
     #[error("RSA error: {context}")]
     Rsa { context: String },
 
+    /// EC error
+    #[error("EC error: {context}")]
+    Ec { context: String },
+
     /// invalid signature
     #[error("invalid signature")]
     BadSignature,
@@ -39,11 +48,98 @@ impl From<KeyError> for SignatureError {
     }
 }
 
+/// RSASSA-PSS parameters
+///
+/// Only MGF1 mask generation function and `0xBC` trailer field are defined by
+/// [RFC 8017](https://tools.ietf.org/html/rfc8017#section-9.1) and thus supported.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RsaPssParams {
+    /// Hash algorithm applied to the message
+    pub hash_algorithm: HashAlgorithm,
+    /// Hash algorithm used by the MGF1 mask generation function
+    pub mgf_hash_algorithm: HashAlgorithm,
+    /// Salt length in bytes
+    pub salt_length: usize,
+}
+
+impl RsaPssParams {
+    /// Uses the same hash algorithm for MGF1 and a salt as long as the hash output.
+    ///
+    /// This is the usual choice and the one mandated by JWS `PS256`, `PS384` and `PS512` algorithms.
+    pub fn new(hash_algorithm: HashAlgorithm) -> Self {
+        Self {
+            hash_algorithm,
+            mgf_hash_algorithm: hash_algorithm,
+            salt_length: hash_algorithm.output_size(),
+        }
+    }
+}
+
+impl TryFrom<&'_ RsassaPssParameters> for RsaPssParams {
+    type Error = SignatureError;
+
+    fn try_from(v: &RsassaPssParameters) -> Result<Self, Self::Error> {
+        fn hash_algorithm_from_identifier(identifier: &AlgorithmIdentifier) -> Result<HashAlgorithm, SignatureError> {
+            ShaVariant::try_from(identifier.oid_asn1().clone())
+                .map_err(|e| SignatureError::UnsupportedAlgorithm { algorithm: e.algorithm })
+                .and_then(|variant| {
+                    HashAlgorithm::try_from(variant)
+                        .map_err(|e| SignatureError::UnsupportedAlgorithm { algorithm: e.algorithm })
+                })
+        }
+
+        if !v.mask_gen_algorithm.algorithm.0.eq(&oids::id_mgf1()) {
+            return Err(SignatureError::UnsupportedAlgorithm {
+                algorithm: format!(
+                    "RSASSA-PSS with mask generation function {}",
+                    Into::<String>::into(&v.mask_gen_algorithm.algorithm.0)
+                ),
+            });
+        }
+
+        if v.trailer_field != RsassaPssParameters::TRAILER_FIELD_BC {
+            return Err(SignatureError::UnsupportedAlgorithm {
+                algorithm: format!("RSASSA-PSS with trailer field {}", v.trailer_field),
+            });
+        }
+
+        Ok(Self {
+            hash_algorithm: hash_algorithm_from_identifier(&v.hash_algorithm)?,
+            mgf_hash_algorithm: hash_algorithm_from_identifier(&v.mask_gen_algorithm.hash_algorithm)?,
+            salt_length: v.salt_length as usize,
+        })
+    }
+}
+
+impl TryFrom<RsaPssParams> for RsassaPssParameters {
+    type Error = SignatureError;
+
+    fn try_from(v: RsaPssParams) -> Result<Self, Self::Error> {
+        let hash = ShaVariant::try_from(v.hash_algorithm)
+            .map_err(|e| SignatureError::UnsupportedAlgorithm { algorithm: e.algorithm })?;
+        let mgf_hash = ShaVariant::try_from(v.mgf_hash_algorithm)
+            .map_err(|e| SignatureError::UnsupportedAlgorithm { algorithm: e.algorithm })?;
+        let salt_length = u32::try_from(v.salt_length).map_err(|_| SignatureError::UnsupportedAlgorithm {
+            algorithm: format!("RSASSA-PSS with a salt length of {}", v.salt_length),
+        })?;
+
+        Ok(Self {
+            hash_algorithm: AlgorithmIdentifier::new_sha(hash),
+            mask_gen_algorithm: MaskGenAlgorithm::new_mgf1(mgf_hash),
+            salt_length,
+            trailer_field: RsassaPssParameters::TRAILER_FIELD_BC,
+        })
+    }
+}
+
 /// Supported signature algorithms
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum SignatureAlgorithm {
     RsaPkcs1v15(HashAlgorithm),
+    RsaPss(RsaPssParams),
+    Ecdsa(HashAlgorithm),
+    Ed25519,
 }
 
 impl TryFrom<&'_ AlgorithmIdentifier> for SignatureAlgorithm {
@@ -60,14 +156,26 @@ impl TryFrom<&'_ AlgorithmIdentifier> for SignatureAlgorithm {
             oids::SHA512_WITH_RSA_ENCRYPTION => Ok(Self::RsaPkcs1v15(HashAlgorithm::SHA2_512)),
             oids::ID_RSASSA_PKCS1_V1_5_WITH_SHA3_384 => Ok(Self::RsaPkcs1v15(HashAlgorithm::SHA3_384)),
             oids::ID_RSASSA_PKCS1_V1_5_WITH_SHA3_512 => Ok(Self::RsaPkcs1v15(HashAlgorithm::SHA3_512)),
+            oids::RSASSA_PSS => match v.parameters() {
+                AlgorithmIdentifierParameters::RsassaPss(params) => Ok(Self::RsaPss(params.as_ref().try_into()?)),
+                _ => Err(SignatureError::UnsupportedAlgorithm {
+                    algorithm: "RSASSA-PSS without parameters".to_owned(),
+                }),
+            },
+            oids::ECDSA_WITH_SHA256 => Ok(Self::Ecdsa(HashAlgorithm::SHA2_256)),
+            oids::ECDSA_WITH_SHA384 => Ok(Self::Ecdsa(HashAlgorithm::SHA2_384)),
+            oids::ECDSA_WITH_SHA512 => Ok(Self::Ecdsa(HashAlgorithm::SHA2_512)),
+            oids::ED25519 => Ok(Self::Ed25519),
             _ => Err(SignatureError::UnsupportedAlgorithm { algorithm: oid_string }),
         }
     }
 }
 
-impl From<SignatureAlgorithm> for AlgorithmIdentifier {
-    fn from(ty: SignatureAlgorithm) -> Self {
-        match ty {
+impl TryFrom<SignatureAlgorithm> for AlgorithmIdentifier {
+    type Error = SignatureError;
+
+    fn try_from(ty: SignatureAlgorithm) -> Result<Self, Self::Error> {
+        let algorithm_identifier = match ty {
             SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::MD5) => AlgorithmIdentifier::new_md5_with_rsa_encryption(),
             SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA1) => AlgorithmIdentifier::new_sha1_with_rsa_encryption(),
             SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_224) => {
@@ -88,7 +196,19 @@ impl From<SignatureAlgorithm> for AlgorithmIdentifier {
             SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA3_512) => {
                 AlgorithmIdentifier::new_sha3_512_with_rsa_encryption()
             }
-        }
+            SignatureAlgorithm::RsaPss(params) => AlgorithmIdentifier::new_rsassa_pss(params.try_into()?),
+            SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_256) => AlgorithmIdentifier::new_ecdsa_with_sha256(),
+            SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_384) => AlgorithmIdentifier::new_ecdsa_with_sha384(),
+            SignatureAlgorithm::Ecdsa(HashAlgorithm::SHA2_512) => AlgorithmIdentifier::new_ecdsa_with_sha512(),
+            SignatureAlgorithm::Ecdsa(hash_algo) => {
+                return Err(SignatureError::UnsupportedAlgorithm {
+                    algorithm: format!("ECDSA with {:?}", hash_algo),
+                })
+            }
+            SignatureAlgorithm::Ed25519 => AlgorithmIdentifier::new_ed25519(),
+        };
+
+        Ok(algorithm_identifier)
     }
 }
 
@@ -104,7 +224,40 @@ impl SignatureAlgorithm {
                 let digest = picky_hash_algo.digest(msg);
                 let rsa_hash_algo = rsa::Hash::from(picky_hash_algo);
                 let padding_scheme = rsa::PaddingScheme::new_pkcs1v15_sign(Some(rsa_hash_algo));
-                rsa_private_key.sign_blinded(&mut rand::rngs::OsRng, padding_scheme, &digest)?
+                rsa_private_key.sign_blinded(&mut OsRng, padding_scheme, &digest)?
+            }
+            SignatureAlgorithm::RsaPss(params) => {
+                let rsa_private_key = RsaPrivateKey::try_from(private_key)?;
+                rsa_pss_sign(params, &rsa_private_key, msg)?
+            }
+            SignatureAlgorithm::Ecdsa(picky_hash_algo) => {
+                use p256::ecdsa::signature::hazmat::PrehashSigner;
+
+                let (curve, secret) = ec::private_key_components(private_key)?;
+                let digest = picky_hash_algo.digest(msg);
+                with_curve_crate!(curve, m => {
+                    let signing_key = m::ecdsa::SigningKey::from_slice(secret).map_err(|e| SignatureError::Ec {
+                        context: e.to_string(),
+                    })?;
+                    let signature: m::ecdsa::Signature = signing_key
+                        .sign_prehash(&digest)
+                        .map_err(|e| SignatureError::Ec { context: e.to_string() })?;
+                    signature.to_der().as_bytes().to_vec()
+                })
+            }
+            SignatureAlgorithm::Ed25519 => {
+                use ed25519_dalek::Signer;
+
+                let (curve, secret) = ed::private_key_components(private_key)?;
+                match curve {
+                    EdCurve::Ed25519 => {
+                        let signing_key =
+                            ed25519_dalek::SigningKey::try_from(secret).map_err(|e| SignatureError::Key {
+                                source: KeyError::ED { context: e.to_string() },
+                            })?;
+                        signing_key.sign(msg).to_bytes().to_vec()
+                    }
+                }
             }
         };
 
@@ -122,14 +275,220 @@ impl SignatureAlgorithm {
                     .verify(padding_scheme, &digest, signature)
                     .map_err(|_| SignatureError::BadSignature)?;
             }
+            SignatureAlgorithm::RsaPss(params) => {
+                let rsa_public_key = RsaPublicKey::try_from(public_key)?;
+                rsa_pss_verify(params, &rsa_public_key, msg, signature)?;
+            }
+            SignatureAlgorithm::Ecdsa(picky_hash_algo) => {
+                use p256::ecdsa::signature::hazmat::PrehashVerifier;
+
+                let (curve, point) = ec::public_key_components(public_key)?;
+                let digest = picky_hash_algo.digest(msg);
+                with_curve_crate!(curve, m => {
+                    let verifying_key = m::ecdsa::VerifyingKey::from_sec1_bytes(point).map_err(|e| SignatureError::Ec {
+                        context: e.to_string(),
+                    })?;
+                    let signature = m::ecdsa::Signature::from_der(signature).map_err(|_| SignatureError::BadSignature)?;
+                    verifying_key
+                        .verify_prehash(&digest, &signature)
+                        .map_err(|_| SignatureError::BadSignature)?;
+                })
+            }
+            SignatureAlgorithm::Ed25519 => {
+                let (curve, public_key) = ed::public_key_components(public_key)?;
+                match curve {
+                    EdCurve::Ed25519 => {
+                        let verifying_key =
+                            ed25519_dalek::VerifyingKey::try_from(public_key).map_err(|e| SignatureError::Key {
+                                source: KeyError::ED { context: e.to_string() },
+                            })?;
+                        let signature = ed25519_dalek::Signature::from_slice(signature)
+                            .map_err(|_| SignatureError::BadSignature)?;
+                        verifying_key
+                            .verify_strict(msg, &signature)
+                            .map_err(|_| SignatureError::BadSignature)?;
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    pub fn hash_algorithm(&self) -> HashAlgorithm {
+    /// Returns the hash algorithm applied to the message before signing.
+    ///
+    /// EdDSA algorithms hash the message as part of the signature scheme and return `None`.
+    pub fn hash_algorithm(&self) -> Option<HashAlgorithm> {
         match &self {
-            SignatureAlgorithm::RsaPkcs1v15(hash_algo) => *hash_algo,
+            SignatureAlgorithm::RsaPkcs1v15(hash_algo) => Some(*hash_algo),
+            SignatureAlgorithm::RsaPss(params) => Some(params.hash_algorithm),
+            SignatureAlgorithm::Ecdsa(hash_algo) => Some(*hash_algo),
+            SignatureAlgorithm::Ed25519 => None,
+        }
+    }
+}
+
+// RSASSA-PSS as described in RFC 8017 section 8.1.
+// Padding is implemented here because `rsa` crate doesn't support a MGF1 hash function
+// different from the message hash function nor enforces the salt length on verification.
+
+fn rsa_pss_sign(params: RsaPssParams, private_key: &RsaPrivateKey, msg: &[u8]) -> Result<Vec<u8>, SignatureError> {
+    let mut salt = vec![0; params.salt_length];
+    OsRng.fill_bytes(&mut salt);
+
+    let em_bits = private_key.n().bits() - 1;
+    let em = emsa_pss_encode(params, &params.hash_algorithm.digest(msg), &salt, em_bits)?;
+
+    let signature = rsa::internals::decrypt_and_check(Some(&mut OsRng), private_key, &BigUint::from_bytes_be(&em))?;
+
+    Ok(rsa::internals::left_pad(&signature.to_bytes_be(), private_key.size()))
+}
+
+fn rsa_pss_verify(
+    params: RsaPssParams,
+    public_key: &RsaPublicKey,
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<(), SignatureError> {
+    if signature.len() != public_key.size() {
+        return Err(SignatureError::BadSignature);
+    }
+
+    let signature = BigUint::from_bytes_be(signature);
+    if &signature >= public_key.n() {
+        return Err(SignatureError::BadSignature);
+    }
+
+    let em_bits = public_key.n().bits() - 1;
+    let em_len = (em_bits + 7) / 8;
+    let em = rsa::internals::encrypt(public_key, &signature).to_bytes_be();
+    if em.len() > em_len {
+        return Err(SignatureError::BadSignature);
+    }
+
+    emsa_pss_verify(
+        params,
+        &params.hash_algorithm.digest(msg),
+        &rsa::internals::left_pad(&em, em_len),
+        em_bits,
+    )
+}
+
+const PSS_TRAILER_FIELD: u8 = 0xBC;
+
+fn emsa_pss_encode(
+    params: RsaPssParams,
+    m_hash: &[u8],
+    salt: &[u8],
+    em_bits: usize,
+) -> Result<Vec<u8>, SignatureError> {
+    let h_len = params.hash_algorithm.output_size();
+    let em_len = (em_bits + 7) / 8;
+
+    if em_len < h_len + salt.len() + 2 {
+        return Err(SignatureError::Rsa {
+            context: "key is too small for the RSASSA-PSS parameters".to_owned(),
+        });
+    }
+
+    let h = params.hash_algorithm.digest(&[&[0u8; 8][..], m_hash, salt].concat());
+
+    let db_len = em_len - h_len - 1;
+    let mut db = vec![0; db_len];
+    db[db_len - salt.len() - 1] = 0x01;
+    db[db_len - salt.len()..].copy_from_slice(salt);
+
+    mgf1_xor(params.mgf_hash_algorithm, &h, &mut db);
+    db[0] &= 0xFF >> (8 * em_len - em_bits);
+
+    let mut em = db;
+    em.extend_from_slice(&h);
+    em.push(PSS_TRAILER_FIELD);
+
+    Ok(em)
+}
+
+fn emsa_pss_verify(params: RsaPssParams, m_hash: &[u8], em: &[u8], em_bits: usize) -> Result<(), SignatureError> {
+    let h_len = params.hash_algorithm.output_size();
+    let s_len = params.salt_length;
+    let em_len = em.len();
+
+    if em_len < h_len + s_len + 2 || em[em_len - 1] != PSS_TRAILER_FIELD {
+        return Err(SignatureError::BadSignature);
+    }
+
+    let (masked_db, h) = em[..em_len - 1].split_at(em_len - h_len - 1);
+
+    let unused_bits_mask = !(0xFF >> (8 * em_len - em_bits));
+    if masked_db[0] & unused_bits_mask != 0 {
+        return Err(SignatureError::BadSignature);
+    }
+
+    let mut db = masked_db.to_vec();
+    mgf1_xor(params.mgf_hash_algorithm, h, &mut db);
+    db[0] &= !unused_bits_mask;
+
+    let (padding, salt) = db.split_at(db.len() - s_len);
+    let (zeroes, separator) = padding.split_at(padding.len() - 1);
+    if zeroes.iter().any(|&b| b != 0) || separator[0] != 0x01 {
+        return Err(SignatureError::BadSignature);
+    }
+
+    let expected_h = params.hash_algorithm.digest(&[&[0u8; 8][..], m_hash, salt].concat());
+    if expected_h.as_slice() != h {
+        return Err(SignatureError::BadSignature);
+    }
+
+    Ok(())
+}
+
+/// XORs `out` with the MGF1 mask generated from `seed`
+fn mgf1_xor(hash_algorithm: HashAlgorithm, seed: &[u8], out: &mut [u8]) {
+    let mask = (0u32..)
+        .flat_map(|counter| hash_algorithm.digest(&[seed, &counter.to_be_bytes()].concat()))
+        .take(out.len());
+
+    out.iter_mut().zip(mask).for_each(|(b, m)| *b ^= m);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pem::Pem;
+
+    #[test]
+    fn rsa_pss_parameters_are_enforced() {
+        let pem = crate::test_files::RSA_2048_PK_1.parse::<Pem>().unwrap();
+        let private_key = PrivateKey::from_pem(&pem).unwrap();
+        let public_key = private_key.to_public_key();
+
+        let params = RsaPssParams {
+            mgf_hash_algorithm: HashAlgorithm::SHA1,
+            ..RsaPssParams::new(HashAlgorithm::SHA2_384)
+        };
+        let signature = SignatureAlgorithm::RsaPss(params)
+            .sign(b"message", &private_key)
+            .unwrap();
+        SignatureAlgorithm::RsaPss(params)
+            .verify(&public_key, b"message", &signature)
+            .unwrap();
+
+        let other_params = [
+            RsaPssParams::new(HashAlgorithm::SHA2_384),
+            RsaPssParams {
+                salt_length: 20,
+                ..params
+            },
+            RsaPssParams {
+                hash_algorithm: HashAlgorithm::SHA2_512,
+                ..params
+            },
+        ];
+        for other_params in other_params {
+            let err = SignatureAlgorithm::RsaPss(other_params)
+                .verify(&public_key, b"message", &signature)
+                .expect_err("signature verification should fail");
+            assert!(matches!(err, SignatureError::BadSignature));
         }
     }
 }
@@ -618,6 +618,12 @@ impl<'a> SshCertificateBuilder<'a> {
                             )))
                         }
                     },
+                    _ => {
+                        return Err(SshCertificateGenerationError::IncorrectSignatureAlgorithm(format!(
+                            "Only RSA signature algorithms are supported for RSA keys, but got {:?}",
+                            signature_algo
+                        )))
+                    }
                 };
 
                 let signature = signature_algo.sign(&raw_signature, rsa)?;
@@ -3,19 +3,30 @@ use crate::hash::HashAlgorithm;
 use crate::key::{PrivateKey, PublicKey};
 use crate::pem::{Pem, PemError};
 use crate::signature::{SignatureAlgorithm, SignatureError};
+use crate::x509::crl::Crl;
 use crate::x509::csr::{Csr, CsrError};
 use crate::x509::date::UTCDate;
 use crate::x509::key_id_gen_method::{KeyIdGenError, KeyIdGenMethod};
-use crate::x509::name::{DirectoryName, GeneralNames};
+use crate::x509::name::{DirectoryName, GeneralName, GeneralNames};
+use crate::x509::policy::{verify_policies, PolicyInputs};
+use crate::x509::trust_store::TrustStore;
+use oid::ObjectIdentifier;
 use picky_asn1::bit_string::BitString;
+use picky_asn1::restricted_string::IA5String;
 use picky_asn1::wrapper::{ExplicitContextTag0, ExplicitContextTag3, IntegerAsn1};
 use picky_asn1_der::{Asn1DerError, Asn1RawDer};
 use picky_asn1_x509::{
-    oids, AlgorithmIdentifier, AuthorityKeyIdentifier, BasicConstraints, Certificate, ExtendedKeyUsage, Extension,
-    ExtensionView, Extensions, KeyIdentifier, KeyUsage, Name, SubjectPublicKeyInfo, TbsCertificate, Validity, Version,
+    oids, AccessDescription, AlgorithmIdentifier, AttributeTypeAndValueParameters, AuthorityInfoAccess,
+    AuthorityKeyIdentifier, BasicConstraints, Certificate, CertificatePolicies, CrlDistributionPoints,
+    DistributionPoint, DistributionPointName, ExtendedKeyUsage, Extension, ExtensionView, Extensions,
+    GeneralName as SerdeGeneralName, GeneralSubtree, KeyIdentifier, KeyUsage, Name, NameConstraints, PolicyConstraints,
+    PolicyMappings, SubjectPublicKeyInfo, TbsCertificate, Validity, Version,
 };
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::IpAddr;
 use thiserror::Error;
 
 const ELEMENT_NAME: &str = "x509 certificate";
@@ -114,6 +125,42 @@ pub enum CaChainError {
     /// issuer name doesn't match
     #[error("issuer name doesn't match (expected: {expected}, got: {actual})")]
     IssuerNameMismatch { expected: String, actual: String },
+
+    /// certificate is listed in a CRL of its issuer
+    #[error("certificate '{cert_id}' has been revoked by its issuer (revocation date: {revocation_date})")]
+    Revoked { cert_id: String, revocation_date: UTCDate },
+
+    /// a name of the certificate is not allowed by the name constraints extension of an issuer
+    #[error("certificate '{cert_id}' violates name constraints of '{issuer_id}' (name: {name})")]
+    NameConstraintViolation {
+        cert_id: String,
+        issuer_id: String,
+        name: String,
+    },
+
+    /// certificate policies processing failed
+    #[error("invalid policy for certificate '{cert_id}': {reason}")]
+    InvalidPolicy { cert_id: String, reason: &'static str },
+
+    /// chain doesn't lead to a trust anchor of the trust store
+    #[error("certificate '{cert_id}' is not trusted and not issued by a trusted certificate")]
+    NotTrusted { cert_id: String },
+
+    /// issuer certificate key usage doesn't allow certificate signing
+    #[error("issuer certificate '{issuer_id}' is not allowed to sign certificates (keyCertSign key usage is missing)")]
+    MissingKeyCertSign { issuer_id: String },
+
+    /// key usage of the certificate doesn't allow the required purpose
+    #[error("key usage of certificate '{cert_id}' doesn't allow {purpose}")]
+    KeyUsageNotAllowed { cert_id: String, purpose: KeyPurpose },
+
+    /// extended key usage of the certificate doesn't allow the required purpose
+    #[error("extended key usage of certificate '{cert_id}' doesn't allow {purpose}")]
+    ExtendedKeyUsageNotAllowed { cert_id: String, purpose: KeyPurpose },
+
+    /// extended key usage of an issuer certificate doesn't allow the required purpose
+    #[error("extended key usage of issuer certificate '{issuer_id}' doesn't allow {purpose}")]
+    ExtendedKeyUsageNotNested { issuer_id: String, purpose: KeyPurpose },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -124,6 +171,49 @@ pub enum CertType {
     Unknown,
 }
 
+/// Purpose a leaf certificate is validated for (see `CertValidator::require_purpose`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPurpose {
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    TimeStamping,
+}
+
+impl KeyPurpose {
+    pub fn oid(self) -> ObjectIdentifier {
+        match self {
+            KeyPurpose::ServerAuth => oids::kp_server_auth(),
+            KeyPurpose::ClientAuth => oids::kp_client_auth(),
+            KeyPurpose::CodeSigning => oids::kp_code_signing(),
+            KeyPurpose::TimeStamping => oids::kp_time_stamping(),
+        }
+    }
+
+    /// Key usage bits consistent with this purpose (RFC 5280 #4.2.1.12)
+    fn is_allowed_by(self, key_usage: &KeyUsage) -> bool {
+        match self {
+            KeyPurpose::ServerAuth => {
+                key_usage.digital_signature() || key_usage.key_encipherment() || key_usage.key_agreement()
+            }
+            KeyPurpose::ClientAuth => key_usage.digital_signature() || key_usage.key_agreement(),
+            KeyPurpose::CodeSigning => key_usage.digital_signature(),
+            KeyPurpose::TimeStamping => key_usage.digital_signature() || key_usage.content_commitment(),
+        }
+    }
+}
+
+impl fmt::Display for KeyPurpose {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyPurpose::ServerAuth => write!(f, "server authentication"),
+            KeyPurpose::ClientAuth => write!(f, "client authentication"),
+            KeyPurpose::CodeSigning => write!(f, "code signing"),
+            KeyPurpose::TimeStamping => write!(f, "time stamping"),
+        }
+    }
+}
+
 const CERT_PEM_LABEL: &str = "CERTIFICATE";
 
 /// CertificateOverview is used to validate signatures (using tbs_certificate der encoding) and encode back original certificate as is.
@@ -272,6 +362,139 @@ impl Cert {
         }
     }
 
+    pub fn key_usage(&self) -> Result<&KeyUsage, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::key_usage(), certificate, "key usage")?;
+        match ext.extn_value() {
+            ExtensionView::KeyUsage(ku) => Ok(ku),
+            _ => unreachable!("invalid extension (expected key usage)"),
+        }
+    }
+
+    pub fn extended_key_usage(&self) -> Result<&ExtendedKeyUsage, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::extended_key_usage(), certificate, "extended key usage")?;
+        match ext.extn_value() {
+            ExtensionView::ExtendedKeyUsage(eku) => Ok(eku),
+            _ => unreachable!("invalid extension (expected extended key usage)"),
+        }
+    }
+
+    pub fn name_constraints(&self) -> Result<&NameConstraints, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::name_constraints(), certificate, "name constraints")?;
+        match ext.extn_value() {
+            ExtensionView::NameConstraints(nc) => Ok(nc),
+            _ => unreachable!("invalid extension (expected name constraints)"),
+        }
+    }
+
+    pub fn certificate_policies(&self) -> Result<&CertificatePolicies, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::certificate_policies(), certificate, "certificate policies")?;
+        match ext.extn_value() {
+            ExtensionView::CertificatePolicies(policies) => Ok(policies),
+            _ => unreachable!("invalid extension (expected certificate policies)"),
+        }
+    }
+
+    pub fn policy_mappings(&self) -> Result<&PolicyMappings, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::policy_mappings(), certificate, "policy mappings")?;
+        match ext.extn_value() {
+            ExtensionView::PolicyMappings(mappings) => Ok(mappings),
+            _ => unreachable!("invalid extension (expected policy mappings)"),
+        }
+    }
+
+    pub fn policy_constraints(&self) -> Result<&PolicyConstraints, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::policy_constraints(), certificate, "policy constraints")?;
+        match ext.extn_value() {
+            ExtensionView::PolicyConstraints(constraints) => Ok(constraints),
+            _ => unreachable!("invalid extension (expected policy constraints)"),
+        }
+    }
+
+    pub fn inhibit_any_policy(&self) -> Result<u8, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::inhibit_any_policy(), certificate, "inhibit any policy")?;
+        match ext.extn_value() {
+            ExtensionView::InhibitAnyPolicy(skip_certs) => Ok(skip_certs),
+            _ => unreachable!("invalid extension (expected inhibit any policy)"),
+        }
+    }
+
+    pub fn crl_distribution_points(&self) -> Result<&CrlDistributionPoints, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::crl_distribution_points(), certificate, "crl distribution points")?;
+        match ext.extn_value() {
+            ExtensionView::CrlDistributionPoints(distribution_points) => Ok(distribution_points),
+            _ => unreachable!("invalid extension (expected crl distribution points)"),
+        }
+    }
+
+    pub fn authority_info_access(&self) -> Result<&AuthorityInfoAccess, CertError> {
+        let certificate = &self.details;
+        let ext = find_ext!(oids::authority_info_access(), certificate, "authority info access")?;
+        match ext.extn_value() {
+            ExtensionView::AuthorityInfoAccess(aia) => Ok(aia),
+            _ => unreachable!("invalid extension (expected authority info access)"),
+        }
+    }
+
+    /// URIs of the full names found in the CRL distribution points extension.
+    ///
+    /// Returns an empty list when the extension is absent.
+    pub fn crl_distribution_point_uris(&self) -> Vec<String> {
+        let distribution_points = match self.crl_distribution_points() {
+            Ok(distribution_points) => distribution_points,
+            Err(_) => return Vec::new(),
+        };
+
+        distribution_points
+            .iter()
+            .filter_map(|distribution_point| match &distribution_point.distribution_point {
+                Some(DistributionPointName::FullName(full_name)) => Some(full_name.0.iter()),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|name| match name {
+                SerdeGeneralName::Uri(uri) => Some(uri.0.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// URIs where the certificate of the issuer can be retrieved (AIA "caIssuers" access method).
+    ///
+    /// Returns an empty list when the extension is absent.
+    pub fn ca_issuers_uris(&self) -> Vec<String> {
+        self.access_location_uris(&oids::id_ad_ca_issuers())
+    }
+
+    /// URIs of the OCSP responders of the issuer (AIA "ocsp" access method).
+    ///
+    /// Returns an empty list when the extension is absent.
+    pub fn ocsp_uris(&self) -> Vec<String> {
+        self.access_location_uris(&oids::id_ad_ocsp())
+    }
+
+    fn access_location_uris(&self, access_method: &ObjectIdentifier) -> Vec<String> {
+        let aia = match self.authority_info_access() {
+            Ok(aia) => aia,
+            Err(_) => return Vec::new(),
+        };
+
+        aia.iter()
+            .filter(|access_description| access_description.access_method.0 == *access_method)
+            .filter_map(|access_description| match &access_description.access_location {
+                SerdeGeneralName::Uri(uri) => Some(uri.0.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn subject_name(&self) -> DirectoryName {
         self.details.tbs_certificate.subject.clone().into()
     }
@@ -340,6 +563,10 @@ impl Cert {
                 strictness: Default::default(),
                 now: None,
                 chain: None,
+                crls: &[],
+                policy_inputs: None,
+                trust_store: None,
+                purposes: Vec::new(),
             }),
         }
     }
@@ -377,6 +604,10 @@ struct CertValidatorInner<'a, 'b, Chain: Iterator<Item = &'b Cert>> {
     strictness: CheckStrictness,
     now: Option<ValidityCheck<'a>>,
     chain: Option<Chain>,
+    crls: &'a [Crl],
+    policy_inputs: Option<PolicyInputs<'a>>,
+    trust_store: Option<&'a TrustStore>,
+    purposes: Vec<KeyPurpose>,
 }
 
 /// Utility to verify x509 `Cert`s
@@ -405,6 +636,86 @@ impl<'a, 'b, Chain: Iterator<Item = &'b Cert>> CertValidator<'a, 'b, Chain> {
         self
     }
 
+    /// Certificate Revocation Lists to check chain certificates against.
+    ///
+    /// Only CRLs issued and signed by the parent of a certificate are considered for that certificate.
+    #[inline]
+    pub fn crls(&self, crls: &'a [Crl]) -> &Self {
+        self.inner.borrow_mut().crls = crls;
+        self
+    }
+
+    /// Requires the certificate to be usable for the given purpose.
+    ///
+    /// Key usage and extended key usage of the certificate are checked against this purpose and,
+    /// when the chain is checked, extended key usages of issuers must allow it as well.
+    /// Can be called several times to require several purposes.
+    #[inline]
+    pub fn require_purpose(&self, purpose: KeyPurpose) -> &Self {
+        let purposes = &mut self.inner.borrow_mut().purposes;
+        if !purposes.contains(&purpose) {
+            purposes.push(purpose);
+        }
+        self
+    }
+
+    /// Trust anchors to use as root of trust.
+    ///
+    /// The chain is not required to contain a root certificate anymore: its last certificate
+    /// must either be a trust anchor itself or be issued by one.
+    #[inline]
+    pub fn trust_store(&self, trust_store: &'a TrustStore) -> &Self {
+        self.inner.borrow_mut().trust_store = Some(trust_store);
+        self
+    }
+
+    /// Enables certificate policies processing (RFC 5280 #6.1) as part of the chain check.
+    ///
+    /// `user_initial_policy_set` is the set of policies acceptable to the relying party.
+    /// An empty set stands for any policy.
+    #[inline]
+    pub fn policies(&self, user_initial_policy_set: &'a [ObjectIdentifier]) -> &Self {
+        self.inner
+            .borrow_mut()
+            .policy_inputs
+            .get_or_insert_with(PolicyInputs::default)
+            .user_initial_policy_set = user_initial_policy_set;
+        self
+    }
+
+    /// Enables certificate policies processing and requires the chain to be valid for at least one policy.
+    #[inline]
+    pub fn require_explicit_policy(&self) -> &Self {
+        self.inner
+            .borrow_mut()
+            .policy_inputs
+            .get_or_insert_with(PolicyInputs::default)
+            .initial_explicit_policy = true;
+        self
+    }
+
+    /// Enables certificate policies processing and rejects policy mappings.
+    #[inline]
+    pub fn inhibit_policy_mapping(&self) -> &Self {
+        self.inner
+            .borrow_mut()
+            .policy_inputs
+            .get_or_insert_with(PolicyInputs::default)
+            .initial_policy_mapping_inhibit = true;
+        self
+    }
+
+    /// Enables certificate policies processing and ignores the `anyPolicy` policy identifier.
+    #[inline]
+    pub fn inhibit_any_policy(&self) -> &Self {
+        self.inner
+            .borrow_mut()
+            .policy_inputs
+            .get_or_insert_with(PolicyInputs::default)
+            .initial_any_policy_inhibit = true;
+        self
+    }
+
     #[inline]
     pub fn require_not_before_check(&self) -> &Self {
         self.inner.borrow_mut().strictness.require_not_before_check = true;
@@ -469,6 +780,10 @@ impl<'a, 'b, Chain: Iterator<Item = &'b Cert>> CertValidator<'a, 'b, Chain> {
             })?;
         }
 
+        for purpose in &inner.purposes {
+            verify_purpose(self.cert, *purpose).map_err(|e| CertError::InvalidChain { source: e })?;
+        }
+
         if !inner.strictness.require_chain_check {
             return Ok(());
         }
@@ -480,6 +795,7 @@ impl<'a, 'b, Chain: Iterator<Item = &'b Cert>> CertValidator<'a, 'b, Chain> {
         };
 
         let mut current_cert = self.cert;
+        let mut subordinate_certs = vec![self.cert];
 
         for (number_certs, parent_cert) in chain.enumerate() {
             // check basic constraints
@@ -504,6 +820,31 @@ impl<'a, 'b, Chain: Iterator<Item = &'b Cert>> CertValidator<'a, 'b, Chain> {
                 _ => {}
             }
 
+            // check key usage allows certificate signing
+            if let Ok(key_usage) = parent_cert.key_usage() {
+                if !key_usage.key_cert_sign() {
+                    return Err(CaChainError::MissingKeyCertSign {
+                        issuer_id: parent_cert.subject_name().to_string(),
+                    })
+                    .map_err(|e| CertError::InvalidChain { source: e });
+                }
+            }
+
+            // check extended key usage is nested
+            if let Ok(extended_key_usage) = parent_cert.extended_key_usage() {
+                for purpose in &inner.purposes {
+                    if !extended_key_usage.contains(purpose.oid())
+                        && !extended_key_usage.contains(oids::kp_any_extended_key_usage())
+                    {
+                        return Err(CaChainError::ExtendedKeyUsageNotNested {
+                            issuer_id: parent_cert.subject_name().to_string(),
+                            purpose: *purpose,
+                        })
+                        .map_err(|e| CertError::InvalidChain { source: e });
+                    }
+                }
+            }
+
             // verify parent validity
             if let Some(now) = &inner.now {
                 verify_cert_validity(parent_cert, &inner.strictness, now.clone()).map_err(|e| {
@@ -518,33 +859,315 @@ impl<'a, 'b, Chain: Iterator<Item = &'b Cert>> CertValidator<'a, 'b, Chain> {
             parent_cert.is_parent_of(current_cert)?;
 
             // validate current cert signature using parent public key
-            let hash_type = SignatureAlgorithm::from_algorithm_identifier(&current_cert.details.signature_algorithm)
-                .map_err(|e| CertError::Signature { source: e })?;
-            let public_key = &parent_cert.details.tbs_certificate.subject_public_key_info;
-            hash_type
-                .verify(
-                    &public_key.clone().into(),
-                    &current_cert.overview.tbs_certificate.0,
-                    current_cert.details.signature_value.0.payload_view(),
-                )
-                .map_err(|e| CertError::Signature { source: e })
-                .map_err(|e| CertError::InvalidCertificate {
-                    source: Box::new(e),
-                    id: current_cert.subject_name().to_string(),
-                })?;
+            verify_signature(current_cert, parent_cert)?;
+
+            // check current_cert isn't revoked by parent_cert
+            verify_not_revoked(current_cert, parent_cert, inner.crls)?;
+
+            // check names of all certificates below parent_cert are allowed by its name constraints
+            if let Ok(name_constraints) = parent_cert.name_constraints() {
+                for (idx, subordinate_cert) in subordinate_certs.iter().enumerate() {
+                    // self-issued intermediate certificates are exempted (RFC 5280 #6.1.3)
+                    let is_self_issued = subordinate_cert.subject_name() == subordinate_cert.issuer_name();
+                    if idx == 0 || !is_self_issued {
+                        verify_name_constraints(subordinate_cert, parent_cert, name_constraints)?;
+                    }
+                }
+            }
 
+            subordinate_certs.push(parent_cert);
             current_cert = parent_cert;
         }
 
-        // make sure `current_cert` (the last certificate of the chain) is a root CA
-        if inner.strictness.chain_should_contains_root_certificate && current_cert.ty() != CertType::Root {
+        let mut anchor_in_chain = true;
+
+        if let Some(trust_store) = inner.trust_store {
+            // `current_cert` (the last certificate of the chain) is either trusted or issued by a trusted certificate
+            if !trust_store.is_trusted(current_cert) {
+                let anchor = trust_store
+                    .issuers_of(current_cert)
+                    .find(|anchor| verify_signature(current_cert, anchor).is_ok())
+                    .ok_or_else(|| CaChainError::NotTrusted {
+                        cert_id: current_cert.subject_name().to_string(),
+                    })
+                    .map_err(|e| CertError::InvalidChain { source: e })?;
+
+                if let Some(now) = &inner.now {
+                    verify_cert_validity(anchor, &inner.strictness, now.clone()).map_err(|e| {
+                        CertError::InvalidCertificate {
+                            source: Box::new(e),
+                            id: anchor.subject_name().to_string(),
+                        }
+                    })?;
+                }
+
+                verify_not_revoked(current_cert, anchor, inner.crls)?;
+
+                anchor_in_chain = false;
+            }
+        } else if inner.strictness.chain_should_contains_root_certificate && current_cert.ty() != CertType::Root {
+            // make sure `current_cert` (the last certificate of the chain) is a root CA
             return Err(CaChainError::NoRoot).map_err(|e| CertError::InvalidChain { source: e });
         }
 
+        // the trust anchor is not part of the certification path
+        if let Some(policy_inputs) = &inner.policy_inputs {
+            let path: Vec<&Cert> = match subordinate_certs.split_last() {
+                _ if !anchor_in_chain => subordinate_certs.iter().rev().copied().collect(),
+                Some((_, path)) if !path.is_empty() => path.iter().rev().copied().collect(),
+                _ => vec![self.cert],
+            };
+            verify_policies(&path, policy_inputs).map_err(|e| CertError::InvalidChain { source: e })?;
+        }
+
         Ok(())
     }
 }
 
+fn verify_purpose(cert: &Cert, purpose: KeyPurpose) -> Result<(), CaChainError> {
+    if let Ok(key_usage) = cert.key_usage() {
+        if !purpose.is_allowed_by(key_usage) {
+            return Err(CaChainError::KeyUsageNotAllowed {
+                cert_id: cert.subject_name().to_string(),
+                purpose,
+            });
+        }
+    }
+
+    // time stamping certificates must assert the time stamping extended key usage (RFC 3161 #2.3)
+    let is_allowed = match cert.extended_key_usage() {
+        Ok(eku) if purpose == KeyPurpose::TimeStamping => eku.contains(purpose.oid()),
+        Ok(eku) => eku.contains(purpose.oid()) || eku.contains(oids::kp_any_extended_key_usage()),
+        Err(_) => purpose != KeyPurpose::TimeStamping,
+    };
+
+    if !is_allowed {
+        return Err(CaChainError::ExtendedKeyUsageNotAllowed {
+            cert_id: cert.subject_name().to_string(),
+            purpose,
+        });
+    }
+
+    Ok(())
+}
+
+fn verify_signature(cert: &Cert, issuer: &Cert) -> Result<(), CertError> {
+    let hash_type = SignatureAlgorithm::from_algorithm_identifier(&cert.details.signature_algorithm)
+        .map_err(|e| CertError::Signature { source: e })?;
+    let public_key = &issuer.details.tbs_certificate.subject_public_key_info;
+    hash_type
+        .verify(
+            &public_key.clone().into(),
+            &cert.overview.tbs_certificate.0,
+            cert.details.signature_value.0.payload_view(),
+        )
+        .map_err(|e| CertError::Signature { source: e })
+        .map_err(|e| CertError::InvalidCertificate {
+            source: Box::new(e),
+            id: cert.subject_name().to_string(),
+        })
+}
+
+fn verify_not_revoked(cert: &Cert, issuer: &Cert, crls: &[Crl]) -> Result<(), CertError> {
+    let revocation_date = crls
+        .iter()
+        .filter(|crl| crl.verify(issuer).is_ok())
+        .find_map(|crl| crl.revocation_date(cert.serial_number()));
+
+    if let Some(revocation_date) = revocation_date {
+        return Err(CaChainError::Revoked {
+            cert_id: cert.subject_name().to_string(),
+            revocation_date,
+        })
+        .map_err(|e| CertError::InvalidChain { source: e });
+    }
+
+    Ok(())
+}
+
+fn verify_name_constraints(cert: &Cert, issuer: &Cert, name_constraints: &NameConstraints) -> Result<(), CertError> {
+    let subject = &cert.details.tbs_certificate.subject;
+    let subject_name = if (subject.0).0.is_empty() {
+        None
+    } else {
+        Some(SerdeGeneralName::DirectoryName(subject.clone()))
+    };
+
+    let subject_alt_names = cert.extensions().iter().find_map(|ext| match ext.extn_value() {
+        ExtensionView::SubjectAltName(san) => Some(san.0),
+        _ => None,
+    });
+
+    // emailAddress attributes of the subject are constrained as rfc822Name (RFC 5280 #4.2.1.10)
+    let mut subject_attr_names: Vec<SerdeGeneralName> = (subject.0)
+        .0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .filter_map(|attr| match &attr.value {
+            AttributeTypeAndValueParameters::EmailAddress(email) => Some(SerdeGeneralName::Rfc822Name(email.clone())),
+            _ => None,
+        })
+        .collect();
+
+    // without SAN, the subject CN is commonly used as host name: constrain it as dNSName
+    if subject_alt_names.is_none() {
+        let common_name = subject.find_common_name().map(|cn| cn.to_string());
+        if let Some(dns_name) = common_name.filter(|cn| is_dns_name(cn)) {
+            if let Ok(dns_name) = IA5String::from_string(dns_name) {
+                subject_attr_names.push(SerdeGeneralName::DnsName(dns_name.into()));
+            }
+        }
+    }
+
+    let subject_alt_names = subject_alt_names.unwrap_or_default();
+
+    for name in subject_name
+        .iter()
+        .chain(subject_attr_names.iter())
+        .chain(subject_alt_names.iter())
+    {
+        let is_excluded = name_constraints
+            .excluded_subtrees()
+            .iter()
+            .any(|subtree| name_matches_constraint(name, subtree.base()) == Some(true));
+
+        // permitted subtrees only restrict names of the same type
+        let permitted_matches: Vec<bool> = name_constraints
+            .permitted_subtrees()
+            .iter()
+            .filter_map(|subtree| name_matches_constraint(name, subtree.base()))
+            .collect();
+        let is_permitted = permitted_matches.is_empty() || permitted_matches.contains(&true);
+
+        if is_excluded || !is_permitted {
+            return Err(CaChainError::NameConstraintViolation {
+                cert_id: cert.subject_name().to_string(),
+                issuer_id: issuer.subject_name().to_string(),
+                name: general_name_to_string(name),
+            })
+            .map_err(|e| CertError::InvalidChain { source: e });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns `None` when the constraint doesn't apply to this kind of name.
+fn name_matches_constraint(name: &SerdeGeneralName, constraint: &SerdeGeneralName) -> Option<bool> {
+    match (name, constraint) {
+        (SerdeGeneralName::DnsName(name), SerdeGeneralName::DnsName(constraint)) => Some(dns_name_matches(
+            &name.0.to_string().to_ascii_lowercase(),
+            &constraint.0.to_string().to_ascii_lowercase(),
+        )),
+        (SerdeGeneralName::Rfc822Name(name), SerdeGeneralName::Rfc822Name(constraint)) => Some(rfc822_name_matches(
+            &name.0.to_string().to_ascii_lowercase(),
+            &constraint.0.to_string().to_ascii_lowercase(),
+        )),
+        (SerdeGeneralName::Uri(name), SerdeGeneralName::Uri(constraint)) => {
+            let uri = name.0.to_string().to_ascii_lowercase();
+            let constraint = constraint.0.to_string().to_ascii_lowercase();
+            Some(matches!(uri_host(&uri), Some(host) if host_matches(host, &constraint)))
+        }
+        (SerdeGeneralName::IpAddress(name), SerdeGeneralName::IpAddress(constraint)) => {
+            Some(ip_address_matches(&name.0, &constraint.0))
+        }
+        (SerdeGeneralName::DirectoryName(name), SerdeGeneralName::DirectoryName(constraint)) => {
+            let name_rdns = &(name.0).0;
+            let constraint_rdns = &(constraint.0).0;
+            Some(constraint_rdns.len() <= name_rdns.len() && name_rdns.iter().zip(constraint_rdns).all(|(a, b)| a == b))
+        }
+        _ => None,
+    }
+}
+
+/// Whether a subject common name looks like a host name (LDH labels, optionally a leading wildcard).
+fn is_dns_name(name: &str) -> bool {
+    let name = name.strip_prefix("*.").unwrap_or(name);
+    !name.is_empty()
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Any DNS name that can be constructed by adding zero or more labels to the left-hand side
+/// of the constraint satisfies it. A leading period restricts matches to subdomains.
+fn dns_name_matches(name: &str, constraint: &str) -> bool {
+    if constraint.is_empty() || constraint.starts_with('.') {
+        name.ends_with(constraint)
+    } else {
+        name == constraint || name.ends_with(&format!(".{}", constraint))
+    }
+}
+
+/// The constraint may specify a particular mailbox, all addresses on a particular host
+/// or all addresses in a domain (when starting with a period).
+fn rfc822_name_matches(name: &str, constraint: &str) -> bool {
+    if constraint.contains('@') {
+        name == constraint
+    } else {
+        match name.rsplit_once('@') {
+            Some((_, host)) => host_matches(host, constraint),
+            None => false,
+        }
+    }
+}
+
+fn host_matches(host: &str, constraint: &str) -> bool {
+    if constraint.starts_with('.') {
+        host.ends_with(constraint)
+    } else {
+        host == constraint
+    }
+}
+
+fn uri_host(uri: &str) -> Option<&str> {
+    let (_, rest) = uri.split_once("://")?;
+    let authority = rest.split(&['/', '?', '#'][..]).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = host_port.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+/// IP address constraints are made of an address followed by a network mask.
+fn ip_address_matches(ip: &[u8], constraint: &[u8]) -> bool {
+    if constraint.len() != ip.len() * 2 {
+        return false;
+    }
+
+    let (network, mask) = constraint.split_at(ip.len());
+    ip.iter()
+        .zip(network)
+        .zip(mask)
+        .all(|((ip, network), mask)| ip & mask == network & mask)
+}
+
+fn general_name_to_string(name: &SerdeGeneralName) -> String {
+    match name {
+        SerdeGeneralName::Rfc822Name(name) | SerdeGeneralName::DnsName(name) | SerdeGeneralName::Uri(name) => {
+            name.0.to_string()
+        }
+        SerdeGeneralName::DirectoryName(name) => name.to_string(),
+        SerdeGeneralName::IpAddress(ip) => {
+            if let Ok(ipv4) = <[u8; 4]>::try_from(ip.0.as_slice()) {
+                IpAddr::from(ipv4).to_string()
+            } else if let Ok(ipv6) = <[u8; 16]>::try_from(ip.0.as_slice()) {
+                IpAddr::from(ipv6).to_string()
+            } else {
+                format!("{:02X?}", ip.0)
+            }
+        }
+        other => format!("{:?}", other),
+    }
+}
+
 fn verify_cert_validity(cert: &Cert, strictness: &CheckStrictness, now: ValidityCheck<'_>) -> Result<(), CertError> {
     let validity = &cert.details.tbs_certificate.validity;
     let not_before: UTCDate = validity.not_before.clone().into();
@@ -626,6 +1249,14 @@ struct CertificateBuilderInner<'a> {
     extended_key_usage: Option<ExtendedKeyUsage>,
     subject_alt_name: Option<GeneralNames>,
     issuer_alt_name: Option<GeneralNames>,
+    name_constraints: Option<NameConstraints>,
+    certificate_policies: Option<CertificatePolicies>,
+    policy_mappings: Option<PolicyMappings>,
+    require_explicit_policy: Option<u8>,
+    inhibit_policy_mapping: Option<u8>,
+    inhibit_any_policy: Option<u8>,
+    crl_distribution_points: Option<CrlDistributionPoints>,
+    authority_info_access: Option<AuthorityInfoAccess>,
     serial_number: Option<Vec<u8>>,
     inherit_extensions_from_csr_attributes: bool,
 }
@@ -766,30 +1397,124 @@ impl<'a> CertificateBuilder<'a> {
 
     /// Optional
     ///
-    /// Bypass picky serial number generator by providing your own.
+    /// Restrict names allowed in the subject and subject alternative names of certificates
+    /// issued below this CA certificate. No name constraints extension is added if both lists are empty.
     #[inline]
-    pub fn serial_number(&self, unsigned_integer_bytes: Vec<u8>) -> &Self {
-        self.inner.borrow_mut().serial_number = Some(unsigned_integer_bytes);
+    pub fn name_constraints(&self, permitted: Vec<GeneralName>, excluded: Vec<GeneralName>) -> &Self {
+        let to_subtrees = |names: Vec<GeneralName>| {
+            names
+                .into_iter()
+                .map(|name| GeneralSubtree::new(name.into()))
+                .collect::<Vec<_>>()
+        };
+
+        self.inner.borrow_mut().name_constraints = if permitted.is_empty() && excluded.is_empty() {
+            None
+        } else {
+            Some(NameConstraints::new(to_subtrees(permitted), to_subtrees(excluded)))
+        };
         self
     }
 
     /// Optional
-    ///
-    /// Inherit extensions from the "extension request" attribute of the provided CSR if applicable
-    /// Extensions already present will be ignored.
     #[inline]
-    pub fn inherit_extensions_from_csr_attributes(&self, inherit: bool) -> &Self {
-        self.inner.borrow_mut().inherit_extensions_from_csr_attributes = inherit;
+    pub fn certificate_policies(&self, certificate_policies: CertificatePolicies) -> &Self {
+        self.inner.borrow_mut().certificate_policies = Some(certificate_policies);
         self
     }
 
-    pub fn build(&self) -> Result<Cert, CertError> {
-        let mut inner = self.inner.borrow_mut();
+    /// Optional
+    #[inline]
+    pub fn policy_mappings(&self, policy_mappings: PolicyMappings) -> &Self {
+        self.inner.borrow_mut().policy_mappings = Some(policy_mappings);
+        self
+    }
 
-        let valid_from = inner.valid_from.take().ok_or(CertError::MissingBuilderArgument {
-            arg: field_str!(valid_from),
-        })?;
-        let valid_to = inner.valid_to.take().ok_or(CertError::MissingBuilderArgument {
+    /// Optional
+    ///
+    /// Number of additional certificates that may appear in the path before an explicit policy
+    /// is required or before policy mapping is no longer permitted.
+    #[inline]
+    pub fn policy_constraints(&self, require_explicit_policy: Option<u8>, inhibit_policy_mapping: Option<u8>) -> &Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.require_explicit_policy = require_explicit_policy;
+        inner.inhibit_policy_mapping = inhibit_policy_mapping;
+        drop(inner);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Number of additional non-self-issued certificates that may appear in the path
+    /// before `anyPolicy` is no longer permitted.
+    #[inline]
+    pub fn inhibit_any_policy(&self, skip_certs: u8) -> &Self {
+        self.inner.borrow_mut().inhibit_any_policy = Some(skip_certs);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Locations where the CRLs covering this certificate can be fetched (usually URIs).
+    /// Each name is added as a distinct distribution point. No extension is added if the list is empty.
+    #[inline]
+    pub fn crl_distribution_points(&self, full_names: Vec<GeneralName>) -> &Self {
+        self.inner.borrow_mut().crl_distribution_points = if full_names.is_empty() {
+            None
+        } else {
+            Some(CrlDistributionPoints::new(
+                full_names.into_iter().map(DistributionPoint::new_full_name).collect(),
+            ))
+        };
+        self
+    }
+
+    /// Optional
+    ///
+    /// Locations of the issuer certificate ("caIssuers", usually URIs) and of the OCSP responders of the issuer.
+    /// No extension is added if both lists are empty.
+    #[inline]
+    pub fn authority_info_access(&self, ca_issuers: Vec<GeneralName>, ocsp: Vec<GeneralName>) -> &Self {
+        let access_descriptions: Vec<AccessDescription> = ocsp
+            .into_iter()
+            .map(AccessDescription::new_ocsp)
+            .chain(ca_issuers.into_iter().map(AccessDescription::new_ca_issuers))
+            .collect();
+
+        self.inner.borrow_mut().authority_info_access = if access_descriptions.is_empty() {
+            None
+        } else {
+            Some(AuthorityInfoAccess::new(access_descriptions))
+        };
+        self
+    }
+
+    /// Optional
+    ///
+    /// Bypass picky serial number generator by providing your own.
+    #[inline]
+    pub fn serial_number(&self, unsigned_integer_bytes: Vec<u8>) -> &Self {
+        self.inner.borrow_mut().serial_number = Some(unsigned_integer_bytes);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Inherit extensions from the "extension request" attribute of the provided CSR if applicable
+    /// Extensions already present will be ignored.
+    #[inline]
+    pub fn inherit_extensions_from_csr_attributes(&self, inherit: bool) -> &Self {
+        self.inner.borrow_mut().inherit_extensions_from_csr_attributes = inherit;
+        self
+    }
+
+    pub fn build(&self) -> Result<Cert, CertError> {
+        let mut inner = self.inner.borrow_mut();
+
+        let valid_from = inner.valid_from.take().ok_or(CertError::MissingBuilderArgument {
+            arg: field_str!(valid_from),
+        })?;
+        let valid_to = inner.valid_to.take().ok_or(CertError::MissingBuilderArgument {
             arg: field_str!(valid_to),
         })?;
 
@@ -860,6 +1585,14 @@ impl<'a> CertificateBuilder<'a> {
         let extended_key_usage_opt = inner.extended_key_usage.take();
         let subject_alt_name_opt = inner.subject_alt_name.take();
         let issuer_alt_name_opt = inner.issuer_alt_name.take();
+        let name_constraints_opt = inner.name_constraints.take();
+        let certificate_policies_opt = inner.certificate_policies.take();
+        let policy_mappings_opt = inner.policy_mappings.take();
+        let require_explicit_policy_opt = inner.require_explicit_policy.take();
+        let inhibit_policy_mapping_opt = inner.inhibit_policy_mapping.take();
+        let inhibit_any_policy_opt = inner.inhibit_any_policy.take();
+        let crl_distribution_points_opt = inner.crl_distribution_points.take();
+        let authority_info_access_opt = inner.authority_info_access.take();
 
         let serial_number = if let Some(unsigned_integer_bytes) = inner.serial_number.take() {
             IntegerAsn1::from_bytes_be_unsigned(unsigned_integer_bytes)
@@ -906,6 +1639,44 @@ impl<'a> CertificateBuilder<'a> {
                 extensions.push(Extension::new_issuer_alt_name(ian));
             }
 
+            // name constraints
+            if let Some(name_constraints) = name_constraints_opt {
+                extensions.push(Extension::new_name_constraints(name_constraints));
+            }
+
+            // certificate policies
+            if let Some(certificate_policies) = certificate_policies_opt {
+                extensions.push(Extension::new_certificate_policies(certificate_policies));
+            }
+
+            // policy mappings
+            if let Some(policy_mappings) = policy_mappings_opt {
+                extensions.push(Extension::new_policy_mappings(policy_mappings));
+            }
+
+            // policy constraints
+            if require_explicit_policy_opt.is_some() || inhibit_policy_mapping_opt.is_some() {
+                extensions.push(Extension::new_policy_constraints(
+                    require_explicit_policy_opt,
+                    inhibit_policy_mapping_opt,
+                ));
+            }
+
+            // inhibit any policy
+            if let Some(skip_certs) = inhibit_any_policy_opt {
+                extensions.push(Extension::new_inhibit_any_policy(skip_certs));
+            }
+
+            // crl distribution points
+            if let Some(crl_distribution_points) = crl_distribution_points_opt {
+                extensions.push(Extension::new_crl_distribution_points(crl_distribution_points));
+            }
+
+            // authority info access
+            if let Some(aia) = authority_info_access_opt {
+                extensions.push(Extension::new_authority_info_access(aia));
+            }
+
             // ski
             let ski = key_id_gen_method
                 .generate_from(&subject_public_key)
@@ -935,10 +1706,14 @@ impl<'a> CertificateBuilder<'a> {
             Extensions(extensions)
         };
 
+        let signature_algorithm = AlgorithmIdentifier::try_from(signature_hash_type)
+            .map_err(|e| CertError::Signature { source: e })
+            .map_err(|e| CertError::CertGeneration { source: Box::new(e) })?;
+
         let tbs_certificate = TbsCertificate {
             version: ExplicitContextTag0(Version::V3),
             serial_number,
-            signature: AlgorithmIdentifier::from(signature_hash_type),
+            signature: signature_algorithm.clone(),
             issuer: Name::from(issuer_name),
             validity,
             subject: Name::from(subject_name),
@@ -946,8 +1721,6 @@ impl<'a> CertificateBuilder<'a> {
             extensions: ExplicitContextTag3(extensions),
         };
 
-        let signature_algorithm = signature_hash_type.into();
-
         let tbs_der = picky_asn1_der::to_vec(&tbs_certificate)
             .map_err(|e| CertError::Asn1Serialization {
                 source: e,
@@ -1370,6 +2143,1000 @@ mod tests {
         );
     }
 
+    #[test]
+    fn eddsa_ca_chain() {
+        let root_key = PrivateKey::generate_ed25519().unwrap();
+        let leaf_key = parse_key(crate::test_files::ED25519_PK);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("TheFuture.usodakedo Root CA"), &root_key)
+            .ca(true)
+            .signature_hash_type(SignatureAlgorithm::Ed25519)
+            .build()
+            .expect("couldn't build root ca");
+
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("ChillingInTheFuture.usobakkari"),
+            &leaf_key,
+            SignatureAlgorithm::Ed25519,
+        )
+        .unwrap();
+        csr.verify().expect("couldn't verify csr");
+
+        let signed_leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2069, 1, 1).unwrap(), UTCDate::ymd(2070, 1, 1).unwrap())
+            .subject_from_csr(csr)
+            .issuer_cert(&root, &root_key)
+            .signature_hash_type(SignatureAlgorithm::Ed25519)
+            .build()
+            .expect("couldn't build signed leaf");
+        assert_eq!(signed_leaf.public_key(), &leaf_key.to_public_key());
+
+        signed_leaf
+            .verifier()
+            .chain([root].iter())
+            .exact_date(&UTCDate::ymd(2069, 10, 1).unwrap())
+            .verify()
+            .expect("couldn't verify chain");
+    }
+
+    #[test]
+    fn rsassa_pss_openssl_root_ca() {
+        use crate::signature::RsaPssParams;
+
+        let pem = parse_pem(crate::test_files::RSA_PSS_ROOT_CA.as_bytes()).unwrap();
+        let cert = Cert::from_der(pem.data()).expect("couldn't parse cert");
+        assert_eq!(cert.to_der().unwrap(), pem.data());
+
+        // generated using OpenSSL with `-sigopt rsa_mgf1_md:sha256`
+        assert_eq!(
+            SignatureAlgorithm::from_algorithm_identifier(cert.signature_algorithm()).unwrap(),
+            SignatureAlgorithm::RsaPss(RsaPssParams {
+                hash_algorithm: HashAlgorithm::SHA2_512,
+                mgf_hash_algorithm: HashAlgorithm::SHA2_256,
+                salt_length: 20,
+            })
+        );
+
+        cert.verifier()
+            .chain(std::iter::once(&cert))
+            .exact_date(&UTCDate::ymd(2030, 1, 1).unwrap())
+            .verify()
+            .expect("couldn't verify self-signed cert");
+    }
+
+    #[test]
+    fn rsassa_pss_ca_chain() {
+        use crate::signature::RsaPssParams;
+
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_2);
+
+        let root_signature_algorithm = SignatureAlgorithm::RsaPss(RsaPssParams {
+            salt_length: 0,
+            ..RsaPssParams::new(HashAlgorithm::SHA2_384)
+        });
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("TheFuture.usodakedo Root CA"), &root_key)
+            .ca(true)
+            .signature_hash_type(root_signature_algorithm)
+            .build()
+            .expect("couldn't build root ca");
+
+        let root = Cert::from_der(&root.to_der().unwrap()).unwrap();
+        assert_eq!(
+            SignatureAlgorithm::from_algorithm_identifier(root.signature_algorithm()).unwrap(),
+            root_signature_algorithm
+        );
+
+        let csr = Csr::generate(
+            DirectoryName::new_common_name("ChillingInTheFuture.usobakkari"),
+            &leaf_key,
+            SignatureAlgorithm::RsaPss(RsaPssParams::new(HashAlgorithm::SHA2_256)),
+        )
+        .unwrap();
+        csr.verify().expect("couldn't verify csr");
+
+        let signed_leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2069, 1, 1).unwrap(), UTCDate::ymd(2070, 1, 1).unwrap())
+            .subject_from_csr(csr)
+            .issuer_cert(&root, &root_key)
+            .signature_hash_type(root_signature_algorithm)
+            .build()
+            .expect("couldn't build signed leaf");
+
+        signed_leaf
+            .verifier()
+            .chain([root].iter())
+            .exact_date(&UTCDate::ymd(2069, 10, 1).unwrap())
+            .verify()
+            .expect("couldn't verify chain");
+    }
+
+    #[test]
+    fn revoked_certificate_in_chain() {
+        use crate::x509::crl::CrlBuilder;
+
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Revocation Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let intermediate = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Revocation Authority"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build intermediate ca");
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Revoked Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&intermediate, &intermediate_key)
+            .build()
+            .expect("couldn't build leaf");
+
+        let chain = [intermediate.clone(), root.clone()];
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let unrelated_crl = CrlBuilder::new()
+            .issuer_cert(&root, &root_key)
+            .this_update(now.clone())
+            .revoke(vec![0x01], now.clone())
+            .build()
+            .unwrap();
+
+        // CRL revoking the leaf, but not signed by its issuer
+        let forged_crl = CrlBuilder::new()
+            .issuer(intermediate.subject_name(), &leaf_key)
+            .this_update(now.clone())
+            .revoke_cert(&leaf, now.clone())
+            .build()
+            .unwrap();
+
+        let crls = [unrelated_crl, forged_crl];
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .crls(&crls)
+            .verify()
+            .expect("couldn't verify chain");
+
+        let leaf_crl = CrlBuilder::new()
+            .issuer_cert(&intermediate, &intermediate_key)
+            .this_update(now.clone())
+            .revoke_cert(&leaf, UTCDate::ymd(2065, 12, 24).unwrap())
+            .build()
+            .unwrap();
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .crls(std::slice::from_ref(&leaf_crl))
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: certificate 'CN=Revoked Leaf' has been revoked by its issuer \
+             (revocation date: 2065-12-24 00:00:00)"
+        );
+
+        let intermediate_crl = CrlBuilder::new()
+            .issuer_cert(&root, &root_key)
+            .this_update(now.clone())
+            .revoke_cert(&intermediate, now.clone())
+            .build()
+            .unwrap();
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .crls(std::slice::from_ref(&intermediate_crl))
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::Revoked { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn name_constraints_in_chain() {
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Constrained Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let intermediate = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Constrained Authority"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .name_constraints(
+                vec![
+                    GeneralName::new_dns_name("example.com").unwrap(),
+                    GeneralName::new_ip_address(vec![10, 0, 0, 0, 255, 0, 0, 0]),
+                ],
+                vec![GeneralName::new_dns_name("bad.example.com").unwrap()],
+            )
+            .build()
+            .expect("couldn't build intermediate ca");
+
+        let name_constraints = intermediate.name_constraints().expect("name constraints");
+        assert_eq!(name_constraints.permitted_subtrees().len(), 2);
+        assert_eq!(name_constraints.excluded_subtrees().len(), 1);
+
+        let build_leaf = |san: GeneralNames| {
+            CertificateBuilder::new()
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(
+                    DirectoryName::new_common_name("Constrained Leaf"),
+                    leaf_key.to_public_key(),
+                )
+                .issuer_cert(&intermediate, &intermediate_key)
+                .subject_alt_name(san)
+                .build()
+                .expect("couldn't build leaf")
+        };
+
+        let chain = [intermediate.clone(), root.clone()];
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let leaf = build_leaf(
+            GeneralNames::new(GeneralName::new_dns_name("www.example.com").unwrap())
+                .with_name(GeneralName::new_ip_address(vec![10, 1, 2, 3])),
+        );
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .expect("couldn't verify chain");
+
+        let leaf = build_leaf(GeneralNames::new(GeneralName::new_dns_name("example.org").unwrap()));
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: certificate 'CN=Constrained Leaf' violates name constraints of \
+             'CN=Constrained Authority' (name: example.org)"
+        );
+
+        let leaf = build_leaf(GeneralNames::new(
+            GeneralName::new_dns_name("host.bad.example.com").unwrap(),
+        ));
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::NameConstraintViolation { .. }
+            }
+        ));
+
+        let leaf = build_leaf(GeneralNames::new(GeneralName::new_ip_address(vec![192, 168, 1, 1])));
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: certificate 'CN=Constrained Leaf' violates name constraints of \
+             'CN=Constrained Authority' (name: 192.168.1.1)"
+        );
+    }
+
+    #[test]
+    fn name_constraints_on_subject_attributes() {
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Constrained Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let intermediate = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Constrained Authority"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .name_constraints(
+                vec![
+                    GeneralName::new_dns_name("example.com").unwrap(),
+                    GeneralName::new_rfc822_name("example.com").unwrap(),
+                ],
+                Vec::new(),
+            )
+            .build()
+            .expect("couldn't build intermediate ca");
+
+        let build_leaf = |subject: DirectoryName, san: Option<GeneralNames>| {
+            let builder = CertificateBuilder::new();
+            builder
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(subject, leaf_key.to_public_key())
+                .issuer_cert(&intermediate, &intermediate_key);
+            if let Some(san) = san {
+                builder.subject_alt_name(san);
+            }
+            builder.build().expect("couldn't build leaf")
+        };
+
+        let chain = [intermediate.clone(), root.clone()];
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+        let verify = |leaf: Cert| leaf.verifier().chain(chain.iter()).exact_date(&now).verify();
+
+        // subject CN is checked against dNSName constraints when there is no SAN
+        verify(build_leaf(DirectoryName::new_common_name("www.example.com"), None)).expect("CN within constraints");
+        verify(build_leaf(DirectoryName::new_common_name("Constrained Leaf"), None)).expect("CN isn't a host name");
+        let err = verify(build_leaf(DirectoryName::new_common_name("www.example.org"), None)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: certificate 'CN=www.example.org' violates name constraints of \
+             'CN=Constrained Authority' (name: www.example.org)"
+        );
+
+        // ... but not when a SAN is present
+        verify(build_leaf(
+            DirectoryName::new_common_name("www.example.org"),
+            Some(GeneralNames::new(GeneralName::new_dns_name("www.example.com").unwrap())),
+        ))
+        .expect("CN is ignored when SAN is present");
+
+        // emailAddress attributes are checked against rfc822Name constraints
+        let mut subject = DirectoryName::new_common_name("Constrained Leaf");
+        subject.add_email(IA5String::from_string("admin@example.com".to_owned()).unwrap());
+        verify(build_leaf(subject, None)).expect("email within constraints");
+
+        let mut subject = DirectoryName::new_common_name("Constrained Leaf");
+        subject.add_email(IA5String::from_string("admin@example.org".to_owned()).unwrap());
+        let err = verify(build_leaf(subject, None)).unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::NameConstraintViolation { ref name, .. }
+            } if name == "admin@example.org"
+        ));
+    }
+
+    #[test]
+    fn name_constraints_matching() {
+        assert!(dns_name_matches("example.com", "example.com"));
+        assert!(dns_name_matches("www.example.com", "example.com"));
+        assert!(!dns_name_matches("wwwexample.com", "example.com"));
+        assert!(!dns_name_matches("example.com", ".example.com"));
+        assert!(dns_name_matches("www.example.com", ".example.com"));
+        assert!(dns_name_matches("anything.org", ""));
+
+        assert!(is_dns_name("www.example.com"));
+        assert!(is_dns_name("*.example.com"));
+        assert!(!is_dns_name("Constrained Leaf"));
+        assert!(!is_dns_name("-bad.example.com"));
+
+        assert!(rfc822_name_matches("admin@example.com", "admin@example.com"));
+        assert!(rfc822_name_matches("admin@example.com", "example.com"));
+        assert!(!rfc822_name_matches("admin@mail.example.com", "example.com"));
+        assert!(rfc822_name_matches("admin@mail.example.com", ".example.com"));
+
+        assert_eq!(
+            uri_host("https://user@www.example.com:8080/path?q"),
+            Some("www.example.com")
+        );
+        assert_eq!(uri_host("urn:example"), None);
+
+        assert!(ip_address_matches(&[10, 1, 2, 3], &[10, 0, 0, 0, 255, 0, 0, 0]));
+        assert!(!ip_address_matches(&[11, 1, 2, 3], &[10, 0, 0, 0, 255, 0, 0, 0]));
+        assert!(!ip_address_matches(&[0; 16], &[10, 0, 0, 0, 255, 0, 0, 0]));
+    }
+
+    #[test]
+    fn certificate_policies_in_chain() {
+        use picky_asn1_x509::{PolicyInformation, PolicyMapping, PolicyQualifierInfo, UserNotice};
+
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let policy_1 = ObjectIdentifier::try_from("1.3.6.1.4.1.99999.1").unwrap();
+        let policy_2 = ObjectIdentifier::try_from("1.3.6.1.4.1.99999.2").unwrap();
+        let policy_3 = ObjectIdentifier::try_from("1.3.6.1.4.1.99999.3").unwrap();
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Policy Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let build_intermediate = |policies: CertificatePolicies, mappings: Option<PolicyMappings>| {
+            let builder = CertificateBuilder::new();
+            builder
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(
+                    DirectoryName::new_common_name("Policy Authority"),
+                    intermediate_key.to_public_key(),
+                )
+                .issuer_cert(&root, &root_key)
+                .ca(true)
+                .certificate_policies(policies)
+                .policy_constraints(Some(0), None);
+            if let Some(mappings) = mappings {
+                builder.policy_mappings(mappings);
+            }
+            builder.build().expect("couldn't build intermediate ca")
+        };
+
+        let build_leaf = |intermediate: &Cert, policies: Vec<ObjectIdentifier>| {
+            let builder = CertificateBuilder::new();
+            builder
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(DirectoryName::new_common_name("Policy Leaf"), leaf_key.to_public_key())
+                .issuer_cert(intermediate, &intermediate_key);
+            if !policies.is_empty() {
+                builder.certificate_policies(CertificatePolicies::new(
+                    policies
+                        .into_iter()
+                        .map(|policy| PolicyInformation::new(policy, Vec::new()))
+                        .collect(),
+                ));
+            }
+            builder.build().expect("couldn't build leaf")
+        };
+
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let intermediate = build_intermediate(
+            CertificatePolicies::new(vec![
+                PolicyInformation::new(
+                    policy_1.clone(),
+                    vec![
+                        PolicyQualifierInfo::new_cps_uri("https://pki.example.com/cps".parse().unwrap()),
+                        PolicyQualifierInfo::new_user_notice(UserNotice {
+                            notice_ref: None,
+                            explicit_text: Some("Policy 1".into()),
+                        }),
+                    ],
+                ),
+                PolicyInformation::new(policy_2.clone(), Vec::new()),
+            ]),
+            None,
+        );
+        assert!(intermediate.certificate_policies().unwrap().contains(policy_1.clone()));
+        assert_eq!(
+            intermediate.policy_constraints().unwrap().require_explicit_policy(),
+            Some(0)
+        );
+        let chain = [intermediate.clone(), root.clone()];
+
+        // policy processing is opt-in
+        let leaf = build_leaf(&intermediate, Vec::new());
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .expect("policy processing should be disabled by default");
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(&[])
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: invalid policy for certificate 'CN=Policy Leaf': no acceptable policy"
+        );
+
+        let leaf = build_leaf(&intermediate, vec![policy_2.clone()]);
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(&[])
+            .verify()
+            .expect("couldn't verify chain with any policy");
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(&[policy_2.clone(), policy_3.clone()])
+            .verify()
+            .expect("couldn't verify chain with acceptable policy");
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(std::slice::from_ref(&policy_1))
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::InvalidPolicy { .. }
+            }
+        ));
+
+        let leaf = build_leaf(&intermediate, vec![policy_3.clone()]);
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(&[])
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::InvalidPolicy { .. }
+            }
+        ));
+
+        // policy mapping: policy 1 in the issuer domain is equivalent to policy 3 in the subject domain
+        let intermediate = build_intermediate(
+            CertificatePolicies::new(vec![PolicyInformation::new(policy_1.clone(), Vec::new())]),
+            Some(PolicyMappings::new(vec![PolicyMapping::new(
+                policy_1.clone(),
+                policy_3.clone(),
+            )])),
+        );
+        let chain = [intermediate.clone(), root.clone()];
+        let leaf = build_leaf(&intermediate, vec![policy_3.clone()]);
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(std::slice::from_ref(&policy_1))
+            .verify()
+            .expect("couldn't verify chain with mapped policy");
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .inhibit_policy_mapping()
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::InvalidPolicy { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn inhibit_any_policy_in_chain() {
+        use picky_asn1_x509::PolicyInformation;
+
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let policy = ObjectIdentifier::try_from("1.3.6.1.4.1.99999.1").unwrap();
+        let any_policy = || CertificatePolicies::new(vec![PolicyInformation::new(oids::any_policy(), Vec::new())]);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Policy Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let build_chain = |skip_certs: Option<u8>| {
+            let builder = CertificateBuilder::new();
+            builder
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(
+                    DirectoryName::new_common_name("Policy Authority"),
+                    intermediate_key.to_public_key(),
+                )
+                .issuer_cert(&root, &root_key)
+                .ca(true)
+                .certificate_policies(any_policy());
+            if let Some(skip_certs) = skip_certs {
+                builder.inhibit_any_policy(skip_certs);
+            }
+            let intermediate = builder.build().expect("couldn't build intermediate ca");
+
+            let leaf = CertificateBuilder::new()
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(DirectoryName::new_common_name("Policy Leaf"), leaf_key.to_public_key())
+                .issuer_cert(&intermediate, &intermediate_key)
+                .certificate_policies(any_policy())
+                .build()
+                .expect("couldn't build leaf");
+
+            (leaf, [intermediate, root.clone()])
+        };
+
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let (leaf, chain) = build_chain(None);
+        assert!(leaf.inhibit_any_policy().is_err());
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .policies(std::slice::from_ref(&policy))
+            .require_explicit_policy()
+            .verify()
+            .expect("couldn't verify chain with any policy");
+
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .require_explicit_policy()
+            .inhibit_any_policy()
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::InvalidPolicy { .. }
+            }
+        ));
+
+        let (leaf, chain) = build_chain(Some(0));
+        assert_eq!(chain[0].inhibit_any_policy().unwrap(), 0);
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .verify()
+            .expect("policies are not required");
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .require_explicit_policy()
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: invalid policy for certificate 'CN=Policy Leaf': no acceptable policy"
+        );
+    }
+
+    #[test]
+    fn authority_info_access_and_crl_distribution_points() {
+        let cert = Cert::from_pem_str(crate::test_files::PSDIAG_INTER).unwrap();
+        assert_eq!(
+            cert.ca_issuers_uris(),
+            ["http://www.microsoft.com/pki/certs/MicRooCerAut2011_2011_03_22.crt"]
+        );
+        assert!(cert.ocsp_uris().is_empty());
+        assert_eq!(
+            cert.crl_distribution_point_uris(),
+            ["http://crl.microsoft.com/pki/crl/products/MicRooCerAut2011_2011_03_22.crl"]
+        );
+
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("AIA Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+        assert!(root.authority_info_access().is_err());
+        assert!(root.ca_issuers_uris().is_empty());
+        assert!(root.crl_distribution_point_uris().is_empty());
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("AIA Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&root, &root_key)
+            .authority_info_access(
+                vec![GeneralName::new_uri("http://pki.example.com/root.crt").unwrap()],
+                vec![GeneralName::new_uri("http://ocsp.example.com").unwrap()],
+            )
+            .crl_distribution_points(vec![
+                GeneralName::new_uri("http://pki.example.com/root.crl").unwrap(),
+                GeneralName::new_uri("ldap://ldap.example.com/cn=root").unwrap(),
+            ])
+            .build()
+            .expect("couldn't build leaf");
+
+        let leaf = Cert::from_der(&leaf.to_der().unwrap()).unwrap();
+        assert_eq!(leaf.ca_issuers_uris(), ["http://pki.example.com/root.crt"]);
+        assert_eq!(leaf.ocsp_uris(), ["http://ocsp.example.com"]);
+        assert_eq!(
+            leaf.crl_distribution_point_uris(),
+            ["http://pki.example.com/root.crl", "ldap://ldap.example.com/cn=root"]
+        );
+        assert_eq!(leaf.crl_distribution_points().unwrap().iter().count(), 2);
+    }
+
+    #[test]
+    fn trust_store_as_root_of_trust() {
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Trusted Root CA"), &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let intermediate = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Trusted Authority"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build intermediate ca");
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Trusted Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&intermediate, &intermediate_key)
+            .build()
+            .expect("couldn't build leaf");
+
+        let now = UTCDate::ymd(2068, 1, 1).unwrap();
+
+        let mut store = TrustStore::new();
+        store.add_certificate(root.clone());
+
+        // root is not in the chain
+        leaf.verifier()
+            .chain(std::slice::from_ref(&intermediate).iter())
+            .exact_date(&now)
+            .trust_store(&store)
+            .verify()
+            .expect("root from trust store");
+
+        // root is in the chain and trusted
+        leaf.verifier()
+            .chain([intermediate.clone(), root.clone()].iter())
+            .exact_date(&now)
+            .trust_store(&store)
+            .verify()
+            .expect("trusted root in chain");
+
+        // without trust store, the root is required
+        let err = leaf
+            .verifier()
+            .chain(std::slice::from_ref(&intermediate).iter())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "CA chain error: chain is missing a root certificate");
+
+        // trusting an intermediate is enough
+        let mut store = TrustStore::new();
+        store.add_certificate(intermediate.clone());
+        leaf.verifier()
+            .chain(std::iter::empty())
+            .exact_date(&now)
+            .trust_store(&store)
+            .verify()
+            .expect("trusted intermediate");
+
+        // the root of the chain must be trusted
+        let store = TrustStore::from_pem_bundle(crate::test_files::PSDIAG_ROOT).unwrap();
+        let err = leaf
+            .verifier()
+            .chain([intermediate, root].iter())
+            .exact_date(&now)
+            .trust_store(&store)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: certificate 'CN=Trusted Root CA' is not trusted and not issued by a trusted certificate"
+        );
+    }
+
+    #[test]
+    fn key_purpose_in_chain() {
+        let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let mut ca_key_usage = KeyUsage::new(7);
+        ca_key_usage.set_key_cert_sign(true);
+        ca_key_usage.set_crl_sign(true);
+
+        let root = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Purpose Root CA"), &root_key)
+            .ca(true)
+            .key_usage(ca_key_usage.clone())
+            .build()
+            .expect("couldn't build root ca");
+
+        let intermediate = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Purpose TLS Server Authority"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .key_usage(ca_key_usage)
+            .extended_key_usage(ExtendedKeyUsage::new(vec![oids::kp_server_auth()]))
+            .build()
+            .expect("couldn't build intermediate ca");
+
+        let mut leaf_key_usage = KeyUsage::new(1);
+        leaf_key_usage.set_digital_signature(true);
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Purpose Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&intermediate, &intermediate_key)
+            .key_usage(leaf_key_usage)
+            .extended_key_usage(ExtendedKeyUsage::new(vec![
+                oids::kp_server_auth(),
+                oids::kp_client_auth(),
+            ]))
+            .build()
+            .expect("couldn't build leaf");
+
+        let chain = [intermediate.clone(), root.clone()];
+        let now = UTCDate::ymd(2068, 1, 1).unwrap();
+
+        leaf.verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::ServerAuth)
+            .verify()
+            .expect("server authentication");
+
+        // intermediate is restricted to server authentication
+        let err = leaf
+            .verifier()
+            .chain(chain.iter())
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::ServerAuth)
+            .require_purpose(KeyPurpose::ClientAuth)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: extended key usage of issuer certificate \
+             'CN=Purpose TLS Server Authority' doesn't allow client authentication"
+        );
+
+        // leaf checks don't require the chain
+        let err = leaf
+            .verifier::<std::iter::Empty<&Cert>>()
+            .ignore_chain_check()
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::CodeSigning)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: extended key usage of certificate 'CN=Purpose Leaf' doesn't allow code signing"
+        );
+
+        let mut key_encipherment_only = KeyUsage::new(3);
+        key_encipherment_only.set_key_encipherment(true);
+        let encipherment_leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Encipherment Leaf"),
+                leaf_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .key_usage(key_encipherment_only)
+            .build()
+            .expect("couldn't build leaf");
+
+        encipherment_leaf
+            .verifier()
+            .chain(std::slice::from_ref(&root).iter())
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::ServerAuth)
+            .verify()
+            .expect("key encipherment is fine for server authentication");
+
+        let err = encipherment_leaf
+            .verifier()
+            .chain(std::slice::from_ref(&root).iter())
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::ClientAuth)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: key usage of certificate 'CN=Encipherment Leaf' doesn't allow client authentication"
+        );
+
+        // issuers must be allowed to sign certificates
+        let mut crl_sign_only = KeyUsage::new(7);
+        crl_sign_only.set_crl_sign(true);
+        let crl_signer = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("Purpose CRL Signer"),
+                intermediate_key.to_public_key(),
+            )
+            .issuer_cert(&root, &root_key)
+            .ca(true)
+            .key_usage(crl_sign_only)
+            .build()
+            .expect("couldn't build crl signer");
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Purpose Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&crl_signer, &intermediate_key)
+            .build()
+            .expect("couldn't build leaf");
+
+        let err = leaf
+            .verifier()
+            .chain([crl_signer, root].iter())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: issuer certificate 'CN=Purpose CRL Signer' is not allowed to sign certificates \
+             (keyCertSign key usage is missing)"
+        );
+
+        // time stamping requires an explicit extended key usage
+        let err = leaf
+            .verifier::<std::iter::Empty<&Cert>>()
+            .ignore_chain_check()
+            .exact_date(&now)
+            .require_purpose(KeyPurpose::TimeStamping)
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CA chain error: extended key usage of certificate 'CN=Purpose Leaf' doesn't allow time stamping"
+        );
+    }
+
     #[test]
     fn bypass_serial_number_generator() {
         let root_key = parse_key(crate::test_files::RSA_2048_PK_1);
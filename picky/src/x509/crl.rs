@@ -0,0 +1,606 @@
+use super::utils::{from_der, from_pem, from_pem_str, to_der, to_pem};
+use crate::hash::HashAlgorithm;
+use crate::key::PrivateKey;
+use crate::pem::{Pem, PemError};
+use crate::signature::{SignatureAlgorithm, SignatureError};
+use crate::x509::certificate::{Cert, CertError};
+use crate::x509::date::UTCDate;
+use crate::x509::name::DirectoryName;
+use picky_asn1::bit_string::BitString;
+use picky_asn1::wrapper::{Asn1SequenceOf, ExplicitContextTag0, IntegerAsn1, OctetStringAsn1Container};
+use picky_asn1_der::{Asn1DerError, Asn1RawDer};
+use picky_asn1_x509::{
+    oids, AlgorithmIdentifier, CertificateList, CertificateSerialNumber, CrlReason, Extension, ExtensionView,
+    Extensions, KeyIdentifier, Name, RevokedCertificate, RevokedCertificates, TbsCertList, Version,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use thiserror::Error;
+
+const ELEMENT_NAME: &str = "certificate revocation list";
+
+#[derive(Debug, Error)]
+pub enum CrlError {
+    /// ASN1 serialization error
+    #[error("(ASN1) couldn't serialize {element}: {source}")]
+    Asn1Serialization {
+        element: &'static str,
+        source: Asn1DerError,
+    },
+
+    /// ASN1 deserialization error
+    #[error("(ASN1) couldn't deserialize {element}: {source}")]
+    Asn1Deserialization {
+        element: &'static str,
+        source: Asn1DerError,
+    },
+
+    /// signature error
+    #[error("signature error: {source}")]
+    Signature { source: SignatureError },
+
+    /// issuer name doesn't match
+    #[error("issuer name doesn't match (expected: {expected}, got: {actual})")]
+    IssuerNameMismatch { expected: String, actual: String },
+
+    /// missing required builder argument
+    #[error("missing required builder argument `{arg}`")]
+    MissingBuilderArgument { arg: &'static str },
+
+    /// invalid PEM label error
+    #[error("invalid PEM label: {label}")]
+    InvalidPemLabel { label: String },
+
+    /// invalid PEM provided
+    #[error("invalid PEM provided: {source}")]
+    Pem { source: PemError },
+}
+
+impl From<CertError> for CrlError {
+    fn from(e: CertError) -> Self {
+        match e {
+            CertError::Asn1Deserialization { element, source } => CrlError::Asn1Deserialization { element, source },
+            CertError::Asn1Serialization { element, source } => CrlError::Asn1Serialization { element, source },
+            CertError::Pem { source } => CrlError::Pem { source },
+            CertError::InvalidPemLabel { label } => CrlError::InvalidPemLabel { label },
+            _ => unreachable!(),
+        }
+    }
+}
+
+const CRL_PEM_LABEL: &str = "X509 CRL";
+
+/// Raw DER parts of the CRL, used to check the signature against the original `tbsCertList` encoding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct CrlOverview {
+    tbs_cert_list: Asn1RawDer,
+    signature_algorithm: Asn1RawDer,
+    signature_value: Asn1RawDer,
+}
+
+/// X.509 Certificate Revocation List ([RFC 5280 #5](https://tools.ietf.org/html/rfc5280#section-5))
+#[derive(Clone, Debug, PartialEq)]
+pub struct Crl {
+    details: CertificateList,
+    overview: CrlOverview,
+}
+
+impl TryFrom<CertificateList> for Crl {
+    type Error = CrlError;
+
+    fn try_from(certificate_list: CertificateList) -> Result<Self, Self::Error> {
+        let der = picky_asn1_der::to_vec(&certificate_list).map_err(|source| CrlError::Asn1Serialization {
+            element: ELEMENT_NAME,
+            source,
+        })?;
+        let overview = picky_asn1_der::from_bytes(&der).map_err(|source| CrlError::Asn1Deserialization {
+            element: ELEMENT_NAME,
+            source,
+        })?;
+        Ok(Self {
+            details: certificate_list,
+            overview,
+        })
+    }
+}
+
+impl From<Crl> for CertificateList {
+    fn from(crl: Crl) -> Self {
+        crl.details
+    }
+}
+
+impl Crl {
+    pub fn from_der<T: ?Sized + AsRef<[u8]>>(der: &T) -> Result<Self, CrlError> {
+        Ok(Self {
+            details: from_der(der, ELEMENT_NAME)?,
+            overview: from_der(der, ELEMENT_NAME)?,
+        })
+    }
+
+    pub fn from_pem(pem: &Pem) -> Result<Self, CrlError> {
+        Ok(Self {
+            details: from_pem(pem, CRL_PEM_LABEL, ELEMENT_NAME)?,
+            overview: from_pem(pem, CRL_PEM_LABEL, ELEMENT_NAME)?,
+        })
+    }
+
+    pub fn from_pem_str(pem_str: &str) -> Result<Self, CrlError> {
+        Ok(Self {
+            details: from_pem_str(pem_str, CRL_PEM_LABEL, ELEMENT_NAME)?,
+            overview: from_pem_str(pem_str, CRL_PEM_LABEL, ELEMENT_NAME)?,
+        })
+    }
+
+    pub fn to_der(&self) -> Result<Vec<u8>, CrlError> {
+        Ok(to_der(&self.overview, ELEMENT_NAME)?)
+    }
+
+    pub fn to_pem(&self) -> Result<Pem<'static>, CrlError> {
+        Ok(to_pem(&self.overview, CRL_PEM_LABEL, ELEMENT_NAME)?)
+    }
+
+    pub fn issuer_name(&self) -> DirectoryName {
+        self.details.tbs_cert_list.issuer.clone().into()
+    }
+
+    pub fn signature_algorithm(&self) -> &AlgorithmIdentifier {
+        &self.details.tbs_cert_list.signature
+    }
+
+    pub fn this_update(&self) -> UTCDate {
+        self.details.tbs_cert_list.this_update.clone().into()
+    }
+
+    pub fn next_update(&self) -> Option<UTCDate> {
+        self.details.tbs_cert_list.next_update.clone().map(UTCDate::from)
+    }
+
+    pub fn extensions(&self) -> &[Extension] {
+        match &self.details.tbs_cert_list.crl_extension.0 {
+            Some(extensions) => extensions.0.as_slice(),
+            None => &[],
+        }
+    }
+
+    pub fn crl_number(&self) -> Option<&IntegerAsn1> {
+        self.extensions().iter().find_map(|ext| match ext.extn_value() {
+            ExtensionView::CrlNumber(number) => Some(&number.0),
+            _ => None,
+        })
+    }
+
+    pub fn revoked_certificates(&self) -> &[RevokedCertificate] {
+        match &self.details.tbs_cert_list.revoked_certificates {
+            Some(revoked_certificates) => (revoked_certificates.0).0.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Returns the revocation date of the certificate with the given serial number if listed.
+    pub fn revocation_date(&self, serial_number: &IntegerAsn1) -> Option<UTCDate> {
+        self.h_find_revoked_certificate(serial_number)
+            .map(|entry| entry.revocation_data.clone().into())
+    }
+
+    /// Returns the revocation reason of the certificate with the given serial number if listed with one.
+    pub fn revocation_reason(&self, serial_number: &IntegerAsn1) -> Option<CrlReason> {
+        let extensions = self
+            .h_find_revoked_certificate(serial_number)?
+            .crl_entry_extensions
+            .as_ref()?;
+        extensions
+            .0
+            .iter()
+            .filter(|ext| ext.extn_id() == &oids::crl_reason_code())
+            .find_map(|ext| match ext.extn_value() {
+                ExtensionView::Generic(value) => picky_asn1_der::from_bytes(&value.0).ok(),
+                _ => None,
+            })
+    }
+
+    /// Checks whether `cert` is listed in this CRL.
+    ///
+    /// The CRL is expected to be issued by the issuer of `cert`: use `verify` to make sure it is.
+    pub fn is_revoked(&self, cert: &Cert) -> bool {
+        cert.issuer_name() == self.issuer_name() && self.revocation_date(cert.serial_number()).is_some()
+    }
+
+    fn h_find_revoked_certificate(&self, serial_number: &IntegerAsn1) -> Option<&RevokedCertificate> {
+        self.revoked_certificates()
+            .iter()
+            .find(|entry| (entry.user_certificate.0).as_unsigned_bytes_be() == serial_number.as_unsigned_bytes_be())
+    }
+
+    /// Verifies this CRL was issued and signed by `issuer`.
+    pub fn verify(&self, issuer: &Cert) -> Result<(), CrlError> {
+        let issuer_name = issuer.subject_name();
+        let crl_issuer_name = self.issuer_name();
+        if issuer_name != crl_issuer_name {
+            return Err(CrlError::IssuerNameMismatch {
+                expected: crl_issuer_name.to_string(),
+                actual: issuer_name.to_string(),
+            });
+        }
+
+        let hash_type = SignatureAlgorithm::from_algorithm_identifier(&self.details.signature_algorithm)
+            .map_err(|e| CrlError::Signature { source: e })?;
+
+        hash_type
+            .verify(
+                issuer.public_key(),
+                &self.overview.tbs_cert_list.0,
+                self.details.signature_value.0.payload_view(),
+            )
+            .map_err(|e| CrlError::Signature { source: e })
+    }
+}
+
+// === builder === //
+
+#[derive(Clone, Debug)]
+struct IssuerInfos<'a> {
+    name: DirectoryName,
+    key: &'a PrivateKey,
+}
+
+// Statically checks the field actually exists and returns a &'static str of the field name
+macro_rules! field_str {
+    ($field:ident) => {{
+        const _: fn() = || {
+            let CrlBuilderInner { $field: _, .. };
+        };
+        stringify!($field)
+    }};
+}
+
+#[derive(Default, Clone, Debug)]
+struct CrlBuilderInner<'a> {
+    issuer_infos: Option<IssuerInfos<'a>>,
+    authority_key_identifier: Option<Vec<u8>>,
+    this_update: Option<UTCDate>,
+    next_update: Option<UTCDate>,
+    crl_number: Option<Vec<u8>>,
+    revoked_certificates: Vec<RevokedCertificate>,
+    signature_hash_type: Option<SignatureAlgorithm>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct CrlBuilder<'a> {
+    inner: RefCell<CrlBuilderInner<'a>>,
+}
+
+impl<'a> CrlBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Required (alternative: `issuer_cert`)
+    #[inline]
+    pub fn issuer(&self, issuer_name: DirectoryName, issuer_key: &'a PrivateKey) -> &Self {
+        self.inner.borrow_mut().issuer_infos = Some(IssuerInfos {
+            name: issuer_name,
+            key: issuer_key,
+        });
+        self
+    }
+
+    /// Required (alternative: `issuer`)
+    #[inline]
+    pub fn issuer_cert(&self, issuer_cert: &Cert, issuer_key: &'a PrivateKey) -> &Self {
+        let builder = self.issuer(issuer_cert.subject_name(), issuer_key);
+
+        if let Ok(issuer_ski) = issuer_cert.subject_key_identifier() {
+            self.authority_key_identifier(issuer_ski.to_vec())
+        } else {
+            builder
+        }
+    }
+
+    /// Optional (alternative: `issuer_cert`)
+    #[inline]
+    pub fn authority_key_identifier(&self, aki: Vec<u8>) -> &Self {
+        self.inner.borrow_mut().authority_key_identifier = Some(aki);
+        self
+    }
+
+    /// Required
+    #[inline]
+    pub fn this_update(&self, this_update: UTCDate) -> &Self {
+        self.inner.borrow_mut().this_update = Some(this_update);
+        self
+    }
+
+    /// Optional
+    #[inline]
+    pub fn next_update(&self, next_update: UTCDate) -> &Self {
+        self.inner.borrow_mut().next_update = Some(next_update);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Monotonically increasing sequence number for this CRL issuer.
+    #[inline]
+    pub fn crl_number(&self, unsigned_integer_bytes: Vec<u8>) -> &Self {
+        self.inner.borrow_mut().crl_number = Some(unsigned_integer_bytes);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Lists the certificate with the given serial number as revoked.
+    #[inline]
+    pub fn revoke(&self, unsigned_serial_number_bytes: Vec<u8>, revocation_date: UTCDate) -> &Self {
+        self.h_revoke(
+            IntegerAsn1::from_bytes_be_unsigned(unsigned_serial_number_bytes),
+            revocation_date,
+        )
+    }
+
+    /// Optional
+    ///
+    /// Lists the certificate with the given serial number as revoked for the given reason.
+    #[inline]
+    pub fn revoke_with_reason(
+        &self,
+        unsigned_serial_number_bytes: Vec<u8>,
+        revocation_date: UTCDate,
+        reason: CrlReason,
+    ) -> &Self {
+        self.h_revoke_with_reason(
+            IntegerAsn1::from_bytes_be_unsigned(unsigned_serial_number_bytes),
+            revocation_date,
+            Some(reason),
+        )
+    }
+
+    /// Optional
+    ///
+    /// Lists the given certificate as revoked.
+    #[inline]
+    pub fn revoke_cert(&self, cert: &Cert, revocation_date: UTCDate) -> &Self {
+        self.h_revoke(cert.serial_number().clone(), revocation_date)
+    }
+
+    /// Optional
+    #[inline]
+    pub fn signature_hash_type(&self, signature_hash_type: SignatureAlgorithm) -> &Self {
+        self.inner.borrow_mut().signature_hash_type = Some(signature_hash_type);
+        self
+    }
+
+    fn h_revoke(&self, serial_number: IntegerAsn1, revocation_date: UTCDate) -> &Self {
+        self.h_revoke_with_reason(serial_number, revocation_date, None)
+    }
+
+    fn h_revoke_with_reason(
+        &self,
+        serial_number: IntegerAsn1,
+        revocation_date: UTCDate,
+        reason: Option<CrlReason>,
+    ) -> &Self {
+        self.inner.borrow_mut().revoked_certificates.push(RevokedCertificate {
+            user_certificate: CertificateSerialNumber(serial_number),
+            revocation_data: revocation_date.into(),
+            crl_entry_extensions: reason.map(|reason| Extensions(vec![Extension::new_crl_reason(reason)])),
+        });
+        self
+    }
+
+    pub fn build(&self) -> Result<Crl, CrlError> {
+        let mut inner = self.inner.borrow_mut();
+
+        let issuer_infos = inner.issuer_infos.take().ok_or(CrlError::MissingBuilderArgument {
+            arg: field_str!(issuer_infos),
+        })?;
+        let this_update = inner.this_update.take().ok_or(CrlError::MissingBuilderArgument {
+            arg: field_str!(this_update),
+        })?;
+        let next_update = inner.next_update.take();
+
+        let signature_hash_type = inner
+            .signature_hash_type
+            .take()
+            .unwrap_or(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256));
+
+        let extensions = {
+            let mut extensions = Vec::new();
+
+            if let Some(aki) = inner.authority_key_identifier.take() {
+                extensions.push(Extension::new_authority_key_identifier(
+                    KeyIdentifier::from(aki),
+                    None,
+                    None,
+                ));
+            }
+
+            if let Some(crl_number) = inner.crl_number.take() {
+                extensions.push(Extension::new_crl_number(OctetStringAsn1Container(
+                    IntegerAsn1::from_bytes_be_unsigned(crl_number),
+                )));
+            }
+
+            extensions
+        };
+
+        let revoked_certificates = std::mem::take(&mut inner.revoked_certificates);
+
+        drop(inner);
+
+        let signature_algorithm =
+            AlgorithmIdentifier::try_from(signature_hash_type).map_err(|e| CrlError::Signature { source: e })?;
+
+        // version MUST be v2 when extensions are used, and omitted otherwise
+        let (version, crl_extension) = if extensions.is_empty() {
+            (None, None)
+        } else {
+            (Some(Version::V2), Some(Extensions(extensions)))
+        };
+
+        // revokedCertificates MUST be absent when there is no revoked certificate
+        let revoked_certificates = if revoked_certificates.is_empty() {
+            None
+        } else {
+            Some(RevokedCertificates(Asn1SequenceOf(revoked_certificates)))
+        };
+
+        let tbs_cert_list = TbsCertList {
+            version,
+            signature: signature_algorithm.clone(),
+            issuer: Name::from(issuer_infos.name),
+            this_update: this_update.into(),
+            next_update: next_update.map(Into::into),
+            revoked_certificates,
+            crl_extension: ExplicitContextTag0(crl_extension),
+        };
+
+        let tbs_der = picky_asn1_der::to_vec(&tbs_cert_list).map_err(|e| CrlError::Asn1Serialization {
+            source: e,
+            element: "tbs cert list",
+        })?;
+
+        let signature_value = BitString::with_bytes(
+            signature_hash_type
+                .sign(&tbs_der, issuer_infos.key)
+                .map_err(|e| CrlError::Signature { source: e })?,
+        )
+        .into();
+
+        let signature_algorithm_der =
+            picky_asn1_der::to_vec(&signature_algorithm).map_err(|source| CrlError::Asn1Serialization {
+                element: "signature_algorithm",
+                source,
+            })?;
+
+        let signature_value_der =
+            picky_asn1_der::to_vec(&signature_value).map_err(|source| CrlError::Asn1Serialization {
+                element: "signature_value",
+                source,
+            })?;
+
+        Ok(Crl {
+            details: CertificateList {
+                tbs_cert_list,
+                signature_algorithm,
+                signature_value,
+            },
+            overview: CrlOverview {
+                tbs_cert_list: Asn1RawDer(tbs_der),
+                signature_algorithm: Asn1RawDer(signature_algorithm_der),
+                signature_value: Asn1RawDer(signature_value_der),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pem::parse_pem;
+    use crate::x509::certificate::CertificateBuilder;
+
+    const CRL_CA: &str = include_str!("../../../test_assets/crl/ca.crt");
+    const CRL_LEAF: &str = include_str!("../../../test_assets/crl/leaf.crt");
+    const EMPTY_CRL: &str = include_str!("../../../test_assets/crl/empty.crl");
+    const REVOKED_CRL: &str = include_str!("../../../test_assets/crl/revoked.crl");
+
+    fn parse_key(pem_str: &str) -> PrivateKey {
+        let pem = pem_str.parse::<Pem>().unwrap();
+        PrivateKey::from_pkcs8(pem.data()).unwrap()
+    }
+
+    #[test]
+    fn read_openssl_crls() {
+        let ca = Cert::from_pem_str(CRL_CA).unwrap();
+        let leaf = Cert::from_pem_str(CRL_LEAF).unwrap();
+
+        let empty = Crl::from_pem_str(EMPTY_CRL).unwrap();
+        empty.verify(&ca).unwrap();
+        assert_eq!(empty.issuer_name(), ca.subject_name());
+        assert!(empty.next_update().is_some());
+        assert!(empty.extensions().is_empty());
+        assert!(empty.revoked_certificates().is_empty());
+        assert!(!empty.is_revoked(&leaf));
+
+        let revoked = Crl::from_pem_str(REVOKED_CRL).unwrap();
+        revoked.verify(&ca).unwrap();
+        assert_eq!(revoked.crl_number().unwrap().as_unsigned_bytes_be(), [0x10, 0x00]);
+        assert_eq!(revoked.revoked_certificates().len(), 1);
+        assert!(revoked.is_revoked(&leaf));
+        assert!(revoked.revocation_date(leaf.serial_number()).unwrap() <= revoked.this_update());
+        assert_eq!(
+            revoked.revocation_reason(leaf.serial_number()),
+            Some(CrlReason::KeyCompromise)
+        );
+
+        // re-encoded as is
+        let pem = parse_pem(REVOKED_CRL).unwrap();
+        assert_eq!(revoked.to_der().unwrap(), pem.data());
+
+        // not issued by the leaf
+        let err = revoked.verify(&leaf).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "issuer name doesn't match (expected: CN=Test CRL CA, got: CN=leaf)"
+        );
+    }
+
+    #[test]
+    fn build_and_verify_crl() {
+        let ca_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let other_key = parse_key(crate::test_files::RSA_2048_PK_2);
+
+        let ca = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Revocation Root CA"), &ca_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let crl = CrlBuilder::new()
+            .issuer_cert(&ca, &ca_key)
+            .this_update(UTCDate::ymd(2066, 1, 1).unwrap())
+            .next_update(UTCDate::ymd(2066, 2, 1).unwrap())
+            .crl_number(vec![0x2a])
+            .revoke(vec![0x80, 0x01], UTCDate::ymd(2065, 12, 24).unwrap())
+            .revoke_with_reason(vec![0x02], UTCDate::ymd(2065, 12, 25).unwrap(), CrlReason::Superseded)
+            .build()
+            .expect("couldn't build crl");
+
+        crl.verify(&ca).expect("couldn't verify crl");
+        assert_eq!(crl.this_update(), UTCDate::ymd(2066, 1, 1).unwrap());
+        assert_eq!(crl.next_update(), Some(UTCDate::ymd(2066, 2, 1).unwrap()));
+        assert_eq!(crl.crl_number().unwrap().as_unsigned_bytes_be(), [0x2a]);
+        assert_eq!(
+            crl.revocation_date(&IntegerAsn1::from_bytes_be_unsigned(vec![0x80, 0x01])),
+            Some(UTCDate::ymd(2065, 12, 24).unwrap())
+        );
+        assert_eq!(
+            crl.revocation_date(&IntegerAsn1::from_bytes_be_unsigned(vec![0x01])),
+            None
+        );
+        assert_eq!(
+            crl.revocation_reason(&IntegerAsn1::from_bytes_be_unsigned(vec![0x80, 0x01])),
+            None
+        );
+        assert_eq!(
+            crl.revocation_reason(&IntegerAsn1::from_bytes_be_unsigned(vec![0x02])),
+            Some(CrlReason::Superseded)
+        );
+
+        let decoded = Crl::from_pem(&crl.to_pem().unwrap()).unwrap();
+        assert_eq!(decoded, crl);
+
+        // same name, different key
+        let impostor = CrlBuilder::new()
+            .issuer(ca.subject_name(), &other_key)
+            .this_update(UTCDate::ymd(2066, 1, 1).unwrap())
+            .build()
+            .expect("couldn't build crl");
+        assert!(matches!(impostor.verify(&ca), Err(CrlError::Signature { .. })));
+
+        let err = CrlBuilder::new().issuer_cert(&ca, &ca_key).build().unwrap_err();
+        assert_eq!(err.to_string(), "missing required builder argument `this_update`");
+    }
+}
@@ -6,7 +6,9 @@ use crate::x509::certificate::CertError;
 use crate::x509::name::DirectoryName;
 use picky_asn1::bit_string::BitString;
 use picky_asn1_der::Asn1DerError;
-use picky_asn1_x509::{CertificationRequest, CertificationRequestInfo};
+use picky_asn1_x509::{
+    AlgorithmIdentifier, AttributeValues, CertificationRequest, CertificationRequestInfo, Extension,
+};
 use thiserror::Error;
 
 pub use picky_asn1_x509::Attribute;
@@ -117,6 +119,18 @@ impl Csr {
         (&self.0.certification_request_info.subject_public_key_info).into()
     }
 
+    /// Extensions requested using the `extensionRequest` attribute.
+    pub fn requested_extensions(&self) -> &[Extension] {
+        ((self.0.certification_request_info.attributes.0).0)
+            .iter()
+            .find_map(|attr| match &attr.value {
+                AttributeValues::Extensions(set_of_extensions) => set_of_extensions.0.first(),
+                _ => None,
+            })
+            .map(|extensions| extensions.0.as_slice())
+            .unwrap_or(&[])
+    }
+
     pub fn into_subject_infos(self) -> (DirectoryName, PublicKey) {
         (
             self.0.certification_request_info.subject.into(),
@@ -149,6 +163,9 @@ fn h_generate_from_cri(
     private_key: &PrivateKey,
     signature_hash_type: SignatureAlgorithm,
 ) -> Result<Csr, CsrError> {
+    let signature_algorithm =
+        AlgorithmIdentifier::try_from(signature_hash_type).map_err(|e| CsrError::Signature { source: e })?;
+
     let cri_der = picky_asn1_der::to_vec(&cri).map_err(|e| CsrError::Asn1Serialization {
         source: e,
         element: "certification request cri",
@@ -161,7 +178,7 @@ fn h_generate_from_cri(
 
     Ok(Csr(CertificationRequest {
         certification_request_info: cri,
-        signature_algorithm: signature_hash_type.into(),
+        signature_algorithm,
         signature: signature.into(),
     }))
 }
@@ -134,18 +134,25 @@ pub mod pkcs7;
 pub mod wincert;
 
 pub mod certificate;
+pub mod crl;
 pub mod csr;
 pub mod date;
 pub mod key_id_gen_method;
 pub mod name;
+pub mod ocsp;
+pub mod path_builder;
+pub mod trust_store;
 
 pub use certificate::Cert;
+pub use crl::Crl;
 pub use csr::Csr;
 pub use key_id_gen_method::KeyIdGenMethod;
 pub use picky_asn1_x509::{DirectoryString, Extension, Extensions};
+pub use trust_store::TrustStore;
 
 pub mod extension {
     pub use picky_asn1_x509::extension::*;
 }
 
+mod policy;
 mod utils;
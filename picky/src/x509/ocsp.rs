@@ -0,0 +1,1319 @@
+//! Online Certificate Status Protocol based on [RFC6960](https://datatracker.ietf.org/doc/html/rfc6960)
+//!
+//! The transport is left to the caller: any type implementing `OcspTransport` can be used
+//! to send requests to a responder.
+
+use crate::hash::{HashAlgorithm, UnsupportedHashAlgorithmError};
+use crate::key::{PrivateKey, PublicKey};
+use crate::signature::{SignatureAlgorithm, SignatureError};
+use crate::x509::certificate::{Cert, CertError};
+use crate::x509::date::UTCDate;
+use crate::x509::name::DirectoryName;
+use picky_asn1::bit_string::BitString;
+use picky_asn1::date::GeneralizedTime;
+use picky_asn1::wrapper::{
+    Asn1SequenceOf, BitStringAsn1Container, ExplicitContextTag0, ExplicitContextTag1, ExplicitContextTag2, IntegerAsn1,
+    OctetStringAsn1,
+};
+use picky_asn1_der::{Asn1DerError, Asn1RawDer};
+use picky_asn1_x509::{
+    oids, AlgorithmIdentifier, BasicOcspResponse, CertId, CertStatus, CertificateSerialNumber, CrlReason, Extension,
+    ExtensionView, Extensions, Name, OcspResponseStatus, Request, ResponderId, ResponseBytes, ResponseData,
+    RevokedInfo, ShaVariant, SingleResponse, TbsRequest, Version,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use thiserror::Error;
+
+const REQUEST_ELEMENT_NAME: &str = "ocsp request";
+const RESPONSE_ELEMENT_NAME: &str = "ocsp response";
+const BASIC_RESPONSE_ELEMENT_NAME: &str = "basic ocsp response";
+
+const NONCE_LEN: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum OcspError {
+    /// ASN1 serialization error
+    #[error("(ASN1) couldn't serialize {element}: {source}")]
+    Asn1Serialization {
+        element: &'static str,
+        source: Asn1DerError,
+    },
+
+    /// ASN1 deserialization error
+    #[error("(ASN1) couldn't deserialize {element}: {source}")]
+    Asn1Deserialization {
+        element: &'static str,
+        source: Asn1DerError,
+    },
+
+    /// signature error
+    #[error("signature error: {source}")]
+    Signature { source: SignatureError },
+
+    /// unsupported hash algorithm
+    #[error("hash algorithm error: {source}")]
+    UnsupportedHashAlgorithm { source: UnsupportedHashAlgorithmError },
+
+    /// responder certificate error
+    #[error("invalid responder certificate: {source}")]
+    Cert { source: CertError },
+
+    /// the responder didn't process the request
+    #[error("unsuccessful response: {status:?}")]
+    UnsuccessfulResponse { status: OcspResponseStatus },
+
+    /// response type is not `id-pkix-ocsp-basic`
+    #[error("unsupported response type: {oid}")]
+    UnsupportedResponseType { oid: String },
+
+    /// the response doesn't contain the status of the requested certificate
+    #[error("no response matching the certificate")]
+    NoMatchingResponse,
+
+    /// the response isn't signed by the issuer nor by a responder it delegated to
+    #[error("response is not signed by an authorized responder")]
+    UnauthorizedResponder,
+
+    /// response nonce doesn't match the request nonce
+    #[error("response nonce doesn't match request nonce")]
+    NonceMismatch,
+
+    /// the request has a nonce but the response doesn't
+    #[error("response nonce is missing")]
+    MissingNonce,
+
+    /// response is not yet valid
+    #[error("response not yet valid (this update: {this_update}, now: {now})")]
+    NotYetValid { this_update: UTCDate, now: UTCDate },
+
+    /// response expired
+    #[error("response expired (next update: {next_update}, now: {now})")]
+    Expired { next_update: UTCDate, now: UTCDate },
+
+    /// transport error
+    #[error("transport error: {source}")]
+    Transport {
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// missing required builder argument
+    #[error("missing required builder argument `{arg}`")]
+    MissingBuilderArgument { arg: &'static str },
+}
+
+/// Sends OCSP requests to a responder.
+///
+/// Implementors are free to pick the protocol (usually HTTP as described in
+/// [RFC 6960 Appendix A](https://datatracker.ietf.org/doc/html/rfc6960#appendix-A)).
+pub trait OcspTransport {
+    fn send(&self, request: &OcspRequest) -> Result<OcspResponse, OcspError>;
+}
+
+// === cert id === //
+
+/// Identifies a certificate by its serial number and hashes of its issuer's name and public key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcspCertId(CertId);
+
+impl From<CertId> for OcspCertId {
+    fn from(cert_id: CertId) -> Self {
+        Self(cert_id)
+    }
+}
+
+impl From<OcspCertId> for CertId {
+    fn from(cert_id: OcspCertId) -> Self {
+        cert_id.0
+    }
+}
+
+impl OcspCertId {
+    pub fn new(serial_number: &IntegerAsn1, issuer: &Cert, hash_algorithm: HashAlgorithm) -> Result<Self, OcspError> {
+        let hash_algorithm_identifier = AlgorithmIdentifier::new_sha(
+            ShaVariant::try_from(hash_algorithm).map_err(|e| OcspError::UnsupportedHashAlgorithm { source: e })?,
+        );
+        let (issuer_name_hash, issuer_key_hash) = h_issuer_hashes(issuer, hash_algorithm)?;
+
+        Ok(Self(CertId {
+            hash_algorithm: hash_algorithm_identifier,
+            issuer_name_hash: issuer_name_hash.into(),
+            issuer_key_hash: issuer_key_hash.into(),
+            serial_number: CertificateSerialNumber(serial_number.clone()),
+        }))
+    }
+
+    pub fn from_cert(cert: &Cert, issuer: &Cert, hash_algorithm: HashAlgorithm) -> Result<Self, OcspError> {
+        Self::new(cert.serial_number(), issuer, hash_algorithm)
+    }
+
+    pub fn serial_number(&self) -> &IntegerAsn1 {
+        &(self.0.serial_number).0
+    }
+
+    pub fn hash_algorithm(&self) -> Result<HashAlgorithm, OcspError> {
+        h_hash_algorithm(&self.0.hash_algorithm)
+    }
+
+    /// Checks the issuer name and key hashes designate `issuer`.
+    pub fn is_issued_by(&self, issuer: &Cert) -> Result<bool, OcspError> {
+        let (issuer_name_hash, issuer_key_hash) = h_issuer_hashes(issuer, self.hash_algorithm()?)?;
+        Ok(self.0.issuer_name_hash.0 == issuer_name_hash && self.0.issuer_key_hash.0 == issuer_key_hash)
+    }
+
+    /// Checks this identifies the certificate with `serial_number` issued by `issuer`.
+    pub fn matches(&self, serial_number: &IntegerAsn1, issuer: &Cert) -> Result<bool, OcspError> {
+        Ok(
+            self.serial_number().as_unsigned_bytes_be() == serial_number.as_unsigned_bytes_be()
+                && self.is_issued_by(issuer)?,
+        )
+    }
+}
+
+fn h_hash_algorithm(algorithm: &AlgorithmIdentifier) -> Result<HashAlgorithm, OcspError> {
+    let sha_variant =
+        ShaVariant::try_from(algorithm.oid_asn1().clone()).map_err(|_| OcspError::UnsupportedHashAlgorithm {
+            source: UnsupportedHashAlgorithmError {
+                algorithm: Into::<String>::into(algorithm.oid()),
+            },
+        })?;
+    HashAlgorithm::try_from(sha_variant).map_err(|e| OcspError::UnsupportedHashAlgorithm { source: e })
+}
+
+fn h_issuer_hashes(issuer: &Cert, hash_algorithm: HashAlgorithm) -> Result<(Vec<u8>, Vec<u8>), OcspError> {
+    let issuer_name_der =
+        picky_asn1_der::to_vec(&Name::from(issuer.subject_name())).map_err(|e| OcspError::Asn1Serialization {
+            element: "issuer name",
+            source: e,
+        })?;
+    let issuer_key = h_public_key_value(issuer.public_key())?;
+    Ok((
+        hash_algorithm.digest(&issuer_name_der),
+        hash_algorithm.digest(&issuer_key),
+    ))
+}
+
+/// Value of the subjectPublicKey BIT STRING (excluding the tag, length, and number of unused bits).
+fn h_public_key_value(public_key: &PublicKey) -> Result<Vec<u8>, OcspError> {
+    use picky_asn1_x509::PublicKey as InnerPublicKey;
+
+    match &public_key.as_inner().subject_public_key {
+        InnerPublicKey::Rsa(BitStringAsn1Container(rsa_pk)) => {
+            picky_asn1_der::to_vec(rsa_pk).map_err(|e| OcspError::Asn1Serialization {
+                element: "RSA public key",
+                source: e,
+            })
+        }
+        InnerPublicKey::Ec(bitstring) => Ok(bitstring.0.payload_view().to_vec()),
+        InnerPublicKey::Ed(bitstring) => Ok(bitstring.0.payload_view().to_vec()),
+    }
+}
+
+fn h_nonce(extensions: Option<&Extensions>) -> Option<Vec<u8>> {
+    let nonce_extension = extensions?
+        .0
+        .iter()
+        .find(|extension| extension.extn_id().0 == oids::id_pkix_ocsp_nonce())?;
+
+    match nonce_extension.extn_value() {
+        // RFC 8954 nonce is an OCTET STRING, but some responders put the raw value directly
+        ExtensionView::Generic(value) => Some(
+            picky_asn1_der::from_bytes::<OctetStringAsn1>(&value.0)
+                .map(|nonce| nonce.0)
+                .unwrap_or_else(|_| value.0.clone()),
+        ),
+        _ => None,
+    }
+}
+
+// === request === //
+
+/// OCSP request ([RFC 6960 #4.1](https://datatracker.ietf.org/doc/html/rfc6960#section-4.1))
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcspRequest(picky_asn1_x509::OcspRequest);
+
+impl From<picky_asn1_x509::OcspRequest> for OcspRequest {
+    fn from(request: picky_asn1_x509::OcspRequest) -> Self {
+        Self(request)
+    }
+}
+
+impl From<OcspRequest> for picky_asn1_x509::OcspRequest {
+    fn from(request: OcspRequest) -> Self {
+        request.0
+    }
+}
+
+impl OcspRequest {
+    pub fn from_der<T: ?Sized + AsRef<[u8]>>(der: &T) -> Result<Self, OcspError> {
+        picky_asn1_der::from_bytes(der.as_ref())
+            .map(Self)
+            .map_err(|e| OcspError::Asn1Deserialization {
+                element: REQUEST_ELEMENT_NAME,
+                source: e,
+            })
+    }
+
+    pub fn to_der(&self) -> Result<Vec<u8>, OcspError> {
+        picky_asn1_der::to_vec(&self.0).map_err(|e| OcspError::Asn1Serialization {
+            element: REQUEST_ELEMENT_NAME,
+            source: e,
+        })
+    }
+
+    pub fn cert_ids(&self) -> impl Iterator<Item = OcspCertId> + '_ {
+        self.0
+            .tbs_request
+            .request_list
+            .0
+            .iter()
+            .map(|request| OcspCertId(request.req_cert.clone()))
+    }
+
+    pub fn nonce(&self) -> Option<Vec<u8>> {
+        h_nonce(
+            self.0
+                .tbs_request
+                .request_extensions
+                .as_ref()
+                .map(|extensions| &extensions.0),
+        )
+    }
+
+    /// Sends this request using the given transport.
+    pub fn send<T: OcspTransport>(&self, transport: &T) -> Result<OcspResponse, OcspError> {
+        transport.send(self)
+    }
+}
+
+// Statically checks the field actually exists and returns a &'static str of the field name
+#[derive(Clone, Debug)]
+struct OcspRequestBuilderInner<'a> {
+    certs: Vec<(&'a Cert, &'a Cert)>,
+    hash_algorithm: HashAlgorithm,
+    nonce: Option<Vec<u8>>,
+    generate_nonce: bool,
+}
+
+impl Default for OcspRequestBuilderInner<'_> {
+    fn default() -> Self {
+        Self {
+            certs: Vec::new(),
+            hash_algorithm: HashAlgorithm::SHA1,
+            nonce: None,
+            generate_nonce: true,
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct OcspRequestBuilder<'a> {
+    inner: RefCell<OcspRequestBuilderInner<'a>>,
+}
+
+impl<'a> OcspRequestBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Required (at least once)
+    ///
+    /// Requests the status of `cert` issued by `issuer`.
+    #[inline]
+    pub fn cert(&self, cert: &'a Cert, issuer: &'a Cert) -> &Self {
+        self.inner.borrow_mut().certs.push((cert, issuer));
+        self
+    }
+
+    /// Optional (SHA-1 by default)
+    ///
+    /// Hash algorithm used to identify the certificates.
+    #[inline]
+    pub fn hash_algorithm(&self, hash_algorithm: HashAlgorithm) -> &Self {
+        self.inner.borrow_mut().hash_algorithm = hash_algorithm;
+        self
+    }
+
+    /// Optional (a random nonce is generated by default)
+    #[inline]
+    pub fn nonce(&self, nonce: Vec<u8>) -> &Self {
+        self.inner.borrow_mut().nonce = Some(nonce);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Don't include a nonce in the request.
+    #[inline]
+    pub fn no_nonce(&self) -> &Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.nonce = None;
+        inner.generate_nonce = false;
+        drop(inner);
+        self
+    }
+
+    pub fn build(&self) -> Result<OcspRequest, OcspError> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.certs.is_empty() {
+            return Err(OcspError::MissingBuilderArgument { arg: "cert" });
+        }
+
+        let hash_algorithm = inner.hash_algorithm;
+        let request_list = std::mem::take(&mut inner.certs)
+            .into_iter()
+            .map(|(cert, issuer)| {
+                Ok(Request {
+                    req_cert: OcspCertId::from_cert(cert, issuer, hash_algorithm)?.into(),
+                    single_request_extensions: None,
+                })
+            })
+            .collect::<Result<Vec<_>, OcspError>>()?;
+
+        let nonce = match inner.nonce.take() {
+            Some(nonce) => Some(nonce),
+            None if inner.generate_nonce => {
+                let mut rnd = rand::thread_rng();
+                Some((0..NONCE_LEN).map(|_| rnd.gen::<u8>()).collect())
+            }
+            None => None,
+        };
+
+        let request_extensions =
+            nonce.map(|nonce| ExplicitContextTag2(Extensions(vec![Extension::new_ocsp_nonce(nonce)])));
+
+        Ok(OcspRequest(picky_asn1_x509::OcspRequest {
+            tbs_request: TbsRequest {
+                version: ExplicitContextTag0(Version::V1),
+                requestor_name: None,
+                request_list: Asn1SequenceOf(request_list),
+                request_extensions,
+            },
+            optional_signature: None,
+        }))
+    }
+}
+
+// === response === //
+
+/// Status of a certificate as reported by an OCSP responder.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OcspCertStatus {
+    Good,
+    Revoked {
+        revocation_time: UTCDate,
+        reason: Option<CrlReason>,
+    },
+    Unknown,
+}
+
+impl From<&CertStatus> for OcspCertStatus {
+    fn from(status: &CertStatus) -> Self {
+        match status {
+            CertStatus::Good => Self::Good,
+            CertStatus::Revoked(revoked_info) => Self::Revoked {
+                revocation_time: revoked_info.revocation_time.0.clone().into(),
+                reason: revoked_info.revocation_reason.as_ref().map(|reason| reason.0),
+            },
+            CertStatus::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl From<OcspCertStatus> for CertStatus {
+    fn from(status: OcspCertStatus) -> Self {
+        match status {
+            OcspCertStatus::Good => Self::Good,
+            OcspCertStatus::Revoked {
+                revocation_time,
+                reason,
+            } => Self::Revoked(RevokedInfo {
+                revocation_time: GeneralizedTime::from(revocation_time).into(),
+                revocation_reason: reason.map(ExplicitContextTag0),
+            }),
+            OcspCertStatus::Unknown => Self::Unknown,
+        }
+    }
+}
+
+/// Raw DER parts of the basic response, used to check the signature against the original `tbsResponseData` encoding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct BasicOcspResponseOverview {
+    tbs_response_data: Asn1RawDer,
+    signature_algorithm: Asn1RawDer,
+    signature: Asn1RawDer,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct BasicResponse {
+    details: BasicOcspResponse,
+    overview: BasicOcspResponseOverview,
+}
+
+/// OCSP response ([RFC 6960 #4.2](https://datatracker.ietf.org/doc/html/rfc6960#section-4.2))
+///
+/// Only the `id-pkix-ocsp-basic` response type is supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OcspResponse {
+    details: picky_asn1_x509::OcspResponse,
+    basic: Option<BasicResponse>,
+}
+
+impl TryFrom<picky_asn1_x509::OcspResponse> for OcspResponse {
+    type Error = OcspError;
+
+    fn try_from(response: picky_asn1_x509::OcspResponse) -> Result<Self, Self::Error> {
+        let basic = match &response.response_bytes {
+            Some(response_bytes) => {
+                let response_bytes = &response_bytes.0;
+                if response_bytes.response_type.0 != oids::id_pkix_ocsp_basic() {
+                    return Err(OcspError::UnsupportedResponseType {
+                        oid: (&response_bytes.response_type.0).into(),
+                    });
+                }
+
+                let der = &response_bytes.response.0;
+                let h_decode_err = |e| OcspError::Asn1Deserialization {
+                    element: BASIC_RESPONSE_ELEMENT_NAME,
+                    source: e,
+                };
+                Some(BasicResponse {
+                    details: picky_asn1_der::from_bytes(der).map_err(h_decode_err)?,
+                    overview: picky_asn1_der::from_bytes(der).map_err(h_decode_err)?,
+                })
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            details: response,
+            basic,
+        })
+    }
+}
+
+impl From<OcspResponse> for picky_asn1_x509::OcspResponse {
+    fn from(response: OcspResponse) -> Self {
+        response.details
+    }
+}
+
+impl OcspResponse {
+    /// Creates a response without any content for the given error status.
+    pub fn unsuccessful(status: OcspResponseStatus) -> Self {
+        Self {
+            details: picky_asn1_x509::OcspResponse {
+                response_status: status,
+                response_bytes: None,
+            },
+            basic: None,
+        }
+    }
+
+    pub fn from_der<T: ?Sized + AsRef<[u8]>>(der: &T) -> Result<Self, OcspError> {
+        let response: picky_asn1_x509::OcspResponse =
+            picky_asn1_der::from_bytes(der.as_ref()).map_err(|e| OcspError::Asn1Deserialization {
+                element: RESPONSE_ELEMENT_NAME,
+                source: e,
+            })?;
+        Self::try_from(response)
+    }
+
+    pub fn to_der(&self) -> Result<Vec<u8>, OcspError> {
+        picky_asn1_der::to_vec(&self.details).map_err(|e| OcspError::Asn1Serialization {
+            element: RESPONSE_ELEMENT_NAME,
+            source: e,
+        })
+    }
+
+    pub fn status(&self) -> OcspResponseStatus {
+        self.details.response_status
+    }
+
+    pub fn produced_at(&self) -> Option<UTCDate> {
+        self.basic
+            .as_ref()
+            .map(|basic| basic.details.tbs_response_data.produced_at.0.clone().into())
+    }
+
+    pub fn responses(&self) -> &[SingleResponse] {
+        match &self.basic {
+            Some(basic) => basic.details.tbs_response_data.responses.0.as_slice(),
+            None => &[],
+        }
+    }
+
+    /// Certificates included by the responder, usually to help verifying its signature.
+    pub fn certs(&self) -> Result<Vec<Cert>, OcspError> {
+        match self.basic.as_ref().and_then(|basic| basic.details.certs.as_ref()) {
+            Some(certs) => certs
+                .0
+                 .0
+                .iter()
+                .map(|certificate| Cert::try_from(certificate.clone()).map_err(|e| OcspError::Cert { source: e }))
+                .collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn nonce(&self) -> Option<Vec<u8>> {
+        let response_data = &self.basic.as_ref()?.details.tbs_response_data;
+        h_nonce(
+            response_data
+                .response_extensions
+                .as_ref()
+                .map(|extensions| &extensions.0),
+        )
+    }
+
+    /// Status reported for the given certificate, without any verification of the response.
+    pub fn cert_status(&self, cert: &Cert, issuer: &Cert) -> Result<OcspCertStatus, OcspError> {
+        Ok(OcspCertStatus::from(
+            &self.h_find_single_response(cert, issuer)?.cert_status,
+        ))
+    }
+
+    pub fn verifier(&self) -> OcspResponseValidator<'_> {
+        OcspResponseValidator {
+            response: self,
+            inner: RefCell::new(OcspResponseValidatorInner {
+                cert: None,
+                issuer: None,
+                request: None,
+                now: None,
+                require_nonce_check: true,
+            }),
+        }
+    }
+
+    fn h_basic(&self) -> Result<&BasicResponse, OcspError> {
+        match &self.basic {
+            Some(basic) if self.details.response_status == OcspResponseStatus::Successful => Ok(basic),
+            _ => Err(OcspError::UnsuccessfulResponse {
+                status: self.details.response_status,
+            }),
+        }
+    }
+
+    fn h_find_single_response(&self, cert: &Cert, issuer: &Cert) -> Result<&SingleResponse, OcspError> {
+        for single_response in &self.h_basic()?.details.tbs_response_data.responses.0 {
+            let cert_id = OcspCertId(single_response.cert_id.clone());
+            if cert_id.matches(cert.serial_number(), issuer)? {
+                return Ok(single_response);
+            }
+        }
+
+        Err(OcspError::NoMatchingResponse)
+    }
+}
+
+// === response verifier === //
+
+#[derive(Clone, Debug)]
+struct OcspResponseValidatorInner<'a> {
+    cert: Option<&'a Cert>,
+    issuer: Option<&'a Cert>,
+    request: Option<&'a OcspRequest>,
+    now: Option<&'a UTCDate>,
+    require_nonce_check: bool,
+}
+
+/// Utility to verify `OcspResponse`s
+///
+/// The response must be signed either by the certificate issuer or by a responder certificate
+/// issued by it and holding the `id-kp-OCSPSigning` extended key usage.
+#[derive(Clone, Debug)]
+pub struct OcspResponseValidator<'a> {
+    response: &'a OcspResponse,
+    inner: RefCell<OcspResponseValidatorInner<'a>>,
+}
+
+impl<'a> OcspResponseValidator<'a> {
+    /// Required
+    #[inline]
+    pub fn cert(&self, cert: &'a Cert) -> &Self {
+        self.inner.borrow_mut().cert = Some(cert);
+        self
+    }
+
+    /// Required
+    #[inline]
+    pub fn issuer(&self, issuer: &'a Cert) -> &Self {
+        self.inner.borrow_mut().issuer = Some(issuer);
+        self
+    }
+
+    /// Required
+    #[inline]
+    pub fn exact_date(&self, now: &'a UTCDate) -> &Self {
+        self.inner.borrow_mut().now = Some(now);
+        self
+    }
+
+    /// Optional
+    ///
+    /// Request the response answers to. Its nonce, if any, must be echoed by the response.
+    #[inline]
+    pub fn request(&self, request: &'a OcspRequest) -> &Self {
+        self.inner.borrow_mut().request = Some(request);
+        self
+    }
+
+    #[inline]
+    pub fn require_nonce_check(&self) -> &Self {
+        self.inner.borrow_mut().require_nonce_check = true;
+        self
+    }
+
+    #[inline]
+    pub fn ignore_nonce_check(&self) -> &Self {
+        self.inner.borrow_mut().require_nonce_check = false;
+        self
+    }
+
+    pub fn verify(&self) -> Result<OcspCertStatus, OcspError> {
+        let inner = self.inner.borrow();
+
+        let cert = inner.cert.ok_or(OcspError::MissingBuilderArgument { arg: "cert" })?;
+        let issuer = inner
+            .issuer
+            .ok_or(OcspError::MissingBuilderArgument { arg: "issuer" })?;
+        let now = inner.now.ok_or(OcspError::MissingBuilderArgument { arg: "now" })?;
+
+        let basic = self.response.h_basic()?;
+
+        // check signature
+        let responder_public_key = self.h_responder_public_key(basic, issuer, now)?;
+        let signature_algorithm = SignatureAlgorithm::from_algorithm_identifier(&basic.details.signature_algorithm)
+            .map_err(|e| OcspError::Signature { source: e })?;
+        signature_algorithm
+            .verify(
+                &responder_public_key,
+                &basic.overview.tbs_response_data.0,
+                basic.details.signature.0.payload_view(),
+            )
+            .map_err(|e| OcspError::Signature { source: e })?;
+
+        // check nonce
+        if inner.require_nonce_check {
+            if let Some(request_nonce) = inner.request.and_then(OcspRequest::nonce) {
+                match self.response.nonce() {
+                    Some(response_nonce) if response_nonce == request_nonce => {}
+                    Some(_) => return Err(OcspError::NonceMismatch),
+                    None => return Err(OcspError::MissingNonce),
+                }
+            }
+        }
+
+        let single_response = self.response.h_find_single_response(cert, issuer)?;
+
+        // check freshness
+        let this_update = UTCDate::from(single_response.this_update.0.clone());
+        if this_update.gt(now) {
+            return Err(OcspError::NotYetValid {
+                this_update,
+                now: now.clone(),
+            });
+        }
+
+        if let Some(next_update) = &single_response.next_update {
+            let next_update = UTCDate::from((next_update.0).0.clone());
+            if next_update.lt(now) {
+                return Err(OcspError::Expired {
+                    next_update,
+                    now: now.clone(),
+                });
+            }
+        }
+
+        Ok(OcspCertStatus::from(&single_response.cert_status))
+    }
+
+    fn h_responder_public_key(
+        &self,
+        basic: &BasicResponse,
+        issuer: &Cert,
+        now: &UTCDate,
+    ) -> Result<PublicKey, OcspError> {
+        let responder_id = &basic.details.tbs_response_data.responder_id;
+
+        if h_responder_id_matches(responder_id, issuer)? {
+            return Ok(issuer.public_key().clone());
+        }
+
+        // delegated responder
+        for responder in self.response.certs()? {
+            if !h_responder_id_matches(responder_id, &responder)? {
+                continue;
+            }
+
+            responder
+                .verifier()
+                .chain(std::iter::once(issuer))
+                .exact_date(now)
+                .chain_should_contains_root_certificate(false)
+                .verify()
+                .map_err(|e| OcspError::Cert { source: e })?;
+
+            let is_ocsp_signer = responder
+                .extensions()
+                .iter()
+                .any(|extension| match extension.extn_value() {
+                    ExtensionView::ExtendedKeyUsage(eku) => eku.contains(oids::kp_ocsp_signing()),
+                    _ => false,
+                });
+
+            if is_ocsp_signer {
+                return Ok(responder.into_public_key());
+            }
+        }
+
+        Err(OcspError::UnauthorizedResponder)
+    }
+}
+
+fn h_responder_id_matches(responder_id: &ResponderId, cert: &Cert) -> Result<bool, OcspError> {
+    match responder_id {
+        ResponderId::ByName(name) => Ok(DirectoryName::from(name.clone()) == cert.subject_name()),
+        ResponderId::ByKey(key_hash) => Ok(key_hash.0 == h_responder_key_hash(cert.public_key())?),
+    }
+}
+
+fn h_responder_key_hash(public_key: &PublicKey) -> Result<Vec<u8>, OcspError> {
+    Ok(HashAlgorithm::SHA1.digest(&h_public_key_value(public_key)?))
+}
+
+// === response builder === //
+
+// Statically checks the field actually exists and returns a &'static str of the field name
+macro_rules! field_str {
+    ($field:ident) => {{
+        const _: fn() = || {
+            let OcspResponseBuilderInner { $field: _, .. };
+        };
+        stringify!($field)
+    }};
+}
+
+#[derive(Clone, Debug)]
+struct ResponderInfos<'a> {
+    cert: Cert,
+    key: &'a PrivateKey,
+}
+
+#[derive(Default, Clone, Debug)]
+struct OcspResponseBuilderInner<'a> {
+    responder_infos: Option<ResponderInfos<'a>>,
+    responder_id_by_key: bool,
+    include_responder_cert: bool,
+    produced_at: Option<UTCDate>,
+    responses: Vec<SingleResponse>,
+    nonce: Option<Vec<u8>>,
+    signature_hash_type: Option<SignatureAlgorithm>,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct OcspResponseBuilder<'a> {
+    inner: RefCell<OcspResponseBuilderInner<'a>>,
+}
+
+impl<'a> OcspResponseBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Required
+    ///
+    /// Either the certificates issuer or a delegated responder holding the `id-kp-OCSPSigning` extended key usage.
+    #[inline]
+    pub fn responder(&self, responder_cert: Cert, responder_key: &'a PrivateKey) -> &Self {
+        self.inner.borrow_mut().responder_infos = Some(ResponderInfos {
+            cert: responder_cert,
+            key: responder_key,
+        });
+        self
+    }
+
+    /// Optional
+    ///
+    /// Identify the responder by the hash of its public key instead of its name.
+    #[inline]
+    pub fn responder_id_by_key(&self) -> &Self {
+        self.inner.borrow_mut().responder_id_by_key = true;
+        self
+    }
+
+    /// Optional
+    ///
+    /// Include the responder certificate in the response (required for delegated responders).
+    #[inline]
+    pub fn include_responder_cert(&self) -> &Self {
+        self.inner.borrow_mut().include_responder_cert = true;
+        self
+    }
+
+    /// Required
+    #[inline]
+    pub fn produced_at(&self, produced_at: UTCDate) -> &Self {
+        self.inner.borrow_mut().produced_at = Some(produced_at);
+        self
+    }
+
+    /// Required (at least once)
+    #[inline]
+    pub fn response(
+        &self,
+        cert_id: OcspCertId,
+        status: OcspCertStatus,
+        this_update: UTCDate,
+        next_update: Option<UTCDate>,
+    ) -> &Self {
+        self.inner.borrow_mut().responses.push(SingleResponse {
+            cert_id: cert_id.into(),
+            cert_status: status.into(),
+            this_update: GeneralizedTime::from(this_update).into(),
+            next_update: next_update.map(|next_update| ExplicitContextTag0(GeneralizedTime::from(next_update).into())),
+            single_extensions: None,
+        });
+        self
+    }
+
+    /// Optional
+    ///
+    /// Usually copied from the request.
+    #[inline]
+    pub fn nonce(&self, nonce: Vec<u8>) -> &Self {
+        self.inner.borrow_mut().nonce = Some(nonce);
+        self
+    }
+
+    /// Optional
+    #[inline]
+    pub fn signature_hash_type(&self, signature_hash_type: SignatureAlgorithm) -> &Self {
+        self.inner.borrow_mut().signature_hash_type = Some(signature_hash_type);
+        self
+    }
+
+    pub fn build(&self) -> Result<OcspResponse, OcspError> {
+        let mut inner = self.inner.borrow_mut();
+
+        let responder_infos = inner.responder_infos.take().ok_or(OcspError::MissingBuilderArgument {
+            arg: field_str!(responder_infos),
+        })?;
+        let produced_at = inner.produced_at.take().ok_or(OcspError::MissingBuilderArgument {
+            arg: field_str!(produced_at),
+        })?;
+
+        if inner.responses.is_empty() {
+            return Err(OcspError::MissingBuilderArgument {
+                arg: field_str!(responses),
+            });
+        }
+        let responses = std::mem::take(&mut inner.responses);
+
+        let signature_hash_type = inner
+            .signature_hash_type
+            .take()
+            .unwrap_or(SignatureAlgorithm::RsaPkcs1v15(HashAlgorithm::SHA2_256));
+
+        let responder_id = if inner.responder_id_by_key {
+            ResponderId::ByKey(h_responder_key_hash(responder_infos.cert.public_key())?.into())
+        } else {
+            ResponderId::ByName(responder_infos.cert.subject_name().into())
+        };
+
+        let response_extensions = inner
+            .nonce
+            .take()
+            .map(|nonce| ExplicitContextTag1(Extensions(vec![Extension::new_ocsp_nonce(nonce)])));
+
+        let certs = if inner.include_responder_cert {
+            Some(ExplicitContextTag0(Asn1SequenceOf(vec![responder_infos
+                .cert
+                .clone()
+                .into()])))
+        } else {
+            None
+        };
+
+        drop(inner);
+
+        let tbs_response_data = ResponseData {
+            version: ExplicitContextTag0(Version::V1),
+            responder_id,
+            produced_at: GeneralizedTime::from(produced_at).into(),
+            responses: Asn1SequenceOf(responses),
+            response_extensions,
+        };
+
+        let tbs_der = picky_asn1_der::to_vec(&tbs_response_data).map_err(|e| OcspError::Asn1Serialization {
+            element: "tbs response data",
+            source: e,
+        })?;
+
+        let signature_algorithm =
+            AlgorithmIdentifier::try_from(signature_hash_type).map_err(|e| OcspError::Signature { source: e })?;
+
+        let signature = BitString::with_bytes(
+            signature_hash_type
+                .sign(&tbs_der, responder_infos.key)
+                .map_err(|e| OcspError::Signature { source: e })?,
+        );
+
+        let basic_response = BasicOcspResponse {
+            tbs_response_data,
+            signature_algorithm,
+            signature: signature.into(),
+            certs,
+        };
+
+        let basic_response_der = picky_asn1_der::to_vec(&basic_response).map_err(|e| OcspError::Asn1Serialization {
+            element: BASIC_RESPONSE_ELEMENT_NAME,
+            source: e,
+        })?;
+
+        OcspResponse::try_from(picky_asn1_x509::OcspResponse {
+            response_status: OcspResponseStatus::Successful,
+            response_bytes: Some(ExplicitContextTag0(ResponseBytes {
+                response_type: oids::id_pkix_ocsp_basic().into(),
+                response: basic_response_der.into(),
+            })),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pem::Pem;
+    use crate::x509::certificate::CertificateBuilder;
+    use picky_asn1_x509::ExtendedKeyUsage;
+
+    const OCSP_CA: &str = include_str!("../../../test_assets/ocsp/ca.crt");
+    const OCSP_LEAF: &str = include_str!("../../../test_assets/ocsp/leaf.crt");
+    const OCSP_RESPONDER: &str = include_str!("../../../test_assets/ocsp/responder.crt");
+    const OCSP_REQUEST: &[u8] = include_bytes!("../../../test_assets/ocsp/request.der");
+    const OCSP_REQUEST_NONCE: &[u8] = include_bytes!("../../../test_assets/ocsp/request_nonce.der");
+    const OCSP_RESPONSE_REVOKED: &[u8] = include_bytes!("../../../test_assets/ocsp/response_revoked.der");
+    const OCSP_RESPONSE_GOOD_NONCE: &[u8] = include_bytes!("../../../test_assets/ocsp/response_good_nonce.der");
+
+    fn parse_key(pem_str: &str) -> PrivateKey {
+        let pem = pem_str.parse::<Pem>().unwrap();
+        PrivateKey::from_pkcs8(pem.data()).unwrap()
+    }
+
+    #[test]
+    fn read_openssl_ocsp() {
+        let ca = Cert::from_pem_str(OCSP_CA).unwrap();
+        let leaf = Cert::from_pem_str(OCSP_LEAF).unwrap();
+        let responder = Cert::from_pem_str(OCSP_RESPONDER).unwrap();
+        let now = UTCDate::ymd(2026, 10, 18).unwrap();
+
+        // same encoding as openssl
+        let request = OcspRequestBuilder::new().cert(&leaf, &ca).no_nonce().build().unwrap();
+        assert_eq!(request.to_der().unwrap(), OCSP_REQUEST);
+        assert_eq!(OcspRequest::from_der(OCSP_REQUEST).unwrap(), request);
+
+        // delegated responder, identified by name
+        let response = OcspResponse::from_der(OCSP_RESPONSE_REVOKED).unwrap();
+        assert_eq!(response.to_der().unwrap(), OCSP_RESPONSE_REVOKED);
+        assert_eq!(response.status(), OcspResponseStatus::Successful);
+        assert_eq!(response.certs().unwrap(), vec![responder]);
+        assert_eq!(response.nonce(), None);
+
+        let status = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap();
+        assert_eq!(
+            status,
+            OcspCertStatus::Revoked {
+                revocation_time: UTCDate::ymd(2026, 1, 1).unwrap(),
+                reason: Some(CrlReason::KeyCompromise),
+            }
+        );
+
+        // issuer as responder, identified by key hash
+        let request = OcspRequest::from_der(OCSP_REQUEST_NONCE).unwrap();
+        let response = OcspResponse::from_der(OCSP_RESPONSE_GOOD_NONCE).unwrap();
+        assert_eq!(response.nonce(), request.nonce());
+        assert!(response.certs().unwrap().is_empty());
+
+        let responder = Cert::from_pem_str(OCSP_RESPONDER).unwrap();
+        let status = response
+            .verifier()
+            .cert(&responder)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap();
+        assert_eq!(status, OcspCertStatus::Good);
+
+        // not the certificate the response is about
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(err, OcspError::NoMatchingResponse));
+    }
+
+    /// Local stand-in for an OCSP responder
+    struct LocalResponder<'a> {
+        issuer: &'a Cert,
+        responder: &'a Cert,
+        responder_key: &'a PrivateKey,
+        revoked: Vec<IntegerAsn1>,
+        now: UTCDate,
+        echo_nonce: bool,
+    }
+
+    impl OcspTransport for LocalResponder<'_> {
+        fn send(&self, request: &OcspRequest) -> Result<OcspResponse, OcspError> {
+            let builder = OcspResponseBuilder::new();
+            builder.responder(self.responder.clone(), self.responder_key);
+            builder.produced_at(self.now.clone());
+
+            if self.responder != self.issuer {
+                builder.include_responder_cert();
+            }
+
+            for cert_id in request.cert_ids() {
+                if !cert_id.is_issued_by(self.issuer)? {
+                    return Ok(OcspResponse::unsuccessful(OcspResponseStatus::Unauthorized));
+                }
+
+                let status = if self.revoked.contains(cert_id.serial_number()) {
+                    OcspCertStatus::Revoked {
+                        revocation_time: UTCDate::ymd(2065, 12, 24).unwrap(),
+                        reason: Some(CrlReason::Superseded),
+                    }
+                } else {
+                    OcspCertStatus::Good
+                };
+
+                builder.response(
+                    cert_id,
+                    status,
+                    self.now.clone(),
+                    Some(UTCDate::ymd(2066, 1, 8).unwrap()),
+                );
+            }
+
+            if let Some(nonce) = request.nonce().filter(|_| self.echo_nonce) {
+                builder.nonce(nonce);
+            }
+
+            builder.build()
+        }
+    }
+
+    #[test]
+    fn verify_local_responder() {
+        let ca_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let responder_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let ca = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("OCSP Root CA"), &ca_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root ca");
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&ca, &ca_key)
+            .serial_number(vec![0x01])
+            .build()
+            .expect("couldn't build leaf");
+
+        let revoked_leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Revoked Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&ca, &ca_key)
+            .serial_number(vec![0x02])
+            .build()
+            .expect("couldn't build revoked leaf");
+
+        let delegated_responder = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("OCSP Responder"),
+                responder_key.to_public_key(),
+            )
+            .issuer_cert(&ca, &ca_key)
+            .extended_key_usage(ExtendedKeyUsage::new(vec![oids::kp_ocsp_signing()]))
+            .build()
+            .expect("couldn't build delegated responder");
+
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let mut transport = LocalResponder {
+            issuer: &ca,
+            responder: &ca,
+            responder_key: &ca_key,
+            revoked: vec![IntegerAsn1::from_bytes_be_unsigned(vec![0x02])],
+            now: now.clone(),
+            echo_nonce: true,
+        };
+
+        let request = OcspRequestBuilder::new()
+            .cert(&leaf, &ca)
+            .cert(&revoked_leaf, &ca)
+            .hash_algorithm(HashAlgorithm::SHA2_256)
+            .build()
+            .unwrap();
+        assert_eq!(request.nonce().unwrap().len(), NONCE_LEN);
+
+        // issuer as responder
+        let response = request.send(&transport).unwrap();
+        let status = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap();
+        assert_eq!(status, OcspCertStatus::Good);
+
+        let status = response
+            .verifier()
+            .cert(&revoked_leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap();
+        assert_eq!(
+            status,
+            OcspCertStatus::Revoked {
+                revocation_time: UTCDate::ymd(2065, 12, 24).unwrap(),
+                reason: Some(CrlReason::Superseded),
+            }
+        );
+
+        // freshness
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .exact_date(&UTCDate::ymd(2066, 1, 9).unwrap())
+            .verify()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "response expired (next update: 2066-01-08 00:00:00, now: 2066-01-09 00:00:00)"
+        );
+
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .exact_date(&UTCDate::ymd(2065, 12, 31).unwrap())
+            .verify()
+            .unwrap_err();
+        assert!(matches!(err, OcspError::NotYetValid { .. }));
+
+        // nonce
+        let replayed_request = OcspRequestBuilder::new().cert(&leaf, &ca).build().unwrap();
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&replayed_request)
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(err, OcspError::NonceMismatch));
+
+        response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&replayed_request)
+            .exact_date(&now)
+            .ignore_nonce_check()
+            .verify()
+            .unwrap();
+
+        transport.echo_nonce = false;
+        let response = request.send(&transport).unwrap();
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(err, OcspError::MissingNonce));
+        transport.echo_nonce = true;
+
+        // delegated responder
+        transport.responder = &delegated_responder;
+        transport.responder_key = &responder_key;
+        let response = request.send(&transport).unwrap();
+        let status = response
+            .verifier()
+            .cert(&revoked_leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap();
+        assert!(matches!(status, OcspCertStatus::Revoked { .. }));
+
+        // delegated responder without the OCSP signing extended key usage
+        let not_a_responder = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(
+                DirectoryName::new_common_name("OCSP Responder"),
+                responder_key.to_public_key(),
+            )
+            .issuer_cert(&ca, &ca_key)
+            .build()
+            .expect("couldn't build certificate");
+        transport.responder = &not_a_responder;
+        let response = request.send(&transport).unwrap();
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&ca)
+            .request(&request)
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(err, OcspError::UnauthorizedResponder));
+
+        // certificate from another issuer
+        let other_ca = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Other Root CA"), &responder_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build other root ca");
+        let request = OcspRequestBuilder::new().cert(&leaf, &other_ca).build().unwrap();
+        let response = request.send(&transport).unwrap();
+        assert_eq!(response.status(), OcspResponseStatus::Unauthorized);
+        let err = response
+            .verifier()
+            .cert(&leaf)
+            .issuer(&other_ca)
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unsuccessful response: Unauthorized");
+    }
+}
@@ -0,0 +1,521 @@
+//! Certification path building from an unordered pool of certificates
+//! (see [RFC 4158](https://datatracker.ietf.org/doc/html/rfc4158) for background)
+//!
+//! Certificates gathered from a PKCS7 bag or a TLS handshake don't come in any particular order
+//! and may contain several issuers for a same certificate (e.g.: cross-signed intermediates).
+//! `PathBuilder` finds candidate paths from a leaf certificate to one of the trust anchors
+//! and validates them in turn until one is accepted.
+
+use crate::x509::certificate::{Cert, CertError};
+use crate::x509::crl::Crl;
+use crate::x509::date::UTCDate;
+use std::cell::RefCell;
+use thiserror::Error;
+
+/// Default maximum number of certificates in a path, trust anchor included.
+pub const DEFAULT_MAX_PATH_LEN: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum PathBuilderError {
+    /// no chain of issuers leads from the leaf certificate to a trust anchor
+    #[error("couldn't find any certification path to a trust anchor")]
+    NoPathFound,
+
+    /// all candidate paths have been rejected by the validator
+    #[error("all {} candidate certification path(s) have been rejected", .rejected.len())]
+    NoValidPath { rejected: Vec<RejectedPath> },
+}
+
+/// Candidate path rejected during path validation
+#[derive(Debug)]
+pub struct RejectedPath {
+    /// Certificates of the path, from the leaf certificate to the trust anchor
+    pub certificates: Vec<Cert>,
+    /// Reason of the rejection
+    pub error: CertError,
+}
+
+/// Validated certification path
+#[derive(Debug)]
+pub struct CertPath<'a> {
+    certificates: Vec<&'a Cert>,
+    rejected: Vec<RejectedPath>,
+}
+
+impl<'a> CertPath<'a> {
+    /// Certificates of the path, from the leaf certificate to the trust anchor.
+    pub fn certificates(&self) -> &[&'a Cert] {
+        &self.certificates
+    }
+
+    /// Chain to provide to `CertValidator::chain` (the leaf certificate excluded).
+    pub fn chain(&self) -> impl Iterator<Item = &'a Cert> + '_ {
+        self.certificates.iter().skip(1).copied()
+    }
+
+    pub fn trust_anchor(&self) -> &'a Cert {
+        // a path contains at least the leaf certificate
+        self.certificates[self.certificates.len() - 1]
+    }
+
+    /// Candidate paths tried and rejected before this one.
+    pub fn rejected(&self) -> &[RejectedPath] {
+        &self.rejected
+    }
+
+    pub fn into_certificates(self) -> Vec<&'a Cert> {
+        self.certificates
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ValidityDate<'a> {
+    Exact(&'a UTCDate),
+    Interval { lower: &'a UTCDate, upper: &'a UTCDate },
+}
+
+#[derive(Debug, Clone)]
+struct PathBuilderInner<'a> {
+    intermediates: &'a [Cert],
+    trust_anchors: &'a [Cert],
+    crls: &'a [Crl],
+    now: Option<ValidityDate<'a>>,
+    require_not_before_check: bool,
+    require_not_after_check: bool,
+    max_path_len: usize,
+}
+
+/// Utility to find a valid certification path from a leaf `Cert` to a trust anchor
+#[derive(Debug, Clone)]
+pub struct PathBuilder<'a> {
+    leaf: &'a Cert,
+    inner: RefCell<PathBuilderInner<'a>>,
+}
+
+impl<'a> PathBuilder<'a> {
+    pub fn new(leaf: &'a Cert) -> Self {
+        Self {
+            leaf,
+            inner: RefCell::new(PathBuilderInner {
+                intermediates: &[],
+                trust_anchors: &[],
+                crls: &[],
+                now: None,
+                require_not_before_check: true,
+                require_not_after_check: true,
+                max_path_len: DEFAULT_MAX_PATH_LEN,
+            }),
+        }
+    }
+
+    /// Pool of certificates that may appear in the path, in any order.
+    ///
+    /// Unrelated certificates are ignored.
+    #[inline]
+    pub fn intermediates(&self, intermediates: &'a [Cert]) -> &Self {
+        self.inner.borrow_mut().intermediates = intermediates;
+        self
+    }
+
+    /// Trusted certificates a path must end with.
+    ///
+    /// Trust anchors are not required to be self-signed root certificates.
+    #[inline]
+    pub fn trust_anchors(&self, trust_anchors: &'a [Cert]) -> &Self {
+        self.inner.borrow_mut().trust_anchors = trust_anchors;
+        self
+    }
+
+    /// Certificate Revocation Lists to check path certificates against.
+    #[inline]
+    pub fn crls(&self, crls: &'a [Crl]) -> &Self {
+        self.inner.borrow_mut().crls = crls;
+        self
+    }
+
+    #[inline]
+    pub fn exact_date(&self, exact: &'a UTCDate) -> &Self {
+        self.inner.borrow_mut().now = Some(ValidityDate::Exact(exact));
+        self
+    }
+
+    #[inline]
+    pub fn interval_date(&self, lower: &'a UTCDate, upper: &'a UTCDate) -> &Self {
+        self.inner.borrow_mut().now = Some(ValidityDate::Interval { lower, upper });
+        self
+    }
+
+    #[inline]
+    pub fn ignore_not_before_check(&self) -> &Self {
+        self.inner.borrow_mut().require_not_before_check = false;
+        self
+    }
+
+    #[inline]
+    pub fn ignore_not_after_check(&self) -> &Self {
+        self.inner.borrow_mut().require_not_after_check = false;
+        self
+    }
+
+    /// Maximum number of certificates in a path, leaf certificate and trust anchor included.
+    ///
+    /// Default is `DEFAULT_MAX_PATH_LEN`.
+    #[inline]
+    pub fn max_path_len(&self, max_path_len: usize) -> &Self {
+        self.inner.borrow_mut().max_path_len = max_path_len;
+        self
+    }
+
+    /// Searches and validates candidate paths until one is accepted.
+    ///
+    /// Issuers are found by matching the issuer name and, when available, the authority key identifier
+    /// against the subject name and subject key identifier of the candidate issuer.
+    /// At each step, trust anchors are tried before intermediate certificates.
+    pub fn build(&self) -> Result<CertPath<'a>, PathBuilderError> {
+        let inner = self.inner.borrow();
+        let mut search = PathSearch {
+            inner: &inner,
+            rejected: Vec::new(),
+        };
+
+        let mut path = vec![self.leaf];
+        if search.find(&mut path) {
+            Ok(CertPath {
+                certificates: path,
+                rejected: search.rejected,
+            })
+        } else if search.rejected.is_empty() {
+            Err(PathBuilderError::NoPathFound)
+        } else {
+            Err(PathBuilderError::NoValidPath {
+                rejected: search.rejected,
+            })
+        }
+    }
+}
+
+struct PathSearch<'a, 'i> {
+    inner: &'i PathBuilderInner<'a>,
+    rejected: Vec<RejectedPath>,
+}
+
+impl<'a> PathSearch<'a, '_> {
+    /// Depth-first search. On success, `path` holds the accepted path.
+    fn find(&mut self, path: &mut Vec<&'a Cert>) -> bool {
+        // cannot panic: path always contains the leaf certificate
+        let current = path[path.len() - 1];
+
+        // the leaf certificate itself may be trusted
+        if path.len() == 1 && self.inner.trust_anchors.contains(current) {
+            return self.try_path(path);
+        }
+
+        if path.len() >= self.inner.max_path_len {
+            return false;
+        }
+
+        for trust_anchor in issuer_candidates(current, self.inner.trust_anchors) {
+            if is_in_path(trust_anchor, path) {
+                continue;
+            }
+
+            path.push(trust_anchor);
+            if self.try_path(path) {
+                return true;
+            }
+            path.pop();
+        }
+
+        // an intermediate certificate can't close a path: at least one more certificate is required
+        if path.len() + 1 >= self.inner.max_path_len {
+            return false;
+        }
+
+        for intermediate in issuer_candidates(current, self.inner.intermediates) {
+            if is_in_path(intermediate, path) || self.inner.trust_anchors.contains(intermediate) {
+                continue;
+            }
+
+            path.push(intermediate);
+            if self.find(path) {
+                return true;
+            }
+            path.pop();
+        }
+
+        false
+    }
+
+    fn try_path(&mut self, path: &[&'a Cert]) -> bool {
+        match self.validate(path) {
+            Ok(()) => true,
+            Err(error) => {
+                self.rejected.push(RejectedPath {
+                    certificates: path.iter().map(|cert| (*cert).clone()).collect(),
+                    error,
+                });
+                false
+            }
+        }
+    }
+
+    fn validate(&self, path: &[&'a Cert]) -> Result<(), CertError> {
+        let validator = path[0].verifier();
+        validator
+            .chain(path[1..].iter().copied())
+            .crls(self.inner.crls)
+            .chain_should_contains_root_certificate(false);
+
+        match &self.inner.now {
+            Some(ValidityDate::Exact(exact)) => {
+                validator.exact_date(exact);
+            }
+            Some(ValidityDate::Interval { lower, upper }) => {
+                validator.interval_date(lower, upper);
+            }
+            None => {}
+        }
+
+        if !self.inner.require_not_before_check {
+            validator.ignore_not_before_check();
+        }
+
+        if !self.inner.require_not_after_check {
+            validator.ignore_not_after_check();
+        }
+
+        validator.verify()
+    }
+}
+
+/// Certificates of the pool that may have issued `cert`.
+///
+/// Candidates whose subject key identifier matches the authority key identifier of `cert` come first.
+/// Candidates with a mismatching key identifier are discarded.
+fn issuer_candidates<'a>(cert: &Cert, pool: &'a [Cert]) -> Vec<&'a Cert> {
+    let issuer_name = cert.issuer_name();
+    let authority_key_id = cert
+        .authority_key_identifier()
+        .ok()
+        .and_then(|aki| aki.key_identifier().map(<[u8]>::to_vec));
+
+    let mut candidates: Vec<(bool, &'a Cert)> = pool
+        .iter()
+        .filter(|candidate| *candidate != cert && candidate.subject_name() == issuer_name)
+        .filter_map(
+            |candidate| match (&authority_key_id, candidate.subject_key_identifier()) {
+                (Some(aki), Ok(ski)) if aki.as_slice() == ski => Some((true, candidate)),
+                (Some(_), Ok(_)) => None,
+                _ => Some((false, candidate)),
+            },
+        )
+        .collect();
+
+    // stable sort: pool order is kept otherwise
+    candidates.sort_by_key(|(key_id_matches, _)| !key_id_matches);
+    candidates.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Prevents loops: a same subject and public key pair can appear only once in a path.
+fn is_in_path(cert: &Cert, path: &[&Cert]) -> bool {
+    path.iter()
+        .any(|in_path| in_path.subject_name() == cert.subject_name() && in_path.public_key() == cert.public_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::PrivateKey;
+    use crate::pem::Pem;
+    use crate::x509::certificate::{CaChainError, CertificateBuilder};
+    use crate::x509::name::DirectoryName;
+
+    fn parse_key(pem_str: &str) -> PrivateKey {
+        let pem = pem_str.parse::<Pem>().unwrap();
+        PrivateKey::from_pkcs8(pem.data()).unwrap()
+    }
+
+    struct CrossSignedPki {
+        root_a: Cert,
+        root_b: Cert,
+        intermediate_a: Cert,
+        intermediate_b: Cert,
+        leaf: Cert,
+    }
+
+    /// Intermediate authority signed by root A (expired in 2067) and cross-signed by root B.
+    fn cross_signed_pki() -> CrossSignedPki {
+        let root_a_key = parse_key(crate::test_files::RSA_2048_PK_1);
+        let root_b_key = parse_key(crate::test_files::RSA_2048_PK_4);
+        let intermediate_key = parse_key(crate::test_files::RSA_2048_PK_2);
+        let leaf_key = parse_key(crate::test_files::RSA_2048_PK_3);
+
+        let root_a = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2067, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Root A"), &root_a_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root A");
+
+        let root_b = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .self_signed(DirectoryName::new_common_name("Root B"), &root_b_key)
+            .ca(true)
+            .build()
+            .expect("couldn't build root B");
+
+        let build_intermediate = |issuer: &Cert, issuer_key: &PrivateKey| {
+            CertificateBuilder::new()
+                .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+                .subject(
+                    DirectoryName::new_common_name("Cross-Signed Authority"),
+                    intermediate_key.to_public_key(),
+                )
+                .issuer_cert(issuer, issuer_key)
+                .ca(true)
+                .build()
+                .expect("couldn't build intermediate")
+        };
+        let intermediate_a = build_intermediate(&root_a, &root_a_key);
+        let intermediate_b = build_intermediate(&root_b, &root_b_key);
+
+        let leaf = CertificateBuilder::new()
+            .validity(UTCDate::ymd(2065, 6, 15).unwrap(), UTCDate::ymd(2070, 6, 15).unwrap())
+            .subject(DirectoryName::new_common_name("Leaf"), leaf_key.to_public_key())
+            .issuer_cert(&intermediate_a, &intermediate_key)
+            .build()
+            .expect("couldn't build leaf");
+
+        CrossSignedPki {
+            root_a,
+            root_b,
+            intermediate_a,
+            intermediate_b,
+            leaf,
+        }
+    }
+
+    #[test]
+    fn unordered_pool() {
+        let pki = cross_signed_pki();
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let pool = [
+            pki.root_b.clone(),
+            pki.leaf.clone(),
+            pki.intermediate_b.clone(),
+            pki.root_a.clone(),
+            pki.intermediate_a.clone(),
+        ];
+        let trust_anchors = [pki.root_a.clone()];
+
+        let path = PathBuilder::new(&pki.leaf)
+            .intermediates(&pool)
+            .trust_anchors(&trust_anchors)
+            .exact_date(&now)
+            .build()
+            .expect("couldn't build path");
+
+        assert_eq!(path.certificates(), [&pki.leaf, &pki.intermediate_a, &trust_anchors[0]]);
+        assert!(path.rejected().is_empty());
+
+        pki.leaf
+            .verifier()
+            .chain(path.chain())
+            .exact_date(&now)
+            .verify()
+            .expect("couldn't verify path");
+    }
+
+    #[test]
+    fn cross_signed_fallback() {
+        let pki = cross_signed_pki();
+
+        // root A is expired
+        let now = UTCDate::ymd(2068, 1, 1).unwrap();
+
+        let pool = [pki.intermediate_a.clone(), pki.intermediate_b.clone()];
+        let trust_anchors = [pki.root_a.clone(), pki.root_b.clone()];
+
+        let path = PathBuilder::new(&pki.leaf)
+            .intermediates(&pool)
+            .trust_anchors(&trust_anchors)
+            .exact_date(&now)
+            .build()
+            .expect("couldn't build path");
+
+        assert_eq!(path.certificates(), [&pki.leaf, &pool[1], &trust_anchors[1]]);
+        assert_eq!(path.rejected().len(), 1);
+        assert_eq!(path.rejected()[0].certificates[2], pki.root_a);
+        assert!(matches!(path.rejected()[0].error, CertError::InvalidCertificate { .. }));
+    }
+
+    #[test]
+    fn no_path() {
+        let pki = cross_signed_pki();
+        let now = UTCDate::ymd(2068, 1, 1).unwrap();
+
+        let pool = [pki.intermediate_a.clone()];
+        let trust_anchors = [pki.root_b.clone()];
+        let err = PathBuilder::new(&pki.leaf)
+            .intermediates(&pool)
+            .trust_anchors(&trust_anchors)
+            .exact_date(&now)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, PathBuilderError::NoPathFound));
+
+        let pool = [pki.intermediate_a.clone()];
+        let trust_anchors = [pki.root_a.clone()];
+        let err = PathBuilder::new(&pki.leaf)
+            .intermediates(&pool)
+            .trust_anchors(&trust_anchors)
+            .exact_date(&now)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "all 1 candidate certification path(s) have been rejected"
+        );
+
+        // path is too long
+        let pool = [pki.intermediate_a.clone()];
+        let err = PathBuilder::new(&pki.leaf)
+            .intermediates(&pool)
+            .trust_anchors(&trust_anchors)
+            .ignore_not_after_check()
+            .exact_date(&now)
+            .max_path_len(2)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, PathBuilderError::NoPathFound));
+    }
+
+    #[test]
+    fn intermediate_trust_anchor() {
+        let pki = cross_signed_pki();
+        let now = UTCDate::ymd(2066, 1, 1).unwrap();
+
+        let trust_anchors = [pki.intermediate_b.clone()];
+        let path = PathBuilder::new(&pki.leaf)
+            .trust_anchors(&trust_anchors)
+            .exact_date(&now)
+            .build()
+            .expect("couldn't build path");
+        assert_eq!(path.certificates(), [&pki.leaf, &trust_anchors[0]]);
+
+        let err = pki
+            .leaf
+            .verifier()
+            .chain(path.chain())
+            .exact_date(&now)
+            .verify()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            CertError::InvalidChain {
+                source: CaChainError::NoRoot
+            }
+        ));
+    }
+}
@@ -13,6 +13,7 @@ use crate::x509::name::DirectoryName;
 use crate::x509::pkcs7::ctl::{self, CTLEntryAttributeValues, CertificateTrustList};
 use crate::x509::pkcs7::timestamp::{self, Timestamper};
 use crate::x509::pkcs7::{self, Pkcs7};
+use crate::x509::trust_store::TrustStore;
 use crate::x509::utils::{from_der, from_pem, from_pem_str, to_der, to_pem};
 use picky_asn1::restricted_string::CharSetError;
 use picky_asn1::tag::Tag;
@@ -336,6 +337,7 @@ impl AuthenticodeSignature {
                 now: None,
                 excluded_cert_authorities: vec![],
                 expected_file_hash: None,
+                trust_store: None,
                 #[cfg(feature = "ctl")]
                 ctl: None,
             }),
@@ -424,6 +426,7 @@ struct AuthenticodeValidatorInner<'a> {
     excluded_cert_authorities: Vec<DirectoryName>,
     now: Option<ValidityCheck<'a>>,
     expected_file_hash: Option<Vec<u8>>,
+    trust_store: Option<&'a TrustStore>,
     #[cfg(feature = "ctl")]
     ctl: Option<&'a CertificateTrustList>,
 }
@@ -527,6 +530,15 @@ impl<'a> AuthenticodeValidator<'a> {
         self
     }
 
+    /// Trust anchors the certificate chain must lead to.
+    ///
+    /// By default, the chain is checked without requiring a trusted root.
+    #[inline]
+    pub fn trust_store(&self, trust_store: &'a TrustStore) -> &Self {
+        self.inner.borrow_mut().trust_store = Some(trust_store);
+        self
+    }
+
     #[inline]
     pub fn exclude_cert_authorities(&self, excluded_cert_authorities: &'a [DirectoryName]) -> &Self {
         self.inner
@@ -642,9 +654,7 @@ impl<'a> AuthenticodeValidator<'a> {
         // the SET OF tag, rather than of the IMPLICIT [0] tag [...]"
         raw_attributes[0] = Tag::SET.inner();
 
-        let signature_algorithm_identifier = AlgorithmIdentifier::new_rsa_encryption_with_sha(hash_algo)
-            .map_err(AuthenticodeError::UnsupportedAlgorithmError)?;
-        let signature_algorithm = SignatureAlgorithm::from_algorithm_identifier(&signature_algorithm_identifier)?;
+        let signature_algorithm = h_signer_info_signature_algorithm(&signer_info.signature_algorithm.0, hash_algo)?;
         signature_algorithm
             .verify(public_key, &raw_attributes, &signer_info.signature.0 .0)
             .map_err(AuthenticodeError::SignatureError)?;
@@ -708,11 +718,16 @@ impl<'a> AuthenticodeValidator<'a> {
         let cert_validator = if inner.strictness.require_chain_check {
             // Authenticode has the signer certificate and any intermediate certificates,
             // but typically does not contain the root
-            cert_validator.chain_should_contains_root_certificate(false).chain(
+            let cert_validator = cert_validator.chain_should_contains_root_certificate(false).chain(
                 certificates
                     .iter()
                     .filter(|cert| cert.subject_name() != signing_certificate.subject_name()),
-            )
+            );
+
+            match inner.trust_store {
+                Some(trust_store) => cert_validator.trust_store(trust_store),
+                None => cert_validator,
+            }
         } else {
             cert_validator.ignore_chain_check()
         };
@@ -986,6 +1001,33 @@ impl<'a> AuthenticodeValidator<'a> {
     }
 }
 
+/// Signature algorithm of a SignerInfo.
+///
+/// `rsaEncryption` stands for RSA PKCS#1 v1.5 using the digest algorithm of the SignerInfo,
+/// other algorithms identifiers (e.g. RSASSA-PSS) are self-describing.
+fn h_signer_info_signature_algorithm(
+    signature_algorithm: &AlgorithmIdentifier,
+    digest_algorithm: ShaVariant,
+) -> AuthenticodeResult<SignatureAlgorithm> {
+    if signature_algorithm.is_a(oids::rsa_encryption()) {
+        let signature_algorithm = AlgorithmIdentifier::new_rsa_encryption_with_sha(digest_algorithm)
+            .map_err(AuthenticodeError::UnsupportedAlgorithmError)?;
+        Ok(SignatureAlgorithm::from_algorithm_identifier(&signature_algorithm)?)
+    } else {
+        Ok(SignatureAlgorithm::from_algorithm_identifier(signature_algorithm)?)
+    }
+}
+
+/// Inverse of `h_signer_info_signature_algorithm`
+fn h_signer_info_signature_algorithm_identifier(
+    signature_algorithm: SignatureAlgorithm,
+) -> AuthenticodeResult<AlgorithmIdentifier> {
+    match signature_algorithm {
+        SignatureAlgorithm::RsaPkcs1v15(_) => Ok(AlgorithmIdentifier::new_rsa_encryption()),
+        other => Ok(AlgorithmIdentifier::try_from(other)?),
+    }
+}
+
 fn h_check_eku_code_signing(certificates: &[Cert]) -> AuthenticodeResult<()> {
     if certificates
         .iter()
@@ -1047,6 +1089,7 @@ pub enum AuthenticodeSignatureBuilderError {
 struct AuthenticodeSignatureBuilderInner<'a> {
     certs: Option<Vec<Cert>>,
     digest_algorithm: Option<HashAlgorithm>,
+    signature_algorithm: Option<SignatureAlgorithm>,
     content_info: Option<EncapsulatedContentInfo>,
     signing_key: Option<&'a PrivateKey>,
     issuer_and_serial_number: Option<IssuerAndSerialNumber>,
@@ -1078,6 +1121,13 @@ impl<'a> AuthenticodeSignatureBuilder<'a> {
         self
     }
 
+    /// Optional, RSA PKCS#1 v1.5 with the digest algorithm is used by default
+    #[inline]
+    pub fn signature_algorithm(&self, signature_algorithm: SignatureAlgorithm) -> &Self {
+        self.inner.borrow_mut().signature_algorithm = Some(signature_algorithm);
+        self
+    }
+
     /// Required
     #[inline]
     pub fn content_info(&self, content_info: EncapsulatedContentInfo) -> &Self {
@@ -1121,6 +1171,7 @@ impl<'a> AuthenticodeSignatureBuilder<'a> {
         let AuthenticodeSignatureBuilderInner {
             certs,
             digest_algorithm,
+            signature_algorithm,
             content_info,
             signing_key,
             issuer_and_serial_number,
@@ -1180,10 +1231,11 @@ impl<'a> AuthenticodeSignatureBuilder<'a> {
             }
         });
 
-        let digest_encryption_algorithm = AlgorithmIdentifier::new_rsa_encryption_with_sha(digest_algorithm)
-            .map_err(AuthenticodeError::UnsupportedAlgorithmError)?;
-
-        let signature_algo = SignatureAlgorithm::from_algorithm_identifier(&digest_encryption_algorithm)?;
+        let signature_algo = match signature_algorithm.take() {
+            Some(signature_algo) => signature_algo,
+            None => h_signer_info_signature_algorithm(&AlgorithmIdentifier::new_rsa_encryption(), digest_algorithm)?,
+        };
+        let signature_algorithm_identifier = h_signer_info_signature_algorithm_identifier(signature_algo)?;
 
         let to_sign_data = if let Some(ref authenticated_attributes) = authenticated_attributes {
             let mut auth_raw_data = picky_asn1_der::to_vec(&authenticated_attributes)?;
@@ -1214,7 +1266,7 @@ impl<'a> AuthenticodeSignatureBuilder<'a> {
             sid: SignerIdentifier::IssuerAndSerialNumber(issuer_and_serial_number),
             digest_algorithm: DigestAlgorithmIdentifier(digest_algorithm.clone()),
             signed_attrs: Attributes(authenticated_attributes.take().unwrap_or_default().into()).into(),
-            signature_algorithm: SignatureAlgorithmIdentifier(AlgorithmIdentifier::new_rsa_encryption()),
+            signature_algorithm: SignatureAlgorithmIdentifier(signature_algorithm_identifier),
             signature: encrypted_digest,
             unsigned_attrs: UnsignedAttributes(unsigned_attributes.take().unwrap_or_default()).into(),
         };
@@ -2181,8 +2233,9 @@ mod test {
             .unwrap();
     }
 
-    #[test]
-    fn validate_authenticode_singature_create_by_authenticode_builder() {
+    fn build_and_validate_authenticode_signature(
+        signature_algorithm: Option<SignatureAlgorithm>,
+    ) -> AuthenticodeSignature {
         let parse_key = |pem_str: &str| -> PrivateKey {
             let pem = pem_str.parse::<Pem>().unwrap();
             PrivateKey::from_pkcs8(pem.data()).unwrap()
@@ -2287,15 +2340,18 @@ mod test {
             content: Some(ContentValue::SpcIndirectDataContent(content).into()),
         };
 
-        let authenticode_signature = AuthenticodeSignatureBuilder::new()
+        let builder = AuthenticodeSignatureBuilder::new();
+        builder
             .digest_algorithm(HashAlgorithm::SHA2_256)
             .content_info(content_info)
             .issuer_and_serial_number(signed_leaf.issuer_name(), signed_leaf.serial_number().0.clone())
             .authenticated_attributes(authenticated_attributes)
             .signing_key(&leaf_key)
-            .certs(vec![signed_leaf, intermediate, root])
-            .build()
-            .unwrap();
+            .certs(vec![signed_leaf, intermediate, root]);
+        if let Some(signature_algorithm) = signature_algorithm {
+            builder.signature_algorithm(signature_algorithm);
+        }
+        let authenticode_signature = builder.build().unwrap();
 
         let validator = authenticode_signature.authenticode_verifier();
         validator
@@ -2306,5 +2362,31 @@ mod test {
             .ignore_not_after_check()
             .verify()
             .unwrap();
+
+        authenticode_signature
+    }
+
+    #[test]
+    fn validate_authenticode_singature_create_by_authenticode_builder() {
+        let authenticode_signature = build_and_validate_authenticode_signature(None);
+
+        assert_eq!(
+            authenticode_signature.0.signer_infos()[0].signature_algorithm.0,
+            AlgorithmIdentifier::new_rsa_encryption()
+        );
+    }
+
+    #[test]
+    fn validate_rsassa_pss_authenticode_signature_create_by_authenticode_builder() {
+        use crate::signature::RsaPssParams;
+
+        let authenticode_signature = build_and_validate_authenticode_signature(Some(SignatureAlgorithm::RsaPss(
+            RsaPssParams::new(HashAlgorithm::SHA2_256),
+        )));
+
+        assert!(authenticode_signature.0.signer_infos()[0]
+            .signature_algorithm
+            .0
+            .is_a(oids::rsassa_pss()));
     }
 }
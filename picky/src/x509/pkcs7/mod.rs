@@ -2,10 +2,14 @@ use crate::pem::Pem;
 use crate::x509::certificate::{Cert, CertError};
 use crate::x509::utils::{from_der, from_pem, from_pem_str, to_der, to_pem};
 use crate::AlgorithmIdentifier;
-use picky_asn1_der::Asn1DerError;
+use picky_asn1_der::{Asn1DerError, Asn1RawDer};
+use picky_asn1_x509::cmsversion::CmsVersion;
 use picky_asn1_x509::content_info::EncapsulatedContentInfo;
+use picky_asn1_x509::oids;
 use picky_asn1_x509::pkcs7::Pkcs7Certificate;
-use picky_asn1_x509::signed_data::CertificateChoices;
+use picky_asn1_x509::signed_data::{
+    CertificateChoices, CertificateSet, DigestAlgorithmIdentifiers, SignedData, SignersInfos,
+};
 use picky_asn1_x509::signer_info::SignerInfo;
 use thiserror::Error;
 
@@ -32,6 +36,29 @@ const PKCS7_PEM_LABEL: &str = "PKCS7";
 pub struct Pkcs7(Pkcs7Certificate);
 
 impl Pkcs7 {
+    /// Builds a degenerate "certs-only" structure, without content nor signer
+    /// ([RFC 8551 #3.2.2](https://datatracker.ietf.org/doc/html/rfc8551#section-3.2.2)).
+    pub fn from_certificates<'a>(certificates: impl IntoIterator<Item = &'a Cert>) -> Pkcs7Result<Self> {
+        let certificates = certificates
+            .into_iter()
+            .map(|cert| Ok(CertificateChoices::Certificate(Asn1RawDer(cert.to_der()?))))
+            .collect::<Pkcs7Result<Vec<CertificateChoices>>>()?;
+
+        let signed_data = SignedData {
+            version: CmsVersion::V1,
+            digest_algorithms: DigestAlgorithmIdentifiers(Vec::new().into()),
+            content_info: EncapsulatedContentInfo::new_pkcs7_data(None),
+            certificates: CertificateSet(certificates).into(),
+            crls: None,
+            signers_infos: SignersInfos(Vec::new().into()),
+        };
+
+        Ok(Self(Pkcs7Certificate {
+            oid: oids::signed_data().into(),
+            signed_data: signed_data.into(),
+        }))
+    }
+
     pub fn from_der<V: ?Sized + AsRef<[u8]>>(data: &V) -> Pkcs7Result<Self> {
         Ok(from_der(data, ELEMENT_NAME).map(Self)?)
     }
@@ -64,6 +91,8 @@ impl Pkcs7 {
         &self.0.signed_data.0.content_info
     }
 
+    /// Certificates are returned in encoding order, which is arbitrary.
+    /// `PathBuilder` can be used to find a certification path among them.
     pub fn decode_certificates(&self) -> Vec<Cert> {
         self.0
             .signed_data
@@ -101,4 +130,18 @@ mod tests {
         let pem = parse_pem(crate::test_files::PKCS7.as_bytes()).unwrap();
         Pkcs7::from_pem(&pem).unwrap();
     }
+
+    #[test]
+    fn certs_only_roundtrip() {
+        let intermediate = Cert::from_pem_str(crate::test_files::INTERMEDIATE_CA).unwrap();
+        let root = Cert::from_pem_str(crate::test_files::ROOT_CA).unwrap();
+
+        let pkcs7 = Pkcs7::from_certificates(&[intermediate.clone(), root.clone()]).unwrap();
+        assert!(pkcs7.signer_infos().is_empty());
+        assert!(pkcs7.digest_algorithms().is_empty());
+
+        let decoded = Pkcs7::from_der(&pkcs7.to_der().unwrap()).unwrap();
+        assert_eq!(decoded, pkcs7);
+        assert_eq!(decoded.decode_certificates(), vec![intermediate, root]);
+    }
 }
@@ -0,0 +1,350 @@
+//! Certificate policies processing as specified by [RFC 5280 #6.1](https://tools.ietf.org/html/rfc5280#section-6.1)
+
+use super::certificate::{CaChainError, Cert};
+use oid::ObjectIdentifier;
+use picky_asn1_x509::{oids, CertificatePolicies, PolicyMappings};
+
+/// Policy related inputs of the path validation algorithm.
+///
+/// An empty `user_initial_policy_set` stands for `anyPolicy`.
+#[derive(Clone, Debug, Default)]
+pub(super) struct PolicyInputs<'a> {
+    pub user_initial_policy_set: &'a [ObjectIdentifier],
+    pub initial_explicit_policy: bool,
+    pub initial_policy_mapping_inhibit: bool,
+    pub initial_any_policy_inhibit: bool,
+}
+
+/// Runs the policy part of the path validation algorithm.
+///
+/// `path` is ordered from the certificate issued by the trust anchor to the end entity certificate.
+pub(super) fn verify_policies(path: &[&Cert], inputs: &PolicyInputs<'_>) -> Result<(), CaChainError> {
+    let any_policy = oids::any_policy();
+    let n = path.len();
+
+    let mut valid_policy_tree = Some(PolicyTree::new());
+    let mut explicit_policy = if inputs.initial_explicit_policy { 0 } else { n + 1 };
+    let mut inhibit_any_policy = if inputs.initial_any_policy_inhibit { 0 } else { n + 1 };
+    let mut policy_mapping = if inputs.initial_policy_mapping_inhibit {
+        0
+    } else {
+        n + 1
+    };
+
+    for (idx, cert) in path.iter().enumerate() {
+        let i = idx + 1;
+        let is_self_issued = cert.subject_name() == cert.issuer_name();
+
+        // basic certificate processing (RFC 5280 #6.1.3 d, e)
+        valid_policy_tree = match (valid_policy_tree, cert.certificate_policies()) {
+            (Some(mut tree), Ok(policies)) => {
+                let any_policy_allowed = inhibit_any_policy > 0 || (i < n && is_self_issued);
+                tree.process_certificate_policies(i, policies, any_policy_allowed);
+                tree.into_non_null()
+            }
+            _ => None,
+        };
+
+        // RFC 5280 #6.1.3 f
+        if explicit_policy == 0 && valid_policy_tree.is_none() {
+            return Err(invalid_policy(cert, "no acceptable policy"));
+        }
+
+        if i == n {
+            break;
+        }
+
+        // preparation for the next certificate (RFC 5280 #6.1.4 a, b)
+        if let Ok(mappings) = cert.policy_mappings() {
+            let maps_any_policy = mappings.iter().any(|mapping| {
+                mapping.issuer_domain_policy.0 == any_policy || mapping.subject_domain_policy.0 == any_policy
+            });
+            if maps_any_policy {
+                return Err(invalid_policy(cert, "anyPolicy can't be mapped"));
+            }
+
+            valid_policy_tree = valid_policy_tree.and_then(|mut tree| {
+                tree.process_policy_mappings(i, mappings, policy_mapping > 0);
+                tree.into_non_null()
+            });
+        }
+
+        // RFC 5280 #6.1.4 h
+        if !is_self_issued {
+            explicit_policy = explicit_policy.saturating_sub(1);
+            policy_mapping = policy_mapping.saturating_sub(1);
+            inhibit_any_policy = inhibit_any_policy.saturating_sub(1);
+        }
+
+        // RFC 5280 #6.1.4 i
+        if let Ok(constraints) = cert.policy_constraints() {
+            if let Some(require_explicit_policy) = constraints.require_explicit_policy() {
+                explicit_policy = explicit_policy.min(usize::from(require_explicit_policy));
+            }
+
+            if let Some(inhibit_policy_mapping) = constraints.inhibit_policy_mapping() {
+                policy_mapping = policy_mapping.min(usize::from(inhibit_policy_mapping));
+            }
+        }
+
+        // RFC 5280 #6.1.4 j
+        if let Ok(skip_certs) = cert.inhibit_any_policy() {
+            inhibit_any_policy = inhibit_any_policy.min(usize::from(skip_certs));
+        }
+    }
+
+    // wrap-up procedure (RFC 5280 #6.1.5 a, b)
+    let end_entity = path[n - 1];
+
+    explicit_policy = explicit_policy.saturating_sub(1);
+
+    let require_explicit_policy = end_entity
+        .policy_constraints()
+        .ok()
+        .and_then(|constraints| constraints.require_explicit_policy());
+    if require_explicit_policy == Some(0) {
+        explicit_policy = 0;
+    }
+
+    // RFC 5280 #6.1.5 g
+    let user_policies_are_any_policy =
+        inputs.user_initial_policy_set.is_empty() || inputs.user_initial_policy_set.contains(&any_policy);
+    if !user_policies_are_any_policy {
+        valid_policy_tree = valid_policy_tree.and_then(|mut tree| {
+            tree.intersect(n, inputs.user_initial_policy_set);
+            tree.into_non_null()
+        });
+    }
+
+    if explicit_policy == 0 && valid_policy_tree.is_none() {
+        return Err(invalid_policy(end_entity, "no acceptable policy"));
+    }
+
+    Ok(())
+}
+
+fn invalid_policy(cert: &Cert, reason: &'static str) -> CaChainError {
+    CaChainError::InvalidPolicy {
+        cert_id: cert.subject_name().to_string(),
+        reason,
+    }
+}
+
+#[derive(Debug)]
+struct PolicyNode {
+    valid_policy: ObjectIdentifier,
+    expected_policy_set: Vec<ObjectIdentifier>,
+    parent: Option<usize>,
+    depth: usize,
+    deleted: bool,
+}
+
+/// `valid_policy_tree` stored as an arena: children are always stored after their parent.
+///
+/// Policy qualifiers are not tracked.
+#[derive(Debug)]
+struct PolicyTree {
+    nodes: Vec<PolicyNode>,
+}
+
+impl PolicyTree {
+    fn new() -> Self {
+        Self {
+            nodes: vec![PolicyNode {
+                valid_policy: oids::any_policy(),
+                expected_policy_set: vec![oids::any_policy()],
+                parent: None,
+                depth: 0,
+                deleted: false,
+            }],
+        }
+    }
+
+    /// Returns `None` when the tree is NULL (root node has been deleted).
+    fn into_non_null(self) -> Option<Self> {
+        if self.nodes[0].deleted {
+            None
+        } else {
+            Some(self)
+        }
+    }
+
+    fn nodes_at(&self, depth: usize) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&idx| !self.nodes[idx].deleted && self.nodes[idx].depth == depth)
+            .collect()
+    }
+
+    fn children_of(&self, parent: usize) -> impl Iterator<Item = &PolicyNode> {
+        self.nodes
+            .iter()
+            .filter(move |node| !node.deleted && node.parent == Some(parent))
+    }
+
+    fn add_child(&mut self, parent: usize, valid_policy: ObjectIdentifier, expected_policy_set: Vec<ObjectIdentifier>) {
+        let depth = self.nodes[parent].depth + 1;
+        self.nodes.push(PolicyNode {
+            valid_policy,
+            expected_policy_set,
+            parent: Some(parent),
+            depth,
+            deleted: false,
+        });
+    }
+
+    /// Deletes a node and all its descendants.
+    fn delete(&mut self, idx: usize) {
+        self.nodes[idx].deleted = true;
+        for child_idx in idx + 1..self.nodes.len() {
+            if let Some(parent) = self.nodes[child_idx].parent {
+                if self.nodes[parent].deleted {
+                    self.nodes[child_idx].deleted = true;
+                }
+            }
+        }
+    }
+
+    /// Deletes nodes of depth lower than `depth` without any child, repeatedly.
+    fn prune(&mut self, depth: usize) {
+        for current_depth in (0..depth).rev() {
+            for idx in self.nodes_at(current_depth) {
+                if self.children_of(idx).next().is_none() {
+                    self.nodes[idx].deleted = true;
+                }
+            }
+        }
+    }
+
+    /// RFC 5280 #6.1.3 d
+    fn process_certificate_policies(&mut self, i: usize, policies: &CertificatePolicies, any_policy_allowed: bool) {
+        let any_policy = oids::any_policy();
+        let parents = self.nodes_at(i - 1);
+
+        for policy in policies.iter() {
+            let policy = &policy.policy_identifier.0;
+            if *policy == any_policy {
+                continue;
+            }
+
+            let matching_parents: Vec<usize> = parents
+                .iter()
+                .copied()
+                .filter(|&parent| self.nodes[parent].expected_policy_set.contains(policy))
+                .collect();
+
+            if !matching_parents.is_empty() {
+                for parent in matching_parents {
+                    self.add_child(parent, policy.clone(), vec![policy.clone()]);
+                }
+            } else if let Some(&any_policy_parent) = parents
+                .iter()
+                .find(|&&parent| self.nodes[parent].valid_policy == any_policy)
+            {
+                self.add_child(any_policy_parent, policy.clone(), vec![policy.clone()]);
+            }
+        }
+
+        if any_policy_allowed && policies.contains(any_policy) {
+            for parent in parents {
+                for expected_policy in self.nodes[parent].expected_policy_set.clone() {
+                    if !self
+                        .children_of(parent)
+                        .any(|child| child.valid_policy == expected_policy)
+                    {
+                        self.add_child(parent, expected_policy.clone(), vec![expected_policy]);
+                    }
+                }
+            }
+        }
+
+        self.prune(i);
+    }
+
+    /// RFC 5280 #6.1.4 b
+    fn process_policy_mappings(&mut self, i: usize, mappings: &PolicyMappings, mapping_allowed: bool) {
+        let any_policy = oids::any_policy();
+
+        let mut issuer_domain_policies: Vec<(ObjectIdentifier, Vec<ObjectIdentifier>)> = Vec::new();
+        for mapping in mappings.iter() {
+            let issuer_policy = &mapping.issuer_domain_policy.0;
+            let subject_policy = mapping.subject_domain_policy.0.clone();
+            match issuer_domain_policies
+                .iter_mut()
+                .find(|(policy, _)| policy == issuer_policy)
+            {
+                Some((_, subject_policies)) => subject_policies.push(subject_policy),
+                None => issuer_domain_policies.push((issuer_policy.clone(), vec![subject_policy])),
+            }
+        }
+
+        for (issuer_policy, subject_policies) in issuer_domain_policies {
+            let nodes = self.nodes_at(i);
+            let mapped_nodes: Vec<usize> = nodes
+                .iter()
+                .copied()
+                .filter(|&idx| self.nodes[idx].valid_policy == issuer_policy)
+                .collect();
+
+            if !mapping_allowed {
+                for idx in mapped_nodes {
+                    self.delete(idx);
+                }
+            } else if !mapped_nodes.is_empty() {
+                for idx in mapped_nodes {
+                    self.nodes[idx].expected_policy_set = subject_policies.clone();
+                }
+            } else if let Some(any_policy_node) = nodes
+                .into_iter()
+                .find(|&idx| self.nodes[idx].valid_policy == any_policy)
+            {
+                if let Some(parent) = self.nodes[any_policy_node].parent {
+                    self.add_child(parent, issuer_policy, subject_policies);
+                }
+            }
+        }
+
+        if !mapping_allowed {
+            self.prune(i);
+        }
+    }
+
+    /// RFC 5280 #6.1.5 g (iii)
+    fn intersect(&mut self, n: usize, user_initial_policy_set: &[ObjectIdentifier]) {
+        let any_policy = oids::any_policy();
+
+        let valid_policy_node_set: Vec<usize> = (0..self.nodes.len())
+            .filter(|&idx| !self.nodes[idx].deleted)
+            .filter(
+                |&idx| matches!(self.nodes[idx].parent, Some(parent) if self.nodes[parent].valid_policy == any_policy),
+            )
+            .collect();
+        let valid_policies: Vec<ObjectIdentifier> = valid_policy_node_set
+            .iter()
+            .map(|&idx| self.nodes[idx].valid_policy.clone())
+            .collect();
+
+        for idx in valid_policy_node_set {
+            let valid_policy = &self.nodes[idx].valid_policy;
+            if *valid_policy != any_policy && !user_initial_policy_set.contains(valid_policy) {
+                self.delete(idx);
+            }
+        }
+
+        let any_policy_leaf = self
+            .nodes_at(n)
+            .into_iter()
+            .find(|&idx| self.nodes[idx].valid_policy == any_policy);
+        if let Some(any_policy_leaf) = any_policy_leaf {
+            if let Some(parent) = self.nodes[any_policy_leaf].parent {
+                for policy in user_initial_policy_set {
+                    if !valid_policies.contains(policy) {
+                        self.add_child(parent, policy.clone(), vec![policy.clone()]);
+                    }
+                }
+            }
+            self.delete(any_policy_leaf);
+        }
+
+        self.prune(n);
+    }
+}
@@ -0,0 +1,274 @@
+//! Sets of trust anchors used as root of trust when validating certification paths.
+
+use crate::hash::HashAlgorithm;
+use crate::pem::{parse_pem, PemError};
+use crate::x509::certificate::{Cert, CertError};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+#[cfg(feature = "ctl")]
+use crate::x509::pkcs7::ctl::{CTLEntryAttributeValues, CertificateTrustList, CtlError};
+
+const CERT_PEM_LABEL: &str = "CERTIFICATE";
+const PEM_HEADER_START: &str = "-----BEGIN";
+
+#[derive(Debug, Error)]
+pub enum TrustStoreError {
+    /// invalid PEM provided
+    #[error("invalid PEM provided: {source}")]
+    Pem { source: PemError },
+
+    /// couldn't read a file or directory
+    #[error("couldn't read '{}': {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    /// certificate error
+    #[error("invalid certificate in '{}': {source}", .path.display())]
+    Cert { path: PathBuf, source: CertError },
+
+    /// CTL error
+    #[cfg(feature = "ctl")]
+    #[error("invalid certificate trust list: {source}")]
+    Ctl { source: CtlError },
+}
+
+/// A set of trusted certificates (trust anchors).
+///
+/// Trust anchors are not required to be self-signed: trusting an intermediate certificate
+/// is allowed and validation stops there.
+#[derive(Clone, Debug, Default)]
+pub struct TrustStore {
+    anchors: Vec<Cert>,
+    trusted_sha1_fingerprints: Vec<Vec<u8>>,
+}
+
+impl TrustStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads all certificates from a PEM bundle such as the ones distributed by Mozilla or curl.
+    ///
+    /// Text outside of PEM blocks and PEM blocks that are not certificates are ignored.
+    /// Certificates that picky can't decode (e.g. V1 certificates) are skipped.
+    pub fn from_pem_bundle(bundle: &str) -> Result<Self, TrustStoreError> {
+        let mut store = Self::new();
+        store.add_pem_bundle(bundle)?;
+        Ok(store)
+    }
+
+    /// Loads all certificates from a directory of PEM or DER files.
+    ///
+    /// OpenSSL hashed directories (as generated by `c_rehash`) are supported.
+    /// Files that do not contain any certificate are ignored.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Self, TrustStoreError> {
+        let mut store = Self::new();
+        store.add_dir(path)?;
+        Ok(store)
+    }
+
+    /// Trusts all certificates listed in a Microsoft Certificate Trust List.
+    ///
+    /// A CTL only contains fingerprints: trusted root certificates still have to be provided
+    /// as last certificate of the chain. Entries with a disallowed date are not trusted.
+    #[cfg(feature = "ctl")]
+    pub fn from_ctl(ctl: &CertificateTrustList) -> Result<Self, TrustStoreError> {
+        let mut store = Self::new();
+        store.add_ctl(ctl)?;
+        Ok(store)
+    }
+
+    pub fn add_certificate(&mut self, cert: Cert) {
+        if !self.anchors.contains(&cert) {
+            self.anchors.push(cert);
+        }
+    }
+
+    /// Returns the number of certificates added.
+    pub fn add_pem_bundle(&mut self, bundle: &str) -> Result<usize, TrustStoreError> {
+        let mut added = 0;
+        let mut cursor = 0;
+
+        while let Some(offset) = bundle[cursor..].find(PEM_HEADER_START) {
+            let start = cursor + offset;
+            let pem = parse_pem(&bundle[start..]).map_err(|source| TrustStoreError::Pem { source })?;
+
+            if pem.label() == CERT_PEM_LABEL {
+                if let Ok(cert) = Cert::from_der(pem.data()) {
+                    self.add_certificate(cert);
+                    added += 1;
+                }
+            }
+
+            cursor = start + PEM_HEADER_START.len();
+        }
+
+        Ok(added)
+    }
+
+    /// Returns the number of certificates added.
+    pub fn add_dir<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, TrustStoreError> {
+        let path = path.as_ref();
+        let entries = fs::read_dir(path).map_err(|source| TrustStoreError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        let mut file_paths = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|source| TrustStoreError::Io {
+                path: path.to_owned(),
+                source,
+            })?;
+            let file_path = entry.path();
+            if file_path.is_file() {
+                file_paths.push(file_path);
+            }
+        }
+        file_paths.sort();
+
+        let mut added = 0;
+        for file_path in file_paths {
+            let contents = fs::read(&file_path).map_err(|source| TrustStoreError::Io {
+                path: file_path.clone(),
+                source,
+            })?;
+
+            match std::str::from_utf8(&contents) {
+                Ok(text) if text.contains(PEM_HEADER_START) => {
+                    added += self.add_pem_bundle(text)?;
+                }
+                _ if is_der_certificate_file(&file_path) => {
+                    let cert = Cert::from_der(&contents).map_err(|source| TrustStoreError::Cert {
+                        path: file_path.clone(),
+                        source,
+                    })?;
+                    self.add_certificate(cert);
+                    added += 1;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(added)
+    }
+
+    /// Returns the number of fingerprints added.
+    #[cfg(feature = "ctl")]
+    pub fn add_ctl(&mut self, ctl: &CertificateTrustList) -> Result<usize, TrustStoreError> {
+        let mut added = 0;
+
+        for entry in ctl.ctl_entries().map_err(|source| TrustStoreError::Ctl { source })? {
+            let is_disallowed = entry
+                .attributes
+                .0
+                .iter()
+                .any(|attr| matches!(attr.value, CTLEntryAttributeValues::CertDisallowedFileTimePropId(_)));
+
+            if !is_disallowed && !self.trusted_sha1_fingerprints.contains(&entry.cert_fingerprint.0) {
+                self.trusted_sha1_fingerprints.push(entry.cert_fingerprint.0.clone());
+                added += 1;
+            }
+        }
+
+        Ok(added)
+    }
+
+    pub fn certificates(&self) -> &[Cert] {
+        &self.anchors
+    }
+
+    pub fn len(&self) -> usize {
+        self.anchors.len() + self.trusted_sha1_fingerprints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.anchors.is_empty() && self.trusted_sha1_fingerprints.is_empty()
+    }
+
+    /// Returns `true` if this certificate is itself a trust anchor.
+    pub fn is_trusted(&self, cert: &Cert) -> bool {
+        if self.anchors.contains(cert) {
+            return true;
+        }
+
+        if self.trusted_sha1_fingerprints.is_empty() {
+            return false;
+        }
+
+        match cert.to_der() {
+            Ok(der) => {
+                let fingerprint = HashAlgorithm::SHA1.digest(&der);
+                self.trusted_sha1_fingerprints.contains(&fingerprint)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Trust anchors that could have issued this certificate, based on names and key identifiers.
+    ///
+    /// Signatures are not verified.
+    pub fn issuers_of<'a>(&'a self, cert: &'a Cert) -> impl Iterator<Item = &'a Cert> + 'a {
+        self.anchors
+            .iter()
+            .filter(move |anchor| anchor.is_parent_of(cert).is_ok())
+    }
+}
+
+fn is_der_certificate_file(path: &Path) -> bool {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => {
+            matches!(ext.to_ascii_lowercase().as_str(), "der" | "cer" | "crt")
+                // hashed directory entries are named `<subject hash>.<n>`
+                || ext.bytes().all(|byte| byte.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pem_bundle() {
+        let store = TrustStore::from_pem_bundle(crate::test_files::MKCERT_ALL_ROOT_CA).unwrap();
+        assert!(store.len() > 100);
+
+        let global_sign = store
+            .certificates()
+            .iter()
+            .find(|cert| cert.subject_name().to_string() == "C=BE,O=GlobalSign nv-sa,OU=Root CA,CN=GlobalSign Root CA")
+            .expect("GlobalSign Root CA");
+        assert!(store.is_trusted(global_sign));
+        assert_eq!(store.issuers_of(global_sign).count(), 1);
+
+        let psdiag_root = Cert::from_pem_str(crate::test_files::PSDIAG_ROOT).unwrap();
+        assert!(!store.is_trusted(&psdiag_root));
+    }
+
+    #[test]
+    fn hashed_dir() {
+        let dir = std::env::temp_dir().join(format!("picky-trust-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let root = Cert::from_pem_str(crate::test_files::PSDIAG_ROOT).unwrap();
+        let inter = Cert::from_pem_str(crate::test_files::PSDIAG_INTER).unwrap();
+        fs::write(dir.join("3a5c9e2f.0"), crate::test_files::PSDIAG_ROOT).unwrap();
+        fs::write(dir.join("81f0b4d7.0"), inter.to_der().unwrap()).unwrap();
+        fs::write(dir.join("README"), "not a certificate").unwrap();
+
+        let store = TrustStore::from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let store = store.unwrap();
+
+        assert_eq!(store.len(), 2);
+        assert!(store.is_trusted(&root));
+        assert!(store.is_trusted(&inter));
+
+        let leaf = Cert::from_pem_str(crate::test_files::PSDIAG_LEAF).unwrap();
+        assert!(!store.is_trusted(&leaf));
+        assert_eq!(store.issuers_of(&leaf).collect::<Vec<_>>(), vec![&inter]);
+    }
+}